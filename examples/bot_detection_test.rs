@@ -21,7 +21,7 @@ async fn main() -> Result<()> {
     )
     .await?;
 
-    tokio::spawn(async move { while let Some(_) = handler.next().await {} });
+    tokio::spawn(async move { while handler.next().await.is_some() {} });
 
     // Create page and apply profile
     let page = browser.new_page("about:blank").await?;
@@ -106,7 +106,13 @@ async fn main() -> Result<()> {
     println!("📊 TEST 3: Cloudflare Turnstile (winna.com)");
     println!("   URL: https://winna.com");
     chaser.goto("https://winna.com").await?;
-    tokio::time::sleep(Duration::from_secs(6)).await;
+    match chaser
+        .wait_for_challenge_clear(Duration::from_secs(15))
+        .await
+    {
+        Ok(challenge) => println!("   Challenge: {:?}", challenge),
+        Err(e) => println!("   Challenge: {}", e),
+    }
 
     let turnstile_status = extract_string(
         &chaser
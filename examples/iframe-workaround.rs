@@ -1,6 +1,9 @@
 // This example is for checking the iframe workaround.
 // a problem with the iframe workaround is that it will always fail to load the page
 // and goto will cause a timeout.
+// Stealth scripts no longer need it: init scripts registered on a page are
+// replayed into out-of-process iframes as they attach (see
+// `ChaserPage::apply_profile`).
 
 use chaser_oxide::browser::{Browser, BrowserConfig};
 use futures::StreamExt;
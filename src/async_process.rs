@@ -54,6 +54,11 @@ impl Command {
         self
     }
 
+    pub fn stdout<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Self {
+        self.inner.stdout(cfg);
+        self
+    }
+
     pub fn stderr<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Self {
         self.inner.stderr(cfg);
         self
@@ -67,6 +72,7 @@ impl Command {
 
 #[derive(Debug)]
 pub struct Child {
+    pub stdout: Option<ChildStdout>,
     pub stderr: Option<ChildStderr>,
     pub inner: process::Child,
 }
@@ -77,9 +83,11 @@ pub struct Child {
 /// or `tokio-runtime`).
 impl Child {
     fn new(mut inner: process::Child) -> Self {
+        let stdout = inner.stdout.take();
         let stderr = inner.stderr.take();
         Self {
             inner,
+            stdout: stdout.map(|inner| ChildStdout { inner }),
             stderr: stderr.map(|inner| ChildStderr { inner }),
         }
     }
@@ -123,7 +131,7 @@ impl Child {
 
     /// Return a mutable reference to the inner process
     ///
-    /// `stderr` may not be available.
+    /// `stdout` and `stderr` may not be available.
     pub fn as_mut_inner(&mut self) -> &mut process::Child {
         &mut self.inner
     }
@@ -131,11 +139,23 @@ impl Child {
     /// Return the inner process
     pub fn into_inner(self) -> process::Child {
         let mut inner = self.inner;
+        inner.stdout = self.stdout.map(ChildStdout::into_inner);
         inner.stderr = self.stderr.map(ChildStderr::into_inner);
         inner
     }
 }
 
+#[derive(Debug)]
+pub struct ChildStdout {
+    pub inner: process::ChildStdout,
+}
+
+impl ChildStdout {
+    pub fn into_inner(self) -> process::ChildStdout {
+        self.inner
+    }
+}
+
 #[derive(Debug)]
 pub struct ChildStderr {
     pub inner: process::ChildStderr,
@@ -147,24 +167,31 @@ impl ChildStderr {
     }
 }
 
-impl futures::AsyncRead for ChildStderr {
-    fn poll_read(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
-        buf: &mut [u8],
-    ) -> Poll<std::io::Result<usize>> {
-        cfg_if::cfg_if! {
-            if #[cfg(feature = "async-std-runtime")] {
-                Pin::new(&mut self.inner).poll_read(cx, buf)
-            } else if #[cfg(feature = "tokio-runtime")] {
-                let mut buf = tokio::io::ReadBuf::new(buf);
-                futures::ready!(tokio::io::AsyncRead::poll_read(
-                    Pin::new(&mut self.inner),
-                    cx,
-                    &mut buf
-                ))?;
-                Poll::Ready(Ok(buf.filled().len()))
+macro_rules! impl_async_read {
+    ($ty:ty) => {
+        impl futures::AsyncRead for $ty {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<std::io::Result<usize>> {
+                cfg_if::cfg_if! {
+                    if #[cfg(feature = "async-std-runtime")] {
+                        Pin::new(&mut self.inner).poll_read(cx, buf)
+                    } else if #[cfg(feature = "tokio-runtime")] {
+                        let mut buf = tokio::io::ReadBuf::new(buf);
+                        futures::ready!(tokio::io::AsyncRead::poll_read(
+                            Pin::new(&mut self.inner),
+                            cx,
+                            &mut buf
+                        ))?;
+                        Poll::Ready(Ok(buf.filled().len()))
+                    }
+                }
             }
         }
-    }
+    };
 }
+
+impl_async_read!(ChildStdout);
+impl_async_read!(ChildStderr);
@@ -12,10 +12,20 @@ impl ArgsBuilder {
         self.0.contains_key(key)
     }
 
+    pub fn remove(&mut self, key: &str) -> &mut Self {
+        self.0.remove(key);
+        self
+    }
+
     pub fn arg<T: Into<Arg>>(&mut self, arg: T) -> &mut Self {
         let arg = arg.into();
         if let Some(values) = self.0.get_mut(&arg.key) {
-            values.extend(arg.values);
+            for value in arg.values {
+                // e.g. a blink feature disabled by two options
+                if !values.contains(&value) {
+                    values.push(value);
+                }
+            }
         } else {
             self.0.insert(arg.key, arg.values);
         }
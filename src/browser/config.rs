@@ -8,12 +8,17 @@ use std::{
 use super::argument::{Arg, ArgConst, ArgsBuilder};
 use crate::async_process::{self, Child, Stdio};
 use crate::detection::{self, DetectionOptions};
-use crate::handler::viewport::Viewport;
-use crate::handler::REQUEST_TIMEOUT;
+use crate::devices;
+use crate::handler::viewport::{self, DevicePreset, Viewport, BROWSER_UI_HEIGHT, TASKBAR_HEIGHT};
+use crate::handler::{CHANNEL_CAPACITY, REQUEST_TIMEOUT, TRACE_PAYLOAD_LIMIT};
 
 /// Default `Browser::launch` timeout in MS
 pub const LAUNCH_TIMEOUT: u64 = 20_000;
 
+/// Default number of trailing output bytes kept while `Browser::launch`
+/// waits for the websocket URL
+pub const LAUNCH_STDERR_LIMIT: usize = 64 * 1024;
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum HeadlessMode {
     /// The "headful" mode.
@@ -37,6 +42,9 @@ pub struct BrowserConfig {
     /// Launch the browser with a specific window width and height.
     pub(crate) window_size: Option<(u32, u32)>,
 
+    /// The screen picked by [`BrowserConfigBuilder::randomized_viewport`]
+    pub(crate) screen: Option<(u32, u32)>,
+
     /// Launch the browser with a specific debugging port.
     pub(crate) port: u16,
 
@@ -68,6 +76,9 @@ pub struct BrowserConfig {
     /// Timeout duration for `Browser::launch`.
     pub(crate) launch_timeout: Duration,
 
+    /// How many trailing bytes of stderr launch errors keep
+    pub(crate) launch_stderr_limit: usize,
+
     /// Ignore https errors, default is true
     pub(crate) ignore_https_errors: bool,
 
@@ -80,6 +91,12 @@ pub struct BrowserConfig {
     /// The viewport of the browser
     pub(crate) viewport: Option<Viewport>,
 
+    /// Replaces the default `--user-agent` argument
+    pub(crate) user_agent: Option<String>,
+
+    /// The device selected with [`BrowserConfigBuilder::device`]
+    pub(crate) device: Option<DevicePreset>,
+
     /// The duration after a request with no response should time out
     pub(crate) request_timeout: Duration,
 
@@ -89,6 +106,9 @@ pub struct BrowserConfig {
     /// Whether to disable DEFAULT_ARGS or not, default is false
     pub(crate) disable_default_args: bool,
 
+    /// Whether to launch with STEALTH_ARGS instead of DEFAULT_ARGS
+    pub(crate) stealth_args: bool,
+
     /// Whether to enable request interception
     pub request_intercept: bool,
 
@@ -97,6 +117,39 @@ pub struct BrowserConfig {
 
     /// Avoid easy bot detection by setting `navigator.webdriver` to false
     pub(crate) hidden: bool,
+
+    /// Whether to disable the `AutomationControlled` blink feature on its
+    /// own, see [`BrowserConfigBuilder::disable_automation_controlled`]
+    pub(crate) disable_automation_controlled: bool,
+
+    /// Whether to force hardware-accelerated GL instead of SwiftShader
+    pub(crate) gpu: bool,
+
+    /// Whether WebRTC may only send UDP through a proxy, see
+    /// [`BrowserConfigBuilder::block_webrtc`]
+    pub(crate) block_webrtc: bool,
+
+    /// Capacity of the channels to the handler, see
+    /// [`HandlerConfig::channel_capacity`](crate::handler::HandlerConfig::channel_capacity)
+    pub(crate) channel_capacity: usize,
+
+    /// How often the handler checks that the browser still responds, see
+    /// [`HandlerConfig::heartbeat_interval`](crate::handler::HandlerConfig::heartbeat_interval)
+    pub(crate) heartbeat_interval: Option<Duration>,
+    /// See [`HandlerConfig::idle_timeout`](crate::handler::HandlerConfig::idle_timeout)
+    pub(crate) idle_timeout: Option<Duration>,
+
+    /// URL patterns every page blocks, see
+    /// [`HandlerConfig::blocked_urls`](crate::handler::HandlerConfig::blocked_urls)
+    pub(crate) blocked_urls: Vec<String>,
+
+    /// See [`HandlerConfig::max_message_size`](crate::handler::HandlerConfig::max_message_size)
+    pub(crate) max_message_size: Option<usize>,
+
+    /// See [`HandlerConfig::trace_protocol`](crate::handler::HandlerConfig::trace_protocol)
+    pub(crate) trace_protocol: bool,
+    /// See [`HandlerConfig::trace_payload_limit`](crate::handler::HandlerConfig::trace_payload_limit)
+    pub(crate) trace_payload_limit: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -104,6 +157,7 @@ pub struct BrowserConfigBuilder {
     headless: HeadlessMode,
     sandbox: bool,
     window_size: Option<(u32, u32)>,
+    screen: Option<(u32, u32)>,
     port: u16,
     executable: Option<PathBuf>,
     executation_detection: DetectionOptions,
@@ -112,16 +166,30 @@ pub struct BrowserConfigBuilder {
     user_data_dir: Option<PathBuf>,
     incognito: bool,
     launch_timeout: Duration,
+    launch_stderr_limit: usize,
     ignore_https_errors: bool,
     ignore_invalid_events: bool,
     disable_https_first: bool,
     viewport: Option<Viewport>,
+    user_agent: Option<String>,
+    device: Option<String>,
     request_timeout: Duration,
     args: Vec<Arg>,
     disable_default_args: bool,
+    stealth_args: bool,
     request_intercept: bool,
     cache_enabled: bool,
     hidden: bool,
+    disable_automation_controlled: bool,
+    gpu: bool,
+    block_webrtc: bool,
+    channel_capacity: usize,
+    heartbeat_interval: Option<Duration>,
+    idle_timeout: Option<Duration>,
+    blocked_urls: Vec<String>,
+    max_message_size: Option<usize>,
+    trace_protocol: bool,
+    trace_payload_limit: Option<usize>,
 }
 
 impl BrowserConfig {
@@ -132,6 +200,18 @@ impl BrowserConfig {
     pub fn with_executable(path: impl AsRef<Path>) -> Self {
         Self::builder().chrome_executable(path).build().unwrap()
     }
+
+    /// The screen resolution picked by
+    /// [`BrowserConfigBuilder::randomized_viewport`]
+    pub fn screen(&self) -> Option<(u32, u32)> {
+        self.screen
+    }
+
+    /// The device selected with [`BrowserConfigBuilder::device`], whose
+    /// [profile](DevicePreset::profile) pages should be given
+    pub fn device(&self) -> Option<DevicePreset> {
+        self.device
+    }
 }
 
 impl Default for BrowserConfigBuilder {
@@ -140,6 +220,7 @@ impl Default for BrowserConfigBuilder {
             headless: HeadlessMode::False,
             sandbox: true,
             window_size: None,
+            screen: None,
             port: 0,
             executable: None,
             executation_detection: DetectionOptions::default(),
@@ -148,16 +229,30 @@ impl Default for BrowserConfigBuilder {
             user_data_dir: None,
             incognito: false,
             launch_timeout: Duration::from_millis(LAUNCH_TIMEOUT),
+            launch_stderr_limit: LAUNCH_STDERR_LIMIT,
             ignore_https_errors: true,
             ignore_invalid_events: true,
             disable_https_first: false,
             viewport: Some(Default::default()),
+            user_agent: None,
+            device: None,
             request_timeout: Duration::from_millis(REQUEST_TIMEOUT),
             args: Vec::new(),
             disable_default_args: false,
+            stealth_args: false,
             request_intercept: false,
             cache_enabled: true,
             hidden: true,
+            disable_automation_controlled: false,
+            gpu: false,
+            block_webrtc: false,
+            channel_capacity: CHANNEL_CAPACITY,
+            heartbeat_interval: None,
+            idle_timeout: None,
+            blocked_urls: Vec::new(),
+            max_message_size: None,
+            trace_protocol: false,
+            trace_payload_limit: Some(TRACE_PAYLOAD_LIMIT),
         }
     }
 }
@@ -215,6 +310,16 @@ impl BrowserConfigBuilder {
         self
     }
 
+    /// How many trailing bytes of the browser's stderr and stdout to keep while waiting
+    /// for the websocket URL, defaults to [`LAUNCH_STDERR_LIMIT`].
+    ///
+    /// Launch errors such as [`CdpError::LaunchTimeout`](crate::error::CdpError::LaunchTimeout)
+    /// include this output, so it stays bounded if the browser logs a lot.
+    pub fn launch_stderr_limit(mut self, bytes: usize) -> Self {
+        self.launch_stderr_limit = bytes;
+        self
+    }
+
     pub fn request_timeout(mut self, timeout: Duration) -> Self {
         self.request_timeout = timeout;
         self
@@ -230,11 +335,80 @@ impl BrowserConfigBuilder {
         self
     }
 
+    /// Picks a screen resolution from a weighted distribution of real-world
+    /// desktop resolutions, the same one for every `seed`, and launches a
+    /// maximized window with the matching [`window_size`](Self::window_size)
+    /// and [`viewport`](Self::viewport). Seeding each browser of a fleet
+    /// differently avoids them all sharing one viewport.
+    ///
+    /// Give pages a profile with the same screen, see [`BrowserConfig::screen`]:
+    ///
+    /// ```ignore
+    /// let (width, height) = config.screen().unwrap();
+    /// let profile = ChaserProfile::windows().screen(width, height).build();
+    /// ```
+    pub fn randomized_viewport(mut self, seed: u64) -> Self {
+        let (width, height) = viewport::desktop_screen(seed);
+        let window_height = height - TASKBAR_HEIGHT;
+        self.screen = Some((width, height));
+        self.window_size = Some((width, window_height));
+        self.viewport = Some(Viewport {
+            width,
+            height: window_height - BROWSER_UI_HEIGHT,
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Emulates a mobile device: sets the viewport to the device metrics with
+    /// touch emulation and launches the browser with the device's user agent.
+    ///
+    /// A later call to [`viewport`](Self::viewport) overrides the metrics.
+    pub fn device_preset(mut self, preset: DevicePreset) -> Self {
+        self.viewport = Some(preset.viewport());
+        self.user_agent = Some(preset.user_agent());
+        self
+    }
+
+    /// Emulates a device of the [registry](crate::devices) by its name, e.g.
+    /// `"iPhone 13 Pro"`: sets the viewport to the device metrics with touch
+    /// emulation and launches the browser with the user agent of Chrome on
+    /// that device. [`build`](Self::build) fails for unknown names.
+    ///
+    /// Give pages the matching profile of [`BrowserConfig::device`] for a
+    /// consistent `navigator`:
+    ///
+    /// ```ignore
+    /// let profile = config.device().unwrap().profile().build();
+    /// ```
+    ///
+    /// Overrides the metrics of [`viewport`](Self::viewport) and
+    /// [`device_preset`](Self::device_preset). A user agent set by
+    /// `device_preset` is kept, otherwise the device's user agent replaces a
+    /// `--user-agent` argument, as a preset's does.
+    pub fn device(mut self, name: impl Into<String>) -> Self {
+        self.device = Some(name.into());
+        self
+    }
+
     pub fn user_data_dir(mut self, data_dir: impl AsRef<Path>) -> Self {
         self.user_data_dir = Some(data_dir.as_ref().to_path_buf());
         self
     }
 
+    /// Keep the user data (cookies, history, cache, extension state) in a
+    /// stable directory per `name`, so relaunches continue with a warm
+    /// profile instead of a fresh one.
+    ///
+    /// The directory is `chaser-oxide/profiles/<name>` in the user's cache
+    /// directory (e.g. `~/.cache` on Linux) and created on launch. Characters
+    /// of `name` other than ASCII letters, digits, `-` and `_` are replaced by
+    /// `_`. Chrome locks its user data dir, so browsers running at the same
+    /// time must not share a name.
+    pub fn persistent_profile(self, name: &str) -> Self {
+        self.user_data_dir(persistent_profile_dir(name))
+    }
+
     pub fn chrome_executable(mut self, path: impl AsRef<Path>) -> Self {
         self.executable = Some(path.as_ref().to_path_buf());
         self
@@ -301,6 +475,21 @@ impl BrowserConfigBuilder {
         self
     }
 
+    /// Launch with arguments closer to a regular Chrome start instead of the
+    /// Puppeteer-derived defaults, which are a well known automation
+    /// fingerprint.
+    ///
+    /// Drops the default arguments a page can observe or that only
+    /// automation tools pass, see `STEALTH_ARGS` for each of them, among
+    /// them `--password-store=basic`, `--use-mock-keychain` and
+    /// `--disable-popup-blocking`. Arguments added with
+    /// [`BrowserConfigBuilder::arg`] are kept, and `--enable-automation` is
+    /// never passed by default.
+    pub fn stealth_args(mut self) -> Self {
+        self.stealth_args = true;
+        self
+    }
+
     pub fn disable_https_first(mut self) -> Self {
         self.disable_https_first = true;
         self
@@ -331,17 +520,136 @@ impl BrowserConfigBuilder {
         self
     }
 
+    /// Only disable the `AutomationControlled` blink feature, which sets
+    /// `navigator.webdriver`, independently of [`hide`](Self::hide), e.g. to
+    /// combine it with the `navigator.webdriver` override of a
+    /// [`ChaserProfile`](crate::ChaserProfile).
+    pub fn disable_automation_controlled(mut self) -> Self {
+        self.disable_automation_controlled = true;
+        self
+    }
+
+    /// Force hardware-accelerated GL through ANGLE, so that WebGL is not
+    /// rendered by SwiftShader.
+    ///
+    /// Headless Chrome falls back to software rendering by default, which
+    /// fingerprinting scripts detect from the renderer string and from render
+    /// timings regardless of any spoofed vendor. This selects the native ANGLE
+    /// backend of the platform (Direct3D 11 on Windows, Metal on macOS and
+    /// Vulkan on Linux) and ignores the GPU blocklist.
+    ///
+    /// Only the new headless mode ([`new_headless_mode`](Self::new_headless_mode))
+    /// and headful mode can use the GPU, the old headless mode always renders
+    /// in software. On other platforms this logs a warning and does nothing.
+    pub fn enable_gpu(mut self) -> Self {
+        self.gpu = true;
+        self
+    }
+
+    /// Keep WebRTC from leaking the IP addresses of the machine, with
+    /// `--force-webrtc-ip-handling-policy=disable_non_proxied_udp`.
+    ///
+    /// WebRTC then only sends UDP through a proxy, so no host or STUN
+    /// candidates are gathered, whatever scripts do. Combine it with
+    /// [`ChaserPage::disable_webrtc`](crate::ChaserPage::disable_webrtc),
+    /// which applies the same restriction from within the page. An explicit
+    /// `force-webrtc-ip-handling-policy` [arg](Self::arg) is kept.
+    pub fn block_webrtc(mut self) -> Self {
+        self.block_webrtc = true;
+        self
+    }
+
+    /// How many commands the channels to the handler buffer before callers
+    /// have to wait, defaults to [`CHANNEL_CAPACITY`].
+    ///
+    /// See [`HandlerConfig::channel_capacity`](crate::handler::HandlerConfig::channel_capacity)
+    /// for the tradeoff.
+    pub fn channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = capacity;
+        self
+    }
+
+    /// End the handler with [`CdpError::ConnectionClosed`](crate::error::CdpError::ConnectionClosed)
+    /// if the browser doesn't answer a heartbeat within `interval`, disabled
+    /// by default.
+    ///
+    /// See [`HandlerConfig::heartbeat_interval`](crate::handler::HandlerConfig::heartbeat_interval).
+    pub fn heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = Some(interval);
+        self
+    }
+
+    /// Close the browser once no command was sent for `timeout`, `None`
+    /// (the default) keeps it open until closed explicitly.
+    ///
+    /// See [`HandlerConfig::idle_timeout`](crate::handler::HandlerConfig::idle_timeout).
+    pub fn idle_timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.idle_timeout = timeout.into();
+        self
+    }
+
+    /// Block all requests to these domains and their subdomains in every
+    /// page, e.g. analytics and trackers, so they fail fast.
+    ///
+    /// The requests fail with `net::ERR_BLOCKED_BY_CLIENT` via
+    /// `Network.setBlockedURLs`, use [`Page::set_blocked_urls`](crate::Page::set_blocked_urls)
+    /// to change the blocked patterns of a page later on.
+    pub fn block_domains(mut self, domains: &[&str]) -> Self {
+        for domain in domains {
+            self.blocked_urls.push(format!("*://{domain}/*"));
+            self.blocked_urls.push(format!("*://*.{domain}/*"));
+        }
+        self
+    }
+
+    /// The largest websocket message in bytes accepted from the browser,
+    /// unlimited by default.
+    ///
+    /// See [`HandlerConfig::max_message_size`](crate::handler::HandlerConfig::max_message_size)
+    /// for the memory implications.
+    pub fn max_message_size(mut self, size: impl Into<Option<usize>>) -> Self {
+        self.max_message_size = size.into();
+        self
+    }
+
+    /// Log the CDP traffic at `TRACE` level for debugging.
+    ///
+    /// See [`HandlerConfig::trace_protocol`](crate::handler::HandlerConfig::trace_protocol).
+    pub fn trace_protocol(mut self, enabled: bool) -> Self {
+        self.trace_protocol = enabled;
+        self
+    }
+
+    /// How many bytes of a payload the protocol trace logs, defaults to
+    /// [`TRACE_PAYLOAD_LIMIT`], `None` logs them completely.
+    pub fn trace_payload_limit(mut self, bytes: impl Into<Option<usize>>) -> Self {
+        self.trace_payload_limit = bytes.into();
+        self
+    }
+
     pub fn build(self) -> std::result::Result<BrowserConfig, String> {
         let executable = if let Some(e) = self.executable {
             e
         } else {
             detection::default_executable(self.executation_detection)?
         };
+        let device = self
+            .device
+            .map(|name| devices::get(&name).ok_or_else(|| format!("unknown device `{name}`")))
+            .transpose()?;
+        let (viewport, user_agent) = match device {
+            Some(device) => (
+                Some(device.viewport()),
+                self.user_agent.or_else(|| Some(device.user_agent())),
+            ),
+            None => (self.viewport, self.user_agent),
+        };
 
         Ok(BrowserConfig {
             headless: self.headless,
             sandbox: self.sandbox,
             window_size: self.window_size,
+            screen: self.screen,
             port: self.port,
             executable,
             extensions: self.extensions,
@@ -349,30 +657,143 @@ impl BrowserConfigBuilder {
             user_data_dir: self.user_data_dir,
             incognito: self.incognito,
             launch_timeout: self.launch_timeout,
+            launch_stderr_limit: self.launch_stderr_limit,
             ignore_https_errors: self.ignore_https_errors,
             ignore_invalid_messages: self.ignore_invalid_events,
             disable_https_first: self.disable_https_first,
-            viewport: self.viewport,
+            viewport,
+            user_agent,
+            device,
             request_timeout: self.request_timeout,
             args: self.args,
             disable_default_args: self.disable_default_args,
+            stealth_args: self.stealth_args,
             request_intercept: self.request_intercept,
             cache_enabled: self.cache_enabled,
             hidden: self.hidden,
+            disable_automation_controlled: self.disable_automation_controlled,
+            gpu: self.gpu,
+            block_webrtc: self.block_webrtc,
+            channel_capacity: self.channel_capacity,
+            heartbeat_interval: self.heartbeat_interval,
+            idle_timeout: self.idle_timeout,
+            blocked_urls: self.blocked_urls,
+            max_message_size: self.max_message_size,
+            trace_protocol: self.trace_protocol,
+            trace_payload_limit: self.trace_payload_limit,
         })
     }
 }
 
+/// The user data dir of [`BrowserConfigBuilder::persistent_profile`]
+fn persistent_profile_dir(name: &str) -> PathBuf {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    cache_dir().join("chaser-oxide").join("profiles").join(name)
+}
+
+/// The per-user cache directory of the platform, or the temp dir if it is
+/// unknown
+fn cache_dir() -> PathBuf {
+    let var = |name| {
+        std::env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    };
+    let dir = if cfg!(windows) {
+        var("LOCALAPPDATA")
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| home.join("Library").join("Caches"))
+    } else {
+        var("XDG_CACHE_HOME").or_else(|| var("HOME").map(|home| home.join(".cache")))
+    };
+    dir.unwrap_or_else(std::env::temp_dir)
+}
+
+/// The name of the snap package `executable` belongs to, for paths like
+/// `/snap/bin/chromium` or `/snap/chromium/current/usr/lib/chromium-browser/chrome`
+fn snap_name(executable: &Path) -> Option<&str> {
+    let mut components = executable.strip_prefix("/snap").ok()?.iter();
+    match components.next()?.to_str()? {
+        // apps besides the main one are named `<snap>.<app>`
+        "bin" => components.next()?.to_str()?.split('.').next(),
+        name => Some(name),
+    }
+}
+
+/// A user data dir a snap confined `executable` can write to, `None` if it
+/// isn't a snap
+fn snap_user_data_dir(executable: &Path, home: &Path) -> Option<PathBuf> {
+    let snap = snap_name(executable)?;
+    Some(
+        home.join("snap")
+            .join(snap)
+            .join("common")
+            .join("chromiumoxide-runner"),
+    )
+}
+
 impl BrowserConfig {
+    /// Move the default user data dir of a snap confined browser to the
+    /// snap's home directory, the browser can't write to the system temp dir
+    /// and exits right after launch otherwise.
+    pub(crate) fn adjust_for_snap(&mut self) {
+        if self.user_data_dir.is_some() {
+            return;
+        }
+        let Some(home) = std::env::var_os("HOME").filter(|home| !home.is_empty()) else {
+            return;
+        };
+        if let Some(dir) = snap_user_data_dir(&self.executable, Path::new(&home)) {
+            tracing::warn!(
+                "{} is confined by snap, using the user data dir {} instead of one in the temp dir",
+                self.executable.display(),
+                dir.display()
+            );
+            self.user_data_dir = Some(dir);
+        }
+    }
+
     pub fn launch(&self) -> io::Result<Child> {
+        if let Some(ref user_data) = self.user_data_dir {
+            std::fs::create_dir_all(user_data)?;
+        }
+        let mut cmd = async_process::Command::new(&self.executable);
+
+        cmd.args(self.command_args());
+
+        if let Some(ref envs) = self.process_envs {
+            cmd.envs(envs);
+        }
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()
+    }
+
+    /// The command line arguments the browser is launched with
+    pub(crate) fn command_args(&self) -> Vec<String> {
         let mut builder = ArgsBuilder::new();
 
         if self.disable_default_args {
             builder.args(self.args.clone());
+        } else if self.stealth_args {
+            builder.args(STEALTH_ARGS.clone()).args(self.args.clone());
         } else {
             builder.args(DEFAULT_ARGS.clone()).args(self.args.clone());
         }
 
+        if let Some(ref user_agent) = self.user_agent {
+            builder
+                .remove("user-agent")
+                .arg(Arg::value("user-agent", user_agent));
+        }
+
         if !builder.has("remote-debugging-port") {
             builder.arg(Arg::value("remote-debugging-port", self.port));
         }
@@ -429,7 +850,7 @@ impl BrowserConfig {
             builder.arg(Arg::key("incognito"));
         }
 
-        if self.hidden {
+        if self.hidden || self.disable_automation_controlled {
             builder.arg(Arg::value("disable-blink-features", "AutomationControlled"));
         }
 
@@ -440,16 +861,58 @@ impl BrowserConfig {
             ));
         }
 
-        let mut cmd = async_process::Command::new(&self.executable);
-
-        let args = builder.into_iter().collect::<Vec<String>>();
-        cmd.args(args);
+        if self.block_webrtc && !builder.has("force-webrtc-ip-handling-policy") {
+            builder.arg(Arg::value(
+                "force-webrtc-ip-handling-policy",
+                "disable_non_proxied_udp",
+            ));
+        }
 
-        if let Some(ref envs) = self.process_envs {
-            cmd.envs(envs);
+        if self.gpu {
+            match gpu_args() {
+                Some(args) => {
+                    if self.headless == HeadlessMode::True {
+                        tracing::warn!(
+                            "The old headless mode always renders in software, use the new headless mode to enable the GPU"
+                        );
+                    }
+                    builder
+                        .remove("disable-gpu")
+                        .remove("enable-unsafe-swiftshader")
+                        .args(args);
+                }
+                None => {
+                    tracing::warn!("Hardware-accelerated GL is not available on this platform")
+                }
+            }
         }
-        cmd.stderr(Stdio::piped()).spawn()
+
+        builder.into_iter().collect()
+    }
+}
+
+/// The arguments selecting the native ANGLE backend of this platform
+fn gpu_args() -> Option<Vec<Arg>> {
+    let backend = if cfg!(target_os = "windows") {
+        "d3d11"
+    } else if cfg!(target_os = "macos") {
+        "metal"
+    } else if cfg!(target_os = "linux") {
+        "vulkan"
+    } else {
+        return None;
+    };
+
+    let mut args = vec![
+        Arg::value("use-gl", "angle"),
+        Arg::value("use-angle", backend),
+        Arg::key("ignore-gpu-blocklist"),
+        Arg::key("enable-gpu-rasterization"),
+    ];
+    if backend == "vulkan" {
+        args.push(Arg::values("enable-features", ["Vulkan"]));
     }
+    Some(args)
 }
 
 /// These are passed to the Chrome binary by default.
@@ -483,3 +946,306 @@ static DEFAULT_ARGS: [ArgConst; 24] = [
     ArgConst::values("enable-blink-features", &["IdleDetection"]),
     ArgConst::values("lang", &["en_US"]),
 ];
+
+/// The defaults of [`BrowserConfigBuilder::stealth_args`], the subset of
+/// `DEFAULT_ARGS` a page can't tell apart from a regular start.
+///
+/// Dropped from `DEFAULT_ARGS`:
+/// - `--password-store=basic`, `--use-mock-keychain`: only passed by
+///   automation tools
+/// - `--disable-popup-blocking`: `window.open` without a user gesture
+///   succeeds, which no regular browser allows
+/// - `--enable-blink-features=IdleDetection`: exposes an API that needs a
+///   permission in regular browsers
+/// - `--force-color-profile=srgb`: shows in the `color-gamut` media query
+/// - `--disable-ipc-flooding-protection`: lifts the rate limits of e.g.
+///   `history.pushState`
+/// - `--enable-features=NetworkService,NetworkServiceInProcess`,
+///   `--metrics-recording-only`, `--disable-sync`, `--disable-default-apps`,
+///   `--disable-component-extensions-with-background-pages`,
+///   `--disable-client-side-phishing-detection`,
+///   `--disable-background-networking`, `--disable-prompt-on-repost`: part
+///   of the recognizable Puppeteer set, without use for scraping
+///
+/// The background throttling switches stay, so pages that aren't in front
+/// keep running. So does the `--user-agent`, without it headless Chrome
+/// reports itself as `HeadlessChrome`.
+static STEALTH_ARGS: [ArgConst; 10] = [
+    ArgConst::key("disable-background-timer-throttling"),
+    ArgConst::key("disable-backgrounding-occluded-windows"),
+    ArgConst::key("disable-renderer-backgrounding"),
+    ArgConst::key("disable-breakpad"),
+    ArgConst::key("disable-dev-shm-usage"),
+    ArgConst::values("disable-features", &["TranslateUI"]),
+    ArgConst::key("disable-hang-monitor"),
+    ArgConst::key("no-first-run"),
+    ArgConst::values("user-agent", &["Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/129.0.0.0 Safari/537.36"]),
+    ArgConst::values("lang", &["en_US"]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> BrowserConfigBuilder {
+        BrowserConfig::builder().chrome_executable("chrome")
+    }
+
+    #[test]
+    fn snap_browser_gets_writable_user_data_dir() {
+        let home = Path::new("/home/user");
+        assert_eq!(
+            snap_user_data_dir(Path::new("/snap/bin/chromium"), home),
+            Some(PathBuf::from(
+                "/home/user/snap/chromium/common/chromiumoxide-runner"
+            ))
+        );
+        assert_eq!(
+            snap_user_data_dir(
+                Path::new("/snap/chromium/current/usr/lib/chromium-browser/chrome"),
+                home
+            ),
+            Some(PathBuf::from(
+                "/home/user/snap/chromium/common/chromiumoxide-runner"
+            ))
+        );
+        assert_eq!(
+            snap_name(Path::new("/snap/bin/chromium.chromedriver")),
+            Some("chromium")
+        );
+        assert_eq!(
+            snap_user_data_dir(Path::new("/usr/bin/chromium"), home),
+            None
+        );
+        assert_eq!(snap_user_data_dir(Path::new("/snap/bin"), home), None);
+
+        let mut config = config()
+            .chrome_executable("/snap/bin/chromium")
+            .user_data_dir("/data")
+            .build()
+            .unwrap();
+        config.adjust_for_snap();
+        assert_eq!(config.user_data_dir, Some(PathBuf::from("/data")));
+    }
+
+    #[test]
+    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+    fn enable_gpu_adds_angle_args() {
+        let args = config()
+            .new_headless_mode()
+            .arg("disable-gpu")
+            .enable_gpu()
+            .build()
+            .unwrap()
+            .command_args();
+
+        assert!(args.contains(&"--use-gl=angle".to_string()));
+        assert!(args.iter().any(|arg| arg.starts_with("--use-angle=")));
+        assert!(args.contains(&"--ignore-gpu-blocklist".to_string()));
+        assert!(!args.contains(&"--disable-gpu".to_string()));
+    }
+
+    #[test]
+    fn gpu_is_not_forced_by_default() {
+        let args = config().build().unwrap().command_args();
+        assert!(!args.iter().any(|arg| arg.starts_with("--use-angle")));
+    }
+
+    #[test]
+    fn block_webrtc_keeps_an_explicit_policy() {
+        let policy = |builder: BrowserConfigBuilder| {
+            builder
+                .build()
+                .unwrap()
+                .command_args()
+                .into_iter()
+                .filter(|arg| arg.starts_with("--force-webrtc-ip-handling-policy"))
+                .collect::<Vec<_>>()
+        };
+        assert!(policy(config()).is_empty());
+        assert_eq!(
+            policy(config().block_webrtc()),
+            ["--force-webrtc-ip-handling-policy=disable_non_proxied_udp"]
+        );
+        assert_eq!(
+            policy(config().block_webrtc().arg((
+                "force-webrtc-ip-handling-policy",
+                "default_public_interface_only"
+            ))),
+            ["--force-webrtc-ip-handling-policy=default_public_interface_only"]
+        );
+    }
+
+    #[test]
+    fn device_preset_replaces_user_agent() {
+        let args = config()
+            .device_preset(DevicePreset::Pixel7)
+            .build()
+            .unwrap()
+            .command_args();
+
+        let user_agents: Vec<_> = args
+            .iter()
+            .filter(|arg| arg.starts_with("--user-agent="))
+            .collect();
+        assert_eq!(
+            user_agents,
+            [&format!(
+                "--user-agent={}",
+                DevicePreset::Pixel7.user_agent()
+            )]
+        );
+    }
+
+    #[test]
+    fn device_sets_viewport_and_user_agent() {
+        let iphone = config().device("iPhone 13 Pro").build().unwrap();
+        let viewport = iphone.viewport.clone().unwrap();
+        assert_eq!((viewport.width, viewport.height), (390, 844));
+        assert_eq!(viewport.device_scale_factor, Some(3.));
+        assert!(viewport.emulating_mobile && viewport.has_touch);
+
+        let device = iphone.device().unwrap();
+        assert_eq!(device, DevicePreset::IPhone13Pro);
+        assert_eq!(device.profile().build().platform(), "iPhone");
+        let user_agent = format!("--user-agent={}", device.user_agent());
+        assert!(user_agent.contains("iPhone"));
+        assert!(iphone.command_args().contains(&user_agent));
+
+        // the user agent of a preset is kept
+        let pixel = config()
+            .device_preset(DevicePreset::Pixel7)
+            .device("iPhone 13 Pro")
+            .build()
+            .unwrap();
+        assert_eq!(pixel.user_agent, Some(DevicePreset::Pixel7.user_agent()));
+        assert_eq!(pixel.viewport.unwrap().width, 390);
+
+        let err = config().device("Nokia 3310").build().unwrap_err();
+        assert_eq!(err, "unknown device `Nokia 3310`");
+    }
+
+    #[test]
+    fn randomized_viewport_is_seeded() {
+        let launch = |seed| {
+            let config = config().randomized_viewport(seed).build().unwrap();
+            let (width, height) = config.screen().unwrap();
+            let viewport = config.viewport.clone().unwrap();
+            assert!((1280..=2560).contains(&width) && (720..=1440).contains(&height));
+            assert_eq!(viewport.width, width);
+            assert!(viewport.height < height);
+            let window_size = format!("--window-size={},{}", width, height - TASKBAR_HEIGHT);
+            assert!(config.command_args().contains(&window_size));
+            (width, height)
+        };
+
+        assert_eq!(launch(7), launch(7));
+        assert_ne!(launch(1), launch(2));
+        // a seed keeps its resolution across releases
+        assert_eq!(launch(7), (1360, 768));
+        assert_eq!(launch(42), (1280, 1024));
+    }
+
+    #[test]
+    fn automation_controlled_is_disabled_once() {
+        let blink_features = |hidden: bool, standalone: bool| {
+            let mut builder = config().arg(("disable-blink-features", "AutomationControlled"));
+            builder.hidden = hidden;
+            if standalone {
+                builder = builder.disable_automation_controlled();
+            }
+            builder
+                .build()
+                .unwrap()
+                .command_args()
+                .into_iter()
+                .filter(|arg| arg.starts_with("--disable-blink-features"))
+                .collect::<Vec<_>>()
+        };
+        let disabled = ["--disable-blink-features=AutomationControlled".to_string()];
+
+        for (hidden, standalone) in [(true, false), (false, true), (true, true), (false, false)] {
+            assert_eq!(blink_features(hidden, standalone), disabled);
+        }
+
+        let mut builder = config();
+        builder.hidden = false;
+        let args = builder.build().unwrap().command_args();
+        assert!(!args.iter().any(|arg| arg.contains("AutomationControlled")));
+        let args = config()
+            .disable_automation_controlled()
+            .build()
+            .unwrap()
+            .command_args();
+        assert!(args.contains(&disabled[0]));
+    }
+
+    #[test]
+    fn stealth_args_omit_automation_tells() {
+        let args = config()
+            .stealth_args()
+            .arg("disable-popup-blocking")
+            .build()
+            .unwrap()
+            .command_args();
+
+        for tell in [
+            "--enable-automation",
+            "--password-store=basic",
+            "--use-mock-keychain",
+            "--enable-blink-features=IdleDetection",
+            "--force-color-profile=srgb",
+            "--metrics-recording-only",
+        ] {
+            assert!(!args.contains(&tell.to_string()), "{tell} is passed");
+        }
+        let user_agent = args
+            .iter()
+            .find(|arg| arg.starts_with("--user-agent="))
+            .expect("headless needs a user agent");
+        assert!(!user_agent.contains("HeadlessChrome"));
+        assert!(args.contains(&"--no-first-run".to_string()));
+        // explicit arguments are kept
+        assert!(args.contains(&"--disable-popup-blocking".to_string()));
+    }
+
+    #[test]
+    fn block_domains_blocks_subdomains() {
+        let config = config()
+            .block_domains(&["doubleclick.net", "example.com"])
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.blocked_urls,
+            vec![
+                "*://doubleclick.net/*",
+                "*://*.doubleclick.net/*",
+                "*://example.com/*",
+                "*://*.example.com/*",
+            ]
+        );
+    }
+
+    #[test]
+    fn persistent_profile_dir_is_stable_per_name() {
+        let dir = config()
+            .persistent_profile("shop")
+            .build()
+            .unwrap()
+            .user_data_dir;
+        assert_eq!(
+            dir,
+            config()
+                .persistent_profile("shop")
+                .build()
+                .unwrap()
+                .user_data_dir
+        );
+        let dir = dir.unwrap();
+        assert!(dir.ends_with(Path::new("chaser-oxide").join("profiles").join("shop")));
+
+        let other = persistent_profile_dir("../shop/1");
+        assert_eq!(other.file_name().unwrap(), "___shop_1");
+        assert_eq!(other.parent(), dir.parent());
+    }
+}
@@ -0,0 +1,150 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::channel::mpsc::Sender;
+use futures::channel::oneshot::channel as oneshot_channel;
+use futures::lock::Mutex;
+use futures::SinkExt;
+
+use chromiumoxide_cdp::cdp::browser_protocol::target::{
+    CloseTargetParams, CreateTargetParams, TargetId,
+};
+use chromiumoxide_types::{Command, CommandResponse};
+
+use crate::cmd::{to_command_response, CommandMessage};
+use crate::error::{CdpError, Result};
+use crate::handler::browser::BrowserContext;
+use crate::handler::HandlerMessage;
+use crate::page::Page;
+
+/// How often the creation of a page is retried after it timed out
+const NEW_PAGE_RETRIES: usize = 2;
+
+/// A cheap to clone handle to the [`Handler`](crate::Handler) of a
+/// [`Browser`](crate::Browser), returned by
+/// [`Browser::handle`](crate::Browser::handle).
+///
+/// Each handle owns a sender of its own, so tasks holding different handles
+/// wait for room in the channel to the handler independently of each other,
+/// and none of them needs the `Browser` or an `Arc` of it. Pages are created
+/// in the browser context the `Browser` used when the handle was made, and
+/// one at a time across the browser and all of its handles.
+///
+/// ```ignore
+/// let handle = browser.handle();
+/// let task = tokio::spawn(async move {
+///     let page = handle.new_page("https://example.com").await?;
+///     page.content().await
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct BrowserHandle {
+    sender: Sender<HandlerMessage>,
+    browser_context: BrowserContext,
+    page_creation: Arc<Mutex<()>>,
+}
+
+impl BrowserHandle {
+    pub(crate) fn new(
+        sender: Sender<HandlerMessage>,
+        browser_context: BrowserContext,
+        page_creation: Arc<Mutex<()>>,
+    ) -> Self {
+        Self {
+            sender,
+            browser_context,
+            page_creation,
+        }
+    }
+
+    /// Create a new browser page, see [`Browser::new_page`](crate::Browser::new_page)
+    pub async fn new_page(&self, params: impl Into<CreateTargetParams>) -> Result<Page> {
+        let params = self.page_params(params.into());
+        let _creation = self.page_creation.lock().await;
+        self.create_page_with_retries(params).await
+    }
+
+    /// Call a browser method.
+    pub async fn execute<T: Command>(&self, cmd: T) -> Result<CommandResponse<T::Response>> {
+        self.execute_with(cmd, None).await
+    }
+
+    /// Close the page of the target, see [`Browser::close_page`](crate::Browser::close_page)
+    pub async fn close_page(&self, target_id: TargetId) -> Result<()> {
+        self.execute(CloseTargetParams::new(target_id.clone()))
+            .await?;
+        self.sender
+            .clone()
+            .send(HandlerMessage::DisposeTarget(target_id))
+            .await?;
+        Ok(())
+    }
+
+    /// Return all of the pages of the browser
+    pub async fn pages(&self) -> Result<Vec<Page>> {
+        let (tx, rx) = oneshot_channel();
+        self.sender
+            .clone()
+            .send(HandlerMessage::GetPages(tx))
+            .await?;
+        Ok(rx.await?)
+    }
+
+    pub(crate) async fn execute_with<T: Command>(
+        &self,
+        cmd: T,
+        timeout: Option<Duration>,
+    ) -> Result<CommandResponse<T::Response>> {
+        let (tx, rx) = oneshot_channel();
+        let method = cmd.identifier();
+        let mut msg = CommandMessage::new(cmd, tx)?;
+        msg.timeout = timeout;
+
+        self.sender
+            .clone()
+            .send(HandlerMessage::Command(msg))
+            .await?;
+        let resp = rx.await??;
+        to_command_response::<T>(resp, method)
+    }
+
+    /// Serializes the target creation of `new_page` and `new_pages`
+    pub(crate) fn page_creation(&self) -> &Mutex<()> {
+        &self.page_creation
+    }
+
+    /// Puts the page into the context of the browser unless it has one
+    pub(crate) fn page_params(&self, mut params: CreateTargetParams) -> CreateTargetParams {
+        if let Some(id) = self.browser_context.id() {
+            if params.browser_context_id.is_none() {
+                params.browser_context_id = Some(id.clone());
+            }
+        }
+        params
+    }
+
+    pub(crate) async fn create_page_with_retries(
+        &self,
+        params: CreateTargetParams,
+    ) -> Result<Page> {
+        let mut retries = 0;
+        loop {
+            match self.create_page(params.clone()).await {
+                // the handler closes targets whose announcement or
+                // initialization timed out
+                Err(CdpError::Timeout) if retries < NEW_PAGE_RETRIES => retries += 1,
+                page => return page,
+            }
+        }
+    }
+
+    async fn create_page(&self, params: CreateTargetParams) -> Result<Page> {
+        let (tx, rx) = oneshot_channel();
+        self.sender
+            .clone()
+            .send(HandlerMessage::CreatePage(params, tx))
+            .await?;
+
+        rx.await?
+    }
+}
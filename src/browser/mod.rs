@@ -1,10 +1,16 @@
 use std::future::Future;
 use std::io;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex as SyncMutex};
+use std::time::Duration;
 
 use futures::channel::mpsc::{channel, unbounded, Sender};
 use futures::channel::oneshot::channel as oneshot_channel;
+use futures::future::join_all;
+use futures::lock::Mutex;
 use futures::select;
-use futures::SinkExt;
+use futures::{SinkExt, StreamExt};
 
 use chromiumoxide_cdp::cdp::browser_protocol::browser::{
     BrowserContextId, CloseReturns, GetVersionParams, GetVersionReturns,
@@ -14,15 +20,18 @@ use chromiumoxide_cdp::cdp::browser_protocol::storage::{
     ClearCookiesParams, GetCookiesParams, SetCookiesParams,
 };
 use chromiumoxide_cdp::cdp::browser_protocol::target::{
-    CreateBrowserContextParams, CreateTargetParams, DisposeBrowserContextParams, TargetId,
-    TargetInfo,
+    CreateBrowserContextParams, CreateTargetParams, DisposeBrowserContextParams,
+    EventTargetCreated, TargetId, TargetInfo,
 };
 use chromiumoxide_cdp::cdp::{CdpEventMessage, IntoEventKind};
 use chromiumoxide_types::*;
 
 pub use self::config::{BrowserConfig, BrowserConfigBuilder, LAUNCH_TIMEOUT};
+pub use self::handle::BrowserHandle;
+pub use self::session::{OriginStorage, SessionSnapshot, SESSION_FORMAT_VERSION};
 use crate::async_process::{Child, ExitStatus};
-use crate::cmd::{to_command_response, CommandMessage};
+use crate::chaser::ChaserPage;
+use crate::cmd::CommandMessage;
 use crate::conn::Connection;
 use crate::error::{BrowserStderr, CdpError, Result};
 use crate::handler::browser::BrowserContext;
@@ -33,6 +42,8 @@ use crate::utils;
 
 mod argument;
 mod config;
+mod handle;
+mod session;
 
 /// A [`Browser`] is created when chromiumoxide connects to a Chromium instance.
 #[derive(Debug)]
@@ -48,8 +59,35 @@ pub struct Browser {
     debug_ws_url: String,
     /// The context of the browser
     browser_context: BrowserContext,
+    /// Serializes the target creation of `new_page` and `new_pages`, shared
+    /// with the handles of the browser
+    page_creation: Arc<Mutex<()>>,
+    /// Contexts created through this browser that weren't disposed yet
+    contexts: SyncMutex<Vec<TrackedContext>>,
 }
 
+/// A browser context created through a [`Browser`]
+#[derive(Debug, Clone)]
+struct TrackedContext {
+    id: BrowserContextId,
+    /// Whether the context was started by `start_incognito_context`, these
+    /// are disposed when a connected browser is dropped
+    ephemeral: bool,
+}
+
+/// How long [`Browser::first_page`] waits for the initial tab
+const FIRST_PAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often [`Browser::first_page`] checks whether the initial tab is ready
+const FIRST_PAGE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How often [`Browser::wait_for_new_page`] checks whether the new page is
+/// ready
+const NEW_PAGE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How often [`Browser::wait_for_cookie`] checks the cookies
+const COOKIE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// Browser connection information.
 #[derive(serde::Deserialize, Debug, Default)]
 pub struct BrowserConnection {
@@ -73,6 +111,45 @@ pub struct BrowserConnection {
     pub web_socket_debugger_url: String,
 }
 
+/// A target as listed by the `/json/list` endpoint of the remote debugging
+/// port, see [`Browser::list_targets_http`]
+#[derive(serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetDescriptor {
+    /// The target id, the same as the `TargetId` of the target's `Page`
+    pub id: String,
+    /// `page`, `iframe`, `service_worker`, `background_page`, ...
+    #[serde(rename = "type")]
+    pub target_type: String,
+    pub url: String,
+    pub title: String,
+    /// The websocket URL to attach to this target only, see
+    /// [`Page::attach`](crate::Page::attach). Chromium omits it while
+    /// another DevTools frontend is attached to the target.
+    #[serde(default)]
+    pub web_socket_debugger_url: Option<String>,
+}
+
+/// The URL of the `/json/<endpoint>` of the remote debugging port at `url`,
+/// which may point to the port or to one of its `/json/*` endpoints
+fn json_endpoint(url: &str, endpoint: &str) -> String {
+    let base = url.trim_end_matches('/');
+    let base = ["/json/version", "/json/list", "/json"]
+        .iter()
+        .find_map(|suffix| base.strip_suffix(suffix))
+        .unwrap_or(base);
+    format!("{base}/json/{endpoint}")
+}
+
+/// Point a websocket URL the browser reported for `127.0.0.1` to the address
+/// that answered, so a browser behind a proxy is reached at the proxy
+fn rewrite_local_ip(ws_url: &str, remote: Option<SocketAddr>) -> String {
+    match remote {
+        Some(addr) => ws_url.replace("127.0.0.1", &addr.ip().to_string()),
+        None => ws_url.to_string(),
+    }
+}
+
 impl Browser {
     /// Connect to an already running chromium instance via the given URL.
     ///
@@ -92,43 +169,34 @@ impl Browser {
 
         if debug_ws_url.starts_with("http") {
             match reqwest::Client::new()
-                .get(
-                    if debug_ws_url.ends_with("/json/version")
-                        || debug_ws_url.ends_with("/json/version/")
-                    {
-                        debug_ws_url.clone()
-                    } else {
-                        format!(
-                            "{}{}json/version",
-                            &debug_ws_url,
-                            if debug_ws_url.ends_with('/') { "" } else { "/" }
-                        )
-                    },
-                )
+                .get(json_endpoint(&debug_ws_url, "version"))
                 .header("content-type", "application/json")
                 .send()
                 .await
             {
                 Ok(req) => {
-                    let socketaddr = req.remote_addr().unwrap();
+                    let socketaddr = req.remote_addr();
                     let connection: BrowserConnection =
                         serde_json::from_slice(&req.bytes().await.unwrap_or_default())
                             .unwrap_or_default();
 
                     if !connection.web_socket_debugger_url.is_empty() {
                         // prevent proxy interfaces from returning local ips to connect to the exact machine
-                        debug_ws_url = connection
-                            .web_socket_debugger_url
-                            .replace("127.0.0.1", &socketaddr.ip().to_string());
+                        debug_ws_url =
+                            rewrite_local_ip(&connection.web_socket_debugger_url, socketaddr);
                     }
                 }
                 Err(_) => return Err(CdpError::NoResponse),
             }
         }
 
-        let conn = Connection::<CdpEventMessage>::connect(&debug_ws_url).await?;
+        let conn = Connection::<CdpEventMessage>::connect_with_limit(
+            &debug_ws_url,
+            config.max_message_size,
+        )
+        .await?;
 
-        let (tx, rx) = channel(1);
+        let (tx, rx) = channel(config.channel_capacity);
 
         let fut = Handler::new(conn, rx, config);
         let browser_context = fut.default_browser_context().clone();
@@ -139,21 +207,57 @@ impl Browser {
             child: None,
             debug_ws_url,
             browser_context,
+            page_creation: Arc::new(Mutex::new(())),
+            contexts: SyncMutex::new(Vec::new()),
         };
         Ok((browser, fut))
     }
 
+    /// List the targets of the browser at `url` through the `/json/list`
+    /// endpoint of its remote debugging port, without connecting to it.
+    ///
+    /// Like [`Browser::connect`], `url` is the `http(s)` address of the port
+    /// and the `127.0.0.1` of the websocket URLs is replaced with the address
+    /// that answered. Unlike [`Browser::fetch_targets`] this doesn't need a
+    /// CDP connection, e.g. to pick a tab to [`Page::attach`](crate::Page::attach) to.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let targets = Browser::list_targets_http("http://10.0.0.5:9222").await?;
+    /// let shop = targets
+    ///     .iter()
+    ///     .find(|target| target.target_type == "page" && target.url.contains("shop"))
+    ///     .and_then(|target| target.web_socket_debugger_url.as_deref());
+    /// ```
+    pub async fn list_targets_http(url: &str) -> Result<Vec<TargetDescriptor>> {
+        let res = reqwest::Client::new()
+            .get(json_endpoint(url, "list"))
+            .send()
+            .await
+            .map_err(|_| CdpError::NoResponse)?;
+        let remote = res.remote_addr();
+        let body = res.bytes().await.map_err(|_| CdpError::NoResponse)?;
+        let mut targets: Vec<TargetDescriptor> = serde_json::from_slice(&body)?;
+        for target in &mut targets {
+            if let Some(ws_url) = target.web_socket_debugger_url.as_mut() {
+                *ws_url = rewrite_local_ip(ws_url, remote);
+            }
+        }
+        Ok(targets)
+    }
+
     /// Launches a new instance of `chromium` in the background and attaches to
     /// its debug web socket.
     ///
     /// This fails when no chromium executable could be detected.
     ///
     /// This fails if no web socket url could be detected from the child
-    /// processes stderr for more than the configured `launch_timeout`
+    /// processes stderr or stdout for more than the configured `launch_timeout`
     /// (20 seconds by default).
     pub async fn launch(mut config: BrowserConfig) -> Result<(Self, Handler)> {
         // Canonalize paths to reduce issues with sandboxing
         config.executable = utils::canonicalize_except_snap(config.executable).await?;
+        config.adjust_for_snap();
 
         // Launch a new chromium instance
         let mut child = config.launch()?;
@@ -166,19 +270,15 @@ impl Browser {
             config: &BrowserConfig,
             child: &mut Child,
         ) -> Result<(String, Connection<CdpEventMessage>)> {
-            let dur = config.launch_timeout;
-            cfg_if::cfg_if! {
-                if #[cfg(feature = "async-std-runtime")] {
-                    let timeout_fut = Box::pin(async_std::task::sleep(dur));
-                } else if #[cfg(feature = "tokio-runtime")] {
-                    let timeout_fut = Box::pin(tokio::time::sleep(dur));
-                } else {
-                    panic!("missing chromiumoxide runtime: enable `async-std-runtime` or `tokio-runtime`")
-                }
-            };
+            let timeout_fut = Box::pin(utils::sleep(config.launch_timeout));
             // extract the ws:
-            let debug_ws_url = ws_url_from_output(child, timeout_fut).await?;
-            let conn = Connection::<CdpEventMessage>::connect(&debug_ws_url).await?;
+            let debug_ws_url =
+                ws_url_from_output(child, timeout_fut, config.launch_stderr_limit).await?;
+            let conn = Connection::<CdpEventMessage>::connect_with_limit(
+                &debug_ws_url,
+                config.max_message_size,
+            )
+            .await?;
             Ok((debug_ws_url, conn))
         }
 
@@ -200,7 +300,7 @@ impl Browser {
         // Only infaillible calls are allowed after this point to avoid clean-up issues with the
         // child process.
 
-        let (tx, rx) = channel(1);
+        let (tx, rx) = channel(config.channel_capacity);
 
         let handler_config = HandlerConfig {
             ignore_https_errors: config.ignore_https_errors,
@@ -210,6 +310,13 @@ impl Browser {
             request_timeout: config.request_timeout,
             request_intercept: config.request_intercept,
             cache_enabled: config.cache_enabled,
+            channel_capacity: config.channel_capacity,
+            heartbeat_interval: config.heartbeat_interval,
+            idle_timeout: config.idle_timeout,
+            blocked_urls: config.blocked_urls.clone(),
+            max_message_size: config.max_message_size,
+            trace_protocol: config.trace_protocol,
+            trace_payload_limit: config.trace_payload_limit,
         };
 
         let fut = Handler::new(conn, rx, handler_config);
@@ -221,6 +328,8 @@ impl Browser {
             child: Some(child),
             debug_ws_url,
             browser_context,
+            page_creation: Arc::new(Mutex::new(())),
+            contexts: SyncMutex::new(Vec::new()),
         };
 
         Ok((browser, fut))
@@ -309,6 +418,27 @@ impl Browser {
         self.child.as_mut()
     }
 
+    /// Take the spawned chromium instance out of this [`Browser`], e.g. to hand
+    /// it to an external supervisor.
+    ///
+    /// Dropping the `Browser` afterwards neither warns nor kills the process,
+    /// and [`Browser::kill`], [`Browser::wait`] and [`Browser::try_wait`] no
+    /// longer have an effect. Like for a browser attached with
+    /// [`Browser::connect`], the drop disposes the incognito contexts this
+    /// `Browser` started instead.
+    ///
+    /// The process is still killed once the returned [`Child`] is dropped:
+    /// the runtimes don't allow to clear the `kill_on_drop` flag after
+    /// spawning. Keep the `Child` for as long as the process should run, or
+    /// [`std::mem::forget`] it to leave the process running on its own, which
+    /// leaves its exit status uncollected.
+    ///
+    /// Returns `None` if this `Browser` did not spawn any chromium instance
+    /// or it was already detached.
+    pub fn detach(&mut self) -> Option<Child> {
+        self.child.take()
+    }
+
     /// Forcibly kill the spawned chromium instance
     ///
     /// The instance is spawned by [`Browser::launch`]. `kill` will automatically wait for the child
@@ -331,16 +461,27 @@ impl Browser {
     /// New pages created while being in incognito mode will also run in the
     /// incognito context. Incognito contexts won't share cookies/cache with
     /// other browser contexts.
+    ///
+    /// The context should be disposed with
+    /// [`Browser::quit_incognito_context`]. If a browser attached with
+    /// [`Browser::connect`] is dropped before, the drop makes a best-effort
+    /// attempt to dispose it, which can't report failures and depends on the
+    /// [`Handler`] still being polled.
     pub async fn start_incognito_context(&mut self) -> Result<&mut Self> {
         if !self.is_incognito_configured() {
             let browser_context_id = self
                 .create_browser_context(CreateBrowserContextParams::default())
                 .await?;
+            if let Some(context) = self
+                .contexts
+                .get_mut()
+                .unwrap()
+                .iter_mut()
+                .find(|ctx| ctx.id == browser_context_id)
+            {
+                context.ephemeral = true;
+            }
             self.browser_context = BrowserContext::from(browser_context_id);
-            self.sender
-                .clone()
-                .send(HandlerMessage::InsertContext(self.browser_context.clone()))
-                .await?;
         }
 
         Ok(self)
@@ -353,11 +494,7 @@ impl Browser {
     /// incognito context.
     pub async fn quit_incognito_context(&mut self) -> Result<&mut Self> {
         if let Some(id) = self.browser_context.take() {
-            self.dispose_browser_context(id.clone()).await?;
-            self.sender
-                .clone()
-                .send(HandlerMessage::DisposeContext(BrowserContext::from(id)))
-                .await?;
+            self.dispose_browser_context(id).await?;
         }
         Ok(self)
     }
@@ -386,21 +523,54 @@ impl Browser {
     }
 
     /// Create a new browser page
+    ///
+    /// Concurrent calls are queued, so that only one target is created at a
+    /// time. A creation that timed out is retried.
     pub async fn new_page(&self, params: impl Into<CreateTargetParams>) -> Result<Page> {
-        let (tx, rx) = oneshot_channel();
-        let mut params = params.into();
-        if let Some(id) = self.browser_context.id() {
-            if params.browser_context_id.is_none() {
-                params.browser_context_id = Some(id.clone());
-            }
-        }
+        self.handle().new_page(params).await
+    }
 
-        self.sender
-            .clone()
-            .send(HandlerMessage::CreatePage(params, tx))
-            .await?;
+    /// Create a new page in the given browser context, e.g. one returned by
+    /// [`Browser::create_browser_context`], without switching the context
+    /// `new_page` uses
+    pub async fn new_page_in_context(
+        &self,
+        context_id: BrowserContextId,
+        url: impl Into<String>,
+    ) -> Result<Page> {
+        let mut params = CreateTargetParams::new(url);
+        params.browser_context_id = Some(context_id);
+        self.new_page(params).await
+    }
 
-        rx.await?
+    /// Create `n` browser pages that all navigated to `url`
+    ///
+    /// All targets are requested at once and every page is retried on its
+    /// own, so a failed creation doesn't fail the others. If a page can't be
+    /// created, all pages created by this call are closed again and the first
+    /// error is returned.
+    pub async fn new_pages(&self, n: usize, url: &str) -> Result<Vec<Page>> {
+        let handle = self.handle();
+        let params = handle.page_params(CreateTargetParams::new(url));
+        let _creation = handle.page_creation().lock().await;
+
+        let pages = join_all((0..n).map(|_| handle.create_page_with_retries(params.clone()))).await;
+        if pages.iter().all(|page| page.is_ok()) {
+            return Ok(pages.into_iter().flatten().collect());
+        }
+
+        let mut error = None;
+        for page in pages {
+            match page {
+                Ok(page) => {
+                    let _ = page.close().await;
+                }
+                Err(err) => {
+                    error.get_or_insert(err);
+                }
+            }
+        }
+        Err(error.expect("a page failed"))
     }
 
     /// Version information about the browser
@@ -415,26 +585,120 @@ impl Browser {
 
     /// Call a browser method.
     pub async fn execute<T: Command>(&self, cmd: T) -> Result<CommandResponse<T::Response>> {
-        let (tx, rx) = oneshot_channel();
-        let method = cmd.identifier();
-        let msg = CommandMessage::new(cmd, tx)?;
+        self.execute_with(cmd, None).await
+    }
 
-        self.sender
-            .clone()
-            .send(HandlerMessage::Command(msg))
-            .await?;
-        let resp = rx.await??;
-        to_command_response::<T>(resp, method)
+    /// Call a browser method that may take longer than the configured
+    /// request timeout. The call fails with [`CdpError::Timeout`] after
+    /// `timeout` instead.
+    pub async fn execute_timeout<T: Command>(
+        &self,
+        cmd: T,
+        timeout: Duration,
+    ) -> Result<CommandResponse<T::Response>> {
+        self.execute_with(cmd, Some(timeout)).await
+    }
+
+    async fn execute_with<T: Command>(
+        &self,
+        cmd: T,
+        timeout: Option<Duration>,
+    ) -> Result<CommandResponse<T::Response>> {
+        self.handle().execute_with(cmd, timeout).await
     }
 
     /// Return all of the pages of the browser
     pub async fn pages(&self) -> Result<Vec<Page>> {
-        let (tx, rx) = oneshot_channel();
-        self.sender
-            .clone()
-            .send(HandlerMessage::GetPages(tx))
-            .await?;
-        Ok(rx.await?)
+        self.handle().pages().await
+    }
+
+    /// Close the page of the target without closing the browser, e.g. a tab
+    /// a long running scraper is done with.
+    ///
+    /// The page is no longer returned by [`Browser::pages`] once this
+    /// returns, and its `Page`s fail with [`CdpError::PageClosed`].
+    pub async fn close_page(&self, target_id: TargetId) -> Result<()> {
+        self.handle().close_page(target_id).await
+    }
+
+    /// A handle to send commands to the handler from other tasks, without
+    /// sharing the `Browser`, see [`BrowserHandle`]
+    pub fn handle(&self) -> BrowserHandle {
+        BrowserHandle::new(
+            self.sender.clone(),
+            self.browser_context.clone(),
+            self.page_creation.clone(),
+        )
+    }
+
+    /// Adopt the tab chromium opens on startup instead of creating another
+    /// one with [`Browser::new_page`].
+    ///
+    /// A session with a single tab looks like a regular one, a stray blank
+    /// tab next to the automated page shows in the tab count. Waits up to
+    /// 5 seconds for the initial tab to be attached, fails with
+    /// [`CdpError::NotFound`] if there is none, e.g. because the browser was
+    /// launched with `--no-startup-window` or the tab was closed.
+    ///
+    /// Call it right after launching: once more pages exist in the default
+    /// browser context, any of them may be returned.
+    pub async fn first_page(&self) -> Result<Page> {
+        utils::timeout(FIRST_PAGE_TIMEOUT, async {
+            loop {
+                let (tx, rx) = oneshot_channel();
+                self.sender
+                    .clone()
+                    .send(HandlerMessage::FirstPage(tx))
+                    .await?;
+                if let Some(page) = rx.await? {
+                    return Ok(page);
+                }
+                utils::sleep(FIRST_PAGE_POLL_INTERVAL).await;
+            }
+        })
+        .await
+        .unwrap_or(Err(CdpError::NotFound))
+    }
+
+    /// Wait for the next page to open, e.g. a popup from `window.open` or a
+    /// link with `target="_blank"`, and return it once it can be driven.
+    ///
+    /// Only pages opened after this future is first polled are seen, so
+    /// start waiting before triggering the popup, e.g. by joining both with
+    /// this future first. Fails with [`CdpError::Timeout`] if no page opened
+    /// within `timeout`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let (popup, _) = futures::try_join!(
+    ///     browser.wait_for_new_page(Duration::from_secs(10)),
+    ///     page.click(link_center),
+    /// )?;
+    /// popup.wait_for_navigation().await?;
+    /// ```
+    pub async fn wait_for_new_page(&self, timeout: Duration) -> Result<Page> {
+        utils::timeout(timeout, async {
+            let mut created = self.event_listener::<EventTargetCreated>().await?;
+            let target_id = loop {
+                let event = created.next().await.ok_or(CdpError::NoResponse)?;
+                if event.target_info.r#type == "page" {
+                    break event.target_info.target_id.clone();
+                }
+            };
+            loop {
+                let (tx, rx) = oneshot_channel();
+                self.sender
+                    .clone()
+                    .send(HandlerMessage::InitializedPage(target_id.clone(), tx))
+                    .await?;
+                if let Some(page) = rx.await? {
+                    return Ok(page);
+                }
+                utils::sleep(NEW_PAGE_POLL_INTERVAL).await;
+            }
+        })
+        .await
+        .unwrap_or(Err(CdpError::Timeout))
     }
 
     /// Return page of given target_id
@@ -466,7 +730,29 @@ impl Browser {
         params: CreateBrowserContextParams,
     ) -> Result<BrowserContextId> {
         let response = self.execute(params).await?;
-        Ok(response.result.browser_context_id)
+        let id = response.result.browser_context_id;
+        self.sender
+            .clone()
+            .send(HandlerMessage::InsertContext(BrowserContext::from(
+                id.clone(),
+            )))
+            .await?;
+        self.contexts.lock().unwrap().push(TrackedContext {
+            id: id.clone(),
+            ephemeral: false,
+        });
+        Ok(id)
+    }
+
+    /// All browser contexts created through this browser that weren't
+    /// disposed yet, including the one of `start_incognito_context`
+    pub fn contexts(&self) -> Vec<BrowserContextId> {
+        self.contexts
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|ctx| ctx.id.clone())
+            .collect()
     }
 
     /// Deletes a browser context.
@@ -474,7 +760,13 @@ impl Browser {
         &self,
         browser_context_id: impl Into<BrowserContextId>,
     ) -> Result<()> {
-        self.execute(DisposeBrowserContextParams::new(browser_context_id))
+        let id = browser_context_id.into();
+        self.execute(DisposeBrowserContextParams::new(id.clone()))
+            .await?;
+        self.contexts.lock().unwrap().retain(|ctx| ctx.id != id);
+        self.sender
+            .clone()
+            .send(HandlerMessage::DisposeContext(BrowserContext::from(id)))
             .await?;
 
         Ok(())
@@ -510,17 +802,132 @@ impl Browser {
             .cookies)
     }
 
-    /// Sets given cookies.
-    pub async fn set_cookies(&self, mut cookies: Vec<CookieParam>) -> Result<&Self> {
-        for cookie in &mut cookies {
-            if let Some(url) = cookie.url.as_ref() {
-                crate::page::validate_cookie_url(url)?;
+    /// Wait until a cookie named `name` is set, e.g. the session cookie that
+    /// marks a finished login, and return it.
+    ///
+    /// Fails with [`CdpError::Timeout`] if there is no such cookie after
+    /// `timeout`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// chaser.click_human("#login").await?;
+    /// let session = browser
+    ///     .wait_for_cookie("session_id", Duration::from_secs(30))
+    ///     .await?;
+    /// ```
+    pub async fn wait_for_cookie(&self, name: &str, timeout: Duration) -> Result<Cookie> {
+        self.wait_for_cookie_on(name, None, timeout).await
+    }
+
+    /// Like [`Browser::wait_for_cookie`], but only for cookies of `domain`
+    /// or one of its subdomains if a domain is given.
+    pub async fn wait_for_cookie_on(
+        &self,
+        name: &str,
+        domain: Option<&str>,
+        timeout: Duration,
+    ) -> Result<Cookie> {
+        let domain = domain.map(|domain| domain.trim_start_matches('.'));
+        utils::timeout(timeout, async {
+            loop {
+                let found = self.get_cookies().await?.into_iter().find(|cookie| {
+                    cookie.name == name
+                        && domain.map_or(true, |domain| {
+                            let host = cookie.domain.trim_start_matches('.');
+                            host == domain || host.ends_with(&format!(".{domain}"))
+                        })
+                });
+                if let Some(cookie) = found {
+                    return Ok(cookie);
+                }
+                utils::sleep(COOKIE_POLL_INTERVAL).await;
             }
+        })
+        .await
+        .unwrap_or(Err(CdpError::Timeout))
+    }
+
+    /// Sets given cookies.
+    ///
+    /// Fails for a cookie with conflicting attributes, see
+    /// [`validate_cookie`](crate::cookie::validate_cookie).
+    pub async fn set_cookies(&self, cookies: Vec<CookieParam>) -> Result<&Self> {
+        for cookie in &cookies {
+            crate::cookie::validate_cookie(cookie)?;
         }
 
         self.execute(SetCookiesParams::new(cookies)).await?;
         Ok(self)
     }
+
+    /// Snapshot all cookies plus the local and session storage of the
+    /// origins of all open pages.
+    ///
+    /// Pages without an origin, like `about:blank`, are skipped.
+    pub async fn session_snapshot(&self) -> Result<SessionSnapshot> {
+        let cookies = self.get_cookies().await?;
+
+        let mut origins: Vec<OriginStorage> = Vec::new();
+        for page in self.pages().await? {
+            let page = ChaserPage::new(page);
+            let Ok(origin) = page.evaluate_as::<String>("location.origin").await else {
+                continue;
+            };
+            let (Ok(local_storage), Ok(session_storage)) =
+                (page.local_storage().await, page.session_storage().await)
+            else {
+                continue;
+            };
+            match origins.iter_mut().find(|storage| storage.origin == origin) {
+                Some(storage) => {
+                    storage.local_storage.extend(local_storage);
+                    storage.session_storage.extend(session_storage);
+                }
+                None => origins.push(OriginStorage {
+                    origin,
+                    local_storage,
+                    session_storage,
+                }),
+            }
+        }
+
+        Ok(SessionSnapshot::new(cookies, origins))
+    }
+
+    /// Write a [`SessionSnapshot`] of this browser as JSON to `path`.
+    pub async fn export_session(&self, path: impl AsRef<Path>) -> Result<SessionSnapshot> {
+        let snapshot = self.session_snapshot().await?;
+        utils::write(path.as_ref(), serde_json::to_vec_pretty(&snapshot)?).await?;
+        Ok(snapshot)
+    }
+
+    /// Restore a session written by [`Browser::export_session`].
+    ///
+    /// Cookies are restored right away. Web storage can only be written once
+    /// a page is on the matching origin, so it is restored lazily: every page
+    /// open at the time of the import fills in the missing storage keys of an
+    /// origin when it first navigates to it, see [`Page::restore_storage`],
+    /// which pages created afterwards can call themselves.
+    pub async fn import_session(&self, path: impl AsRef<Path>) -> Result<SessionSnapshot> {
+        let snapshot: SessionSnapshot = serde_json::from_slice(&utils::read(path.as_ref()).await?)?;
+        if snapshot.version > SESSION_FORMAT_VERSION {
+            return Err(CdpError::msg(format!(
+                "Unsupported session format version {}, expected at most {}",
+                snapshot.version, SESSION_FORMAT_VERSION
+            )));
+        }
+
+        if !snapshot.cookies.is_empty() {
+            self.set_cookies(snapshot.cookie_params()).await?;
+        }
+        if !snapshot.origins.is_empty() {
+            for page in self.pages().await? {
+                page.restore_storage(&snapshot).await?;
+            }
+        }
+
+        Ok(snapshot)
+    }
 }
 
 impl Drop for Browser {
@@ -538,60 +945,128 @@ impl Drop for Browser {
                 // warn him here.
                 tracing::warn!("Browser was not closed manually, it will be killed automatically in the background");
             }
+        } else {
+            // A connected browser outlives this instance, so its contexts would leak
+            let contexts = std::mem::take(self.contexts.get_mut().unwrap());
+            for id in contexts
+                .into_iter()
+                .filter(|ctx| ctx.ephemeral)
+                .map(|ctx| ctx.id)
+            {
+                if let Err(err) = self.try_dispose_context(id.clone()) {
+                    tracing::warn!(
+                        "Failed to dispose incognito context {:?} on drop: {}",
+                        id,
+                        err
+                    );
+                }
+            }
         }
     }
 }
 
+impl Browser {
+    /// Queues the disposal of a context without waiting for the handler
+    fn try_dispose_context(&mut self, id: BrowserContextId) -> Result<()> {
+        let (tx, _) = oneshot_channel();
+        let dispose = CommandMessage::new(DisposeBrowserContextParams::new(id.clone()), tx)?;
+        self.sender
+            .try_send(HandlerMessage::Command(dispose))
+            .map_err(|err| err.into_send_error())?;
+        self.sender
+            .try_send(HandlerMessage::DisposeContext(BrowserContext::from(id)))
+            .map_err(|err| err.into_send_error())?;
+        Ok(())
+    }
+}
+
 /// Resolve devtools WebSocket URL from the provided browser process
 ///
-/// If an error occurs, it returns the browser's stderr output.
+/// The listening line is looked for on both stderr and stdout, since some
+/// wrapped Chromium installs (flatpak, snap shims) print it on stdout.
+/// If an error occurs, it returns the browser's combined output.
 ///
 /// The URL resolution fails if:
 /// - [`CdpError::LaunchTimeout`]: `timeout_fut` completes, this corresponds to a timeout
 /// - [`CdpError::LaunchExit`]: the browser process exits (or is killed)
 /// - [`CdpError::LaunchIo`]: an input/output error occurs when await the process exit or reading
-///   the browser's stderr: end of stream, invalid UTF-8, other
+///   the browser's output: end of both streams, invalid UTF-8, other
 async fn ws_url_from_output(
     child_process: &mut Child,
     timeout_fut: impl Future<Output = ()> + Unpin,
+    output_limit: usize,
 ) -> Result<String> {
+    let stderr = child_process.stderr.take().expect("no stderror");
+    let stdout = child_process.stdout.take().expect("no stdout");
+    let exit_status_fut = Box::pin(child_process.wait());
+    ws_url_from_streams(stderr, stdout, exit_status_fut, timeout_fut, output_limit).await
+}
+
+/// Reads `stderr` and `stdout` line by line until either yields the websocket
+/// URL, keeping only the last `output_limit` bytes for errors, see
+/// [`ws_url_from_output`]
+async fn ws_url_from_streams(
+    stderr: impl futures::AsyncRead + Unpin,
+    stdout: impl futures::AsyncRead + Unpin,
+    exit_status_fut: impl Future<Output = io::Result<ExitStatus>> + Unpin,
+    timeout_fut: impl Future<Output = ()> + Unpin,
+    output_limit: usize,
+) -> Result<String> {
+    use futures::stream::{self, Stream, StreamExt};
     use futures::{AsyncBufReadExt, FutureExt};
+
+    /// Complete lines of `reader` with their line ending, so multi-byte
+    /// characters split across reads are never decoded partially
+    fn lines(reader: impl futures::AsyncRead + Unpin) -> impl Stream<Item = io::Result<Vec<u8>>> {
+        stream::unfold(
+            futures::io::BufReader::new(reader),
+            |mut reader| async move {
+                let mut line = Vec::new();
+                match reader.read_until(b'\n', &mut line).await {
+                    Ok(0) => None,
+                    res => Some((res.map(|_| line), reader)),
+                }
+            },
+        )
+    }
+
+    /// The websocket URL announced by `line`, if any
+    fn listening_url(line: &[u8]) -> Option<String> {
+        // the browser may log in the legacy code page, which must not fail the
+        // launch, the listening line itself is ASCII
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (_, ws) = line.rsplit_once("listening on ")?;
+        (ws.starts_with("ws") && ws.contains("devtools/browser")).then(|| ws.trim().to_string())
+    }
+
     let mut timeout_fut = timeout_fut.fuse();
-    let stderr = child_process.stderr.take().expect("no stderror");
-    let mut stderr_bytes = Vec::<u8>::new();
-    let mut exit_status_fut = Box::pin(child_process.wait()).fuse();
-    let mut buf = futures::io::BufReader::new(stderr);
+    let mut output = Vec::<u8>::new();
+    let mut exit_status_fut = exit_status_fut.fuse();
+    let mut lines = Box::pin(stream::select(lines(stderr), lines(stdout)));
     loop {
         select! {
-            _ = timeout_fut => return Err(CdpError::LaunchTimeout(BrowserStderr::new(stderr_bytes))),
+            _ = timeout_fut => return Err(CdpError::LaunchTimeout(BrowserStderr::new(output))),
             exit_status = exit_status_fut => {
                 return Err(match exit_status {
-                    Err(e) => CdpError::LaunchIo(e, BrowserStderr::new(stderr_bytes)),
-                    Ok(exit_status) => CdpError::LaunchExit(exit_status, BrowserStderr::new(stderr_bytes)),
+                    Err(e) => CdpError::LaunchIo(e, BrowserStderr::new(output)),
+                    Ok(exit_status) => CdpError::LaunchExit(exit_status, BrowserStderr::new(output)),
                 })
             },
-            read_res = buf.read_until(b'\n', &mut stderr_bytes).fuse() => {
-                match read_res {
-                    Err(e) => return Err(CdpError::LaunchIo(e, BrowserStderr::new(stderr_bytes))),
-                    Ok(byte_count) => {
-                        if byte_count == 0 {
-                            let e = io::Error::new(io::ErrorKind::UnexpectedEof, "unexpected end of stream");
-                            return Err(CdpError::LaunchIo(e, BrowserStderr::new(stderr_bytes)));
+            line = lines.next().fuse() => {
+                match line {
+                    None => {
+                        let e = io::Error::new(io::ErrorKind::UnexpectedEof, "unexpected end of stream");
+                        return Err(CdpError::LaunchIo(e, BrowserStderr::new(output)));
+                    }
+                    Some(Err(e)) => return Err(CdpError::LaunchIo(e, BrowserStderr::new(output))),
+                    Some(Ok(line)) => {
+                        output.extend_from_slice(&line);
+                        if output.len() > output_limit {
+                            output.drain(..output.len() - output_limit);
                         }
-                        let start_offset = stderr_bytes.len() - byte_count;
-                        let new_bytes = &stderr_bytes[start_offset..];
-                        match std::str::from_utf8(new_bytes) {
-                            Err(_) => {
-                                let e = io::Error::new(io::ErrorKind::InvalidData, "stream did not contain valid UTF-8");
-                                return Err(CdpError::LaunchIo(e, BrowserStderr::new(stderr_bytes)));
-                            }
-                            Ok(line) => {
-                                if let Some((_, ws)) = line.rsplit_once("listening on ") {
-                                    if ws.starts_with("ws") && ws.contains("devtools/browser") {
-                                        return Ok(ws.trim().to_string());
-                                    }
-                                }
-                            }
+                        if let Some(ws) = listening_url(&line) {
+                            return Ok(ws);
                         }
                     }
                 }
@@ -599,3 +1074,154 @@ async fn ws_url_from_output(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures::future;
+    use futures::io::{empty, Cursor};
+
+    use super::*;
+
+    #[test]
+    fn json_endpoints_of_the_debugging_port() {
+        for url in [
+            "http://10.0.0.5:9222",
+            "http://10.0.0.5:9222/",
+            "http://10.0.0.5:9222/json/version",
+            "http://10.0.0.5:9222/json/list/",
+        ] {
+            assert_eq!(json_endpoint(url, "list"), "http://10.0.0.5:9222/json/list");
+        }
+        assert_eq!(
+            json_endpoint("https://json.example.com/json", "version"),
+            "https://json.example.com/json/version"
+        );
+        assert_eq!(
+            rewrite_local_ip(
+                "ws://127.0.0.1:9222/devtools/page/A1",
+                Some("10.0.0.5:9222".parse().unwrap())
+            ),
+            "ws://10.0.0.5:9222/devtools/page/A1"
+        );
+    }
+
+    #[test]
+    fn target_descriptor_without_websocket_url() {
+        let targets: Vec<TargetDescriptor> = serde_json::from_str(
+            r#"[{"description": "", "devtoolsFrontendUrl": "/devtools/inspector.html",
+                "id": "A1", "title": "Shop", "type": "page", "url": "https://shop.example.com/"}]"#,
+        )
+        .unwrap();
+        assert_eq!(targets[0].target_type, "page");
+        assert_eq!(targets[0].web_socket_debugger_url, None);
+    }
+
+    /// A stderr with `noise` bytes of warnings, then the listening line
+    fn stderr(noise: usize) -> Cursor<Vec<u8>> {
+        let mut stderr = Vec::new();
+        while stderr.len() < noise {
+            stderr.extend_from_slice(b"[WARNING:gpu_init.cc(525)] noisy warning\n");
+        }
+        stderr.extend_from_slice(
+            b"\nDevTools listening on ws://127.0.0.1:9222/devtools/browser/abc\n",
+        );
+        Cursor::new(stderr)
+    }
+
+    #[tokio::test]
+    async fn ws_url_is_found_after_large_stderr() {
+        let url = ws_url_from_streams(
+            stderr(1 << 20),
+            empty(),
+            future::pending(),
+            future::pending(),
+            1024,
+        )
+        .await
+        .unwrap();
+        assert_eq!(url, "ws://127.0.0.1:9222/devtools/browser/abc");
+    }
+
+    #[tokio::test]
+    async fn ws_url_is_found_on_stdout() {
+        let noise = Cursor::new(b"[WARNING:gpu_init.cc(525)] noisy warning\n".to_vec());
+        let url = ws_url_from_streams(noise, stderr(0), future::pending(), future::pending(), 1024)
+            .await
+            .unwrap();
+        assert_eq!(url, "ws://127.0.0.1:9222/devtools/browser/abc");
+    }
+
+    #[tokio::test]
+    async fn stderr_of_launch_errors_is_capped() {
+        let mut stderr = stderr(1 << 20).into_inner();
+        let listening = stderr.len()
+            - b"DevTools listening on ws://127.0.0.1:9222/devtools/browser/abc\n".len();
+        stderr.truncate(listening);
+        let err = ws_url_from_streams(
+            Cursor::new(stderr),
+            empty(),
+            future::pending(),
+            future::pending(),
+            1024,
+        )
+        .await
+        .unwrap_err();
+        match err {
+            CdpError::LaunchIo(e, stderr) => {
+                assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+                assert_eq!(stderr.as_slice().len(), 1024);
+                assert!(stderr.as_slice().ends_with(b"noisy warning\n\n"));
+            }
+            other => panic!("should fail at the end of stderr, got {other:?}"),
+        }
+    }
+
+    /// Yields a single byte per read, splitting multi-byte characters
+    struct Trickle(Cursor<Vec<u8>>);
+
+    impl futures::AsyncRead for Trickle {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let len = buf.len().min(1);
+            Pin::new(&mut self.0).poll_read(cx, &mut buf[..len])
+        }
+    }
+
+    #[tokio::test]
+    async fn ws_url_is_found_after_crlf_lines() {
+        let stderr = Cursor::new(
+            b"[WARNING] noisy warning\r\n\r\nDevTools listening on ws://127.0.0.1:9222/devtools/browser/abc\r\n"
+                .to_vec(),
+        );
+        let url = ws_url_from_streams(stderr, empty(), future::pending(), future::pending(), 1024)
+            .await
+            .unwrap();
+        assert_eq!(url, "ws://127.0.0.1:9222/devtools/browser/abc");
+    }
+
+    #[tokio::test]
+    async fn ws_url_is_found_after_split_multibyte_characters() {
+        let mut output = "[WARNING] Schriftart „Ärial“ nicht gefunden 🦀\n"
+            .as_bytes()
+            .to_vec();
+        // a latin-1 line, as logged in a legacy code page
+        output.extend_from_slice(b"[WARNING] caf\xe9\n");
+        output.extend_from_slice(stderr(0).get_ref());
+        let url = ws_url_from_streams(
+            Trickle(Cursor::new(output)),
+            empty(),
+            future::pending(),
+            future::pending(),
+            1024,
+        )
+        .await
+        .unwrap();
+        assert_eq!(url, "ws://127.0.0.1:9222/devtools/browser/abc");
+    }
+}
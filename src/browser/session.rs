@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use chromiumoxide_cdp::cdp::browser_protocol::network::{Cookie, CookieParam, TimeSinceEpoch};
+
+/// The current version of the [`SessionSnapshot`] format
+pub const SESSION_FORMAT_VERSION: u32 = 1;
+
+/// A snapshot of a browsing session: all cookies of the browser plus the web
+/// storage of every origin that had an open page.
+///
+/// Created by [`Browser::export_session`](crate::Browser::export_session) and
+/// restored by [`Browser::import_session`](crate::Browser::import_session).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSnapshot {
+    /// The version of the format, see [`SESSION_FORMAT_VERSION`]
+    pub version: u32,
+    pub cookies: Vec<Cookie>,
+    pub origins: Vec<OriginStorage>,
+}
+
+/// The `localStorage` and `sessionStorage` entries of a single origin
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OriginStorage {
+    /// The origin, e.g. `https://example.com`
+    pub origin: String,
+    pub local_storage: HashMap<String, String>,
+    pub session_storage: HashMap<String, String>,
+}
+
+impl SessionSnapshot {
+    pub fn new(cookies: Vec<Cookie>, origins: Vec<OriginStorage>) -> Self {
+        Self {
+            version: SESSION_FORMAT_VERSION,
+            cookies,
+            origins,
+        }
+    }
+
+    /// The cookies as params for `Storage.setCookies`
+    pub fn cookie_params(&self) -> Vec<CookieParam> {
+        self.cookies
+            .iter()
+            .map(|cookie| CookieParam {
+                name: cookie.name.clone(),
+                value: cookie.value.clone(),
+                url: None,
+                domain: Some(cookie.domain.clone()),
+                path: Some(cookie.path.clone()),
+                secure: Some(cookie.secure),
+                http_only: Some(cookie.http_only),
+                same_site: cookie.same_site.clone(),
+                expires: (!cookie.session).then(|| TimeSinceEpoch::new(cookie.expires)),
+                priority: Some(cookie.priority.clone()),
+                same_party: None,
+                source_scheme: Some(cookie.source_scheme.clone()),
+                source_port: Some(cookie.source_port),
+                partition_key: cookie.partition_key.clone(),
+            })
+            .collect()
+    }
+}
+
+impl OriginStorage {
+    /// A script restoring these storage entries when a new document of the
+    /// origin is created, documents of other origins are left alone.
+    ///
+    /// Only keys missing from the storage are written. See
+    /// [`Page::restore_storage`](crate::Page::restore_storage), which runs it
+    /// only on the first visit of the origin.
+    pub fn restore_script(&self) -> String {
+        format!(
+            r#"(function(entry) {{
+                if (location.origin !== entry.origin) return;
+                const restore = (storage, kv) => {{
+                    for (const [k, v] of Object.entries(kv)) {{
+                        if (storage.getItem(k) === null) storage.setItem(k, v);
+                    }}
+                }};
+                try {{
+                    restore(window.localStorage, entry.localStorage);
+                    restore(window.sessionStorage, entry.sessionStorage);
+                }} catch (e) {{}}
+            }})({})"#,
+            serde_json::to_string(self).expect("storage is serializable")
+        )
+    }
+}
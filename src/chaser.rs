@@ -1,22 +1,65 @@
-use crate::page::Page;
+use crate::cmd::BatchCommand;
+use crate::error::CdpError;
+use crate::handler::network::NavigationHeaders;
+use crate::keys;
+use crate::page::{Page, ScreenshotParams};
 use crate::profiles::ChaserProfile;
+use crate::slider;
+use crate::utils;
 use anyhow::{anyhow, Result};
 use base64::{engine::general_purpose::STANDARD, Engine};
+use chromiumoxide_cdp::cdp::browser_protocol::accessibility::{
+    AxNode as CdpAxNode, AxNodeId, AxValue, GetFullAxTreeParams,
+};
+use chromiumoxide_cdp::cdp::browser_protocol::device_orientation::{
+    ClearDeviceOrientationOverrideParams, SetDeviceOrientationOverrideParams,
+};
+use chromiumoxide_cdp::cdp::browser_protocol::dom::BackendNodeId;
+use chromiumoxide_cdp::cdp::browser_protocol::emulation::{
+    ClearGeolocationOverrideParams, MediaFeature, SetEmulatedMediaParams,
+    SetFocusEmulationEnabledParams, SetGeolocationOverrideParams, SetLocaleOverrideParams,
+    SetTimezoneOverrideParams, SetTouchEmulationEnabledParams,
+};
 use chromiumoxide_cdp::cdp::browser_protocol::fetch::{
-    ContinueRequestParams, DisableParams as FetchDisableParams, EnableParams as FetchEnableParams,
-    FulfillRequestParams, HeaderEntry, RequestPattern,
+    ContinueRequestParams, FulfillRequestParams, HeaderEntry, RequestPattern,
 };
 use chromiumoxide_cdp::cdp::browser_protocol::input::{
     DispatchKeyEventParams, DispatchKeyEventType,
 };
-use chromiumoxide_cdp::cdp::browser_protocol::network::ResourceType;
+use chromiumoxide_cdp::cdp::browser_protocol::log::{
+    EnableParams as LogEnableParams, EventEntryAdded, LogEntryLevel, LogEntrySource,
+};
+#[allow(deprecated)]
+use chromiumoxide_cdp::cdp::browser_protocol::network::EmulateNetworkConditionsParams;
+use chromiumoxide_cdp::cdp::browser_protocol::network::{
+    ConnectionType, EventLoadingFailed, EventLoadingFinished, EventRequestWillBeSent,
+    EventResponseReceived, GetResponseBodyParams, Headers, RequestId, ResourceType,
+    SetExtraHttpHeadersParams, SetUserAgentOverrideParams,
+};
 use chromiumoxide_cdp::cdp::browser_protocol::page::{
-    AddScriptToEvaluateOnNewDocumentParams, CreateIsolatedWorldParams,
+    AddScriptToEvaluateOnNewDocumentParams, CaptureScreenshotFormat, CaptureScreenshotParams,
+    CreateIsolatedWorldParams, EventDomContentEventFired, EventFrameNavigated,
+    EventFrameRequestedNavigation, EventFrameStartedLoading, EventFrameStoppedLoading,
+    EventNavigatedWithinDocument, FrameId, NavigateParams, ReferrerPolicy,
+    RemoveScriptToEvaluateOnNewDocumentParams, ScriptIdentifier, SetBypassCspParams,
+    TransitionType,
+};
+use chromiumoxide_cdp::cdp::browser_protocol::performance::{
+    EnableParams as PerformanceEnableParams, GetMetricsParams,
 };
-use chromiumoxide_cdp::cdp::js_protocol::runtime::EvaluateParams;
+use chromiumoxide_cdp::cdp::js_protocol::runtime::{
+    CallArgument, CallFunctionOnParams, ConsoleApiCalledType, EnableParams as RuntimeEnableParams,
+    EvaluateParams, EventConsoleApiCalled, EventExceptionThrown, ExecutionContextId,
+    ReleaseObjectParams, RemoteObject, StackTrace,
+};
+use chromiumoxide_types::{Command, CommandResponse};
+use futures::{future, stream, Stream, StreamExt};
 use rand::Rng;
+use serde::de::DeserializeOwned;
 use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Point {
@@ -24,6 +67,830 @@ pub struct Point {
     pub y: f64,
 }
 
+/// A modifier key held by [`ChaserPage::press_combo`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    /// Alt, the Option key on macOS
+    Alt,
+    /// Ctrl
+    Control,
+    /// The Command key on macOS, the Windows key elsewhere
+    Meta,
+    /// Shift
+    Shift,
+}
+
+impl Modifier {
+    /// The bit of the modifier in `Input.dispatchKeyEvent`'s `modifiers`
+    fn bit(self) -> i64 {
+        match self {
+            Modifier::Alt => 1,
+            Modifier::Control => 2,
+            Modifier::Meta => 4,
+            Modifier::Shift => 8,
+        }
+    }
+
+    fn definition(self) -> &'static keys::KeyDefinition {
+        let key = match self {
+            Modifier::Alt => "Alt",
+            Modifier::Control => "Control",
+            Modifier::Meta => "Meta",
+            Modifier::Shift => "Shift",
+        };
+        keys::get_key_definition(key).expect("modifiers are in the layout")
+    }
+}
+
+/// The accuracy in meters of the geolocation a profile reports, like a
+/// Wi-Fi based fix
+const GEOLOCATION_ACCURACY: f64 = 50.0;
+
+/// How long to wait for a navigation lifecycle point
+const NAVIGATION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long the network has to be quiet to be considered idle
+const NETWORK_IDLE_TIME: Duration = Duration::from_millis(500);
+
+/// When [`ChaserPage::goto_with`] considers a navigation finished
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WaitUntil {
+    /// Don't wait, return once the navigation was committed
+    None,
+    /// Wait for the `DOMContentLoaded` event
+    DomContentLoaded,
+    /// Wait for the `load` event
+    #[default]
+    Load,
+    /// Wait for the `load` event, then until there were no network
+    /// connections for at least 500ms
+    NetworkIdle,
+    /// Wait for the `load` event, then until there were at most 2 network
+    /// connections for at least 500ms
+    NetworkAlmostIdle,
+}
+
+/// A network event of a page, see [`ChaserPage::network_events`]
+#[derive(Debug, Clone)]
+pub enum NetworkEvent {
+    /// `Network.requestWillBeSent`
+    RequestWillBeSent(Arc<EventRequestWillBeSent>),
+    /// `Network.responseReceived`
+    ResponseReceived(Arc<EventResponseReceived>),
+    /// `Network.loadingFinished`
+    LoadingFinished(Arc<EventLoadingFinished>),
+    /// `Network.loadingFailed`
+    LoadingFailed(Arc<EventLoadingFailed>),
+}
+
+impl NetworkEvent {
+    /// The request this event belongs to
+    pub fn request_id(&self) -> &RequestId {
+        match self {
+            NetworkEvent::RequestWillBeSent(ev) => &ev.request_id,
+            NetworkEvent::ResponseReceived(ev) => &ev.request_id,
+            NetworkEvent::LoadingFinished(ev) => &ev.request_id,
+            NetworkEvent::LoadingFailed(ev) => &ev.request_id,
+        }
+    }
+}
+
+/// How severe a [`ConsoleEvent`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConsoleLevel {
+    /// `console.debug` and verbose log entries
+    Debug,
+    /// `console.log`, `console.info` and the like
+    Info,
+    /// `console.warn`
+    Warning,
+    /// `console.error`, failed assertions and uncaught exceptions
+    Error,
+}
+
+/// Where a [`ConsoleEvent`] comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleSource {
+    /// A call of the `console` API, `Runtime.consoleAPICalled`
+    ConsoleApi,
+    /// An uncaught exception, `Runtime.exceptionThrown`
+    Exception,
+    /// An entry of the browser log, `Log.entryAdded`, e.g. a failed request
+    /// or a CSP violation
+    Log(LogEntrySource),
+}
+
+/// A console message or error of a page, see
+/// [`ChaserPage::console_messages`] and [`ChaserPage::page_errors`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsoleEvent {
+    pub level: ConsoleLevel,
+    /// The message, with the arguments of a `console` call joined by spaces
+    pub text: String,
+    pub source: ConsoleSource,
+    /// The URL of the script or document the message comes from, if known
+    pub url: Option<String>,
+    /// The 1-based line in `url`, if known
+    pub line: Option<i64>,
+}
+
+impl ConsoleEvent {
+    fn from_console_api(ev: &EventConsoleApiCalled) -> Self {
+        let level = match ev.r#type {
+            ConsoleApiCalledType::Debug => ConsoleLevel::Debug,
+            ConsoleApiCalledType::Warning => ConsoleLevel::Warning,
+            ConsoleApiCalledType::Error | ConsoleApiCalledType::Assert => ConsoleLevel::Error,
+            _ => ConsoleLevel::Info,
+        };
+        let text = ev
+            .args
+            .iter()
+            .map(remote_object_text)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let (url, line) = top_frame(ev.stack_trace.as_ref());
+        Self {
+            level,
+            text,
+            source: ConsoleSource::ConsoleApi,
+            url,
+            line,
+        }
+    }
+
+    fn from_exception(ev: &EventExceptionThrown) -> Self {
+        let details = &ev.exception_details;
+        let text = details
+            .exception
+            .as_ref()
+            .and_then(|exception| exception.description.clone())
+            .unwrap_or_else(|| details.text.clone());
+        let (url, line) = match details.url.clone() {
+            Some(url) => (Some(url), Some(details.line_number + 1)),
+            None => top_frame(details.stack_trace.as_ref()),
+        };
+        Self {
+            level: ConsoleLevel::Error,
+            text,
+            source: ConsoleSource::Exception,
+            url,
+            line,
+        }
+    }
+
+    fn from_log_entry(ev: &EventEntryAdded) -> Self {
+        let entry = &ev.entry;
+        let level = match entry.level {
+            LogEntryLevel::Verbose => ConsoleLevel::Debug,
+            LogEntryLevel::Info => ConsoleLevel::Info,
+            LogEntryLevel::Warning => ConsoleLevel::Warning,
+            LogEntryLevel::Error => ConsoleLevel::Error,
+        };
+        Self {
+            level,
+            text: entry.text.clone(),
+            source: ConsoleSource::Log(entry.source.clone()),
+            url: entry.url.clone(),
+            line: entry.line_number.map(|line| line + 1),
+        }
+    }
+}
+
+/// The text `console.log` prints for a value
+fn remote_object_text(object: &RemoteObject) -> String {
+    match &object.value {
+        Some(Value::String(text)) => text.clone(),
+        Some(value) => value.to_string(),
+        None => object
+            .unserializable_value
+            .as_ref()
+            .map(|value| value.inner().clone())
+            .or_else(|| object.description.clone())
+            .unwrap_or_else(|| object.r#type.as_ref().to_string()),
+    }
+}
+
+/// The URL and 1-based line of the innermost call of a stack trace
+fn top_frame(stack_trace: Option<&StackTrace>) -> (Option<String>, Option<i64>) {
+    match stack_trace.and_then(|trace| trace.call_frames.first()) {
+        Some(frame) if !frame.url.is_empty() => {
+            (Some(frame.url.clone()), Some(frame.line_number + 1))
+        }
+        Some(frame) => (None, Some(frame.line_number + 1)),
+        None => (None, None),
+    }
+}
+
+/// How often the page is checked for challenge markers
+const CHALLENGE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Serialize `cmd` for [`Page::execute_batch`]
+fn batch(cmd: impl chromiumoxide_types::Command) -> Result<BatchCommand> {
+    BatchCommand::new(cmd).map_err(|e| anyhow!("{}", e))
+}
+
+/// Delay between the frames compared by [`ChaserPage::screenshot_when_stable`]
+const STABLE_POLL_INTERVAL: Duration = Duration::from_millis(150);
+
+/// A kind of anti-bot challenge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChallengeKind {
+    /// Cloudflare's "Just a moment..." interstitial
+    CloudflareInterstitial,
+    /// A Cloudflare Turnstile widget that has no token yet
+    Turnstile,
+    /// A challenge recognized by a user supplied marker
+    Custom(String),
+}
+
+/// How a challenge is recognized on a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChallengeCheck {
+    /// An element matching the CSS selector exists
+    Selector(String),
+    /// The document title contains the text
+    Title(String),
+    /// A cookie whose name starts with the prefix is set
+    CookiePrefix(String),
+    /// The JavaScript expression, evaluated in the isolated world, is truthy
+    Script(String),
+}
+
+/// A marker whose presence means a challenge is still active
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChallengeMarker {
+    pub kind: ChallengeKind,
+    pub check: ChallengeCheck,
+}
+
+impl ChallengeMarker {
+    pub fn new(kind: ChallengeKind, check: ChallengeCheck) -> Self {
+        Self { kind, check }
+    }
+
+    /// The markers of the Cloudflare interstitial and Turnstile widgets
+    pub fn defaults() -> Vec<Self> {
+        use ChallengeCheck::*;
+        use ChallengeKind::*;
+        vec![
+            Self::new(CloudflareInterstitial, Title("Just a moment...".into())),
+            Self::new(CloudflareInterstitial, Selector("#cf-challenge".into())),
+            Self::new(CloudflareInterstitial, Selector("#challenge-form".into())),
+            Self::new(CloudflareInterstitial, CookiePrefix("cf_chl_".into())),
+            Self::new(
+                Turnstile,
+                Script(
+                    r#"(() => {
+                        if (!document.querySelector('iframe[src*="turnstile"]')) return false;
+                        const token = document.querySelector('[name="cf-turnstile-response"]');
+                        return !token || !token.value;
+                    })()"#
+                        .into(),
+                ),
+            ),
+        ]
+    }
+}
+
+/// The JavaScript world a function is called in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExecutionWorld {
+    /// The "chaser" isolated world: shares the DOM, but not the JS globals of
+    /// the page, and is invisible to site scripts
+    #[default]
+    Isolated,
+    /// The page's own world, where site globals (and their overrides) are
+    /// visible
+    Main,
+}
+
+/// Options for [`ChaserPage::call_function_with`]
+#[derive(Debug, Clone, Copy)]
+pub struct CallFunctionOptions {
+    /// The world the function is called in
+    pub world: ExecutionWorld,
+    /// Whether to wait for a returned promise to resolve
+    pub await_promise: bool,
+}
+
+impl Default for CallFunctionOptions {
+    fn default() -> Self {
+        Self {
+            world: ExecutionWorld::Isolated,
+            await_promise: true,
+        }
+    }
+}
+
+/// Options for [`ChaserPage::evaluate_with`]
+#[derive(Debug, Clone, Copy)]
+pub struct EvaluateOptions {
+    /// Whether to wait for a returned promise to resolve, and return its
+    /// value instead of the promise
+    pub await_promise: bool,
+    /// How long to wait for the result, the request timeout of the browser
+    /// if `None`
+    pub timeout: Option<Duration>,
+}
+
+impl Default for EvaluateOptions {
+    fn default() -> Self {
+        Self {
+            await_promise: true,
+            timeout: None,
+        }
+    }
+}
+
+/// Network conditions emulated by [`ChaserPage::emulate_network`]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum NetworkConditions {
+    /// The real network, without throttling
+    #[default]
+    Unthrottled,
+    /// No connection, requests fail with `net::ERR_INTERNET_DISCONNECTED`
+    Offline,
+    /// 2s latency, 400kbit/s down and up, DevTools' "Slow 3G"
+    Slow3G,
+    /// 562.5ms latency, 1.44Mbit/s down, 675kbit/s up, DevTools' "Fast 3G"
+    Fast3G,
+    /// 2ms latency, 30Mbit/s down, 15Mbit/s up
+    Wifi,
+    /// Custom throttling
+    Custom {
+        /// Minimum time from sending a request to receiving the response
+        /// headers
+        latency: Duration,
+        /// Download throughput in bytes per second, `None` doesn't throttle
+        download_throughput: Option<u64>,
+        /// Upload throughput in bytes per second, `None` doesn't throttle
+        upload_throughput: Option<u64>,
+    },
+}
+
+impl NetworkConditions {
+    /// The `Network.emulateNetworkConditions` command for these conditions
+    // The command was recently deprecated, its replacement
+    // `emulateNetworkConditionsByRule` isn't available in older chromium
+    #[allow(deprecated)]
+    fn params(&self, offline: bool) -> EmulateNetworkConditionsParams {
+        let (latency, download, upload, connection_type) = match *self {
+            NetworkConditions::Unthrottled => (0.0, -1.0, -1.0, None),
+            NetworkConditions::Offline => (0.0, -1.0, -1.0, Some(ConnectionType::None)),
+            NetworkConditions::Slow3G => {
+                (2000.0, 50_000.0, 50_000.0, Some(ConnectionType::Cellular3g))
+            }
+            NetworkConditions::Fast3G => {
+                (562.5, 180_000.0, 84_375.0, Some(ConnectionType::Cellular3g))
+            }
+            NetworkConditions::Wifi => (2.0, 3_750_000.0, 1_875_000.0, Some(ConnectionType::Wifi)),
+            NetworkConditions::Custom {
+                latency,
+                download_throughput,
+                upload_throughput,
+            } => (
+                latency.as_secs_f64() * 1000.0,
+                download_throughput.map_or(-1.0, |bytes| bytes as f64),
+                upload_throughput.map_or(-1.0, |bytes| bytes as f64),
+                None,
+            ),
+        };
+        let offline = offline || *self == NetworkConditions::Offline;
+        let connection_type = if offline {
+            Some(ConnectionType::None)
+        } else {
+            connection_type
+        };
+        let mut params = EmulateNetworkConditionsParams::new(offline, latency, download, upload);
+        params.connection_type = connection_type;
+        params
+    }
+}
+
+/// A named set of init scripts registered on a page with
+/// `Page.addScriptToEvaluateOnNewDocument`, created by
+/// [`ChaserPage::script_bundle`].
+///
+/// The scripts run in the order they were added, after the profile bootstrap
+/// if the profile was applied first. The bundle keeps the identifiers of the
+/// registered scripts, so reloading or removing it never leaves stale copies
+/// behind.
+///
+/// ```ignore
+/// let mut bundle = chaser.script_bundle("stealth-extras");
+/// bundle.add("webrtc", BLOCK_WEBRTC).await?;
+/// bundle.add("fonts", SPOOF_FONTS).await?;
+/// chaser.goto("https://example.com").await?;
+/// ```
+#[derive(Debug)]
+pub struct ScriptBundle {
+    page: Page,
+    name: String,
+    scripts: Vec<(String, String)>,
+    registered: Vec<ScriptIdentifier>,
+}
+
+impl ScriptBundle {
+    /// The name of the bundle
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The names of the scripts, in registration order
+    pub fn script_names(&self) -> impl Iterator<Item = &str> {
+        self.scripts.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Add `source` as the script `name` and register it for new documents.
+    ///
+    /// Replacing a script of the same name keeps its position, the bundle
+    /// is reloaded in that case.
+    pub async fn add(&mut self, name: impl Into<String>, source: impl Into<String>) -> Result<()> {
+        let name = name.into();
+        let source = source.into();
+        if let Some((_, existing)) = self.scripts.iter_mut().find(|(n, _)| *n == name) {
+            *existing = source;
+            return self.reload().await;
+        }
+        let identifier = self.register(&source).await?;
+        self.scripts.push((name, source));
+        self.registered.push(identifier);
+        Ok(())
+    }
+
+    /// Remove the script `name`, returns whether it was part of the bundle
+    pub async fn remove(&mut self, name: &str) -> Result<bool> {
+        let Some(pos) = self.scripts.iter().position(|(n, _)| n == name) else {
+            return Ok(false);
+        };
+        self.scripts.remove(pos);
+        if pos < self.registered.len() {
+            let identifier = self.registered.remove(pos);
+            self.page
+                .remove_script_on_new_document(identifier)
+                .await
+                .map_err(|e| anyhow!("{}", e))?;
+        }
+        Ok(true)
+    }
+
+    /// Remove all scripts and register them again in order, e.g. after
+    /// other init scripts were added that must run before the bundle
+    pub async fn reload(&mut self) -> Result<()> {
+        self.unregister().await?;
+        for (_, source) in &self.scripts {
+            let identifier = self.register(source).await?;
+            self.registered.push(identifier);
+        }
+        Ok(())
+    }
+
+    /// Unregister and forget all scripts of the bundle
+    pub async fn remove_all(&mut self) -> Result<()> {
+        self.unregister().await?;
+        self.scripts.clear();
+        Ok(())
+    }
+
+    async fn register(&self, source: &str) -> Result<ScriptIdentifier> {
+        self.page
+            .evaluate_on_new_document(source)
+            .await
+            .map_err(|e| anyhow!("{}", e))
+    }
+
+    /// Remove the registered scripts, keeping their sources
+    async fn unregister(&mut self) -> Result<()> {
+        while let Some(identifier) = self.registered.pop() {
+            self.page
+                .remove_script_on_new_document(identifier)
+                .await
+                .map_err(|e| anyhow!("{}", e))?;
+        }
+        Ok(())
+    }
+}
+
+/// A node of the accessibility tree, see [`ChaserPage::accessibility_tree`]
+#[derive(Debug, Clone)]
+pub struct AxNode {
+    pub id: AxNodeId,
+    /// The id of the parent node, `None` for the root of the tree
+    pub parent_id: Option<AxNodeId>,
+    /// The role, e.g. `button`, or `RootWebArea` for a document
+    pub role: String,
+    /// The accessible name, empty if the node has none
+    pub name: String,
+    /// The value of form controls
+    pub value: Option<Value>,
+    /// Whether the node is hidden from assistive technology, e.g. by
+    /// `aria-hidden`. Its children are still part of the tree.
+    pub ignored: bool,
+    /// The DOM node the accessibility node belongs to
+    pub backend_node_id: Option<BackendNodeId>,
+    pub children: Vec<AxNode>,
+}
+
+impl AxNode {
+    /// Nest the flat node list of `Accessibility.getFullAXTree` below the
+    /// node matching `is_root`
+    fn from_cdp(nodes: Vec<CdpAxNode>, is_root: impl Fn(&CdpAxNode) -> bool) -> Option<Self> {
+        let root = nodes.iter().find(|node| is_root(node))?.node_id.clone();
+        let mut nodes: HashMap<AxNodeId, CdpAxNode> = nodes
+            .into_iter()
+            .map(|node| (node.node_id.clone(), node))
+            .collect();
+        // nodes are taken out of the map, so a malformed list can't loop
+        fn build(id: &AxNodeId, nodes: &mut HashMap<AxNodeId, CdpAxNode>) -> Option<AxNode> {
+            let node = nodes.remove(id)?;
+            let children = node
+                .child_ids
+                .iter()
+                .flatten()
+                .filter_map(|child| build(child, nodes))
+                .collect();
+            Some(AxNode {
+                id: node.node_id,
+                parent_id: node.parent_id,
+                role: ax_string(node.role),
+                name: ax_string(node.name),
+                value: node.value.and_then(|value| value.value),
+                ignored: node.ignored,
+                backend_node_id: node.backend_dom_node_id,
+                children,
+            })
+        }
+        build(&root, &mut nodes)
+    }
+
+    /// The first node of this subtree matching `predicate`, depth first
+    pub fn find(&self, predicate: impl Fn(&AxNode) -> bool + Copy) -> Option<&AxNode> {
+        if predicate(self) {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(predicate))
+    }
+
+    /// The first node of this subtree with the `role` and accessible `name`
+    pub fn find_by_role(&self, role: &str, name: &str) -> Option<&AxNode> {
+        self.find(|node| node.role == role && node.name == name)
+    }
+}
+
+/// Load timing of the current document, see [`ChaserPage::performance_metrics`].
+///
+/// The timings are relative to the start of the navigation, they are `None`
+/// while the page hasn't reached them or if the browser doesn't report them.
+#[derive(Debug, Clone, Default)]
+pub struct PerfMetrics {
+    /// Time to first byte of the document response
+    pub ttfb: Option<Duration>,
+    /// When the `DOMContentLoaded` handlers finished
+    pub dom_content_loaded: Option<Duration>,
+    /// When the `load` handlers finished
+    pub load: Option<Duration>,
+    pub first_contentful_paint: Option<Duration>,
+    pub largest_contentful_paint: Option<Duration>,
+    /// The values of `Performance.getMetrics` by name, e.g. `JSHeapUsedSize`
+    /// or `Nodes`
+    pub metrics: HashMap<String, f64>,
+}
+
+/// Reads the navigation and paint timings in milliseconds, LCP is only
+/// reported to a buffered observer
+const PERF_TIMINGS_JS: &str = r#"
+    (async () => {
+        const nav = performance.getEntriesByType('navigation')[0];
+        const fcp = performance.getEntriesByName('first-contentful-paint')[0];
+        const lcp = await new Promise((resolve) => {
+            try {
+                new PerformanceObserver((list, observer) => {
+                    observer.disconnect();
+                    resolve(list.getEntries().at(-1).startTime);
+                }).observe({ type: 'largest-contentful-paint', buffered: true });
+                setTimeout(() => resolve(null), 50);
+            } catch {
+                resolve(null);
+            }
+        });
+        return {
+            ttfb: nav ? nav.responseStart : null,
+            domContentLoaded: nav ? nav.domContentLoadedEventEnd : null,
+            load: nav ? nav.loadEventEnd : null,
+            fcp: fcp ? fcp.startTime : null,
+            lcp,
+        };
+    })()
+"#;
+
+/// The string of an accessibility value, empty if there is none
+fn ax_string(value: Option<AxValue>) -> String {
+    match value.and_then(|value| value.value) {
+        Some(Value::String(s)) => s,
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// The most accesses an [`AccessLog`] keeps per document, as detectors may
+/// probe properties in tight loops
+const ACCESS_LOG_LIMIT: usize = 10_000;
+
+/// Wraps the getters and methods of the objects in proxies that log each
+/// access. The log is only kept by the proxies, which hand it out when read
+/// under the registered symbol of the key, so no global leads to it. Proxies
+/// of native functions still stringify as native code.
+const ACCESS_LOG_JS: &str = r#"
+    (key, objects, limit) => {
+        key = Symbol.for(key);
+        const log = [];
+        const now = performance.now.bind(performance);
+        const record = (object, property) => {
+            if (log.length < limit) log.push({ object, property, timestamp: now() });
+        };
+        const wrap = (fn, object, property) => new Proxy(fn, {
+            apply(target, thisArg, args) {
+                record(object, property);
+                return Reflect.apply(target, thisArg, args);
+            },
+            get(target, property, receiver) {
+                return property === key ? log : Reflect.get(target, property, receiver);
+            },
+        });
+        const seen = new Set();
+        for (const name of objects) {
+            const value = globalThis[name];
+            const proto = typeof value === 'function'
+                ? value.prototype
+                : value && Object.getPrototypeOf(value);
+            if (!proto || seen.has(proto)) continue;
+            seen.add(proto);
+            const descriptors = Object.getOwnPropertyDescriptors(proto);
+            for (const [property, desc] of Object.entries(descriptors)) {
+                if (property === 'constructor' || !desc.configurable) continue;
+                if (desc.get) desc.get = wrap(desc.get, name, property);
+                else if (typeof desc.value === 'function') desc.value = wrap(desc.value, name, property);
+                else continue;
+                Object.defineProperty(proto, property, desc);
+            }
+        }
+    }
+"#;
+
+/// Finds the log of [`ACCESS_LOG_JS`] through the instrumented functions of
+/// the objects and returns it, emptied first if `clear` is set
+const ACCESS_LOG_ENTRIES_JS: &str = r#"
+    (key, objects, clear) => {
+        key = Symbol.for(key);
+        for (const name of objects) {
+            const value = globalThis[name];
+            const proto = typeof value === 'function'
+                ? value.prototype
+                : value && Object.getPrototypeOf(value);
+            if (!proto) continue;
+            for (const desc of Object.values(Object.getOwnPropertyDescriptors(proto))) {
+                const fn = desc.get || desc.value;
+                const log = typeof fn === 'function' && fn[key];
+                if (!log) continue;
+                if (clear) log.length = 0;
+                return log;
+            }
+        }
+        return [];
+    }
+"#;
+
+/// The headers of a navigation the user typed into the address bar, as
+/// Chrome sends them since version 108
+const USER_NAVIGATION_HEADERS: [(&str, &str); 6] = [
+    (
+        "Accept",
+        "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,\
+         image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7",
+    ),
+    ("Upgrade-Insecure-Requests", "1"),
+    ("Sec-Fetch-Site", "none"),
+    ("Sec-Fetch-Mode", "navigate"),
+    ("Sec-Fetch-User", "?1"),
+    ("Sec-Fetch-Dest", "document"),
+];
+
+/// Resolves the center of the visible part of an element's box, or null if
+/// the element is missing, hidden, outside the viewport or covered by
+/// another element at that point
+const ELEMENT_CENTER_JS: &str = r#"
+    (selector) => {
+        const el = document.querySelector(selector);
+        if (!el) return null;
+        const style = getComputedStyle(el);
+        if (style.display === 'none' || style.visibility !== 'visible') return null;
+        if (!el.checkVisibility({ opacityProperty: true, visibilityProperty: true })) return null;
+        const rect = el.getBoundingClientRect();
+        const left = Math.max(rect.left, 0);
+        const top = Math.max(rect.top, 0);
+        const right = Math.min(rect.right, innerWidth);
+        const bottom = Math.min(rect.bottom, innerHeight);
+        if (right <= left || bottom <= top) return null;
+        const x = (left + right) / 2;
+        const y = (top + bottom) / 2;
+        const hit = document.elementFromPoint(x, y);
+        return hit && el.contains(hit) ? [x, y] : null;
+    }
+"#;
+
+/// Forces `iceTransportPolicy: 'relay'` on every `RTCPeerConnection`, so ICE
+/// gathers neither host nor server reflexive candidates. Connections made
+/// with a constructor saved before the patch are switched to relay on
+/// `setLocalDescription`, when gathering starts.
+const WEBRTC_RELAY_ONLY_JS: &str = r#"
+    () => {
+        const Native = window.RTCPeerConnection;
+        if (typeof Native !== 'function') return;
+        const proto = Native.prototype;
+        const relayOnly = (config) => Object.assign({}, config, { iceTransportPolicy: 'relay' });
+        const { getConfiguration, setConfiguration, setLocalDescription } = proto;
+        proto.setLocalDescription = {
+            setLocalDescription(...args) {
+                try {
+                    setConfiguration.call(this, relayOnly(getConfiguration.call(this)));
+                } catch (e) {}
+                return setLocalDescription.apply(this, args);
+            },
+        }.setLocalDescription;
+        const Patched = new Proxy(Native, {
+            construct(target, args, newTarget) {
+                return Reflect.construct(target, [relayOnly(args[0]), ...args.slice(1)], newTarget);
+            },
+        });
+        Object.defineProperty(proto, 'constructor', {
+            value: Patched,
+            writable: true,
+            enumerable: false,
+            configurable: true,
+        });
+        for (const name of ['RTCPeerConnection', 'webkitRTCPeerConnection']) {
+            if (window[name] === Native) window[name] = Patched;
+        }
+    }
+"#;
+
+/// A property read or method call recorded by an [`AccessLog`]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct PropertyAccess {
+    /// The object as named in [`ChaserPage::record_property_access`], e.g.
+    /// `navigator`
+    pub object: String,
+    pub property: String,
+    /// Milliseconds since the document's time origin
+    pub timestamp: f64,
+}
+
+/// The accesses of page scripts to the properties of some objects, see
+/// [`ChaserPage::record_property_access`]
+#[derive(Debug, Clone)]
+pub struct AccessLog {
+    chaser: ChaserPage,
+    key: String,
+    objects: Vec<String>,
+    script: ScriptIdentifier,
+}
+
+impl AccessLog {
+    /// The accesses in the current document, in order
+    pub async fn entries(&self) -> Result<Vec<PropertyAccess>> {
+        self.chaser
+            .query_main(ACCESS_LOG_ENTRIES_JS, &self.args(false))
+            .await
+    }
+
+    /// Forget the accesses recorded so far in the current document
+    pub async fn clear(&self) -> Result<()> {
+        self.chaser
+            .query_main::<Value>(ACCESS_LOG_ENTRIES_JS, &self.args(true))
+            .await?;
+        Ok(())
+    }
+
+    fn args(&self, clear: bool) -> [Value; 3] {
+        [
+            Value::from(self.key.as_str()),
+            Value::from(self.objects.clone()),
+            Value::from(clear),
+        ]
+    }
+
+    /// Stop instrumenting new documents. The current document keeps
+    /// recording until it is navigated away from.
+    pub async fn stop(self) -> Result<()> {
+        self.chaser
+            .page
+            .remove_script_on_new_document(self.script)
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        Ok(())
+    }
+}
+
 /// Stealth browser page with human-like input simulation.
 ///
 /// # Stealth JavaScript Execution
@@ -47,111 +914,1512 @@ pub struct Point {
 pub struct ChaserPage {
     page: Page,
     mouse_pos: Arc<Mutex<Point>>,
+    bootstrap: Arc<Mutex<Bootstrap>>,
+    /// The conditions of the last [`ChaserPage::emulate_network`]
+    network: Arc<Mutex<NetworkConditions>>,
+    /// The headers of the last [`ChaserPage::set_extra_headers`]
+    extra_headers: Arc<Mutex<HashMap<String, String>>>,
+}
+
+/// The profile bootstrap script registered on the page
+#[derive(Debug)]
+struct Bootstrap {
+    profile: Option<ChaserProfile>,
+    script_id: Option<ScriptIdentifier>,
+    worker_spoofing: bool,
 }
 
-impl ChaserPage {
-    /// Create a new ChaserPage wrapping the given Page.
-    pub fn new(page: Page) -> Self {
-        Self {
-            page,
-            mouse_pos: Arc::new(Mutex::new(Point { x: 0.0, y: 0.0 })),
+impl ChaserPage {
+    /// Create a new ChaserPage wrapping the given Page.
+    pub fn new(page: Page) -> Self {
+        Self {
+            page,
+            mouse_pos: Arc::new(Mutex::new(Point { x: 0.0, y: 0.0 })),
+            bootstrap: Arc::new(Mutex::new(Bootstrap {
+                profile: None,
+                script_id: None,
+                worker_spoofing: true,
+            })),
+            network: Arc::new(Mutex::new(NetworkConditions::Unthrottled)),
+            extra_headers: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    // ========== SAFE PAGE ACCESS ==========
+
+    /// Access the underlying Page.
+    ///
+    /// Most methods are safe, **except `raw_page().evaluate()`** which
+    /// triggers `Runtime.enable` detection. Use `chaser.evaluate()` instead.
+    #[doc(alias = "inner")]
+    pub fn raw_page(&self) -> &Page {
+        &self.page
+    }
+
+    /// Deprecated: Use `raw_page()` instead.
+    ///
+    /// This method is kept for backwards compatibility but will be removed in a future version.
+    #[deprecated(since = "0.1.1", note = "Use `raw_page()` instead for clarity")]
+    pub fn inner(&self) -> &Page {
+        &self.page
+    }
+
+    /// Send any CDP command to this page's session, for methods without a
+    /// typed wrapper.
+    ///
+    /// Page-scoped domains (`Page`, `Runtime`, `Network`, `DOM`, ...) act on
+    /// this page only. Browser-scoped domains (`Browser`, `Target`, `Storage`)
+    /// reach the whole browser like [`Browser::execute`](crate::Browser::execute).
+    /// Commands go out as they are, so e.g. `Runtime.enable` still exposes the
+    /// automation.
+    ///
+    /// ```ignore
+    /// use chaser_oxide::cdp::browser_protocol::page::BringToFrontParams;
+    /// chaser.execute(BringToFrontParams::default()).await?;
+    /// ```
+    pub async fn execute<T: Command>(&self, cmd: T) -> Result<CommandResponse<T::Response>> {
+        self.page.execute(cmd).await.map_err(|e| anyhow!("{}", e))
+    }
+
+    // ========== STEALTH-SAFE PAGE OPERATIONS ==========
+
+    /// Navigate to a URL (stealth-safe).
+    ///
+    /// This is equivalent to `raw_page().goto()` but provided for convenience.
+    ///
+    /// The URL goes to `Page.navigate` verbatim, the order of the query
+    /// parameters and the fragment are kept as given, e.g. for signed URLs.
+    /// Only chromium canonicalizes it the way the address bar would, e.g. it
+    /// lowercases the host and percent-encodes spaces.
+    ///
+    /// A failed navigation keeps its [`CdpError::Navigation`], so callers can
+    /// match on the net error:
+    ///
+    /// ```ignore
+    /// if let Err(err) = chaser.goto(url).await {
+    ///     if let Some(CdpError::Navigation { net_error, .. }) = err.downcast_ref() {
+    ///         // e.g. retry on "net::ERR_CONNECTION_RESET"
+    ///     }
+    /// }
+    /// ```
+    pub async fn goto(&self, url: &str) -> Result<()> {
+        self.page.goto(url).await?;
+        Ok(())
+    }
+
+    /// Navigate to a URL and wait for the given lifecycle point.
+    ///
+    /// `goto` is equivalent to `goto_with(url, WaitUntil::Load)`. Like `goto`,
+    /// the URL is passed on verbatim and a failed navigation is a
+    /// [`CdpError::Navigation`].
+    ///
+    /// # Example
+    /// ```ignore
+    /// // Wait for a SPA to finish fetching its data
+    /// chaser.goto_with("https://example.com", WaitUntil::NetworkIdle).await?;
+    /// ```
+    pub async fn goto_with(&self, url: &str, wait: WaitUntil) -> Result<()> {
+        match wait {
+            WaitUntil::Load => self.goto(url).await,
+            WaitUntil::None => self.navigate(url).await,
+            WaitUntil::DomContentLoaded => {
+                let mut dom_content_loaded = self
+                    .page
+                    .event_listener::<EventDomContentEventFired>()
+                    .await
+                    .map_err(|e| anyhow!("{}", e))?;
+                self.navigate(url).await?;
+                utils::timeout(NAVIGATION_TIMEOUT, dom_content_loaded.next())
+                    .await
+                    .ok_or_else(|| anyhow!("Timed out waiting for DOMContentLoaded"))?;
+                Ok(())
+            }
+            WaitUntil::NetworkIdle => self.goto_network_idle(url, 0).await,
+            WaitUntil::NetworkAlmostIdle => self.goto_network_idle(url, 2).await,
+        }
+    }
+
+    /// Navigate to a URL as if coming from the page `referer`, and wait for
+    /// the load event.
+    ///
+    /// The page's default referrer policy applies, so for a cross-origin
+    /// `url` the `Referer` header only carries the origin of `referer`, as
+    /// for a link click. Use [`ChaserPage::goto_with_referer_policy`] to send
+    /// more or less of it. Fails if `referer` isn't an absolute `http` or
+    /// `https` URL.
+    ///
+    /// # Example
+    /// ```ignore
+    /// chaser
+    ///     .goto_with_referer("https://example.com/item/42", "https://www.google.com/")
+    ///     .await?;
+    /// ```
+    pub async fn goto_with_referer(&self, url: &str, referer: &str) -> Result<()> {
+        self.navigate_from(url, referer, None).await
+    }
+
+    /// Like [`ChaserPage::goto_with_referer`], with the referrer policy of
+    /// the navigation, e.g. [`ReferrerPolicy::UnsafeUrl`] to send the full
+    /// `referer` to another origin
+    pub async fn goto_with_referer_policy(
+        &self,
+        url: &str,
+        referer: &str,
+        policy: ReferrerPolicy,
+    ) -> Result<()> {
+        self.navigate_from(url, referer, Some(policy)).await
+    }
+
+    async fn navigate_from(
+        &self,
+        url: &str,
+        referer: &str,
+        policy: Option<ReferrerPolicy>,
+    ) -> Result<()> {
+        let parsed =
+            url::Url::parse(referer).map_err(|e| anyhow!("invalid referer `{referer}`: {e}"))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(anyhow!("referer `{referer}` must be an http or https URL"));
+        }
+        let mut params = NavigateParams::new(url);
+        params.referrer = Some(referer.to_string());
+        params.referrer_policy = policy;
+        self.page.goto(params).await?;
+        Ok(())
+    }
+
+    /// Navigate to a URL like a user typing it into the address bar, and
+    /// wait for the load event.
+    ///
+    /// The document request carries the `Accept`, `Upgrade-Insecure-Requests`
+    /// and `Sec-Fetch-*` headers Chrome sends for such a navigation
+    /// (`Sec-Fetch-Site: none`, `Sec-Fetch-Mode: navigate`,
+    /// `Sec-Fetch-User: ?1`, `Sec-Fetch-Dest: document`), whatever the
+    /// navigation through CDP would send. They are set on the document
+    /// request and its redirects with `Fetch.continueRequest`, so no other
+    /// request of the page gets them. Headers of
+    /// [`ChaserPage::set_extra_headers`] with the same name take precedence.
+    pub async fn navigate_like_user(&self, url: &str) -> Result<()> {
+        let headers = {
+            let extra = self.extra_headers.lock().unwrap();
+            USER_NAVIGATION_HEADERS
+                .iter()
+                .filter(|(name, _)| !extra.keys().any(|key| key.eq_ignore_ascii_case(name)))
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect()
+        };
+        let main_frame = self
+            .page
+            .mainframe()
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .ok_or_else(|| anyhow!("the page has no main frame"))?;
+        self.page
+            .set_navigation_headers(Some(NavigationHeaders::new(main_frame, url, headers)))
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+
+        let mut params = NavigateParams::new(url);
+        params.transition_type = Some(TransitionType::Typed);
+        let result = self.page.goto(params).await;
+        // if this future is dropped before, the headers stay set for another
+        // navigation to `url` only
+        self.page
+            .set_navigation_headers(None)
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        result?;
+        Ok(())
+    }
+
+    /// Issue `Page.navigate` without waiting for the new document
+    async fn navigate(&self, url: &str) -> Result<()> {
+        let res = self
+            .page
+            .execute(NavigateParams::new(url))
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        match res.result.error_text {
+            Some(net_error) => Err(CdpError::Navigation {
+                url: url.to_string(),
+                net_error,
+            }
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Navigate, wait for the load event, then until at most
+    /// `max_connections` requests were in flight for `NETWORK_IDLE_TIME`
+    async fn goto_network_idle(&self, url: &str, max_connections: usize) -> Result<()> {
+        let mut activity = self.network_events().await?.filter_map(|ev| {
+            future::ready(match ev {
+                NetworkEvent::RequestWillBeSent(_) => Some((ev.request_id().clone(), true)),
+                NetworkEvent::ResponseReceived(_) => None,
+                NetworkEvent::LoadingFinished(_) | NetworkEvent::LoadingFailed(_) => {
+                    Some((ev.request_id().clone(), false))
+                }
+            })
+        });
+
+        self.goto(url).await?;
+
+        let wait_for_idle = async {
+            let mut in_flight = HashSet::new();
+            loop {
+                match utils::timeout(NETWORK_IDLE_TIME, activity.next()).await {
+                    Some(Some((request_id, true))) => {
+                        in_flight.insert(request_id);
+                    }
+                    Some(Some((request_id, false))) => {
+                        in_flight.remove(&request_id);
+                    }
+                    Some(None) => return,
+                    None if in_flight.len() <= max_connections => return,
+                    None => {}
+                }
+            }
+        };
+        utils::timeout(NAVIGATION_TIMEOUT, wait_for_idle)
+            .await
+            .ok_or_else(|| anyhow!("Timed out waiting for network idle"))
+    }
+
+    /// Wait until the URL of the page stopped changing for `settle` and
+    /// return it, to follow redirects by meta refresh or script after `goto`.
+    ///
+    /// Every main frame navigation, including same-document ones like
+    /// `history.pushState`, restarts the settle window. A navigation the
+    /// main frame requested or started loading is waited for even if its
+    /// response takes longer than `settle`. Fails if the page is still
+    /// navigating after 30 seconds.
+    ///
+    /// # Example
+    /// ```ignore
+    /// chaser.goto("https://example.com/short-link").await?;
+    /// let canonical = chaser.final_url(Duration::from_secs(1)).await?;
+    /// ```
+    pub async fn final_url(&self, settle: Duration) -> Result<String> {
+        enum Signal {
+            Requested,
+            Started,
+            Stopped,
+            Navigated,
+        }
+
+        let main_frame = self.page.mainframe().await.map_err(|e| anyhow!("{}", e))?;
+        let is_main = |frame_id: &FrameId| Some(frame_id) == main_frame.as_ref();
+        let navigated = self
+            .page
+            .event_listener::<EventFrameNavigated>()
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .filter(|ev| future::ready(ev.frame.parent_id.is_none()))
+            .map(|_| Signal::Navigated);
+        let within_document = self
+            .page
+            .event_listener::<EventNavigatedWithinDocument>()
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .filter(move |ev| future::ready(is_main(&ev.frame_id)))
+            .map(|_| Signal::Navigated);
+        let requested = self
+            .page
+            .event_listener::<EventFrameRequestedNavigation>()
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .filter(move |ev| future::ready(is_main(&ev.frame_id)))
+            .map(|_| Signal::Requested);
+        let started = self
+            .page
+            .event_listener::<EventFrameStartedLoading>()
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .filter(move |ev| future::ready(is_main(&ev.frame_id)))
+            .map(|_| Signal::Started);
+        let stopped = self
+            .page
+            .event_listener::<EventFrameStoppedLoading>()
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .filter(move |ev| future::ready(is_main(&ev.frame_id)))
+            .map(|_| Signal::Stopped);
+        let mut signals = stream::select_all([
+            navigated.boxed(),
+            within_document.boxed(),
+            requested.boxed(),
+            started.boxed(),
+            stopped.boxed(),
+        ]);
+
+        let settled = utils::timeout(NAVIGATION_TIMEOUT, async {
+            // a requested navigation that didn't commit yet, and a load in
+            // progress
+            let (mut requested, mut loading) = (false, false);
+            loop {
+                match utils::timeout(settle, signals.next()).await {
+                    Some(Some(Signal::Requested)) => requested = true,
+                    Some(Some(Signal::Started)) => loading = true,
+                    Some(Some(Signal::Stopped)) => {
+                        requested = false;
+                        loading = false;
+                    }
+                    Some(Some(Signal::Navigated)) => requested = false,
+                    Some(None) => return Err(anyhow!("Page closed while waiting for its URL")),
+                    None if !requested && !loading => return Ok(()),
+                    None => {}
+                }
+            }
+        })
+        .await
+        .ok_or_else(|| anyhow!("URL still changing after {:?}", NAVIGATION_TIMEOUT))?;
+        settled?;
+
+        self.page
+            .url()
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .ok_or_else(|| anyhow!("Page has no URL"))
+    }
+
+    /// Stream the network events of this page and its same-process frames,
+    /// in the order the browser emits them (stealth-safe).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut events = chaser.network_events().await?;
+    /// while let Some(NetworkEvent::RequestWillBeSent(ev)) = events.next().await {
+    ///     println!("{} {}", ev.request.method, ev.request.url);
+    /// }
+    /// ```
+    pub async fn network_events(&self) -> Result<impl Stream<Item = NetworkEvent> + Unpin> {
+        let requests = self
+            .page
+            .event_listener::<EventRequestWillBeSent>()
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .map(NetworkEvent::RequestWillBeSent);
+        let responses = self
+            .page
+            .event_listener::<EventResponseReceived>()
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .map(NetworkEvent::ResponseReceived);
+        let finished = self
+            .page
+            .event_listener::<EventLoadingFinished>()
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .map(NetworkEvent::LoadingFinished);
+        let failed = self
+            .page
+            .event_listener::<EventLoadingFailed>()
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .map(NetworkEvent::LoadingFailed);
+        Ok(stream::select(
+            stream::select(requests, responses),
+            stream::select(finished, failed),
+        ))
+    }
+
+    /// Stream the `console` calls of the page, e.g. to find out why a
+    /// bootstrap patch fails on a site.
+    ///
+    /// **Not stealth-safe**: this enables the `Runtime` domain, which pages
+    /// can detect. Messages logged before the call are replayed.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut messages = chaser.console_messages().await?;
+    /// while let Some(message) = messages.next().await {
+    ///     println!("{:?} {}", message.level, message.text);
+    /// }
+    /// ```
+    pub async fn console_messages(&self) -> Result<impl Stream<Item = ConsoleEvent> + Unpin> {
+        let messages = self
+            .page
+            .event_listener::<EventConsoleApiCalled>()
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .map(|ev| ConsoleEvent::from_console_api(&ev));
+        self.page
+            .execute(RuntimeEnableParams::default())
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        Ok(messages)
+    }
+
+    /// Stream the uncaught exceptions of the page and the errors of the
+    /// browser log, e.g. failed requests and CSP violations.
+    ///
+    /// **Not stealth-safe**: like [`ChaserPage::console_messages`], this
+    /// enables the `Runtime` domain.
+    pub async fn page_errors(&self) -> Result<impl Stream<Item = ConsoleEvent> + Unpin> {
+        let exceptions = self
+            .page
+            .event_listener::<EventExceptionThrown>()
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .map(|ev| ConsoleEvent::from_exception(&ev));
+        let entries = self
+            .page
+            .event_listener::<EventEntryAdded>()
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .filter(|ev| future::ready(ev.entry.level == LogEntryLevel::Error))
+            .map(|ev| ConsoleEvent::from_log_entry(&ev));
+        self.page
+            .execute(LogEnableParams::default())
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        self.page
+            .execute(RuntimeEnableParams::default())
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        Ok(stream::select(exceptions, entries))
+    }
+
+    /// Wait for the first response whose URL contains `url_substring` and
+    /// return its status and body (stealth-safe).
+    ///
+    /// The network domain is enabled when the page is created, but only
+    /// responses received after this call are matched: start waiting before
+    /// the navigation or click that triggers the request. Fails if the
+    /// request fails or no response finished loading within `timeout`.
+    ///
+    /// The body is read with `Network.getResponseBody`, which returns it
+    /// with its `Content-Encoding` undone already. See
+    /// [`Page::on_response_with`] for the raw bytes.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let (response, _) = futures::join!(
+    ///     chaser.wait_for_response("/api/products", Duration::from_secs(10)),
+    ///     chaser.goto("https://example.com/shop"),
+    /// );
+    /// let (status, body) = response?;
+    /// let products: serde_json::Value = serde_json::from_slice(&body)?;
+    /// ```
+    pub async fn wait_for_response(
+        &self,
+        url_substring: &str,
+        timeout: Duration,
+    ) -> Result<(u16, Vec<u8>)> {
+        let mut events = self.network_events().await?;
+        let matched = utils::timeout(timeout, async {
+            // the events come from separate listeners, so a request may
+            // finish before its response is seen
+            let mut finished = HashSet::new();
+            let mut response = None;
+            while let Some(event) = events.next().await {
+                match event {
+                    NetworkEvent::ResponseReceived(ev)
+                        if response.is_none() && ev.response.url.contains(url_substring) =>
+                    {
+                        let status = ev.response.status as u16;
+                        if finished.contains(&ev.request_id) {
+                            return Ok((ev.request_id.clone(), status));
+                        }
+                        response = Some((ev.request_id.clone(), status));
+                    }
+                    NetworkEvent::LoadingFinished(ev) => match response.take() {
+                        Some((id, status)) if id == ev.request_id => return Ok((id, status)),
+                        other => {
+                            response = other;
+                            finished.insert(ev.request_id.clone());
+                        }
+                    },
+                    NetworkEvent::LoadingFailed(ev)
+                        if response.as_ref().map(|(id, _)| id) == Some(&ev.request_id) =>
+                    {
+                        return Err(anyhow!(
+                            "Response for {} failed: {}",
+                            url_substring,
+                            ev.error_text
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+            Err(anyhow!("Page closed while waiting for {}", url_substring))
+        })
+        .await
+        .ok_or_else(|| anyhow!("No response for {} within {:?}", url_substring, timeout))??;
+
+        let (request_id, status) = matched;
+        let body = self
+            .page
+            .execute(GetResponseBodyParams::new(request_id))
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .result;
+        let body = if body.base64_encoded {
+            STANDARD.decode(body.body)?
+        } else {
+            body.body.into_bytes()
+        };
+        Ok((status, body))
+    }
+
+    /// Get the page HTML content (stealth-safe).
+    pub async fn content(&self) -> Result<String> {
+        self.page.content().await.map_err(|e| anyhow!("{}", e))
+    }
+
+    /// Get the HTML of the live DOM, including nodes inserted by scripts
+    /// (stealth-safe).
+    ///
+    /// This is `document.documentElement.outerHTML` read in the main world,
+    /// so call it after waiting for the content you're after.
+    pub async fn rendered_html(&self) -> Result<String> {
+        self.query_main("() => document.documentElement.outerHTML", &[])
+            .await
+    }
+
+    /// Get the `innerHTML` of the first element matching `selector` in the
+    /// live DOM, `None` if nothing matches (stealth-safe).
+    pub async fn inner_html(&self, selector: &str) -> Result<Option<String>> {
+        self.query_main(
+            "(selector) => document.querySelector(selector)?.innerHTML ?? null",
+            &[Value::from(selector)],
+        )
+        .await
+    }
+
+    /// Get the rendered text (`innerText`) of every element matching
+    /// `selector`, in document order (stealth-safe).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let titles = chaser.query_all_text("h2.product-title").await?;
+    /// ```
+    pub async fn query_all_text(&self, selector: &str) -> Result<Vec<String>> {
+        self.query_main(
+            "(selector) => Array.from(document.querySelectorAll(selector), (el) => el.innerText)",
+            &[Value::from(selector)],
+        )
+        .await
+    }
+
+    /// Get the `attr` attribute of every element matching `selector`, in
+    /// document order, `None` for elements without it (stealth-safe).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let links = chaser.query_all_attr("a.result", "href").await?;
+    /// ```
+    pub async fn query_all_attr(&self, selector: &str, attr: &str) -> Result<Vec<Option<String>>> {
+        self.query_main(
+            "(selector, attr) => Array.from(document.querySelectorAll(selector), (el) => el.getAttribute(attr))",
+            &[Value::from(selector), Value::from(attr)],
+        )
+        .await
+    }
+
+    /// The point to click the first element matching `selector` at, in
+    /// viewport coordinates: the center of the part of its box inside the
+    /// viewport.
+    ///
+    /// `None` if nothing matches, or if the element can't be clicked: it is
+    /// hidden by `display`, `visibility` or `opacity` (its own or an
+    /// ancestor's), has an empty box, is scrolled out of the viewport, or
+    /// another element covers that point. Children of the element, like the
+    /// label of a button, don't count as covering it.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// if let Some((x, y)) = chaser.element_center("button#submit").await? {
+    ///     chaser.click_human(x, y).await?;
+    /// }
+    /// ```
+    pub async fn element_center(&self, selector: &str) -> Result<Option<(f64, f64)>> {
+        self.query_main(ELEMENT_CENTER_JS, &[Value::from(selector)])
+            .await
+    }
+
+    /// Whether the first element matching `selector` is rendered, inside the
+    /// viewport and not covered by another element, see
+    /// [`ChaserPage::element_center`]
+    pub async fn is_visible(&self, selector: &str) -> Result<bool> {
+        Ok(self.element_center(selector).await?.is_some())
+    }
+
+    /// Call `fn_decl` in the main world, which sees the rendered DOM, and
+    /// deserialize its result
+    async fn query_main<T: DeserializeOwned>(&self, fn_decl: &str, args: &[Value]) -> Result<T> {
+        let value = self
+            .call_function_with(
+                fn_decl,
+                args,
+                CallFunctionOptions {
+                    world: ExecutionWorld::Main,
+                    await_promise: false,
+                },
+            )
+            .await?;
+        serde_json::from_value(value).map_err(|e| anyhow!("{}", e))
+    }
+
+    /// Get the current page URL (stealth-safe).
+    pub async fn url(&self) -> Result<Option<String>> {
+        self.page.url().await.map_err(|e| anyhow!("{}", e))
+    }
+
+    /// Execute JavaScript using **stealth execution** (no Runtime.enable leak).
+    ///
+    /// This is the safe way to run JavaScript on protected sites.
+    /// Under the hood, it uses `Page.createIsolatedWorld` to avoid detection.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// // Get page title
+    /// let title: String = chaser.evaluate("document.title").await?;
+    ///
+    /// // Check a value
+    /// let ua: String = chaser.evaluate("navigator.userAgent").await?;
+    /// ```
+    pub async fn evaluate(&self, script: &str) -> Result<Option<Value>> {
+        self.evaluate_stealth(script).await
+    }
+
+    /// Execute JavaScript using stealth execution and deserialize the result.
+    ///
+    /// Fails if the result doesn't match the shape of `T`, or if the script
+    /// evaluates to `null`/`undefined` and `T` isn't an `Option`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let cores: u32 = chaser.evaluate_as("navigator.hardwareConcurrency").await?;
+    /// let langs: Vec<String> = chaser.evaluate_as("navigator.languages").await?;
+    /// ```
+    pub async fn evaluate_as<T: DeserializeOwned>(&self, script: &str) -> Result<T> {
+        let value = self.evaluate_stealth(script).await?;
+        match value {
+            Some(value) if !value.is_null() => serde_json::from_value(value).map_err(|e| {
+                anyhow!(
+                    "`{}` can't be deserialized into {}: {}",
+                    script,
+                    std::any::type_name::<T>(),
+                    e
+                )
+            }),
+            value => serde_json::from_value(Value::Null).map_err(|_| {
+                anyhow!(
+                    "`{}` evaluated to {}, expected {}",
+                    script,
+                    if value.is_some() { "null" } else { "undefined" },
+                    std::any::type_name::<T>()
+                )
+            }),
+        }
+    }
+
+    /// Apply a ChaserProfile to this page in one clean call.
+    ///
+    /// This method:
+    /// 1. Overrides the User-Agent HTTP header and client hints (`Sec-CH-UA*`)
+    /// 2. Injects the profile's bootstrap script for JS-level spoofing
+    /// 3. Enables touch emulation if the profile has touch points
+    /// 4. Bypasses the page's CSP if the profile opted in, see
+    ///    [`ChaserProfileBuilder::bypass_csp`](crate::ChaserProfileBuilder::bypass_csp)
+    ///
+    /// **IMPORTANT:** Call this BEFORE navigating to the target site.
+    ///
+    /// Fails with [`MalformedScript`](crate::MalformedScript) if an extra
+    /// script of the profile would break the bootstrap script.
+    ///
+    /// The script is registered with `Page.addScriptToEvaluateOnNewDocument`,
+    /// so it also runs in same-process iframes. Cross-origin (out-of-process)
+    /// iframes are auto-attached paused, receive the script and User-Agent
+    /// override, then resumed. Unlike the `iframe-workaround` example this
+    /// doesn't block navigation, but iframes nested inside an out-of-process
+    /// iframe are not covered.
+    ///
+    /// # Example
+    /// ```rust
+    /// let profile = ChaserProfile::windows().build();
+    /// let page = browser.new_page("about:blank").await?;
+    /// let chaser = ChaserPage::new(page);
+    /// chaser.apply_profile(&profile).await?;
+    /// chaser.inner().goto("https://example.com").await?;
+    /// ```
+    pub async fn apply_profile(&self, profile: &ChaserProfile) -> Result<()> {
+        profile.check_extra_scripts()?;
+
+        // 1. Set the HTTP User-Agent header and the matching client hints
+        let mut overrides = vec![batch(profile.user_agent_override())?];
+
+        // 2. Expose touch events, otherwise `maxTouchPoints` stays 0
+        if profile.touch_points() > 0 {
+            overrides.push(batch(
+                SetTouchEmulationEnabledParams::builder()
+                    .enabled(true)
+                    .max_touch_points(profile.touch_points() as i64)
+                    .build()
+                    .map_err(|e| anyhow!("{}", e))?,
+            )?);
+        }
+
+        // 3. Keep a strict CSP from blocking the injected scripts
+        if profile.bypass_csp() {
+            overrides.push(batch(SetBypassCspParams::new(true))?);
+        }
+
+        // 4. Report the profile's color scheme and motion preference
+        overrides.push(batch(
+            SetEmulatedMediaParams::builder()
+                .features(profile.media_features())
+                .build(),
+        )?);
+
+        // 5. Report a location consistent with the profile's timezone
+        if let Some((latitude, longitude)) = profile.geolocation() {
+            overrides.push(batch(
+                SetGeolocationOverrideParams::builder()
+                    .latitude(latitude)
+                    .longitude(longitude)
+                    .accuracy(GEOLOCATION_ACCURACY)
+                    .build(),
+            )?);
+        }
+
+        // 6. Inject the bootstrap script to run on every new document, while
+        // the overrides are in flight
+        let overrides = async {
+            self.page
+                .execute_batch(overrides)
+                .await
+                .map_err(|e| anyhow!("{}", e))
+        };
+        future::try_join(overrides, self.register_bootstrap(profile.clone())).await?;
+
+        Ok(())
+    }
+
+    /// Enable or disable injecting the profile into dedicated workers
+    /// (default: enabled).
+    ///
+    /// The `Worker` shim fetches the worker source and prepends the profile
+    /// script, which breaks workers that rely on their own URL, e.g. for
+    /// `importScripts` with relative paths. Module workers and workers whose
+    /// source can't be fetched are never wrapped. If a profile was applied,
+    /// its script is re-registered, so this takes effect on the next
+    /// navigation.
+    pub async fn set_worker_spoofing(&self, enabled: bool) -> Result<()> {
+        let profile = {
+            let mut bootstrap = self.bootstrap.lock().unwrap();
+            if bootstrap.worker_spoofing == enabled {
+                return Ok(());
+            }
+            bootstrap.worker_spoofing = enabled;
+            bootstrap.profile.clone()
+        };
+        if let Some(profile) = profile {
+            self.register_bootstrap(profile).await?;
+        }
+        Ok(())
+    }
+
+    /// Check that the patches of the applied profile are in effect in the
+    /// current document, e.g. after a navigation.
+    ///
+    /// Compares values the bootstrap script spoofs (`navigator.platform`,
+    /// `hardwareConcurrency` and the client hints brands, which iOS profiles
+    /// remove) with the profile, in the main world. The script sets no marker
+    /// for this on purpose, as pages could look for it as well. Returns false
+    /// if no profile was applied, or if the document was loaded before the
+    /// profile was applied and needs a reload.
+    pub async fn verify_stealth(&self) -> Result<bool> {
+        let Some(profile) = self.bootstrap.lock().unwrap().profile.clone() else {
+            return Ok(false);
+        };
+        let brands = if profile.has_client_hints() {
+            Value::from(serde_json::to_string(&profile.brands())?)
+        } else {
+            Value::Null
+        };
+        let applied = self
+            .call_function_with(
+                "(platform, cores, brands) => navigator.platform === platform
+                    && navigator.hardwareConcurrency === cores
+                    && (brands === null
+                        ? navigator.userAgentData === undefined
+                        : JSON.stringify((navigator.userAgentData || {}).brands) === brands)",
+                &[
+                    Value::from(profile.platform()),
+                    Value::from(profile.cpu_cores()),
+                    brands,
+                ],
+                CallFunctionOptions {
+                    world: ExecutionWorld::Main,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        Ok(applied == Value::Bool(true))
+    }
+
+    /// Revert [`ChaserPage::apply_profile`], so the page reports the real
+    /// browser identity again from the next navigation on.
+    ///
+    /// Removes the bootstrap script and clears the User-Agent, media,
+    /// timezone, locale and geolocation overrides, as well as the touch
+    /// emulation, CSP bypass and device orientation if the profile enabled
+    /// them. The viewport emulation of the browser config is kept. Does
+    /// nothing if no profile was applied.
+    pub async fn reset_identity(&self) -> Result<()> {
+        let (profile, script_id) = {
+            let mut bootstrap = self.bootstrap.lock().unwrap();
+            (bootstrap.profile.take(), bootstrap.script_id.take())
+        };
+        let Some(profile) = profile else {
+            return Ok(());
+        };
+
+        // empty values disable the overrides
+        let mut overrides = vec![
+            batch(SetUserAgentOverrideParams::new(""))?,
+            batch(
+                SetEmulatedMediaParams::builder()
+                    .media("")
+                    .features(Vec::<MediaFeature>::new())
+                    .build(),
+            )?,
+            batch(SetTimezoneOverrideParams::new(""))?,
+            batch(SetLocaleOverrideParams::default())?,
+            batch(ClearGeolocationOverrideParams::default())?,
+        ];
+        if let Some(identifier) = script_id {
+            overrides.push(batch(RemoveScriptToEvaluateOnNewDocumentParams::new(
+                identifier,
+            ))?);
+        }
+        if profile.touch_points() > 0 {
+            overrides.push(batch(SetTouchEmulationEnabledParams::new(false))?);
+        }
+        if profile.bypass_csp() {
+            overrides.push(batch(SetBypassCspParams::new(false))?);
+        }
+        if profile.mobile() {
+            overrides.push(batch(ClearDeviceOrientationOverrideParams::default())?);
+        }
+        self.page
+            .execute_batch(overrides)
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        Ok(())
+    }
+
+    /// Register the bootstrap script of `profile`, replacing a previously
+    /// registered one
+    async fn register_bootstrap(&self, profile: ChaserProfile) -> Result<()> {
+        let (previous, worker_spoofing) = {
+            let mut bootstrap = self.bootstrap.lock().unwrap();
+            (bootstrap.script_id.take(), bootstrap.worker_spoofing)
+        };
+        if let Some(identifier) = previous {
+            self.page
+                .remove_script_on_new_document(identifier)
+                .await
+                .map_err(|e| anyhow!("{}", e))?;
+        }
+
+        let source = if worker_spoofing {
+            profile.bootstrap_script()
+        } else {
+            profile.page_script()
+        };
+        let res = self
+            .page
+            .execute(AddScriptToEvaluateOnNewDocumentParams {
+                source,
+                world_name: None,
+                include_command_line_api: None,
+                run_immediately: None,
+            })
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+
+        let mut bootstrap = self.bootstrap.lock().unwrap();
+        bootstrap.profile = Some(profile);
+        bootstrap.script_id = Some(res.result.identifier);
+        Ok(())
+    }
+
+    // ========== CHALLENGE DETECTION ==========
+
+    /// Wait until no Cloudflare challenge (interstitial or Turnstile) is
+    /// active on the page and the document finished loading.
+    ///
+    /// Returns the first challenge that was detected while waiting, `None` if
+    /// the page never showed one. Fails if a challenge is still active after
+    /// `timeout`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// chaser.goto("https://example.com").await?;
+    /// if let Some(kind) = chaser.wait_for_challenge_clear(Duration::from_secs(15)).await? {
+    ///     println!("passed {:?}", kind);
+    /// }
+    /// ```
+    pub async fn wait_for_challenge_clear(
+        &self,
+        timeout: Duration,
+    ) -> Result<Option<ChallengeKind>> {
+        self.wait_for_challenge_clear_with(timeout, &ChallengeMarker::defaults())
+            .await
+    }
+
+    /// Like [`ChaserPage::wait_for_challenge_clear`], with a custom marker
+    /// list. Extend [`ChallengeMarker::defaults`] to add markers.
+    pub async fn wait_for_challenge_clear_with(
+        &self,
+        timeout: Duration,
+        markers: &[ChallengeMarker],
+    ) -> Result<Option<ChallengeKind>> {
+        let deadline = std::time::Instant::now() + timeout;
+        let mut detected = None;
+        loop {
+            // errors are expected while the challenge navigates to the final page
+            if let Ok(active) = self.active_challenge(markers).await {
+                match active {
+                    Some(kind) => {
+                        detected.get_or_insert(kind);
+                    }
+                    None => {
+                        let ready_state = self.evaluate_as::<String>("document.readyState").await;
+                        if ready_state.map(|s| s == "complete").unwrap_or_default() {
+                            return Ok(detected);
+                        }
+                    }
+                }
+            }
+            if std::time::Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "Challenge {:?} not cleared within {:?}",
+                    detected,
+                    timeout
+                ));
+            }
+            utils::sleep(CHALLENGE_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Returns the kind of the first marker present on the page
+    async fn active_challenge(&self, markers: &[ChallengeMarker]) -> Result<Option<ChallengeKind>> {
+        let mut cookies = None;
+        for marker in markers {
+            let present = match &marker.check {
+                ChallengeCheck::Selector(selector) => self
+                    .call_function(
+                        "(selector) => !!document.querySelector(selector)",
+                        &[Value::from(selector.as_str())],
+                    )
+                    .await?
+                    .as_bool()
+                    .unwrap_or_default(),
+                ChallengeCheck::Title(title) => self
+                    .call_function(
+                        "(title) => document.title.includes(title)",
+                        &[Value::from(title.as_str())],
+                    )
+                    .await?
+                    .as_bool()
+                    .unwrap_or_default(),
+                ChallengeCheck::CookiePrefix(prefix) => {
+                    if cookies.is_none() {
+                        cookies = Some(
+                            self.page
+                                .get_cookies()
+                                .await
+                                .map_err(|e| anyhow!("{}", e))?,
+                        );
+                    }
+                    cookies
+                        .iter()
+                        .flatten()
+                        .any(|cookie| cookie.name.starts_with(prefix.as_str()))
+                }
+                ChallengeCheck::Script(script) => self
+                    .call_function(
+                        "(script) => !!(0, eval)(script)",
+                        &[Value::from(script.as_str())],
+                    )
+                    .await?
+                    .as_bool()
+                    .unwrap_or_default(),
+            };
+            if present {
+                return Ok(Some(marker.kind.clone()));
+            }
+        }
+        Ok(None)
+    }
+
+    // ========== SCREENSHOTS ==========
+
+    /// Take a screenshot once the page stopped changing visually.
+    ///
+    /// Cheap low quality frames are captured until two consecutive ones are
+    /// identical, then the screenshot is taken with `params`. Fails if the
+    /// page is still changing after `timeout`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// chaser.goto("https://example.com").await?;
+    /// let png = chaser
+    ///     .screenshot_when_stable(ScreenshotParams::default(), Duration::from_secs(5))
+    ///     .await?;
+    /// ```
+    pub async fn screenshot_when_stable(
+        &self,
+        params: impl Into<ScreenshotParams>,
+        timeout: Duration,
+    ) -> Result<Vec<u8>> {
+        let sample = CaptureScreenshotParams::builder()
+            .format(CaptureScreenshotFormat::Jpeg)
+            .quality(30)
+            .build();
+        let stable = utils::timeout(timeout, async {
+            let mut previous = None;
+            loop {
+                let frame = self.page.execute(sample.clone()).await?.result.data;
+                if previous.as_ref() == Some(&frame) {
+                    return Ok::<_, CdpError>(());
+                }
+                previous = Some(frame);
+                utils::sleep(STABLE_POLL_INTERVAL).await;
+            }
+        })
+        .await;
+        match stable {
+            Some(res) => res.map_err(|e| anyhow!("{}", e))?,
+            None => return Err(anyhow!("Page not visually stable within {:?}", timeout)),
+        }
+        self.page
+            .screenshot(params)
+            .await
+            .map_err(|e| anyhow!("{}", e))
+    }
+
+    /// Take a screenshot as a base64 `data:` URL, e.g. to hand it to an OCR
+    /// or captcha solving API.
+    ///
+    /// The MIME type follows the format of `params`, PNG if none is set.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let jpeg = chaser
+    ///     .screenshot_data_url(
+    ///         ScreenshotParams::builder()
+    ///             .format(CaptureScreenshotFormat::Jpeg)
+    ///             .quality(80)
+    ///             .build(),
+    ///     )
+    ///     .await?;
+    /// assert!(jpeg.starts_with("data:image/jpeg;base64,"));
+    /// ```
+    pub async fn screenshot_data_url(&self, params: impl Into<ScreenshotParams>) -> Result<String> {
+        let params = params.into();
+        let format = params
+            .cdp_params
+            .format
+            .clone()
+            .unwrap_or(CaptureScreenshotFormat::Png);
+        let image = self
+            .page
+            .screenshot(params)
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        Ok(format!(
+            "data:image/{};base64,{}",
+            format.as_ref(),
+            STANDARD.encode(image)
+        ))
+    }
+
+    // ========== ACCESSIBILITY ==========
+
+    /// The accessibility tree of the page, rooted at the document.
+    ///
+    /// Roles and accessible names don't change with the page layout, which
+    /// makes them a stable way to locate elements.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let tree = chaser.accessibility_tree().await?;
+    /// let login = tree.find_by_role("button", "Log in");
+    /// ```
+    pub async fn accessibility_tree(&self) -> Result<AxNode> {
+        let nodes = self.full_ax_tree().await?;
+        AxNode::from_cdp(nodes, |node| node.parent_id.is_none())
+            .ok_or_else(|| anyhow!("Page has no accessibility tree"))
+    }
+
+    /// The accessibility subtree of the DOM node `backend_node_id`, e.g. of
+    /// an [`Element`](crate::Element)
+    pub async fn accessibility_subtree(&self, backend_node_id: BackendNodeId) -> Result<AxNode> {
+        let nodes = self.full_ax_tree().await?;
+        AxNode::from_cdp(nodes, |node| {
+            node.backend_dom_node_id.as_ref() == Some(&backend_node_id)
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "No accessibility node for backend node {:?}",
+                backend_node_id
+            )
+        })
+    }
+
+    async fn full_ax_tree(&self) -> Result<Vec<CdpAxNode>> {
+        Ok(self
+            .page
+            .execute(GetFullAxTreeParams::default())
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .result
+            .nodes)
+    }
+
+    // ========== PERFORMANCE ==========
+
+    /// The load timing of the current document together with the runtime
+    /// metrics of `Performance.getMetrics`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// chaser.goto("https://example.com").await?;
+    /// let perf = chaser.performance_metrics().await?;
+    /// println!("TTFB: {:?}, load: {:?}", perf.ttfb, perf.load);
+    /// ```
+    pub async fn performance_metrics(&self) -> Result<PerfMetrics> {
+        self.page
+            .execute(PerformanceEnableParams::default())
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        let metrics = self
+            .page
+            .execute(GetMetricsParams::default())
+            .await
+            .map_err(|e| anyhow!("{}", e))?
+            .result
+            .metrics
+            .into_iter()
+            .map(|metric| (metric.name, metric.value))
+            .collect();
+
+        let timings: Value = self.evaluate_as(PERF_TIMINGS_JS).await?;
+        // unreached timings are reported as 0
+        let timing = |key: &str| {
+            timings[key]
+                .as_f64()
+                .filter(|ms| *ms > 0.0)
+                .map(|ms| Duration::from_secs_f64(ms / 1000.0))
+        };
+        Ok(PerfMetrics {
+            ttfb: timing("ttfb"),
+            dom_content_loaded: timing("domContentLoaded"),
+            load: timing("load"),
+            first_contentful_paint: timing("fcp"),
+            largest_contentful_paint: timing("lcp"),
+            metrics,
+        })
+    }
+
+    // ========== FOCUS ==========
+
+    /// Make the page report itself as the visible, focused tab.
+    ///
+    /// Brings the page to the front and enables focus emulation, so
+    /// `document.visibilityState` is `"visible"` and `document.hasFocus()`
+    /// returns true even while the browser window isn't focused. `false`
+    /// only disables focus emulation, the page then loses focus like a
+    /// window in the background.
+    pub async fn set_visible(&self, visible: bool) -> Result<()> {
+        if visible {
+            self.page
+                .bring_to_front()
+                .await
+                .map_err(|e| anyhow!("{}", e))?;
         }
+        self.page
+            .execute(SetFocusEmulationEnabledParams::new(visible))
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        Ok(())
     }
 
-    // ========== SAFE PAGE ACCESS ==========
+    /// Look away from the page and come back like a user switching windows.
+    ///
+    /// The page loses focus (`blur`), stays unfocused for a random 0.5-2.5s,
+    /// then is brought back and focused again (`focus`). The events are fired
+    /// by the browser, so they are trusted. `document.visibilityState` stays
+    /// `"visible"`, as for a window that is not minimized.
+    pub async fn simulate_attention(&self) -> Result<()> {
+        self.set_visible(false).await?;
+        let away = Duration::from_millis(rand::thread_rng().gen_range(500..2500));
+        utils::sleep(away).await;
+        self.set_visible(true).await
+    }
 
-    /// Access the underlying Page.
+    // ========== SENSORS ==========
+
+    /// Report the device orientation `alpha`, `beta` and `gamma` in degrees,
+    /// firing `deviceorientation` events.
     ///
-    /// Most methods are safe, **except `raw_page().evaluate()`** which
-    /// triggers `Runtime.enable` detection. Use `chaser.evaluate()` instead.
-    #[doc(alias = "inner")]
-    pub fn raw_page(&self) -> &Page {
-        &self.page
+    /// Only available for a [mobile](crate::ChaserProfileBuilder::mobile)
+    /// profile, desktop devices have no orientation sensor.
+    pub async fn set_device_orientation(&self, alpha: f64, beta: f64, gamma: f64) -> Result<()> {
+        let mobile = self
+            .bootstrap
+            .lock()
+            .unwrap()
+            .profile
+            .as_ref()
+            .is_some_and(ChaserProfile::mobile);
+        if !mobile {
+            return Err(anyhow!("device orientation requires a mobile profile"));
+        }
+        self.page
+            .execute(SetDeviceOrientationOverrideParams::new(alpha, beta, gamma))
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        Ok(())
     }
 
-    /// Deprecated: Use `raw_page()` instead.
+    // ========== WEB STORAGE ==========
+
+    /// Read all `localStorage` entries of the page's current origin.
     ///
-    /// This method is kept for backwards compatibility but will be removed in a future version.
-    #[deprecated(since = "0.1.1", note = "Use `raw_page()` instead for clarity")]
-    pub fn inner(&self) -> &Page {
-        &self.page
+    /// Fails on pages without an origin such as `about:blank`.
+    pub async fn local_storage(&self) -> Result<HashMap<String, String>> {
+        self.read_storage("localStorage").await
     }
 
-    // ========== STEALTH-SAFE PAGE OPERATIONS ==========
+    /// Write the entries into `localStorage` of the page's current origin,
+    /// keeping other existing keys.
+    pub async fn set_local_storage(&self, kv: &HashMap<String, String>) -> Result<()> {
+        self.write_storage("localStorage", kv).await
+    }
 
-    /// Navigate to a URL (stealth-safe).
+    /// Read all `sessionStorage` entries of the page's current origin.
     ///
-    /// This is equivalent to `raw_page().goto()` but provided for convenience.
-    pub async fn goto(&self, url: &str) -> Result<()> {
-        self.page.goto(url).await.map_err(|e| anyhow!("{}", e))?;
-        Ok(())
+    /// Fails on pages without an origin such as `about:blank`.
+    pub async fn session_storage(&self) -> Result<HashMap<String, String>> {
+        self.read_storage("sessionStorage").await
     }
 
-    /// Get the page HTML content (stealth-safe).
-    pub async fn content(&self) -> Result<String> {
-        self.page.content().await.map_err(|e| anyhow!("{}", e))
+    /// Write the entries into `sessionStorage` of the page's current origin,
+    /// keeping other existing keys.
+    pub async fn set_session_storage(&self, kv: &HashMap<String, String>) -> Result<()> {
+        self.write_storage("sessionStorage", kv).await
     }
 
-    /// Get the current page URL (stealth-safe).
-    pub async fn url(&self) -> Result<Option<String>> {
-        self.page.url().await.map_err(|e| anyhow!("{}", e))
+    async fn read_storage(&self, storage: &str) -> Result<HashMap<String, String>> {
+        self.ensure_origin().await?;
+        let entries = self
+            .call_function(
+                "(storage) => Object.fromEntries(Object.entries(window[storage]))",
+                &[Value::from(storage)],
+            )
+            .await?;
+        serde_json::from_value(entries).map_err(|e| anyhow!("Invalid {} entries: {}", storage, e))
     }
 
-    /// Execute JavaScript using **stealth execution** (no Runtime.enable leak).
+    async fn write_storage(&self, storage: &str, kv: &HashMap<String, String>) -> Result<()> {
+        self.ensure_origin().await?;
+        self.call_function(
+            "(storage, kv) => { for (const [k, v] of Object.entries(kv)) window[storage].setItem(k, v); }",
+            &[Value::from(storage), serde_json::to_value(kv)?],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Web storage is scoped to an origin, which `about:blank` and `data:`
+    /// pages don't have
+    async fn ensure_origin(&self) -> Result<()> {
+        let origin = self.evaluate_as::<String>("location.origin").await?;
+        if origin == "null" {
+            let url = self.url().await?.unwrap_or_default();
+            return Err(anyhow!(
+                "Web storage is not available on {}, navigate to a page first",
+                url
+            ));
+        }
+        Ok(())
+    }
+
+    // ========== HEADERS ==========
+
+    /// Send `headers` with every request of this page, via
+    /// `Network.setExtraHTTPHeaders`.
     ///
-    /// This is the safe way to run JavaScript on protected sites.
-    /// Under the hood, it uses `Page.createIsolatedWorld` to avoid detection.
+    /// The headers persist across navigations, each call replaces the
+    /// previous ones and an empty map removes them. The network domain is
+    /// enabled when the page is created, no interception is needed.
+    /// Headers the browser controls, such as `Host`, `Content-Length` or
+    /// `Cookie`, can't be overridden this way.
     ///
     /// # Example
-    ///
-    /// ```rust
-    /// // Get page title
-    /// let title: String = chaser.evaluate("document.title").await?;
-    ///
-    /// // Check a value
-    /// let ua: String = chaser.evaluate("navigator.userAgent").await?;
+    /// ```ignore
+    /// let headers = HashMap::from([("X-Api-Key".to_string(), "secret".to_string())]);
+    /// chaser.set_extra_headers(headers).await?;
     /// ```
-    pub async fn evaluate(&self, script: &str) -> Result<Option<Value>> {
-        self.evaluate_stealth(script).await
+    pub async fn set_extra_headers(&self, headers: HashMap<String, String>) -> Result<()> {
+        self.send_extra_headers(&headers).await?;
+        *self.extra_headers.lock().unwrap() = headers;
+        Ok(())
     }
 
-    /// Apply a ChaserProfile to this page in one clean call.
-    ///
-    /// This method:
-    /// 1. Sets the User-Agent HTTP header
-    /// 2. Injects the profile's bootstrap script for JS-level spoofing
+    async fn send_extra_headers(&self, headers: &HashMap<String, String>) -> Result<()> {
+        let headers = Headers::new(serde_json::to_value(headers)?);
+        self.page
+            .execute(SetExtraHttpHeadersParams::new(headers))
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        Ok(())
+    }
+
+    // ========== NETWORK CONDITIONS ==========
+
+    /// Emulate a slower network or none at all, via
+    /// `Network.emulateNetworkConditions`.
     ///
-    /// **IMPORTANT:** Call this BEFORE navigating to the target site.
+    /// The conditions apply to every request of the page and persist across
+    /// navigations until changed, [`NetworkConditions::Unthrottled`] restores
+    /// the real network. Added latency also makes the timing of automated
+    /// browsing look less robotic.
     ///
     /// # Example
-    /// ```rust
-    /// let profile = ChaserProfile::windows().build();
-    /// let page = browser.new_page("about:blank").await?;
-    /// let chaser = ChaserPage::new(page);
-    /// chaser.apply_profile(&profile).await?;
-    /// chaser.inner().goto("https://example.com").await?;
+    /// ```ignore
+    /// chaser.emulate_network(NetworkConditions::Fast3G).await?;
+    /// chaser.emulate_network(NetworkConditions::Custom {
+    ///     latency: Duration::from_millis(120),
+    ///     download_throughput: Some(1_250_000),
+    ///     upload_throughput: None,
+    /// }).await?;
     /// ```
-    pub async fn apply_profile(&self, profile: &ChaserProfile) -> Result<()> {
-        // 1. Set the HTTP User-Agent header
+    pub async fn emulate_network(&self, conditions: NetworkConditions) -> Result<()> {
         self.page
-            .set_user_agent(&profile.user_agent())
+            .execute(conditions.params(false))
             .await
             .map_err(|e| anyhow!("{}", e))?;
+        *self.network.lock().unwrap() = conditions;
+        Ok(())
+    }
 
-        // 2. Inject the bootstrap script to run on every new document
+    /// Disconnect the page from the network or reconnect it, keeping the
+    /// throttling of the last [`ChaserPage::emulate_network`] when online.
+    pub async fn set_offline(&self, offline: bool) -> Result<()> {
+        let conditions = *self.network.lock().unwrap();
+        let conditions = if !offline && conditions == NetworkConditions::Offline {
+            NetworkConditions::Unthrottled
+        } else {
+            conditions
+        };
         self.page
-            .execute(AddScriptToEvaluateOnNewDocumentParams {
-                source: profile.bootstrap_script(),
-                world_name: None,
-                include_command_line_api: None,
-                run_immediately: None,
-            })
+            .execute(conditions.params(offline))
             .await
             .map_err(|e| anyhow!("{}", e))?;
+        Ok(())
+    }
+
+    // ========== WEBRTC ==========
 
+    /// Keep WebRTC from leaking the local and public IP addresses of the
+    /// machine, in the current document and every new one.
+    ///
+    /// Chrome has no CDP command to turn off WebRTC, so every peer connection
+    /// is limited to relay candidates instead: ICE itself then skips host and
+    /// STUN candidates, and a page can still connect through a TURN server.
+    /// Connections made with a constructor saved before this call are
+    /// switched to relay when they start gathering.
+    ///
+    /// A script can still reach an unpatched `RTCPeerConnection`, e.g. of an
+    /// `about:blank` iframe before its document is set up. For an engine level
+    /// block, also launch the browser with
+    /// [`BrowserConfigBuilder::block_webrtc`](crate::browser::BrowserConfigBuilder::block_webrtc),
+    /// which this method can't enable once the browser runs.
+    pub async fn disable_webrtc(&self) -> Result<()> {
+        self.page
+            .evaluate_on_new_document(format!("({WEBRTC_RELAY_ONLY_JS})();"))
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        self.query_main::<Value>(WEBRTC_RELAY_ONLY_JS, &[]).await?;
         Ok(())
     }
 
+    // ========== INIT SCRIPTS ==========
+
+    /// Create an empty [`ScriptBundle`] called `name` to manage a set of init
+    /// scripts of this page.
+    ///
+    /// The bundle is independent of the profile bootstrap, which
+    /// [`ChaserPage::apply_profile`] manages on its own. Use
+    /// [`ChaserProfile::with_extra_script`] instead for scripts that must be
+    /// part of the bootstrap.
+    pub fn script_bundle(&self, name: impl Into<String>) -> ScriptBundle {
+        ScriptBundle {
+            page: self.page.clone(),
+            name: name.into(),
+            scripts: Vec::new(),
+            registered: Vec::new(),
+        }
+    }
+
+    /// Log every read of a getter and call of a method of the `objects`, e.g.
+    /// `["navigator", "screen", "WebGLRenderingContext"]`, to find out which
+    /// properties the scripts of a site probe.
+    ///
+    /// Globals are instrumented on their prototype, so `navigator` covers
+    /// every property of `Navigator.prototype`. Recording starts in the
+    /// current document and continues in new ones, each with a log of its
+    /// own. Call this after [`ChaserPage::apply_profile`], whose bootstrap
+    /// would replace the instrumented properties otherwise.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let log = chaser.record_property_access(&["navigator", "screen"]).await?;
+    /// chaser.goto("https://example.com").await?;
+    /// for access in log.entries().await? {
+    ///     println!("{}.{}", access.object, access.property);
+    /// }
+    /// ```
+    pub async fn record_property_access(&self, objects: &[&str]) -> Result<AccessLog> {
+        let key = format!("{:016x}", rand::thread_rng().gen::<u64>());
+        let args = serde_json::to_string(&(&key, objects, ACCESS_LOG_LIMIT))?;
+        let source = format!("({ACCESS_LOG_JS}).apply(null, {args});");
+        let script = self
+            .page
+            .evaluate_on_new_document(source)
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        self.query_main::<Value>(
+            ACCESS_LOG_JS,
+            &[
+                Value::from(key.as_str()),
+                Value::from(objects.to_vec()),
+                Value::from(ACCESS_LOG_LIMIT),
+            ],
+        )
+        .await?;
+        Ok(AccessLog {
+            chaser: self.clone(),
+            key,
+            objects: objects.iter().map(|name| name.to_string()).collect(),
+            script,
+        })
+    }
+
     // ========== REQUEST INTERCEPTION API ==========
 
     /// Enable request interception for specific URL patterns.
@@ -160,6 +2428,11 @@ impl ChaserPage {
     /// After enabling, use `fulfill_request` or `continue_request` to handle
     /// intercepted requests.
     ///
+    /// The requests are paused by the handler, which passes those matching
+    /// the pattern on as `EventRequestPaused` and continues the others.
+    /// Requests it blocks or modifies itself, e.g. for [`Page::intercept`] or
+    /// [`Page::modify_requests`], are never passed on.
+    ///
     /// # Arguments
     /// * `url_pattern` - Glob pattern to match URLs (e.g., "*", "https://example.com/*")
     /// * `resource_type` - Optional resource type filter (Document, Script, etc.)
@@ -180,12 +2453,7 @@ impl ChaserPage {
         }
 
         self.page
-            .execute(
-                FetchEnableParams::builder()
-                    .handle_auth_requests(false)
-                    .pattern(pattern_builder.build())
-                    .build(),
-            )
+            .set_user_request_interception(Some(pattern_builder.build()))
             .await
             .map_err(|e| anyhow!("{}", e))?;
 
@@ -195,7 +2463,7 @@ impl ChaserPage {
     /// Disable request interception.
     pub async fn disable_request_interception(&self) -> Result<()> {
         self.page
-            .execute(FetchDisableParams::default())
+            .set_user_request_interception(None)
             .await
             .map_err(|e| anyhow!("{}", e))?;
         Ok(())
@@ -284,6 +2552,64 @@ impl ChaserPage {
     /// Site scripts cannot see your variables (isolated world).
     /// Anti-bots cannot detect CDP activity (Runtime domain untouched).
     pub async fn evaluate_stealth(&self, script: &str) -> Result<Option<Value>> {
+        self.evaluate_with(script, EvaluateOptions::default()).await
+    }
+
+    /// Like [`ChaserPage::evaluate_stealth`], with the options to return a
+    /// promise as is and to bound the wait for the result.
+    ///
+    /// Fails with [`CdpError::Timeout`] if the result takes longer than
+    /// `options.timeout`, e.g. for a promise that never settles. The script
+    /// keeps running in the page, only the wait is given up.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let hints = chaser
+    ///     .evaluate_with(
+    ///         "navigator.userAgentData.getHighEntropyValues(['platformVersion'])",
+    ///         EvaluateOptions {
+    ///             timeout: Some(Duration::from_secs(2)),
+    ///             ..Default::default()
+    ///         },
+    ///     )
+    ///     .await?;
+    /// ```
+    pub async fn evaluate_with(
+        &self,
+        script: &str,
+        options: EvaluateOptions,
+    ) -> Result<Option<Value>> {
+        let evaluate = async {
+            let ctx_id = self.isolated_world_context().await?;
+
+            // Execute in the isolated world using the captured context ID
+            let params = EvaluateParams::builder()
+                .expression(script)
+                .context_id(ctx_id)
+                .await_promise(options.await_promise)
+                .return_by_value(true)
+                .build()
+                .unwrap();
+
+            let res = self
+                .page
+                .execute(params)
+                .await
+                .map_err(|e| anyhow!("{}", e))?;
+            Ok(res.result.result.value)
+        };
+        match options.timeout {
+            Some(timeout) => utils::timeout(timeout, evaluate)
+                .await
+                .unwrap_or_else(|| Err(CdpError::Timeout.into())),
+            None => evaluate.await,
+        }
+    }
+
+    /// Creates the "chaser" isolated world in the main frame and returns its
+    /// execution context, without touching the Runtime domain.
+    async fn isolated_world_context(&self) -> Result<ExecutionContextId> {
         // Get the main frame ID
         let frame_id = self
             .page
@@ -307,23 +2633,109 @@ impl ChaserPage {
             .await
             .map_err(|e| anyhow!("{}", e))?;
 
-        let ctx_id = isolated_world.result.execution_context_id;
+        Ok(isolated_world.result.execution_context_id)
+    }
 
-        // Execute in the isolated world using the captured context ID
-        let params = EvaluateParams::builder()
-            .expression(script)
-            .context_id(ctx_id)
-            .await_promise(true)
-            .return_by_value(true)
-            .build()
-            .unwrap();
+    /// Call a JavaScript function in the stealth isolated world with the
+    /// given arguments, via `Runtime.callFunctionOn`.
+    ///
+    /// The arguments are passed as CDP call arguments instead of being
+    /// interpolated into the source, so user data can't break out of string
+    /// literals. Returns `Value::Null` if the function returns `undefined`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let sum = chaser.call_function("(a, b) => a + b", &[json!(1), json!(2)]).await?;
+    /// assert_eq!(sum, json!(3));
+    /// ```
+    pub async fn call_function(&self, fn_decl: &str, args: &[Value]) -> Result<Value> {
+        self.call_function_with(fn_decl, args, CallFunctionOptions::default())
+            .await
+    }
 
-        let res = self
-            .page
-            .execute(params)
+    /// Like [`ChaserPage::call_function`], in the world selected by `options`.
+    pub async fn call_function_with(
+        &self,
+        fn_decl: &str,
+        args: &[Value],
+        options: CallFunctionOptions,
+    ) -> Result<Value> {
+        let mut params = CallFunctionOnParams::builder()
+            .function_declaration(fn_decl)
+            .arguments(
+                args.iter()
+                    .map(|arg| CallArgument::builder().value(arg.clone()).build()),
+            )
+            .await_promise(options.await_promise)
+            .return_by_value(true);
+
+        // the global object of the main world, released after the call
+        let mut global = None;
+        params = match options.world {
+            ExecutionWorld::Isolated => {
+                params.execution_context_id(self.isolated_world_context().await?)
+            }
+            ExecutionWorld::Main => {
+                // Runtime.evaluate without a context targets the main world and
+                // doesn't require Runtime.enable. The function then runs in the
+                // context of the returned object.
+                let evaluated = self
+                    .page
+                    .execute(EvaluateParams::new("globalThis"))
+                    .await
+                    .map_err(|e| anyhow!("{}", e))?;
+                let object_id = evaluated
+                    .result
+                    .result
+                    .object_id
+                    .clone()
+                    .ok_or_else(|| anyhow!("No global object in the main world"))?;
+                global = Some(object_id.clone());
+                params.object_id(object_id)
+            }
+        };
+
+        let res = match params.build() {
+            Ok(params) => self
+                .page
+                .execute(params)
+                .await
+                .map_err(|e| anyhow!("{}", e)),
+            Err(e) => Err(anyhow!("{}", e)),
+        };
+        if let Some(object_id) = global {
+            // fails only if the document, and the object with it, is gone
+            let _ = self.page.execute(ReleaseObjectParams::new(object_id)).await;
+        }
+        let res = res?;
+        if let Some(exception) = res.result.exception_details.as_ref() {
+            return Err(anyhow!("{}", exception.text));
+        }
+        Ok(res.result.result.value.clone().unwrap_or(Value::Null))
+    }
+
+    /// The last position the mouse was moved to by this page, in viewport
+    /// coordinates, `(0, 0)` before the first move.
+    ///
+    /// The position is shared by the clones of this `ChaserPage`, and the
+    /// human-like movements start from it.
+    pub fn mouse_position(&self) -> (f64, f64) {
+        let pos = *self.mouse_pos.lock().unwrap();
+        (pos.x, pos.y)
+    }
+
+    /// Move the mouse to `(x, y)` at once with a single `mouseMoved` event,
+    /// e.g. to place the cursor before a gesture, so that the next
+    /// [`ChaserPage::move_mouse_human`] starts there instead of at the last
+    /// tracked position.
+    pub async fn set_mouse_position(&self, x: f64, y: f64) -> Result<()> {
+        self.page
+            .move_mouse(crate::layout::Point { x, y })
             .await
             .map_err(|e| anyhow!("{}", e))?;
-        Ok(res.result.result.value)
+        *self.mouse_pos.lock().unwrap() = Point { x, y };
+        Ok(())
     }
 
     /// Moves the mouse to the target coordinates using a human-like Bezier curve path.
@@ -359,7 +2771,7 @@ impl ChaserPage {
                 .map_err(|e| anyhow!("{}", e))?;
             *self.mouse_pos.lock().unwrap() = point;
             // Tiny delay to simulate physical movement
-            tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(5..15))).await;
+            utils::sleep(Duration::from_millis(rng.gen_range(5..15))).await;
         }
 
         Ok(())
@@ -388,17 +2800,178 @@ impl ChaserPage {
         self.move_mouse_human(x, y).await?;
 
         // Small pause before clicking (humans don't click instantly after arriving)
-        tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(50..150))).await;
+        utils::sleep(Duration::from_millis(rng.gen_range(50..150))).await;
 
         // Click
         self.click().await?;
 
         // Small pause after clicking
-        tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(30..80))).await;
+        utils::sleep(Duration::from_millis(rng.gen_range(30..80))).await;
+
+        Ok(())
+    }
+
+    /// Drag with the left mouse button from `from` to `to`, e.g. to solve a
+    /// slider or reorder a list.
+    ///
+    /// Moves to the start like [`ChaserPage::move_mouse_human`], presses the
+    /// button, follows a Bezier curve to the target with the button held
+    /// and releases it there, with short human-like pauses in between.
+    pub async fn drag_human(&self, from: (f64, f64), to: (f64, f64)) -> Result<()> {
+        self.drag_through(from, &[Point { x: to.0, y: to.1 }]).await
+    }
+
+    /// Press the left button at `from`, move through `waypoints` along
+    /// Bezier curves and release it at the last one
+    async fn drag_through(&self, from: (f64, f64), waypoints: &[Point]) -> Result<()> {
+        use chromiumoxide_cdp::cdp::browser_protocol::input::{
+            DispatchMouseEventParams, DispatchMouseEventType, MouseButton,
+        };
+
+        let mouse_event = |r#type, point: Point, buttons| {
+            DispatchMouseEventParams::builder()
+                .r#type(r#type)
+                .x(point.x)
+                .y(point.y)
+                .button(MouseButton::Left)
+                .buttons(buttons)
+                .click_count(1)
+                .build()
+                .unwrap()
+        };
+
+        self.move_mouse_human(from.0, from.1).await?;
+        let mut current = { *self.mouse_pos.lock().unwrap() };
+        utils::sleep(Duration::from_millis(rand::thread_rng().gen_range(50..150))).await;
+        self.page
+            .execute(mouse_event(
+                DispatchMouseEventType::MousePressed,
+                current,
+                1,
+            ))
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        utils::sleep(Duration::from_millis(rand::thread_rng().gen_range(80..200))).await;
+
+        for &waypoint in waypoints {
+            let dist = ((waypoint.x - current.x).powi(2) + (waypoint.y - current.y).powi(2)).sqrt();
+            let steps = (dist / 10.0).clamp(5.0, 30.0) as usize;
+            for point in BezierPath::generate(current, waypoint, steps) {
+                self.page
+                    .execute(mouse_event(DispatchMouseEventType::MouseMoved, point, 1))
+                    .await
+                    .map_err(|e| anyhow!("{}", e))?;
+                *self.mouse_pos.lock().unwrap() = point;
+                utils::sleep(Duration::from_millis(rand::thread_rng().gen_range(8..20))).await;
+            }
+            current = waypoint;
+            utils::sleep(Duration::from_millis(rand::thread_rng().gen_range(50..150))).await;
+        }
 
+        self.page
+            .execute(mouse_event(
+                DispatchMouseEventType::MouseReleased,
+                current,
+                0,
+            ))
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
         Ok(())
     }
 
+    /// Drag the first element matching `from` onto the first element matching
+    /// `to`, between the centers of their boxes, see
+    /// [`ChaserPage::drag_human`].
+    pub async fn drag_element_to(&self, from: &str, to: &str) -> Result<()> {
+        let from = self.box_center(from).await?;
+        let to = self.box_center(to).await?;
+        self.drag_human((from.x, from.y), (to.x, to.y)).await
+    }
+
+    /// Solve a slider puzzle: drag the piece matching `piece_selector` into
+    /// the gap of the puzzle matching `container_selector`.
+    ///
+    /// The page is screenshotted and the gap located as the pair of strong
+    /// vertical edges a piece width apart in the rows of the piece, right of
+    /// it. The piece is then dragged by that distance like
+    /// [`ChaserPage::drag_human`], overshooting a few pixels and correcting.
+    /// Fails if no gap stands out, e.g. because the puzzle hasn't loaded yet.
+    ///
+    /// The heuristic suits the common puzzles with an outlined or shaded gap
+    /// on a photo, heavily textured backgrounds may need several attempts.
+    pub async fn solve_slider(&self, container_selector: &str, piece_selector: &str) -> Result<()> {
+        let boxes = self
+            .call_function(
+                "(container, piece) => { \
+                    const rect = (selector) => { \
+                        const r = document.querySelector(selector)?.getBoundingClientRect(); \
+                        return r && [r.x, r.y, r.width, r.height]; \
+                    }; \
+                    return [rect(container), rect(piece), innerWidth]; \
+                }",
+                &[Value::from(container_selector), Value::from(piece_selector)],
+            )
+            .await?;
+        let (container, piece, viewport_width): (Option<[f64; 4]>, Option<[f64; 4]>, f64) =
+            serde_json::from_value(boxes)?;
+        let container =
+            container.ok_or_else(|| anyhow!("no element matches {container_selector}"))?;
+        let piece = piece.ok_or_else(|| anyhow!("no element matches {piece_selector}"))?;
+
+        let png = self
+            .page
+            .screenshot(
+                ScreenshotParams::builder()
+                    .format(CaptureScreenshotFormat::Png)
+                    .build(),
+            )
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        let image = slider::Luma::from_png(&png)?;
+        // screenshot pixels per CSS pixel
+        let scale = image.width as f64 / viewport_width;
+        let px = |css: f64| (css * scale).round().max(0.0) as usize;
+        let piece_box = slider::PixelBox {
+            x0: px(piece[0]),
+            y0: px(piece[1]),
+            x1: px(piece[0] + piece[2]),
+            y1: px(piece[1] + piece[3]),
+        };
+        let gap = image
+            .find_gap(piece_box, px(container[0] + container[2]))
+            .ok_or_else(|| anyhow!("could not locate the gap of the slider puzzle"))?;
+        let distance = (gap - piece_box.x0) as f64 / scale;
+
+        let mut rng = rand::thread_rng();
+        let from = (piece[0] + piece[2] / 2.0, piece[1] + piece[3] / 2.0);
+        let target = Point {
+            x: from.0 + distance,
+            y: from.1 + rng.gen_range(-1.5..1.5),
+        };
+        let overshoot = Point {
+            x: target.x + rng.gen_range(3.0..8.0),
+            y: target.y + rng.gen_range(-2.0..2.0),
+        };
+        self.drag_through(from, &[overshoot, target]).await
+    }
+
+    /// The center of the box of the first element matching `selector`, in
+    /// viewport coordinates
+    async fn box_center(&self, selector: &str) -> Result<Point> {
+        let center = self
+            .call_function(
+                "(selector) => { \
+                    const rect = document.querySelector(selector)?.getBoundingClientRect(); \
+                    return rect && [rect.x + rect.width / 2, rect.y + rect.height / 2]; \
+                }",
+                &[Value::from(selector)],
+            )
+            .await?;
+        let (x, y): (f64, f64) =
+            serde_json::from_value(center).map_err(|_| anyhow!("no element matches {selector}"))?;
+        Ok(Point { x, y })
+    }
+
     /// Type text with human-like delays between keystrokes.
     ///
     /// Simulates realistic typing with:
@@ -456,66 +3029,123 @@ impl ChaserPage {
                 delay
             };
 
-            tokio::time::sleep(tokio::time::Duration::from_millis(actual_delay)).await;
+            utils::sleep(Duration::from_millis(actual_delay)).await;
         }
 
         Ok(())
     }
 
-    /// Press a specific key (e.g., "Enter", "Tab", "Escape").
+    /// Press a specific key (e.g., "Enter", "Tab", "Escape", "ArrowDown"
+    /// or "a").
+    ///
+    /// Keys are looked up in the US keyboard layout of [`keys`](crate::keys),
+    /// so the events carry the real `key`, `code` and `keyCode`, and keys
+    /// that produce text (like Enter) insert it, e.g. submitting a form.
     pub async fn press_key(&self, key: &str) -> Result<()> {
-        // Map common key names to their key codes
-        let (key_str, code) = match key {
-            "Enter" => ("Enter", "Enter"),
-            "Tab" => ("Tab", "Tab"),
-            "Escape" => ("Escape", "Escape"),
-            "Backspace" => ("Backspace", "Backspace"),
-            "Delete" => ("Delete", "Delete"),
-            "ArrowUp" => ("ArrowUp", "ArrowUp"),
-            "ArrowDown" => ("ArrowDown", "ArrowDown"),
-            "ArrowLeft" => ("ArrowLeft", "ArrowLeft"),
-            "ArrowRight" => ("ArrowRight", "ArrowRight"),
-            _ => (key, key),
-        };
+        self.press_combo(&[], key).await
+    }
 
-        let key_down = DispatchKeyEventParams::builder()
-            .r#type(DispatchKeyEventType::RawKeyDown)
-            .key(key_str)
-            .code(code)
-            .build()
-            .unwrap();
+    /// Press `key` while holding `modifiers`, e.g. Ctrl+A to select all.
+    ///
+    /// The modifiers are pressed in order before the key and released in
+    /// reverse after it, every event carries the bitmask of the modifiers
+    /// held at that time. With Shift the key is typed shifted, e.g. "A"
+    /// for "a".
+    ///
+    /// # Example
+    /// ```ignore
+    /// chaser.press_combo(&[Modifier::Control], "a").await?;
+    /// chaser.press_combo(&[Modifier::Shift], "Tab").await?;
+    /// ```
+    pub async fn press_combo(&self, modifiers: &[Modifier], key: &str) -> Result<()> {
+        let definition =
+            keys::get_key_definition(key).ok_or_else(|| anyhow!("Key not found: {key}"))?;
+
+        let mut held = 0;
+        for modifier in modifiers {
+            held |= modifier.bit();
+            self.dispatch_key(
+                modifier.definition(),
+                DispatchKeyEventType::RawKeyDown,
+                held,
+            )
+            .await?;
+        }
 
+        let definition = if held & Modifier::Shift.bit() != 0 {
+            keys::shifted_key_definition(definition)
+        } else {
+            definition
+        };
+        // only Shift still types, other modifiers turn the key into a shortcut
+        let text = definition
+            .text
+            .or_else(|| (definition.key.chars().count() == 1).then_some(definition.key))
+            .filter(|_| held & !Modifier::Shift.bit() == 0);
+        let key_down = DispatchKeyEventParams::builder()
+            .r#type(if text.is_some() {
+                DispatchKeyEventType::KeyDown
+            } else {
+                DispatchKeyEventType::RawKeyDown
+            })
+            .key(definition.key)
+            .code(definition.code)
+            .windows_virtual_key_code(definition.key_code)
+            .native_virtual_key_code(definition.key_code)
+            .modifiers(held);
+        let key_down = match text {
+            Some(text) => key_down.text(text).unmodified_text(text),
+            None => key_down,
+        };
         self.page
-            .execute(key_down)
+            .execute(key_down.build().unwrap())
             .await
             .map_err(|e| anyhow!("{}", e))?;
+        self.dispatch_key(definition, DispatchKeyEventType::KeyUp, held)
+            .await?;
 
-        let key_up = DispatchKeyEventParams::builder()
-            .r#type(DispatchKeyEventType::KeyUp)
-            .key(key_str)
-            .code(code)
+        for modifier in modifiers.iter().rev() {
+            held &= !modifier.bit();
+            self.dispatch_key(modifier.definition(), DispatchKeyEventType::KeyUp, held)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Dispatch a key event without text
+    async fn dispatch_key(
+        &self,
+        definition: &keys::KeyDefinition,
+        r#type: DispatchKeyEventType,
+        modifiers: i64,
+    ) -> Result<()> {
+        let params = DispatchKeyEventParams::builder()
+            .r#type(r#type)
+            .key(definition.key)
+            .code(definition.code)
+            .windows_virtual_key_code(definition.key_code)
+            .native_virtual_key_code(definition.key_code)
+            .modifiers(modifiers)
             .build()
             .unwrap();
-
         self.page
-            .execute(key_up)
+            .execute(params)
             .await
             .map_err(|e| anyhow!("{}", e))?;
-
         Ok(())
     }
 
     /// Press Enter key with a small random delay before pressing.
     pub async fn press_enter(&self) -> Result<()> {
         let mut rng = rand::thread_rng();
-        tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(100..300))).await;
+        utils::sleep(Duration::from_millis(rng.gen_range(100..300))).await;
         self.press_key("Enter").await
     }
 
     /// Press Tab key to move to next field.
     pub async fn press_tab(&self) -> Result<()> {
         let mut rng = rand::thread_rng();
-        tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(50..150))).await;
+        utils::sleep(Duration::from_millis(rng.gen_range(50..150))).await;
         self.press_key("Tab").await
     }
 
@@ -576,12 +3206,64 @@ impl ChaserPage {
             remaining -= step;
 
             // Variable delay between scroll events (16-50ms for 60-20 FPS feel)
-            tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(16..50))).await;
+            utils::sleep(Duration::from_millis(rng.gen_range(16..50))).await;
         }
 
         Ok(())
     }
 
+    /// Browse the current page like a reader for `duration`: scroll in
+    /// bursts, mostly down and sometimes back up, move the mouse to random
+    /// points of the viewport and pause in between.
+    ///
+    /// Stops when `duration` is over, possibly in the middle of a gesture.
+    /// As no mouse button is held, dropping the future stops cleanly as well.
+    ///
+    /// # Example
+    /// ```ignore
+    /// chaser.goto("https://example.com").await?;
+    /// chaser.simulate_browsing(Duration::from_secs(20)).await?;
+    /// ```
+    pub async fn simulate_browsing(&self, duration: Duration) -> Result<()> {
+        let browse = async {
+            let (width, height): (f64, f64) = self.evaluate_as("[innerWidth, innerHeight]").await?;
+            loop {
+                let (action, pause) = {
+                    let mut rng = rand::thread_rng();
+                    (
+                        rng.gen_range(0..100),
+                        Duration::from_millis(rng.gen_range(300..1500)),
+                    )
+                };
+                match action {
+                    // read on, or go back to something
+                    0..=49 => {
+                        let delta = rand::thread_rng().gen_range(150..600);
+                        let delta = if action < 8 { -delta } else { delta };
+                        self.scroll_human(delta).await?;
+                    }
+                    50..=79 => {
+                        let (x, y) = {
+                            let mut rng = rand::thread_rng();
+                            (
+                                rng.gen_range(0.05..0.95) * width,
+                                rng.gen_range(0.05..0.95) * height,
+                            )
+                        };
+                        self.move_mouse_human(x, y).await?;
+                    }
+                    // idle for a while
+                    _ => utils::sleep(pause * 2).await,
+                }
+                utils::sleep(pause).await;
+            }
+        };
+        match utils::timeout(duration, browse).await {
+            Some(res) => res,
+            None => Ok(()),
+        }
+    }
+
     /// Type text with occasional typos and corrections for ultra-realistic input.
     ///
     /// This method has a small chance (~3%) of making a typo and then correcting it,
@@ -598,12 +3280,11 @@ impl ChaserPage {
                 self.type_single_char(typo).await?;
 
                 // Brief pause to "notice" the mistake
-                tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(100..300)))
-                    .await;
+                utils::sleep(Duration::from_millis(rng.gen_range(100..300))).await;
 
                 // Backspace to correct
                 self.press_key("Backspace").await?;
-                tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(30..80))).await;
+                utils::sleep(Duration::from_millis(rng.gen_range(30..80))).await;
             }
 
             // Type the correct character
@@ -616,7 +3297,7 @@ impl ChaserPage {
             } else {
                 delay
             };
-            tokio::time::sleep(tokio::time::Duration::from_millis(actual_delay)).await;
+            utils::sleep(Duration::from_millis(actual_delay)).await;
         }
 
         Ok(())
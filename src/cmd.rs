@@ -32,6 +32,36 @@ pub(crate) fn to_command_response<T: Command>(
     }
 }
 
+/// A command of a batch, see [`Page::execute_batch`](crate::Page::execute_batch)
+///
+/// Commands in a batch have different response types, so they are kept
+/// serialized and their responses returned as json.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchCommand {
+    pub method: MethodId,
+    pub params: serde_json::Value,
+}
+
+impl BatchCommand {
+    pub fn new<C: Command>(cmd: C) -> serde_json::Result<Self> {
+        Ok(Self {
+            method: cmd.identifier(),
+            params: serde_json::to_value(cmd)?,
+        })
+    }
+}
+
+/// Return the json result of a response to a [`BatchCommand`]
+pub(crate) fn to_batch_response(resp: Response) -> Result<serde_json::Value> {
+    if let Some(res) = resp.result {
+        Ok(res)
+    } else if let Some(err) = resp.error {
+        Err(err.into())
+    } else {
+        Err(CdpError::NoResponse)
+    }
+}
+
 /// Messages used internally to communicate with the connection, which is
 /// executed in the the background task.
 #[derive(Debug, Serialize)]
@@ -42,6 +72,10 @@ pub struct CommandMessage<T = Result<Response>> {
     pub params: serde_json::Value,
     #[serde(skip_serializing)]
     pub sender: OneshotSender<T>,
+    /// Overrides the request timeout of the handler for this command,
+    /// navigations are tracked by their own timeout and ignore this
+    #[serde(skip_serializing)]
+    pub timeout: Option<Duration>,
 }
 
 impl<T> CommandMessage<T> {
@@ -51,6 +85,7 @@ impl<T> CommandMessage<T> {
             session_id: None,
             params: serde_json::to_value(cmd)?,
             sender,
+            timeout: None,
         })
     }
 
@@ -69,6 +104,7 @@ impl<T> CommandMessage<T> {
             session_id,
             params: serde_json::to_value(cmd)?,
             sender,
+            timeout: None,
         })
     }
 
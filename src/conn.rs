@@ -1,4 +1,5 @@
-use std::collections::VecDeque;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::marker::PhantomData;
 use std::pin::Pin;
 use std::task::ready;
@@ -15,6 +16,30 @@ use chromiumoxide_types::{CallId, EventMessage, Message, MethodCall, MethodId};
 use crate::error::CdpError;
 use crate::error::Result;
 
+/// The `tracing` target of the protocol trace, see
+/// [`HandlerConfig::trace_protocol`](crate::handler::HandlerConfig::trace_protocol)
+pub const PROTOCOL_TRACE_TARGET: &str = "chaser_oxide::protocol";
+
+/// Methods whose payloads carry cookies, credentials or request headers and
+/// are redacted in the logs
+const REDACTED_METHODS: &[&str] = &[
+    "Fetch.continueRequest",
+    "Fetch.continueResponse",
+    "Fetch.continueWithAuth",
+    "Fetch.fulfillRequest",
+    "Fetch.requestPaused",
+    "Network.getAllCookies",
+    "Network.getCookies",
+    "Network.requestWillBeSent",
+    "Network.requestWillBeSentExtraInfo",
+    "Network.responseReceivedExtraInfo",
+    "Network.setCookie",
+    "Network.setCookies",
+    "Network.setExtraHTTPHeaders",
+    "Storage.getCookies",
+    "Storage.setCookies",
+];
+
 cfg_if::cfg_if! {
     if #[cfg(feature = "async-std-runtime")] {
        use async_tungstenite::async_std::ConnectStream;
@@ -35,14 +60,101 @@ pub struct Connection<T: EventMessage> {
     needs_flush: bool,
     /// The message that is currently being proceessed
     pending_flush: Option<MethodCall>,
+    /// The session the websocket itself is attached to, e.g. of a page
+    /// endpoint, commands for it are sent without a session id
+    implicit_session: Option<SessionId>,
+    /// Logs the traffic if the protocol trace is enabled
+    trace: Option<ProtocolTrace>,
+    /// Sent commands of a [redacted](REDACTED_METHODS) method, whose
+    /// responses are redacted too
+    redacted_calls: HashSet<CallId>,
     _marker: PhantomData<T>,
 }
 
+/// Logs every message of a connection at `TRACE` level
+#[derive(Debug)]
+struct ProtocolTrace {
+    /// How many bytes of a payload are logged, `None` logs them completely
+    payload_limit: Option<usize>,
+    /// The methods of sent commands, to name and redact their responses
+    pending: HashMap<CallId, MethodId>,
+}
+
+fn is_redacted(method: &str) -> bool {
+    REDACTED_METHODS.contains(&method)
+}
+
+impl ProtocolTrace {
+    fn payload<'a>(&self, method: &str, payload: &'a str) -> Cow<'a, str> {
+        if is_redacted(method) {
+            return Cow::Borrowed("<redacted>");
+        }
+        match self.payload_limit {
+            Some(limit) if payload.len() > limit => {
+                let mut end = limit;
+                while !payload.is_char_boundary(end) {
+                    end -= 1;
+                }
+                Cow::Owned(format!("{}... ({} bytes)", &payload[..end], payload.len()))
+            }
+            _ => Cow::Borrowed(payload),
+        }
+    }
+
+    fn sent(&mut self, call: &MethodCall) {
+        let params = call.params.to_string();
+        tracing::trace!(
+            target: PROTOCOL_TRACE_TARGET,
+            id = ?call.id,
+            method = %call.method,
+            session = ?call.session_id,
+            params = %self.payload(&call.method, &params),
+            "command"
+        );
+        self.pending.insert(call.id, call.method.clone());
+    }
+
+    fn received<T: EventMessage>(&mut self, msg: &Message<T>, text: &str) {
+        match msg {
+            Message::Response(resp) => {
+                let method = self.pending.remove(&resp.id).unwrap_or_default();
+                tracing::trace!(
+                    target: PROTOCOL_TRACE_TARGET,
+                    id = ?resp.id,
+                    method = %method,
+                    error = resp.error.is_some(),
+                    payload = %self.payload(&method, text),
+                    "response"
+                );
+            }
+            Message::Event(event) => {
+                let method = event.identifier();
+                tracing::trace!(
+                    target: PROTOCOL_TRACE_TARGET,
+                    method = %method,
+                    session = ?event.session_id(),
+                    payload = %self.payload(&method, text),
+                    "event"
+                );
+            }
+        }
+    }
+}
+
 impl<T: EventMessage + Unpin> Connection<T> {
     pub async fn connect(debug_ws_url: impl AsRef<str>) -> Result<Self> {
+        Self::connect_with_limit(debug_ws_url, None).await
+    }
+
+    /// Connect to the websocket, rejecting messages and frames larger than
+    /// `max_message_size` bytes, `None` accepts any size
+    pub async fn connect_with_limit(
+        debug_ws_url: impl AsRef<str>,
+        max_message_size: Option<usize>,
+    ) -> Result<Self> {
         let config = WebSocketConfig::default()
-            .max_message_size(None)
-            .max_frame_size(None);
+            .max_message_size(max_message_size)
+            .max_frame_size(max_message_size);
 
         cfg_if::cfg_if! {
             if #[cfg(feature = "async-std-runtime")] {
@@ -58,6 +170,9 @@ impl<T: EventMessage + Unpin> Connection<T> {
             next_id: 0,
             needs_flush: false,
             pending_flush: None,
+            implicit_session: None,
+            trace: None,
+            redacted_calls: HashSet::new(),
             _marker: Default::default(),
         })
     }
@@ -70,6 +185,30 @@ impl<T: EventMessage> Connection<T> {
         id
     }
 
+    /// Send the commands for `session_id` without a session id, as the
+    /// websocket is already attached to it
+    pub(crate) fn set_implicit_session(&mut self, session_id: SessionId) {
+        self.implicit_session = Some(session_id);
+    }
+
+    /// Log every command, response and event at `TRACE` level with the
+    /// [`PROTOCOL_TRACE_TARGET`], payloads cut after `payload_limit` bytes
+    pub(crate) fn trace_protocol(&mut self, payload_limit: Option<usize>) {
+        self.trace = Some(ProtocolTrace {
+            payload_limit,
+            pending: HashMap::new(),
+        });
+    }
+
+    /// Forget a command that won't be answered, e.g. because it timed out or
+    /// its target is gone
+    pub(crate) fn forget_command(&mut self, id: CallId) {
+        self.redacted_calls.remove(&id);
+        if let Some(trace) = self.trace.as_mut() {
+            trace.pending.remove(&id);
+        }
+    }
+
     /// Queue in the command to send over the socket and return the id for this
     /// command
     pub fn submit_command(
@@ -79,6 +218,7 @@ impl<T: EventMessage> Connection<T> {
         params: serde_json::Value,
     ) -> serde_json::Result<CallId> {
         let id = self.next_call_id();
+        let session_id = session_id.filter(|id| Some(id) != self.implicit_session.as_ref());
         let call = MethodCall {
             id,
             method,
@@ -99,7 +239,15 @@ impl<T: EventMessage> Connection<T> {
         }
         if self.pending_flush.is_none() && !self.needs_flush {
             if let Some(cmd) = self.pending_commands.pop_front() {
-                tracing::trace!("Sending {:?}", cmd);
+                if is_redacted(&cmd.method) {
+                    tracing::trace!("Sending {:?} {} <redacted>", cmd.id, cmd.method);
+                    self.redacted_calls.insert(cmd.id);
+                } else {
+                    tracing::trace!("Sending {:?}", cmd);
+                }
+                if let Some(trace) = self.trace.as_mut() {
+                    trace.sent(&cmd);
+                }
                 let msg = serde_json::to_string(&cmd)?;
                 self.ws.start_send_unpin(msg.into())?;
                 self.pending_flush = Some(cmd);
@@ -140,7 +288,18 @@ impl<T: EventMessage + Unpin> Stream for Connection<T> {
             Some(Ok(WsMessage::Text(text))) => {
                 let ready = match serde_json::from_str::<Message<T>>(&text) {
                     Ok(msg) => {
-                        tracing::trace!("Received {:?}", msg);
+                        match &msg {
+                            Message::Response(resp) if pin.redacted_calls.remove(&resp.id) => {
+                                tracing::trace!("Received response {:?} <redacted>", resp.id);
+                            }
+                            Message::Event(event) if is_redacted(&event.identifier()) => {
+                                tracing::trace!("Received {} <redacted>", event.identifier());
+                            }
+                            _ => tracing::trace!("Received {:?}", msg),
+                        }
+                        if let Some(trace) = pin.trace.as_mut() {
+                            trace.received(&msg, text.as_str());
+                        }
                         Ok(msg)
                     }
                     Err(err) => {
@@ -0,0 +1,207 @@
+//! Building and checking cookies for `Network.setCookies`.
+//!
+//! Chromium drops cookies with conflicting attributes without an error, e.g. a
+//! `SameSite=None` cookie that isn't `Secure`, which only shows up later as a
+//! failing login. [`CookieBuilder`] and the `set_cookie` methods reject those
+//! up front.
+//!
+//! ```ignore
+//! let cookie = CookieBuilder::new("__Host-session", "abc")
+//!     .url("https://shop.example.com")
+//!     .path("/")
+//!     .secure(true)
+//!     .same_site(CookieSameSite::None)
+//!     .partitioned("https://example.com")
+//!     .build()?;
+//! page.set_cookie(cookie).await?;
+//! ```
+
+use chromiumoxide_cdp::cdp::browser_protocol::network::{
+    CookieParam, CookiePartitionKey, CookiePriority, CookieSameSite, CookieSourceScheme,
+    TimeSinceEpoch,
+};
+
+use crate::error::{CdpError, Result};
+
+/// Builds a [`CookieParam`] and checks its attributes for conflicts
+#[derive(Debug, Clone)]
+pub struct CookieBuilder {
+    cookie: CookieParam,
+}
+
+impl CookieBuilder {
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            cookie: CookieParam::new(name, value),
+        }
+    }
+
+    /// The URL the cookie is set for, its host and path are the defaults of
+    /// the domain and path.
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.cookie.url = Some(url.into());
+        self
+    }
+
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.cookie.domain = Some(domain.into());
+        self
+    }
+
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.cookie.path = Some(path.into());
+        self
+    }
+
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.cookie.secure = Some(secure);
+        self
+    }
+
+    pub fn http_only(mut self, http_only: bool) -> Self {
+        self.cookie.http_only = Some(http_only);
+        self
+    }
+
+    pub fn same_site(mut self, same_site: CookieSameSite) -> Self {
+        self.cookie.same_site = Some(same_site);
+        self
+    }
+
+    /// Expiration as seconds since the UNIX epoch, a session cookie if unset
+    pub fn expires(mut self, expires: f64) -> Self {
+        self.cookie.expires = Some(TimeSinceEpoch::new(expires));
+        self
+    }
+
+    pub fn priority(mut self, priority: CookiePriority) -> Self {
+        self.cookie.priority = Some(priority);
+        self
+    }
+
+    pub fn same_party(mut self, same_party: bool) -> Self {
+        self.cookie.same_party = Some(same_party);
+        self
+    }
+
+    pub fn source_scheme(mut self, source_scheme: CookieSourceScheme) -> Self {
+        self.cookie.source_scheme = Some(source_scheme);
+        self
+    }
+
+    pub fn source_port(mut self, source_port: i64) -> Self {
+        self.cookie.source_port = Some(source_port);
+        self
+    }
+
+    /// Make this a `Partitioned` (CHIPS) cookie, keyed by the site of the
+    /// top-level page, e.g. `https://example.com`
+    pub fn partitioned(self, top_level_site: impl Into<String>) -> Self {
+        self.partition_key(CookiePartitionKey::new(top_level_site, false))
+    }
+
+    pub fn partition_key(mut self, partition_key: CookiePartitionKey) -> Self {
+        self.cookie.partition_key = Some(partition_key);
+        self
+    }
+
+    /// The cookie, or an error if its attributes conflict, see
+    /// [`validate_cookie`]
+    pub fn build(self) -> Result<CookieParam> {
+        validate_cookie(&self.cookie)?;
+        Ok(self.cookie)
+    }
+}
+
+/// Check a cookie for attribute combinations chromium refuses to store
+pub fn validate_cookie(cookie: &CookieParam) -> Result<()> {
+    let name = &cookie.name;
+    let secure = cookie.secure == Some(true);
+    if let Some(url) = cookie.url.as_deref() {
+        crate::page::validate_cookie_url(url)?;
+    }
+    if cookie.same_site == Some(CookieSameSite::None) && !secure {
+        return Err(CdpError::msg(format!(
+            "cookie `{name}` with SameSite=None must be Secure"
+        )));
+    }
+    if cookie.partition_key.is_some() && !secure {
+        return Err(CdpError::msg(format!(
+            "partitioned cookie `{name}` must be Secure"
+        )));
+    }
+    if cookie.same_party == Some(true) {
+        if !secure {
+            return Err(CdpError::msg(format!(
+                "SameParty cookie `{name}` must be Secure"
+            )));
+        }
+        if cookie.same_site == Some(CookieSameSite::Strict) {
+            return Err(CdpError::msg(format!(
+                "SameParty cookie `{name}` can't be SameSite=Strict"
+            )));
+        }
+    }
+    if secure && cookie.source_scheme == Some(CookieSourceScheme::NonSecure) {
+        return Err(CdpError::msg(format!(
+            "Secure cookie `{name}` can't come from a non-secure scheme"
+        )));
+    }
+    if (name.starts_with("__Secure-") || name.starts_with("__Host-")) && !secure {
+        return Err(CdpError::msg(format!("cookie `{name}` must be Secure")));
+    }
+    if name.starts_with("__Host-") && cookie.path.as_deref().is_some_and(|path| path != "/") {
+        return Err(CdpError::msg(format!(
+            "cookie `{name}` must have the path `/`"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_partitioned_cookie() {
+        let cookie = CookieBuilder::new("__Host-id", "1")
+            .url("https://shop.example.com")
+            .path("/")
+            .secure(true)
+            .same_site(CookieSameSite::None)
+            .priority(CookiePriority::High)
+            .partitioned("https://example.com")
+            .build()
+            .unwrap();
+        assert_eq!(cookie.same_site, Some(CookieSameSite::None));
+        assert_eq!(cookie.priority, Some(CookiePriority::High));
+        assert_eq!(
+            cookie.partition_key,
+            Some(CookiePartitionKey::new("https://example.com", false))
+        );
+    }
+
+    #[test]
+    fn rejects_conflicting_attributes() {
+        let err = |builder: CookieBuilder| builder.build().unwrap_err().to_string();
+        assert!(
+            err(CookieBuilder::new("a", "1").same_site(CookieSameSite::None))
+                .contains("SameSite=None must be Secure")
+        );
+        assert!(
+            err(CookieBuilder::new("a", "1").partitioned("https://example.com"))
+                .contains("partitioned cookie `a` must be Secure")
+        );
+        assert!(err(CookieBuilder::new("a", "1")
+            .secure(true)
+            .same_party(true)
+            .same_site(CookieSameSite::Strict))
+        .contains("SameSite=Strict"));
+        assert!(err(CookieBuilder::new("__Secure-a", "1")).contains("must be Secure"));
+        assert!(err(CookieBuilder::new("__Host-a", "1")
+            .secure(true)
+            .path("/app"))
+        .contains("path `/`"));
+        assert!(err(CookieBuilder::new("a", "1").url("about:blank")).contains("Blank page"));
+    }
+}
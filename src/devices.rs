@@ -0,0 +1,77 @@
+//! A registry of common phones and tablets for device emulation.
+//!
+//! Every [`DevicePreset`] bundles the viewport with a matching
+//! [`ChaserProfile`](crate::ChaserProfile), so the user agent,
+//! `navigator.platform`, the client hints and touch support all describe the
+//! same device. This module looks them up by name.
+//!
+//! ```ignore
+//! let config = BrowserConfig::builder().device("Pixel 7").build()?;
+//! let device = config.device().expect("device is set");
+//! let (browser, handler) = Browser::launch(config).await?;
+//! // ...
+//! chaser.apply_profile(&device.profile().build()).await?;
+//! ```
+
+pub use crate::handler::viewport::DevicePreset;
+
+/// Look up a device by its name, ignoring ASCII case
+pub fn get(name: &str) -> Option<DevicePreset> {
+    DevicePreset::ALL
+        .iter()
+        .find(|device| device.name().eq_ignore_ascii_case(name))
+        .copied()
+}
+
+/// The names accepted by [`get`]
+pub fn names() -> impl Iterator<Item = &'static str> {
+    DevicePreset::ALL.iter().map(DevicePreset::name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::Os;
+
+    #[test]
+    fn devices_are_consistent() {
+        for name in names() {
+            let device = get(name).unwrap();
+            let profile = device.profile().build();
+            let user_agent = profile.user_agent();
+            assert_eq!(device.user_agent(), user_agent);
+            match device.os() {
+                Os::IOS => {
+                    let platform = if device.is_phone() { "iPhone" } else { "iPad" };
+                    assert!(user_agent.contains(platform), "{name}");
+                    assert!(user_agent.contains("CriOS"), "{name}");
+                    assert_eq!(profile.platform(), platform);
+                    assert!(profile.user_agent_override().user_agent_metadata.is_none());
+                }
+                Os::Android => {
+                    assert!(user_agent.contains("Android"), "{name}");
+                    assert_eq!(user_agent.contains("Mobile"), device.is_phone(), "{name}");
+                    let metadata = profile.user_agent_metadata();
+                    assert_eq!(metadata.platform, "Android");
+                    assert_eq!(metadata.model, profile.model());
+                    assert_eq!(metadata.mobile, device.is_phone());
+                }
+                os => panic!("{name} is a {os:?} device"),
+            }
+            assert!(profile.touch_points() > 0, "{name}");
+            let report = profile.coherence();
+            assert!(report.issues.is_empty(), "{name}: {report}");
+            assert!(device.viewport().has_touch);
+        }
+    }
+
+    #[test]
+    fn lookup_ignores_case() {
+        let device = get("iphone 13 pro").unwrap();
+        assert_eq!(device, DevicePreset::IPhone13Pro);
+        let viewport = device.viewport();
+        assert_eq!((viewport.width, viewport.height), (390, 844));
+        assert_eq!(get("iPad Air"), Some(DevicePreset::IPadAir));
+        assert!(get("Nokia 3310").is_none());
+    }
+}
@@ -43,8 +43,27 @@ pub enum CdpError {
     LaunchIo(#[source] io::Error, BrowserStderr),
     #[error("Request timed out.")]
     Timeout,
+    /// The page was closed, e.g. with [`Page::close`](crate::Page::close),
+    /// before or while it handled a command
+    #[error("The page was closed.")]
+    PageClosed,
+    /// The browser stopped answering the heartbeat of the `Handler`
+    #[error("The connection to the browser was lost.")]
+    ConnectionClosed,
     #[error("FrameId {0:?} not found.")]
     FrameNotFound(FrameId),
+    /// A navigation failed with a network error such as
+    /// `net::ERR_NAME_NOT_RESOLVED`
+    #[error("Navigation to {url} failed: {net_error}")]
+    Navigation {
+        /// The url that was navigated to
+        url: String,
+        /// The `errorText` of `Page.navigate`, e.g. `net::ERR_CONNECTION_REFUSED`
+        net_error: String,
+    },
+    /// A download was canceled, by the page or the browser
+    #[error("Download of {0} was canceled")]
+    DownloadCanceled(String),
     /// Error message related to a cdp response that is not a
     /// `chromiumoxide_types::Error`
     #[error("{0}")]
@@ -68,6 +87,16 @@ impl CdpError {
     pub fn msg(msg: impl Into<String>) -> Self {
         CdpError::ChromeMessage(msg.into())
     }
+
+    /// The error of a message to the target of a page, which drops its
+    /// receiver once the target is closed
+    pub(crate) fn from_target_send(err: SendError) -> Self {
+        if err.is_disconnected() {
+            CdpError::PageClosed
+        } else {
+            err.into()
+        }
+    }
 }
 
 #[derive(Debug, Error)]
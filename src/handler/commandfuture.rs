@@ -7,9 +7,10 @@ use std::future::Future;
 use std::marker::PhantomData;
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use crate::cmd::{to_command_response, CommandMessage};
-use crate::error::Result;
+use crate::error::{CdpError, Result};
 use crate::handler::target::TargetMessage;
 use chromiumoxide_cdp::cdp::browser_protocol::target::SessionId;
 use chromiumoxide_types::{Command, CommandResponse, MethodId, Response};
@@ -39,17 +40,28 @@ impl<T: Command> CommandFuture<T> {
         cmd: T,
         target_sender: mpsc::Sender<TargetMessage>,
         session: Option<SessionId>,
+    ) -> Result<Self> {
+        Self::with_timeout(cmd, target_sender, session, None)
+    }
+
+    /// Like `new`, but the command times out after `timeout` instead of the
+    /// default request timeout
+    pub fn with_timeout(
+        cmd: T,
+        target_sender: mpsc::Sender<TargetMessage>,
+        session: Option<SessionId>,
+        timeout: Option<Duration>,
     ) -> Result<Self> {
         let (tx, rx_command) = oneshot_channel::<Result<Response>>();
         let method = cmd.identifier();
 
-        let message = Some(TargetMessage::Command(CommandMessage::with_session(
-            cmd, tx, session,
-        )?));
+        let mut message = CommandMessage::with_session(cmd, tx, session)?;
+        message.timeout = timeout;
+        let message = Some(TargetMessage::Command(message));
 
-        let delay = futures_timer::Delay::new(std::time::Duration::from_millis(
-            crate::handler::REQUEST_TIMEOUT,
-        ));
+        let delay = futures_timer::Delay::new(
+            timeout.unwrap_or(Duration::from_millis(crate::handler::REQUEST_TIMEOUT)),
+        );
 
         Ok(Self {
             target_sender,
@@ -73,10 +85,12 @@ where
 
         if this.message.is_some() {
             match this.target_sender.poll_ready(cx) {
-                Poll::Ready(Err(e)) => Poll::Ready(Err(e.into())),
+                Poll::Ready(Err(e)) => Poll::Ready(Err(CdpError::from_target_send(e))),
                 Poll::Ready(Ok(_)) => {
                     let message = this.message.take().expect("existence checked above");
-                    this.target_sender.start_send(message)?;
+                    this.target_sender
+                        .start_send(message)
+                        .map_err(CdpError::from_target_send)?;
 
                     cx.waker().wake_by_ref();
                     Poll::Pending
@@ -84,14 +98,14 @@ where
                 Poll::Pending => Poll::Pending,
             }
         } else if this.delay.poll(cx).is_ready() {
-            Poll::Ready(Err(crate::error::CdpError::Timeout))
+            Poll::Ready(Err(CdpError::Timeout))
         } else {
             match this.rx_command.as_mut().poll(cx) {
                 Poll::Ready(Ok(Ok(response))) => {
                     Poll::Ready(to_command_response::<T>(response, this.method.clone()))
                 }
                 Poll::Ready(Ok(Err(e))) => Poll::Ready(Err(e)),
-                Poll::Ready(Err(e)) => Poll::Ready(Err(e.into())),
+                Poll::Ready(Err(_)) => Poll::Ready(Err(CdpError::PageClosed)),
                 Poll::Pending => Poll::Pending,
             }
         }
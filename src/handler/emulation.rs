@@ -8,6 +8,9 @@ use crate::cmd::CommandChain;
 use crate::handler::viewport::Viewport;
 use std::time::Duration;
 
+/// `navigator.maxTouchPoints` of an emulated touch screen
+const MAX_TOUCH_POINTS: i64 = 5;
+
 #[derive(Debug)]
 pub struct EmulationManager {
     pub emulating_mobile: bool,
@@ -42,7 +45,15 @@ impl EmulationManager {
             .build()
             .unwrap();
 
-        let set_touch = SetTouchEmulationEnabledParams::new(true);
+        let set_touch = if viewport.has_touch {
+            SetTouchEmulationEnabledParams::builder()
+                .enabled(true)
+                .max_touch_points(MAX_TOUCH_POINTS)
+                .build()
+                .unwrap()
+        } else {
+            SetTouchEmulationEnabledParams::new(false)
+        };
 
         let chain = CommandChain::new(
             vec![
@@ -60,6 +71,8 @@ impl EmulationManager {
 
         self.needs_reload = self.emulating_mobile != viewport.emulating_mobile
             || self.has_touch != viewport.has_touch;
+        self.emulating_mobile = viewport.emulating_mobile;
+        self.has_touch = viewport.has_touch;
         chain
     }
 }
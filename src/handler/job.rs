@@ -20,10 +20,17 @@ impl PeriodicJob {
     pub fn poll_ready(&mut self, cx: &mut Context<'_>) -> bool {
         if !Future::poll(Pin::new(&mut self.delay), cx).is_pending() {
             self.delay.reset(self.interval);
+            // register the waker for the next interval
+            let _ = Future::poll(Pin::new(&mut self.delay), cx);
             return true;
         }
         false
     }
+    /// Start the current interval over
+    pub fn reset(&mut self) {
+        self.delay.reset(self.interval);
+    }
+
     pub fn new(interval: Duration) -> Self {
         Self {
             delay: Delay::new(interval),
@@ -7,7 +7,7 @@ use std::time::{Duration, Instant};
 
 use fnv::FnvHashMap;
 use futures::channel::mpsc::Receiver;
-use futures::channel::oneshot::Sender as OneshotSender;
+use futures::channel::oneshot::{channel as oneshot_channel, Sender as OneshotSender};
 use futures::stream::{Fuse, Stream, StreamExt};
 use futures::task::{Context, Poll};
 
@@ -36,6 +36,12 @@ use crate::page::Page;
 /// Standard timeout in MS
 pub const REQUEST_TIMEOUT: u64 = 30_000;
 
+/// Default capacity of the channels to the `Handler` and its targets
+pub const CHANNEL_CAPACITY: usize = 32;
+
+/// Default number of payload bytes the protocol trace logs per message
+pub const TRACE_PAYLOAD_LIMIT: usize = 1024;
+
 pub mod browser;
 pub mod commandfuture;
 pub mod domworld;
@@ -57,8 +63,7 @@ pub mod viewport;
 #[derive(Debug)]
 pub struct Handler {
     /// Commands that are being processed and awaiting a response from the
-    /// chromium instance together with the timestamp when the request
-    /// started.
+    /// chromium instance together with the deadline of the request.
     pending_commands: FnvHashMap<CallId, (PendingRequest, MethodId, Instant)>,
     /// Connection to the browser instance
     from_browser: Fuse<Receiver<HandlerMessage>>,
@@ -68,6 +73,13 @@ pub struct Handler {
     target_ids: Vec<TargetId>,
     /// The created and attached targets
     targets: HashMap<TargetId, Target>,
+    /// Initiators of `CreateTargetParams` requests whose response arrived
+    /// before the `EventTargetCreated` of their target, failed once their
+    /// deadline passes
+    pending_initiators: HashMap<TargetId, (OneshotSender<Result<Page>>, Instant)>,
+    /// Targets closed because their announcement timed out, a late
+    /// `EventTargetCreated` of them is ignored
+    evicted_targets: HashSet<TargetId>,
     /// Currently queued in navigations for targets
     navigations: FnvHashMap<NavigationId, NavigationRequest>,
     /// Keeps track of all the current active sessions
@@ -86,8 +98,19 @@ pub struct Handler {
     event_listeners: EventListeners,
     /// Keeps track is the browser is closing
     closing: bool,
+    /// Sends `Browser.getVersion` periodically if a heartbeat is configured
+    heartbeat: Option<PeriodicJob>,
+    /// Whether the last heartbeat is still unanswered
+    heartbeat_in_flight: bool,
+    /// Set once the heartbeat failed, ends the stream
+    connection_lost: bool,
+    /// Expires after `idle_timeout` without a command, reset on every one
+    idle: Option<PeriodicJob>,
     /// Stealth Context Registry
     contexts: Arc<DashMap<TargetId, ExecutionContextId>>,
+    /// The session of the page when connected to a page endpoint, its events
+    /// arrive without a session id
+    page_session: Option<SessionId>,
 }
 
 impl Handler {
@@ -104,7 +127,59 @@ impl Handler {
             None,
             serde_json::to_value(discover).unwrap(),
         );
+        Self::with_connection(conn, rx, config)
+    }
+
+    /// Create a `Handler` that drives the connection to the websocket of a
+    /// single page, described by `info`, see [`Page::attach`]
+    pub(crate) fn attach_page(
+        mut conn: Connection<CdpEventMessage>,
+        info: TargetInfo,
+        config: HandlerConfig,
+    ) -> Self {
+        // the websocket is the page's own session
+        let session_id = SessionId::new(info.target_id.inner().clone());
+        conn.set_implicit_session(session_id.clone());
+        // no `Browser` sends messages
+        let (_, rx) = futures::channel::mpsc::channel(1);
+        let mut handler = Self::with_connection(conn, rx, config);
+
+        let target = Target::attached(
+            info,
+            handler.target_config(),
+            handler.default_browser_context.clone(),
+            session_id.clone(),
+        );
+        let target_id = target.target_id().clone();
+        handler.sessions.insert(
+            session_id.clone(),
+            Session::new(session_id.clone(), target_id.clone()),
+        );
+        handler.page_session = Some(session_id);
+        handler.target_ids.push(target_id.clone());
+        handler.targets.insert(target_id, target);
+        handler
+    }
+
+    /// The page of an initialized target
+    pub(crate) fn initialized_page(&mut self, target_id: &TargetId) -> Option<Page> {
+        let target = self.targets.get_mut(target_id)?;
+        if !target.is_initialized() {
+            return None;
+        }
+        target
+            .get_or_create_page()
+            .map(|page| Page::from(page.clone()))
+    }
 
+    fn with_connection(
+        mut conn: Connection<CdpEventMessage>,
+        rx: Receiver<HandlerMessage>,
+        config: HandlerConfig,
+    ) -> Self {
+        if config.trace_protocol {
+            conn.trace_protocol(config.trace_payload_limit);
+        }
         let browser_contexts = config
             .context_ids
             .iter()
@@ -118,15 +193,22 @@ impl Handler {
             browser_contexts,
             target_ids: Default::default(),
             targets: Default::default(),
+            pending_initiators: Default::default(),
+            evicted_targets: Default::default(),
             navigations: Default::default(),
             sessions: Default::default(),
             conn,
             evict_command_timeout: PeriodicJob::new(config.request_timeout),
             next_navigation_id: 0,
-            config,
             event_listeners: Default::default(),
             closing: false,
+            heartbeat: config.heartbeat_interval.map(PeriodicJob::new),
+            heartbeat_in_flight: false,
+            connection_lost: false,
+            idle: config.idle_timeout.map(PeriodicJob::new),
+            config,
             contexts: Arc::new(DashMap::new()),
+            page_session: None,
         }
     }
 
@@ -210,8 +292,11 @@ impl Handler {
                                 // initialized
                                 target.set_initiator(tx);
                             } else {
-                                // TODO can this even happen?
-                                panic!("Created target not present")
+                                // under load the response can overtake the
+                                // `EventTargetCreated` of the target
+                                let deadline = self.deadline(Instant::now());
+                                self.pending_initiators
+                                    .insert(resp.target_id.clone(), (tx, deadline));
                             }
                         }
                         Err(err) => {
@@ -246,7 +331,7 @@ impl Handler {
                 PendingRequest::Navigate(id) => {
                     self.on_navigation_response(id, resp);
                 }
-                PendingRequest::ExternalCommand(tx) => {
+                PendingRequest::ExternalCommand(tx, _) => {
                     let _ = tx.send(Ok(resp)).ok();
                 }
                 PendingRequest::InternalCommand(target_id) => {
@@ -258,22 +343,75 @@ impl Handler {
                     self.closing = true;
                     let _ = tx.send(Ok(CloseReturns {})).ok();
                 }
+                PendingRequest::Heartbeat => {
+                    self.heartbeat_in_flight = false;
+                }
             }
         }
     }
 
+    /// The deadline of a request started at `now` with the default timeout
+    fn deadline(&self, now: Instant) -> Instant {
+        now + self.config.request_timeout
+    }
+
+    /// Send a `Browser.getVersion` that must be answered before the next one
+    fn submit_heartbeat(&mut self, now: Instant) -> Result<()> {
+        let version = GetVersionParams::default();
+        let method = version.identifier();
+        let call_id =
+            self.conn
+                .submit_command(method.clone(), None, serde_json::to_value(version)?)?;
+        self.pending_commands.insert(
+            call_id,
+            (PendingRequest::Heartbeat, method, self.deadline(now)),
+        );
+        self.heartbeat_in_flight = true;
+        Ok(())
+    }
+
+    /// Restart the idle timer, if configured
+    fn on_activity(&mut self) {
+        if let Some(idle) = self.idle.as_mut() {
+            idle.reset();
+        }
+    }
+
+    /// Whether no command other than a heartbeat waits for a response
+    fn is_idle(&self) -> bool {
+        self.pending_commands
+            .values()
+            .all(|(req, _, _)| matches!(req, PendingRequest::Heartbeat))
+    }
+
+    /// The browser stopped responding, fail all pending requests
+    fn on_connection_lost(&mut self) {
+        self.connection_lost = true;
+        let pending = std::mem::take(&mut self.pending_commands);
+        for (req, _, _) in pending.into_values() {
+            self.fail_request(req, CdpError::ConnectionClosed);
+        }
+        for (tx, _) in std::mem::take(&mut self.pending_initiators).into_values() {
+            let _ = tx.send(Err(CdpError::ConnectionClosed));
+        }
+    }
+
     /// Submit a command initiated via channel
     pub(crate) fn submit_external_command(
         &mut self,
         msg: CommandMessage,
         now: Instant,
     ) -> Result<()> {
-        let call_id = self
-            .conn
-            .submit_command(msg.method.clone(), msg.session_id, msg.params)?;
+        let call_id =
+            self.conn
+                .submit_command(msg.method.clone(), msg.session_id.clone(), msg.params)?;
         self.pending_commands.insert(
             call_id,
-            (PendingRequest::ExternalCommand(msg.sender), msg.method, now),
+            (
+                PendingRequest::ExternalCommand(msg.sender, msg.session_id),
+                msg.method,
+                now + msg.timeout.unwrap_or(self.config.request_timeout),
+            ),
         );
         Ok(())
     }
@@ -291,7 +429,11 @@ impl Handler {
         )?;
         self.pending_commands.insert(
             call_id,
-            (PendingRequest::InternalCommand(target_id), req.method, now),
+            (
+                PendingRequest::InternalCommand(target_id),
+                req.method,
+                self.deadline(now),
+            ),
         );
         Ok(())
     }
@@ -304,8 +446,10 @@ impl Handler {
             .submit_command(method.clone(), None, serde_json::to_value(msg).unwrap())
             .unwrap();
 
-        self.pending_commands
-            .insert(call_id, (PendingRequest::GetTargets(tx), method, now));
+        self.pending_commands.insert(
+            call_id,
+            (PendingRequest::GetTargets(tx), method, self.deadline(now)),
+        );
     }
 
     /// Send the Request over to the server and store its identifier to handle
@@ -320,8 +464,10 @@ impl Handler {
             )
             .unwrap();
 
-        self.pending_commands
-            .insert(call_id, (PendingRequest::Navigate(id), req.method, now));
+        self.pending_commands.insert(
+            call_id,
+            (PendingRequest::Navigate(id), req.method, self.deadline(now)),
+        );
     }
 
     fn submit_close(&mut self, tx: OneshotSender<Result<CloseReturns>>, now: Instant) {
@@ -337,8 +483,10 @@ impl Handler {
             )
             .unwrap();
 
-        self.pending_commands
-            .insert(call_id, (PendingRequest::CloseBrowser(tx), method, now));
+        self.pending_commands.insert(
+            call_id,
+            (PendingRequest::CloseBrowser(tx), method, self.deadline(now)),
+        );
     }
 
     /// Process a message received by the target's page via channel
@@ -383,7 +531,11 @@ impl Handler {
                         Ok(call_id) => {
                             self.pending_commands.insert(
                                 call_id,
-                                (PendingRequest::CreateTarget(tx), method, Instant::now()),
+                                (
+                                    PendingRequest::CreateTarget(tx),
+                                    method,
+                                    self.deadline(Instant::now()),
+                                ),
                             );
                         }
                         Err(err) => {
@@ -402,7 +554,11 @@ impl Handler {
     }
 
     /// Process an incoming event read from the websocket
-    fn on_event(&mut self, event: CdpEventMessage) {
+    fn on_event(&mut self, mut event: CdpEventMessage) {
+        if event.session_id.is_none() {
+            event.session_id = self.page_session.clone().map(Into::into);
+        }
+
         // chaser-oxide Stealth: Capture Context ID BEFORE dispatching to target
         // This must be done first because target.on_event() doesn't return the event
         if let CdpEvent::RuntimeBindingCalled(ev) = &event.params {
@@ -439,10 +595,26 @@ impl Handler {
         });
     }
 
+    /// The configuration of new targets
+    fn target_config(&self) -> TargetConfig {
+        TargetConfig {
+            ignore_https_errors: self.config.ignore_https_errors,
+            request_timeout: self.config.request_timeout,
+            viewport: self.config.viewport.clone(),
+            request_intercept: self.config.request_intercept,
+            cache_enabled: self.config.cache_enabled,
+            channel_capacity: self.config.channel_capacity,
+            blocked_urls: self.config.blocked_urls.clone(),
+        }
+    }
+
     /// Fired when a new target was created on the chromium instance
     ///
     /// Creates a new `Target` instance and keeps track of it
     fn on_target_created(&mut self, event: EventTargetCreated) {
+        if self.evicted_targets.contains(&event.target_info.target_id) {
+            return;
+        }
         let browser_ctx = event
             .target_info
             .browser_context_id
@@ -450,17 +622,10 @@ impl Handler {
             .map(BrowserContext::from)
             .filter(|id| self.browser_contexts.contains(id))
             .unwrap_or_else(|| self.default_browser_context.clone());
-        let target = Target::new(
-            event.target_info,
-            TargetConfig {
-                ignore_https_errors: self.config.ignore_https_errors,
-                request_timeout: self.config.request_timeout,
-                viewport: self.config.viewport.clone(),
-                request_intercept: self.config.request_intercept,
-                cache_enabled: self.config.cache_enabled,
-            },
-            browser_ctx,
-        );
+        let mut target = Target::new(event.target_info, self.target_config(), browser_ctx);
+        if let Some((tx, _)) = self.pending_initiators.remove(target.target_id()) {
+            target.set_initiator(tx);
+        }
         self.target_ids.push(target.target_id().clone());
         self.targets.insert(target.target_id().clone(), target);
     }
@@ -484,56 +649,117 @@ impl Handler {
                 target.session_id_mut().take();
             }
         }
+        // chrome detaches before the target is destroyed
+        self.fail_session_commands(&event.session_id);
     }
 
     /// Fired when the target was destroyed in the browser
     fn on_target_destroyed(&mut self, event: EventTargetDestroyed) {
-        if let Some(target) = self.targets.remove(&event.target_id) {
-            // TODO shutdown?
-            if let Some(session) = target.session_id() {
-                self.sessions.remove(session);
+        self.evicted_targets.remove(&event.target_id);
+        if let Some((tx, _)) = self.pending_initiators.remove(&event.target_id) {
+            let _ = tx.send(Err(CdpError::PageClosed));
+        }
+        self.dispose_target(&event.target_id);
+    }
+
+    /// Stop tracking the target and fail the commands still waiting for a
+    /// response from its session, the browser won't answer them anymore
+    fn dispose_target(&mut self, target_id: &TargetId) {
+        let Some(target) = self.targets.remove(target_id) else {
+            return;
+        };
+        if let Some(session) = target.session_id() {
+            self.sessions.remove(session);
+            self.fail_session_commands(session);
+        }
+    }
+
+    /// Fail the commands still waiting for a response from the session with
+    /// [`CdpError::PageClosed`]
+    fn fail_session_commands(&mut self, session: &SessionId) {
+        let orphaned = self
+            .pending_commands
+            .iter()
+            .filter(|(_, (req, _, _))| {
+                matches!(req, PendingRequest::ExternalCommand(_, Some(id)) if id == session)
+            })
+            .map(|(k, _)| *k)
+            .collect::<Vec<_>>();
+        for call in orphaned {
+            self.conn.forget_command(call);
+            if let Some((req, _, _)) = self.pending_commands.remove(&call) {
+                self.fail_request(req, CdpError::PageClosed);
             }
         }
     }
 
     /// House keeping of commands
     ///
-    /// Remove all commands where `now` > `timestamp of command starting point +
-    /// request timeout` and notify the senders that their request timed out.
+    /// Remove all commands where `now` > `deadline of the command` and notify
+    /// the senders that their request timed out.
     fn evict_timed_out_commands(&mut self, now: Instant) {
         let timed_out = self
             .pending_commands
             .iter()
-            .filter(|(_, (_, _, timestamp))| now > (*timestamp + self.config.request_timeout))
+            .filter(|(_, (_, _, deadline))| now > *deadline)
             .map(|(k, _)| *k)
             .collect::<Vec<_>>();
         for call in timed_out {
+            self.conn.forget_command(call);
             if let Some((req, _, _)) = self.pending_commands.remove(&call) {
-                match req {
-                    PendingRequest::CreateTarget(tx) => {
-                        let _ = tx.send(Err(CdpError::Timeout));
-                    }
-                    PendingRequest::GetTargets(tx) => {
-                        let _ = tx.send(Err(CdpError::Timeout));
-                    }
-                    PendingRequest::Navigate(nav) => {
-                        if let Some(nav) = self.navigations.remove(&nav) {
-                            match nav {
-                                NavigationRequest::Navigate(nav) => {
-                                    let _ = nav.tx.send(Err(CdpError::Timeout));
-                                }
-                            }
+                self.fail_request(req, CdpError::Timeout);
+            }
+        }
+        // targets that were never announced
+        let timed_out = self
+            .pending_initiators
+            .iter()
+            .filter(|(_, (_, deadline))| now > *deadline)
+            .map(|(target_id, _)| target_id.clone())
+            .collect::<Vec<_>>();
+        for target_id in timed_out {
+            if let Some((tx, _)) = self.pending_initiators.remove(&target_id) {
+                // chrome created the target, close it before the caller retries
+                let close = CloseTargetParams::new(target_id.clone());
+                let req = CdpRequest {
+                    method: close.identifier(),
+                    session_id: None,
+                    params: serde_json::to_value(close).unwrap(),
+                };
+                let _ = self.submit_internal_command(target_id.clone(), req, now);
+                self.evicted_targets.insert(target_id);
+                let _ = tx.send(Err(CdpError::Timeout));
+            }
+        }
+    }
+
+    /// Answer a pending request with an error
+    fn fail_request(&mut self, req: PendingRequest, err: CdpError) {
+        match req {
+            PendingRequest::CreateTarget(tx) => {
+                let _ = tx.send(Err(err));
+            }
+            PendingRequest::GetTargets(tx) => {
+                let _ = tx.send(Err(err));
+            }
+            PendingRequest::Navigate(nav) => {
+                if let Some(nav) = self.navigations.remove(&nav) {
+                    match nav {
+                        NavigationRequest::Navigate(nav) => {
+                            let _ = nav.tx.send(Err(err));
                         }
                     }
-                    PendingRequest::ExternalCommand(tx) => {
-                        let _ = tx.send(Err(CdpError::Timeout));
-                    }
-                    PendingRequest::InternalCommand(_) => {}
-                    PendingRequest::CloseBrowser(tx) => {
-                        let _ = tx.send(Err(CdpError::Timeout));
-                    }
                 }
             }
+            PendingRequest::ExternalCommand(tx, _) => {
+                let _ = tx.send(Err(err));
+            }
+            PendingRequest::InternalCommand(_) => {}
+            PendingRequest::CloseBrowser(tx) => {
+                let _ = tx.send(Err(err));
+            }
+            // a lost heartbeat is detected when the next one is due
+            PendingRequest::Heartbeat => {}
         }
     }
 
@@ -552,12 +778,17 @@ impl Stream for Handler {
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let pin = self.get_mut();
 
+        if pin.connection_lost {
+            return Poll::Ready(None);
+        }
+
         loop {
             let now = Instant::now();
             // temporary pinning of the browser receiver should be safe as we are pinning
             // through the already pinned self. with the receivers we can also
             // safely ignore exhaustion as those are fused.
             while let Poll::Ready(Some(msg)) = Pin::new(&mut pin.from_browser).poll_next(cx) {
+                pin.on_activity();
                 match msg {
                     HandlerMessage::Command(cmd) => {
                         pin.submit_external_command(cmd, now)?;
@@ -587,6 +818,24 @@ impl Stream for Handler {
                     HandlerMessage::DisposeContext(ctx) => {
                         pin.browser_contexts.remove(&ctx);
                     }
+                    HandlerMessage::DisposeTarget(target_id) => {
+                        pin.dispose_target(&target_id);
+                    }
+                    HandlerMessage::FirstPage(tx) => {
+                        let default_context = pin.default_browser_context.clone();
+                        let page = pin
+                            .targets
+                            .values_mut()
+                            .filter(|target| {
+                                target.is_page()
+                                    && target.is_initialized()
+                                    && target.opener_id().is_none()
+                                    && *target.browser_context() == default_context
+                            })
+                            .find_map(|target| target.get_or_create_page())
+                            .map(|page| Page::from(page.clone()));
+                        let _ = tx.send(page);
+                    }
                     HandlerMessage::GetPage(target_id, tx) => {
                         let page = pin
                             .targets
@@ -595,6 +844,15 @@ impl Stream for Handler {
                             .map(|page| Page::from(page.clone()));
                         let _ = tx.send(page);
                     }
+                    HandlerMessage::InitializedPage(target_id, tx) => {
+                        let page = pin
+                            .targets
+                            .get_mut(&target_id)
+                            .filter(|target| target.is_initialized())
+                            .and_then(|target| target.get_or_create_page())
+                            .map(|page| Page::from(page.clone()));
+                        let _ = tx.send(page);
+                    }
                     HandlerMessage::AddEventListener(req) => {
                         pin.event_listeners.add_listener(req);
                     }
@@ -604,6 +862,7 @@ impl Stream for Handler {
             for n in (0..pin.target_ids.len()).rev() {
                 let target_id = pin.target_ids.swap_remove(n);
                 if let Some((id, mut target)) = pin.targets.remove_entry(&target_id) {
+                    let mut disposed = None;
                     while let Some(event) = target.poll(cx, now) {
                         match event {
                             TargetEvent::Request(req) => {
@@ -614,17 +873,30 @@ impl Stream for Handler {
                                 );
                             }
                             TargetEvent::Command(msg) => {
+                                pin.on_activity();
                                 pin.on_target_message(&mut target, msg, now);
                             }
                             TargetEvent::NavigationRequest(id, req) => {
+                                pin.on_activity();
                                 pin.submit_navigation(id, req, now);
                             }
                             TargetEvent::NavigationResult(res) => {
                                 pin.on_navigation_lifecycle_completed(res)
                             }
+                            TargetEvent::Dispose(tx) => {
+                                disposed = Some(tx);
+                                break;
+                            }
                         }
                     }
 
+                    if let Some(tx) = disposed {
+                        pin.targets.insert(id, target);
+                        pin.dispose_target(&target_id);
+                        let _ = tx.send(());
+                        continue;
+                    }
+
                     // poll the target's event listeners
                     target.event_listeners_mut().poll(cx);
                     // poll the handler's event listeners
@@ -669,6 +941,30 @@ impl Stream for Handler {
                 pin.evict_timed_out_commands(now);
             }
 
+            if let Some(heartbeat) = pin.heartbeat.as_mut() {
+                if heartbeat.poll_ready(cx) {
+                    if pin.heartbeat_in_flight {
+                        pin.on_connection_lost();
+                        return Poll::Ready(Some(Err(CdpError::ConnectionClosed)));
+                    }
+                    pin.submit_heartbeat(now)?;
+                    // the connection was polled already, poll it again to send the probe
+                    done = false;
+                }
+            }
+
+            if let Some(idle) = pin.idle.as_mut() {
+                if idle.poll_ready(cx) && !pin.closing && pin.is_idle() {
+                    tracing::debug!("Closing the idle browser");
+                    // nobody waits for the response, the handler just ends
+                    let (tx, _) = oneshot_channel();
+                    pin.submit_close(tx, now);
+                    pin.idle = None;
+                    // poll the connection again to send `Browser.close`
+                    done = false;
+                }
+            }
+
             if done {
                 // no events/responses were read from the websocket
                 return Poll::Pending;
@@ -694,6 +990,55 @@ pub struct HandlerConfig {
     pub request_intercept: bool,
     /// Whether to enable cache
     pub cache_enabled: bool,
+    /// How many messages the channels from the `Browser` to the `Handler` and
+    /// from every `Page` to its target buffer before senders have to wait.
+    ///
+    /// A larger capacity lets many tasks issue commands in parallel, at the
+    /// cost of memory for the buffered messages and of weaker backpressure
+    /// when the handler isn't polled fast enough.
+    pub channel_capacity: usize,
+    /// How often to check that the browser still responds, `None` (the
+    /// default) disables the check.
+    ///
+    /// Every interval the handler sends a `Browser.getVersion`. If the
+    /// previous one wasn't answered by then, the handler fails all pending
+    /// requests, yields [`CdpError::ConnectionClosed`] and ends. This turns a
+    /// silently dead websocket into an error instead of commands that hang
+    /// until their `request_timeout`, or forever for navigations. The
+    /// interval should leave room for a busy browser to respond.
+    pub heartbeat_interval: Option<Duration>,
+    /// How long the handler waits for a command before closing the browser,
+    /// `None` (the default) disables the auto-close.
+    ///
+    /// Every message from the [`Browser`](crate::Browser) or a page resets
+    /// the timer. Once it expires while no command is in flight, the handler
+    /// sends `Browser.close` and ends when it is answered. This keeps a
+    /// forgotten browser from running forever.
+    pub idle_timeout: Option<Duration>,
+    /// URL patterns every page blocks via `Network.setBlockedURLs`, see
+    /// [`Page::set_blocked_urls`](crate::Page::set_blocked_urls)
+    pub blocked_urls: Vec<String>,
+    /// The largest websocket message and frame in bytes the connection
+    /// accepts, defaults to `None`, which accepts any size.
+    ///
+    /// A CDP response arrives as one message, so this caps e.g. full page
+    /// screenshots, response bodies and evaluation results; a larger message
+    /// fails the connection, and with it every page. A message is buffered completely before it's
+    /// parsed, so a page can make the process allocate up to this size
+    /// (and briefly more while decoding) per message.
+    pub max_message_size: Option<usize>,
+    /// Whether to log the CDP traffic at `TRACE` level with the target
+    /// [`PROTOCOL_TRACE_TARGET`](crate::conn::PROTOCOL_TRACE_TARGET),
+    /// defaults to `false`.
+    ///
+    /// Every command is logged with its id, method, session and params,
+    /// every response and event with its payload. Payloads of methods that
+    /// carry cookies, credentials or request headers, like
+    /// `Network.getCookies` or `Fetch.continueWithAuth`, are redacted.
+    pub trace_protocol: bool,
+    /// How many bytes of a payload the protocol trace logs, `None` logs
+    /// them completely, defaults to [`TRACE_PAYLOAD_LIMIT`]
+    pub trace_payload_limit: Option<usize>,
 }
 
 impl Default for HandlerConfig {
@@ -706,6 +1051,13 @@ impl Default for HandlerConfig {
             request_timeout: Duration::from_millis(REQUEST_TIMEOUT),
             request_intercept: false,
             cache_enabled: true,
+            channel_capacity: CHANNEL_CAPACITY,
+            heartbeat_interval: None,
+            idle_timeout: None,
+            blocked_urls: Vec::new(),
+            max_message_size: None,
+            trace_protocol: false,
+            trace_payload_limit: Some(TRACE_PAYLOAD_LIMIT),
         }
     }
 }
@@ -765,13 +1117,16 @@ enum PendingRequest {
     /// after the `Target` notifies the `Handler` that the `Page` has finished
     /// loading, which comes after the response.
     Navigate(NavigationId),
-    /// A common request received via a channel (`Page`).
-    ExternalCommand(OneshotSender<Result<Response>>),
+    /// A common request received via a channel (`Page`), sent to the
+    /// session, if any.
+    ExternalCommand(OneshotSender<Result<Response>>, Option<SessionId>),
     /// Requests that are initiated directly from a `Target` (all the
     /// initialization commands).
     InternalCommand(TargetId),
     // A Request to close the browser.
     CloseBrowser(OneshotSender<Result<CloseReturns>>),
+    /// A `Browser.getVersion` sent by the heartbeat
+    Heartbeat,
 }
 
 /// Events used internally to communicate with the handler, which are executed
@@ -783,9 +1138,14 @@ pub(crate) enum HandlerMessage {
     FetchTargets(OneshotSender<Result<Vec<TargetInfo>>>),
     InsertContext(BrowserContext),
     DisposeContext(BrowserContext),
+    /// Stop tracking a target that was closed
+    DisposeTarget(TargetId),
     GetPages(OneshotSender<Vec<Page>>),
     Command(CommandMessage),
     GetPage(TargetId, OneshotSender<Option<Page>>),
+    /// The page of the target once it finished initializing
+    InitializedPage(TargetId, OneshotSender<Option<Page>>),
+    FirstPage(OneshotSender<Option<Page>>),
     AddEventListener(EventListenerRequest),
     CloseBrowser(OneshotSender<Result<CloseReturns>>),
 }
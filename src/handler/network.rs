@@ -1,21 +1,30 @@
 use chromiumoxide_cdp::cdp::browser_protocol::fetch::{
-    self, AuthChallengeResponse, AuthChallengeResponseResponse, ContinueRequestParams,
-    ContinueWithAuthParams, DisableParams, EventAuthRequired, EventRequestPaused, RequestPattern,
+    self, AuthChallengeResponse, AuthChallengeResponseResponse, AuthChallengeSource,
+    ContinueRequestParams, ContinueResponseParams, ContinueWithAuthParams, DisableParams,
+    EventAuthRequired, EventRequestPaused, FailRequestParams, FulfillRequestParams, RequestPattern,
+    RequestStage,
 };
 #[allow(deprecated)]
 use chromiumoxide_cdp::cdp::browser_protocol::network::{
-    EmulateNetworkConditionsParams, EventLoadingFailed, EventLoadingFinished,
+    EmulateNetworkConditionsParams, ErrorReason, EventLoadingFailed, EventLoadingFinished,
     EventRequestServedFromCache, EventRequestWillBeSent, EventResponseReceived, Headers,
-    InterceptionId, RequestId, Response, SetCacheDisabledParams, SetExtraHttpHeadersParams,
+    InterceptionId, RequestId, ResourceType, Response, SetBlockedUrLsParams,
+    SetCacheDisabledParams, SetExtraHttpHeadersParams,
 };
 use chromiumoxide_cdp::cdp::browser_protocol::{
-    network::EnableParams, security::SetIgnoreCertificateErrorsParams,
+    network::EnableParams, page::FrameId, security::SetIgnoreCertificateErrorsParams,
 };
 use chromiumoxide_types::{Command, Method, MethodId};
 
 use crate::auth::Credentials;
 use crate::cmd::CommandChain;
 use crate::handler::http::HttpRequest;
+use crate::intercept::{
+    glob_match, header_entries, strip_headers, InterceptRules, InterceptedRequest,
+    InterceptedResponseHead, RequestAction, RequestModifier, ResponseAction, ResponseCapture,
+    ResponseModifier,
+};
+use crate::utils;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::time::Duration;
 
@@ -31,8 +40,22 @@ pub struct NetworkManager {
     user_cache_disabled: bool,
     attempted_authentications: HashSet<RequestId>,
     credentials: Option<Credentials>,
-    user_request_interception_enabled: bool,
+    /// Credentials for server auth challenges, by origin
+    http_credentials: HashMap<String, Credentials>,
+    /// The origin of the main frame, whose `http_credentials` are forgotten
+    /// once it navigates to another origin
+    main_frame_origin: Option<url::Origin>,
+    intercept_rules: Option<InterceptRules>,
+    response_captures: Vec<ResponseCapture>,
+    request_modifier: Option<RequestModifier>,
+    response_modifier: Option<ResponseModifier>,
+    navigation_headers: Option<NavigationHeaders>,
+    /// The requests left to the user's `Fetch.requestPaused` listeners
+    user_request_pattern: Option<RequestPattern>,
     protocol_request_interception_enabled: bool,
+    /// Whether `Fetch.enable` pauses requests at the request stage
+    protocol_request_stage_enabled: bool,
+    protocol_response_interception_enabled: bool,
     offline: bool,
     request_timeout: Duration,
 }
@@ -49,8 +72,17 @@ impl NetworkManager {
             user_cache_disabled: false,
             attempted_authentications: Default::default(),
             credentials: None,
-            user_request_interception_enabled: false,
+            http_credentials: Default::default(),
+            main_frame_origin: None,
+            intercept_rules: None,
+            response_captures: Vec::new(),
+            request_modifier: None,
+            response_modifier: None,
+            navigation_headers: None,
+            user_request_pattern: None,
             protocol_request_interception_enabled: false,
+            protocol_request_stage_enabled: false,
+            protocol_response_interception_enabled: false,
             offline: false,
             request_timeout,
         }
@@ -92,8 +124,20 @@ impl NetworkManager {
         self.push_cdp_request(SetExtraHttpHeadersParams::new(Headers::new(headers)));
     }
 
+    pub fn set_blocked_urls(&mut self, urls: Vec<String>) {
+        self.push_cdp_request(SetBlockedUrLsParams::new(urls));
+    }
+
     pub fn set_request_interception(&mut self, enabled: bool) {
-        self.user_request_interception_enabled = enabled;
+        self.set_user_request_pattern(
+            enabled.then(|| RequestPattern::builder().url_pattern("*").build()),
+        );
+    }
+
+    /// Leave the requests matching `pattern` to the user's
+    /// `Fetch.requestPaused` listeners, `None` handles all requests here
+    pub fn set_user_request_pattern(&mut self, pattern: Option<RequestPattern>) {
+        self.user_request_pattern = pattern;
         self.update_protocol_request_interception();
     }
 
@@ -113,29 +157,154 @@ impl NetworkManager {
         self.update_protocol_request_interception()
     }
 
+    /// Answer server auth challenges of `origin` with `credentials`
+    pub fn set_http_credentials(&mut self, origin: String, credentials: Credentials) {
+        self.http_credentials.insert(origin, credentials);
+        self.update_protocol_request_interception()
+    }
+
+    pub fn clear_http_credentials(&mut self) {
+        self.http_credentials.clear();
+        self.update_protocol_request_interception()
+    }
+
+    /// Forgets the http credentials of the origin the main frame navigated
+    /// away from, the credentials of other origins are kept
+    pub fn on_main_frame_navigated(&mut self, url: &str) {
+        let Some(origin) = url::Url::parse(url)
+            .ok()
+            .map(|url| url.origin())
+            .filter(url::Origin::is_tuple)
+        else {
+            return;
+        };
+        let Some(previous) = self.main_frame_origin.replace(origin.clone()) else {
+            return;
+        };
+        if previous != origin
+            && self
+                .http_credentials
+                .remove(&previous.ascii_serialization())
+                .is_some()
+        {
+            self.update_protocol_request_interception()
+        }
+    }
+
+    pub fn set_intercept_rules(&mut self, rules: InterceptRules) {
+        self.intercept_rules = if rules.is_empty() { None } else { Some(rules) };
+        self.update_protocol_request_interception()
+    }
+
+    pub fn set_request_modifier(&mut self, modifier: RequestModifier) {
+        self.request_modifier = Some(modifier);
+        self.update_protocol_request_interception()
+    }
+
+    pub fn clear_request_modifier(&mut self) {
+        self.request_modifier = None;
+        self.update_protocol_request_interception()
+    }
+
+    pub fn set_response_modifier(&mut self, modifier: ResponseModifier) {
+        self.response_modifier = Some(modifier);
+        self.update_protocol_request_interception()
+    }
+
+    /// Set the headers of the next navigation of a frame, `None` forgets them
+    pub fn set_navigation_headers(&mut self, headers: Option<NavigationHeaders>) {
+        self.navigation_headers = headers;
+        self.update_protocol_request_interception()
+    }
+
+    pub fn capture_responses(&mut self, capture: ResponseCapture) {
+        self.response_captures.push(capture);
+        self.update_protocol_request_interception()
+    }
+
     fn update_protocol_request_interception(&mut self) {
-        let enabled = self.user_request_interception_enabled || self.credentials.is_some();
-        if enabled == self.protocol_request_interception_enabled {
+        let intercept_requests = self.user_request_pattern.is_some()
+            || self.credentials.is_some()
+            || !self.http_credentials.is_empty()
+            || self.intercept_rules.is_some()
+            || self.request_modifier.is_some()
+            || self.navigation_headers.is_some();
+        let intercept_responses =
+            !self.response_captures.is_empty() || self.response_modifier.is_some();
+        let enabled = intercept_requests || intercept_responses;
+        if enabled == self.protocol_request_interception_enabled
+            && intercept_requests == self.protocol_request_stage_enabled
+            && intercept_responses == self.protocol_response_interception_enabled
+        {
             return;
         }
         self.update_protocol_cache_disabled();
         self.protocol_request_interception_enabled = enabled;
+        self.protocol_request_stage_enabled = intercept_requests;
+        self.protocol_response_interception_enabled = intercept_responses;
         if enabled {
-            self.push_cdp_request(
-                fetch::EnableParams::builder()
-                    .handle_auth_requests(true)
-                    .pattern(RequestPattern::builder().url_pattern("*").build())
-                    .build(),
-            )
+            let mut params = fetch::EnableParams::builder().handle_auth_requests(true);
+            if intercept_requests {
+                params = params.pattern(RequestPattern::builder().url_pattern("*").build());
+            }
+            if intercept_responses {
+                params = params.pattern(
+                    RequestPattern::builder()
+                        .url_pattern("*")
+                        .request_stage(RequestStage::Response)
+                        .build(),
+                );
+            }
+            self.push_cdp_request(params.build())
         } else {
             self.push_cdp_request(DisableParams::default())
         }
     }
 
-    pub fn on_fetch_request_paused(&mut self, event: &EventRequestPaused) {
-        if !self.user_request_interception_enabled && self.protocol_request_interception_enabled {
-            self.push_cdp_request(ContinueRequestParams::new(event.request_id.clone()))
+    /// Handle a paused request, returns whether it is left to the user's
+    /// `Fetch.requestPaused` listeners, which then have to continue it.
+    ///
+    /// Every paused request is handled once: the blocking rules, the request
+    /// modifier and the navigation headers come first, requests matching the
+    /// user's pattern are left to the user and all others are continued.
+    pub fn on_fetch_request_paused(&mut self, event: &EventRequestPaused) -> bool {
+        if event.response_status_code.is_some() || event.response_error_reason.is_some() {
+            self.on_fetch_response_paused(event);
+            return false;
         }
+        let blocked = self
+            .intercept_rules
+            .as_ref()
+            .map(|rules| rules.is_blocked(&event.request.url, &event.resource_type))
+            .unwrap_or_default();
+        let navigation_headers = self
+            .navigation_headers
+            .as_mut()
+            .and_then(|headers| headers.on_request_paused(event));
+        let for_user = if blocked {
+            self.push_cdp_request(FailRequestParams::new(
+                event.request_id.clone(),
+                ErrorReason::BlockedByClient,
+            ));
+            false
+        } else if let Some(modifier) = self.request_modifier.as_ref() {
+            let mut action = (modifier.0)(&InterceptedRequest::from(event));
+            if let Some(headers) = navigation_headers {
+                action = with_navigation_headers(action, event, headers);
+            }
+            self.apply_request_action(event.request_id.clone(), action);
+            false
+        } else if let Some(headers) = navigation_headers {
+            let action = with_navigation_headers(RequestAction::Continue, event, headers);
+            self.apply_request_action(event.request_id.clone(), action);
+            false
+        } else if !self.protocol_request_stage_enabled || self.is_user_request(event) {
+            // paused by a `Fetch.enable` sent by the user
+            true
+        } else {
+            self.push_cdp_request(ContinueRequestParams::new(event.request_id.clone()));
+            false
+        };
         if let Some(network_id) = event.network_id.as_ref() {
             if let Some(request_will_be_sent) =
                 self.requests_will_be_sent.remove(network_id.as_ref())
@@ -146,15 +315,114 @@ impl NetworkManager {
                     .insert(network_id.clone(), event.request_id.clone().into());
             }
         }
+        for_user
+    }
+
+    /// Whether the request matches the pattern of the user's interception
+    fn is_user_request(&self, event: &EventRequestPaused) -> bool {
+        self.user_request_pattern.as_ref().is_some_and(|pattern| {
+            pattern
+                .url_pattern
+                .as_deref()
+                .map_or(true, |glob| glob_match(glob, &event.request.url))
+                && pattern
+                    .resource_type
+                    .as_ref()
+                    .map_or(true, |resource_type| *resource_type == event.resource_type)
+        })
+    }
+
+    fn apply_request_action(&mut self, request_id: fetch::RequestId, action: RequestAction) {
+        match action {
+            RequestAction::Continue => {
+                self.push_cdp_request(ContinueRequestParams::new(request_id))
+            }
+            RequestAction::ContinueWith {
+                url,
+                method,
+                headers,
+                post_data,
+            } => {
+                let mut params = ContinueRequestParams::new(request_id);
+                params.url = url;
+                params.method = method;
+                params.headers = headers.map(header_entries);
+                params.post_data = post_data.map(|data| utils::base64::encode(data).into());
+                self.push_cdp_request(params)
+            }
+            RequestAction::Block => self.push_cdp_request(FailRequestParams::new(
+                request_id,
+                ErrorReason::BlockedByClient,
+            )),
+            RequestAction::Fulfill {
+                status,
+                headers,
+                body,
+            } => {
+                let mut params = FulfillRequestParams::new(request_id, status);
+                params.response_headers = Some(header_entries(headers));
+                params.body = Some(utils::base64::encode(body).into());
+                self.push_cdp_request(params)
+            }
+        }
+    }
+
+    /// A response paused at the response stage is either forwarded to the
+    /// first capture that matches it, or continued as the response modifier
+    /// decides, untouched without one
+    fn on_fetch_response_paused(&mut self, event: &EventRequestPaused) {
+        let captures = self.response_captures.len();
+        self.response_captures
+            .retain(|capture| !capture.tx.is_closed());
+        if captures != self.response_captures.len() {
+            self.update_protocol_request_interception();
+        }
+
+        let capture = self
+            .response_captures
+            .iter()
+            .find(|capture| (capture.predicate)(event));
+        let forwarded = capture
+            .map(|capture| capture.tx.unbounded_send(event.clone()).is_ok())
+            .unwrap_or_default();
+        if forwarded {
+            return;
+        }
+        let action = match (&self.response_modifier, event.response_status_code) {
+            (Some(modifier), Some(_)) => (modifier.0)(&InterceptedResponseHead::from(event)),
+            _ => ResponseAction::Continue,
+        };
+        let headers = match action {
+            ResponseAction::Continue => None,
+            ResponseAction::StripHeaders(names) => event
+                .response_headers
+                .as_deref()
+                .and_then(|headers| strip_headers(headers, names)),
+        };
+        match headers {
+            Some(headers) => {
+                let mut params = ContinueResponseParams::new(event.request_id.clone());
+                params.response_headers = Some(headers);
+                self.push_cdp_request(params)
+            }
+            None => self.push_cdp_request(ContinueRequestParams::new(event.request_id.clone())),
+        }
     }
 
     pub fn on_fetch_auth_required(&mut self, event: &EventAuthRequired) {
+        let challenge = &event.auth_challenge;
+        let credentials = match challenge.source {
+            Some(AuthChallengeSource::Proxy) => None,
+            _ => self.http_credentials.get(&challenge.origin),
+        }
+        .or(self.credentials.as_ref())
+        .cloned();
         let response = if self
             .attempted_authentications
             .contains(event.request_id.as_ref())
         {
             AuthChallengeResponseResponse::CancelAuth
-        } else if self.credentials.is_some() {
+        } else if credentials.is_some() {
             self.attempted_authentications
                 .insert(event.request_id.clone().into());
             AuthChallengeResponseResponse::ProvideCredentials
@@ -163,7 +431,7 @@ impl NetworkManager {
         };
 
         let mut auth = AuthChallengeResponse::new(response);
-        if let Some(creds) = self.credentials.clone() {
+        if let Some(creds) = credentials {
             auth.username = Some(creds.username);
             auth.password = Some(creds.password);
         }
@@ -191,7 +459,7 @@ impl NetworkManager {
 
     /// Request interception doesn't happen for data URLs with Network Service.
     pub fn on_request_will_be_sent(&mut self, event: &EventRequestWillBeSent) {
-        if self.protocol_request_interception_enabled && !event.request.url.starts_with("data:") {
+        if self.protocol_request_stage_enabled && !event.request.url.starts_with("data:") {
             if let Some(interception_id) = self
                 .request_id_to_interception_id
                 .remove(event.request_id.as_ref())
@@ -261,7 +529,7 @@ impl NetworkManager {
             event.request_id.clone(),
             event.frame_id.clone(),
             interception_id,
-            self.user_request_interception_enabled,
+            self.user_request_pattern.is_some(),
             redirect_chain,
         );
 
@@ -279,6 +547,111 @@ impl NetworkManager {
     }
 }
 
+/// Headers that replace those Chrome sends on the document request of a
+/// navigation and its redirects, e.g. the `Sec-Fetch-*` headers of a
+/// navigation typed into the address bar.
+///
+/// Only the request of the main frame to the navigated URL gets the headers,
+/// so they don't leak into the other requests of the page.
+#[derive(Debug, Clone)]
+pub struct NavigationHeaders {
+    frame_id: FrameId,
+    /// The navigated URL without its fragment
+    url: String,
+    headers: HashMap<String, String>,
+    /// The request that got the headers, its redirects get them too
+    request_id: Option<fetch::RequestId>,
+}
+
+impl NavigationHeaders {
+    pub fn new(frame_id: FrameId, url: &str, headers: HashMap<String, String>) -> Self {
+        Self {
+            frame_id,
+            url: without_fragment(url),
+            headers,
+            request_id: None,
+        }
+    }
+
+    /// The headers for the paused request if it's the document request of
+    /// the navigation or one of its redirects
+    fn on_request_paused(&mut self, event: &EventRequestPaused) -> Option<HashMap<String, String>> {
+        let matches = match &self.request_id {
+            Some(request_id) => event.redirected_request_id.as_ref() == Some(request_id),
+            None => {
+                event.resource_type == ResourceType::Document
+                    && event.frame_id == self.frame_id
+                    && without_fragment(&event.request.url) == self.url
+            }
+        };
+        if !matches {
+            return None;
+        }
+        self.request_id = Some(event.request_id.clone());
+        Some(self.headers.clone())
+    }
+}
+
+/// The url as Chrome requests it, normalized and without its fragment
+fn without_fragment(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(mut url) => {
+            url.set_fragment(None);
+            url.into()
+        }
+        Err(_) => url.split('#').next().unwrap_or_default().to_string(),
+    }
+}
+
+/// Replace the request headers the action continues with by the navigation
+/// headers of the same name, or add them
+fn with_navigation_headers(
+    action: RequestAction,
+    event: &EventRequestPaused,
+    navigation_headers: HashMap<String, String>,
+) -> RequestAction {
+    let merge = |mut headers: HashMap<String, String>| {
+        headers.retain(|name, _| {
+            !navigation_headers
+                .keys()
+                .any(|key| key.eq_ignore_ascii_case(name))
+        });
+        headers.extend(navigation_headers.clone());
+        Some(headers)
+    };
+    let request_headers = || {
+        event
+            .request
+            .headers
+            .inner()
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+            .collect::<HashMap<_, _>>()
+    };
+    match action {
+        RequestAction::Continue => RequestAction::ContinueWith {
+            url: None,
+            method: None,
+            headers: merge(request_headers()),
+            post_data: None,
+        },
+        RequestAction::ContinueWith {
+            url,
+            method,
+            headers,
+            post_data,
+        } => RequestAction::ContinueWith {
+            url,
+            method,
+            headers: merge(headers.unwrap_or_else(request_headers)),
+            post_data,
+        },
+        action => action,
+    }
+}
+
 #[derive(Debug)]
 pub enum NetworkEvent {
     SendCdpRequest((MethodId, serde_json::Value)),
@@ -1,9 +1,11 @@
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use futures::channel::mpsc::{channel, Receiver, Sender};
 use futures::channel::oneshot::channel as oneshot_channel;
 use futures::stream::Fuse;
-use futures::{SinkExt, StreamExt};
+use futures::{future, SinkExt, StreamExt};
 
 use chromiumoxide_cdp::cdp::browser_protocol::browser::{GetVersionParams, GetVersionReturns};
 use chromiumoxide_cdp::cdp::browser_protocol::dom::{
@@ -27,7 +29,7 @@ use chromiumoxide_cdp::cdp::js_protocol::runtime::{
 };
 use chromiumoxide_types::{Command, CommandResponse};
 
-use crate::cmd::{to_command_response, CommandMessage};
+use crate::cmd::{to_batch_response, to_command_response, BatchCommand, CommandMessage};
 use crate::error::{CdpError, Result};
 use crate::handler::commandfuture::CommandFuture;
 use crate::handler::domworld::DOMWorldKind;
@@ -46,13 +48,19 @@ pub struct PageHandle {
 }
 
 impl PageHandle {
-    pub fn new(target_id: TargetId, session_id: SessionId, opener_id: Option<TargetId>) -> Self {
-        let (commands, rx) = channel(1);
+    pub fn new(
+        target_id: TargetId,
+        session_id: SessionId,
+        opener_id: Option<TargetId>,
+        channel_capacity: usize,
+    ) -> Self {
+        let (commands, rx) = channel(channel_capacity);
         let page = PageInner {
             target_id,
             session_id,
             opener_id,
             sender: commands,
+            download_dir: Mutex::new(None),
         };
         Self {
             rx: rx.fuse(),
@@ -71,6 +79,8 @@ pub(crate) struct PageInner {
     session_id: SessionId,
     opener_id: Option<TargetId>,
     sender: Sender<TargetMessage>,
+    /// Where downloads are saved, set by `Page::set_download_behavior`
+    download_dir: Mutex<Option<PathBuf>>,
 }
 
 impl PageInner {
@@ -79,11 +89,52 @@ impl PageInner {
         execute(cmd, self.sender.clone(), Some(self.session_id.clone())).await
     }
 
+    /// Send all commands, then wait for their responses, in order
+    pub(crate) async fn execute_batch(
+        &self,
+        cmds: Vec<BatchCommand>,
+    ) -> Result<Vec<serde_json::Value>> {
+        let mut sender = self.sender.clone();
+        let mut responses = Vec::with_capacity(cmds.len());
+        for cmd in cmds {
+            let (tx, rx) = oneshot_channel();
+            let msg = CommandMessage {
+                method: cmd.method,
+                session_id: Some(self.session_id.clone()),
+                params: cmd.params,
+                sender: tx,
+                timeout: None,
+            };
+            sender
+                .send(TargetMessage::Command(msg))
+                .await
+                .map_err(CdpError::from_target_send)?;
+            responses.push(async move {
+                to_batch_response(rx.await.map_err(|_| CdpError::PageClosed)??)
+            });
+        }
+        future::try_join_all(responses).await
+    }
+
     /// Create a PDL command future
     pub(crate) fn command_future<T: Command>(&self, cmd: T) -> Result<CommandFuture<T>> {
         CommandFuture::new(cmd, self.sender.clone(), Some(self.session_id.clone()))
     }
 
+    /// Create a PDL command future that times out after `timeout`
+    pub(crate) fn command_future_with_timeout<T: Command>(
+        &self,
+        cmd: T,
+        timeout: Duration,
+    ) -> Result<CommandFuture<T>> {
+        CommandFuture::with_timeout(
+            cmd,
+            self.sender.clone(),
+            Some(self.session_id.clone()),
+            Some(timeout),
+        )
+    }
+
     /// This creates navigation future with the final http response when the page is loaded
     pub(crate) fn wait_for_navigation(&self) -> TargetMessageFuture<ArcHttpRequest> {
         TargetMessageFuture::<ArcHttpRequest>::wait_for_navigation(self.sender.clone())
@@ -113,8 +164,21 @@ impl PageInner {
         &self.opener_id
     }
 
-    pub(crate) fn sender(&self) -> &Sender<TargetMessage> {
-        &self.sender
+    pub(crate) fn download_dir(&self) -> Option<PathBuf> {
+        self.download_dir.lock().unwrap().clone()
+    }
+
+    pub(crate) fn set_download_dir(&self, dir: PathBuf) {
+        *self.download_dir.lock().unwrap() = Some(dir);
+    }
+
+    /// Send a message to the target of this page
+    pub(crate) async fn send(&self, msg: TargetMessage) -> Result<()> {
+        self.sender
+            .clone()
+            .send(msg)
+            .await
+            .map_err(CdpError::from_target_send)
     }
 
     /// Returns the first element in the node which matches the given CSS
@@ -364,14 +428,12 @@ impl PageInner {
         dom_world: DOMWorldKind,
     ) -> Result<Option<ExecutionContextId>> {
         let (tx, rx) = oneshot_channel();
-        self.sender
-            .clone()
-            .send(TargetMessage::GetExecutionContext(GetExecutionContext {
-                dom_world,
-                frame_id,
-                tx,
-            }))
-            .await?;
+        self.send(TargetMessage::GetExecutionContext(GetExecutionContext {
+            dom_world,
+            frame_id,
+            tx,
+        }))
+        .await?;
         Ok(rx.await?)
     }
 
@@ -449,7 +511,11 @@ pub(crate) async fn execute<T: Command>(
     let method = cmd.identifier();
     let msg = CommandMessage::with_session(cmd, tx, session)?;
 
-    sender.send(TargetMessage::Command(msg)).await?;
-    let resp = rx.await??;
+    sender
+        .send(TargetMessage::Command(msg))
+        .await
+        .map_err(CdpError::from_target_send)?;
+    // the target drops the commands it didn't send when it closes
+    let resp = rx.await.map_err(|_| CdpError::PageClosed)??;
     to_command_response::<T>(resp, method)
 }
@@ -4,19 +4,26 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use chromiumoxide_cdp::cdp::browser_protocol::target::DetachFromTargetParams;
-use futures::channel::oneshot::Sender;
+use futures::channel::oneshot::{self, Sender};
+use futures::future::Future;
 use futures::stream::Stream;
 use futures::task::{Context, Poll};
 
-use chromiumoxide_cdp::cdp::browser_protocol::page::{FrameId, GetFrameTreeParams};
+use chromiumoxide_cdp::cdp::browser_protocol::page::{
+    AddScriptToEvaluateOnNewDocumentParams, AddScriptToEvaluateOnNewDocumentReturns, FrameId,
+    GetFrameTreeParams, RemoveScriptToEvaluateOnNewDocumentParams, ScriptIdentifier,
+};
 use chromiumoxide_cdp::cdp::browser_protocol::{
     browser::BrowserContextId,
-    log as cdplog, performance,
+    fetch::RequestPattern,
+    log as cdplog,
+    network::SetUserAgentOverrideParams,
+    performance,
     target::{AttachToTargetParams, SessionId, SetAutoAttachParams, TargetId, TargetInfo},
 };
 use chromiumoxide_cdp::cdp::events::CdpEvent;
 use chromiumoxide_cdp::cdp::CdpEventMessage;
-use chromiumoxide_types::{Command, Method, Request, Response};
+use chromiumoxide_types::{Command, Method, MethodId, Request, Response};
 
 use crate::auth::Credentials;
 use crate::cdp::browser_protocol::target::CloseTargetParams;
@@ -30,10 +37,11 @@ use crate::handler::frame::{
     FrameEvent, FrameManager, NavigationError, NavigationId, NavigationOk,
 };
 use crate::handler::frame::{FrameNavigationRequest, UTILITY_WORLD_NAME};
-use crate::handler::network::{NetworkEvent, NetworkManager};
+use crate::handler::network::{NavigationHeaders, NetworkEvent, NetworkManager};
 use crate::handler::page::PageHandle;
 use crate::handler::viewport::Viewport;
-use crate::handler::{PageInner, REQUEST_TIMEOUT};
+use crate::handler::{PageInner, CHANNEL_CAPACITY, REQUEST_TIMEOUT};
+use crate::intercept::{InterceptRules, RequestModifier, ResponseCapture, ResponseModifier};
 use crate::listeners::{EventListenerRequest, EventListeners};
 use crate::{page::Page, ArcHttpRequest};
 use chromiumoxide_cdp::cdp::js_protocol::runtime::{
@@ -92,6 +100,12 @@ pub struct Target {
     wait_for_frame_navigation: Vec<Sender<ArcHttpRequest>>,
     /// The sender who requested the page.
     initiator: Option<Sender<Result<Page>>>,
+    /// Init scripts and user agent override issued on this target, replayed
+    /// into out-of-process iframes when they attach
+    oopif_replay: OopifReplay,
+    /// Storage restore scripts by origin, removed once the main frame
+    /// navigated to their origin
+    storage_restores: Vec<(String, ScriptIdentifier)>,
 }
 
 impl Target {
@@ -104,6 +118,9 @@ impl Target {
 
         network_manager.set_cache_enabled(config.cache_enabled);
         network_manager.set_request_interception(config.request_intercept);
+        if !config.blocked_urls.is_empty() {
+            network_manager.set_blocked_urls(config.blocked_urls.clone());
+        }
 
         Self {
             info,
@@ -119,10 +136,28 @@ impl Target {
             queued_events: Default::default(),
             event_listeners: Default::default(),
             initiator: None,
+            oopif_replay: Default::default(),
+            storage_restores: Vec::new(),
             browser_context,
         }
     }
 
+    /// Create a target that is already attached via `session_id`, e.g. the
+    /// page of a page websocket endpoint
+    pub(crate) fn attached(
+        info: TargetInfo,
+        config: TargetConfig,
+        browser_context: BrowserContext,
+        session_id: SessionId,
+    ) -> Self {
+        let mut target = Self::new(info, config, browser_context);
+        target.init_state = TargetInit::InitializingFrame(FrameManager::init_commands(
+            target.config.request_timeout,
+        ));
+        target.session_id = Some(session_id);
+        target
+    }
+
     pub fn set_session_id(&mut self, id: SessionId) {
         self.session_id = Some(id)
     }
@@ -162,8 +197,12 @@ impl Target {
     fn create_page(&mut self) {
         if self.page.is_none() {
             if let Some(session) = self.session_id.clone() {
-                let handle =
-                    PageHandle::new(self.target_id().clone(), session, self.opener_id().cloned());
+                let handle = PageHandle::new(
+                    self.target_id().clone(),
+                    session,
+                    self.opener_id().cloned(),
+                    self.config.channel_capacity,
+                );
                 self.page = Some(handle);
             }
         }
@@ -233,7 +272,13 @@ impl Target {
                 .frame_manager
                 .on_frame_attached(ev.frame_id.clone(), Some(ev.parent_frame_id.clone())),
             CdpEvent::PageFrameDetached(ev) => self.frame_manager.on_frame_detached(ev),
-            CdpEvent::PageFrameNavigated(ev) => self.frame_manager.on_frame_navigated(&ev.frame),
+            CdpEvent::PageFrameNavigated(ev) => {
+                if ev.frame.parent_id.is_none() {
+                    self.network_manager.on_main_frame_navigated(&ev.frame.url);
+                    self.on_main_frame_navigated(&ev.frame.url);
+                }
+                self.frame_manager.on_frame_navigated(&ev.frame)
+            }
             CdpEvent::PageNavigatedWithinDocument(ev) => {
                 self.frame_manager.on_frame_navigated_within_document(ev)
             }
@@ -258,6 +303,10 @@ impl Target {
             // `Target` events
             CdpEvent::TargetAttachedToTarget(ev) => {
                 if ev.waiting_for_debugger {
+                    if "iframe" == &ev.target_info.r#type {
+                        self.on_oopif_attached(&ev.session_id);
+                    }
+
                     let runtime_cmd = RunIfWaitingForDebuggerParams::default();
 
                     self.queued_events.push_back(TargetEvent::Request(Request {
@@ -281,7 +330,13 @@ impl Target {
             }
 
             // `NetworkManager` events
-            CdpEvent::FetchRequestPaused(ev) => self.network_manager.on_fetch_request_paused(ev),
+            // requests handled by the network manager aren't for the
+            // listeners, which would handle them a second time
+            CdpEvent::FetchRequestPaused(ev)
+                if !self.network_manager.on_fetch_request_paused(ev) =>
+            {
+                return
+            }
             CdpEvent::FetchAuthRequired(ev) => self.network_manager.on_fetch_auth_required(ev),
             CdpEvent::NetworkRequestWillBeSent(ev) => {
                 self.network_manager.on_request_will_be_sent(ev)
@@ -304,6 +359,47 @@ impl Target {
         });
     }
 
+    /// Removes the storage restore scripts of the origin the main frame
+    /// navigated to, its storage was restored by the new document
+    fn on_main_frame_navigated(&mut self, url: &str) {
+        let Ok(url) = url::Url::parse(url) else {
+            return;
+        };
+        let origin = url.origin().ascii_serialization();
+        let mut idx = 0;
+        while idx < self.storage_restores.len() {
+            if self.storage_restores[idx].0 != origin {
+                idx += 1;
+                continue;
+            }
+            let (_, identifier) = self.storage_restores.remove(idx);
+            self.oopif_replay.forget(&identifier);
+            let remove = RemoveScriptToEvaluateOnNewDocumentParams::new(identifier);
+            self.queued_events.push_back(TargetEvent::Request(Request {
+                method: remove.identifier(),
+                session_id: self.session_id.clone().map(Into::into),
+                params: serde_json::to_value(remove).unwrap(),
+            }));
+        }
+    }
+
+    /// Replays the init scripts and user agent override of this target into an
+    /// out-of-process iframe.
+    ///
+    /// Cross-origin iframes run in their own target, so scripts registered on
+    /// the page's session never reach them. The iframe is paused until
+    /// `Runtime.runIfWaitingForDebugger`, which is queued after these
+    /// requests, so the scripts are in place before its document is created.
+    fn on_oopif_attached(&mut self, session_id: &SessionId) {
+        for (method, params) in self.oopif_replay.requests() {
+            self.queued_events.push_back(TargetEvent::Request(Request {
+                method,
+                session_id: Some(session_id.clone().into()),
+                params,
+            }));
+        }
+    }
+
     /// Called when a init command timed out
     fn on_initialization_failed(&mut self) -> TargetEvent {
         if let Some(initiator) = self.initiator.take() {
@@ -425,6 +521,8 @@ impl Target {
                 }
             }
 
+            self.oopif_replay.poll(cx);
+
             // Drain queued messages first.
             if let Some(ev) = self.queued_events.pop_front() {
                 return Some(ev);
@@ -433,7 +531,8 @@ impl Target {
             if let Some(handle) = self.page.as_mut() {
                 while let Poll::Ready(Some(msg)) = Pin::new(&mut handle.rx).poll_next(cx) {
                     match msg {
-                        TargetMessage::Command(cmd) => {
+                        TargetMessage::Command(mut cmd) => {
+                            self.oopif_replay.track(&mut cmd);
                             self.queued_events.push_back(TargetEvent::Command(cmd));
                         }
                         TargetMessage::MainFrame(tx) => {
@@ -518,6 +617,48 @@ impl Target {
                         TargetMessage::Authenticate(credentials) => {
                             self.network_manager.authenticate(credentials);
                         }
+                        TargetMessage::SetHttpCredentials(origin, credentials) => {
+                            self.network_manager
+                                .set_http_credentials(origin, credentials);
+                        }
+                        TargetMessage::ClearHttpCredentials => {
+                            self.network_manager.clear_http_credentials();
+                        }
+                        TargetMessage::Intercept(rules) => {
+                            self.network_manager.set_intercept_rules(rules);
+                        }
+                        TargetMessage::ModifyRequests(modifier) => {
+                            self.network_manager.set_request_modifier(modifier);
+                        }
+                        TargetMessage::ClearRequestModifier => {
+                            self.network_manager.clear_request_modifier();
+                        }
+                        TargetMessage::UserRequestInterception(pattern) => {
+                            self.network_manager.set_user_request_pattern(pattern);
+                            // enable the interception before the requests
+                            // that may be the next message are sent
+                            break;
+                        }
+                        TargetMessage::ModifyResponses(modifier) => {
+                            self.network_manager.set_response_modifier(modifier);
+                        }
+                        TargetMessage::NavigationHeaders(headers) => {
+                            self.network_manager.set_navigation_headers(headers);
+                            // enable the interception before the navigation
+                            // that may be the next message is sent
+                            break;
+                        }
+                        TargetMessage::Dispose(tx) => {
+                            // later messages are dropped with the target
+                            self.queued_events.push_back(TargetEvent::Dispose(tx));
+                            break;
+                        }
+                        TargetMessage::CaptureResponses(capture) => {
+                            self.network_manager.capture_responses(capture);
+                        }
+                        TargetMessage::RestoreStorage(origin, identifier) => {
+                            self.storage_restores.push((origin, identifier));
+                        }
                     }
                 }
             }
@@ -602,6 +743,10 @@ pub struct TargetConfig {
     pub viewport: Option<Viewport>,
     pub request_intercept: bool,
     pub cache_enabled: bool,
+    /// Capacity of the channel from the `Page` to this target
+    pub channel_capacity: usize,
+    /// URL patterns to block via `Network.setBlockedURLs`
+    pub blocked_urls: Vec<String>,
 }
 
 impl Default for TargetConfig {
@@ -612,6 +757,8 @@ impl Default for TargetConfig {
             viewport: Default::default(),
             request_intercept: false,
             cache_enabled: true,
+            channel_capacity: CHANNEL_CAPACITY,
+            blocked_urls: Vec::new(),
         }
     }
 }
@@ -681,6 +828,132 @@ pub(crate) enum TargetEvent {
     NavigationResult(Result<NavigationOk, NavigationError>),
     /// A new command arrived via a channel
     Command(CommandMessage),
+    /// The page asked to stop tracking this target
+    Dispose(Sender<()>),
+}
+
+/// The init scripts and user agent override of a target, as far as they
+/// need to be replayed into its out-of-process iframes
+#[derive(Debug, Default)]
+struct OopifReplay {
+    /// Init scripts registered on the target
+    init_scripts: Vec<InitScript>,
+    /// Init scripts whose identifier is not known yet
+    pending_init_scripts: Vec<PendingInitScript>,
+    /// The last user agent override of the target
+    user_agent_override: Option<serde_json::Value>,
+}
+
+impl OopifReplay {
+    /// Keeps track of the init scripts and the user agent override issued on
+    /// the target.
+    ///
+    /// The identifier of a new init script is only known once Chrome answers,
+    /// so the response is routed through here before it reaches the caller.
+    fn track(&mut self, cmd: &mut CommandMessage) {
+        if cmd.method == AddScriptToEvaluateOnNewDocumentParams::IDENTIFIER {
+            let (tx, rx) = oneshot::channel();
+            let caller = std::mem::replace(&mut cmd.sender, tx);
+            self.pending_init_scripts.push(PendingInitScript {
+                params: cmd.params.clone(),
+                rx,
+                caller,
+            });
+        } else if cmd.method == RemoveScriptToEvaluateOnNewDocumentParams::IDENTIFIER {
+            if let Ok(params) = serde_json::from_value::<RemoveScriptToEvaluateOnNewDocumentParams>(
+                cmd.params.clone(),
+            ) {
+                self.forget(&params.identifier);
+            }
+        } else if cmd.method == SetUserAgentOverrideParams::IDENTIFIER {
+            self.user_agent_override = Some(cmd.params.clone());
+        }
+    }
+
+    /// Stops replaying a removed init script
+    fn forget(&mut self, identifier: &ScriptIdentifier) {
+        self.init_scripts.retain_mut(|script| {
+            script.identifiers.retain(|id| id != identifier);
+            !script.identifiers.is_empty()
+        });
+    }
+
+    /// Records the identifiers of the init scripts Chrome has answered and
+    /// hands the responses to their callers
+    fn poll(&mut self, cx: &mut Context<'_>) {
+        let mut idx = 0;
+        while idx < self.pending_init_scripts.len() {
+            let Poll::Ready(resp) = Pin::new(&mut self.pending_init_scripts[idx].rx).poll(cx)
+            else {
+                idx += 1;
+                continue;
+            };
+            let PendingInitScript { params, caller, .. } = self.pending_init_scripts.remove(idx);
+            let resp = resp.unwrap_or(Err(CdpError::PageClosed));
+            let identifier = resp
+                .as_ref()
+                .ok()
+                .and_then(|resp| resp.result.clone())
+                .and_then(|result| {
+                    serde_json::from_value::<AddScriptToEvaluateOnNewDocumentReturns>(result).ok()
+                })
+                .map(|ret| ret.identifier);
+            if let Some(identifier) = identifier {
+                // registering the same script again only adds an identifier
+                match self.init_scripts.iter_mut().find(|s| s.params == params) {
+                    Some(script) => script.identifiers.push(identifier),
+                    None => self.init_scripts.push(InitScript {
+                        identifiers: vec![identifier],
+                        params,
+                    }),
+                }
+            }
+            let _ = caller.send(resp);
+        }
+    }
+
+    /// The requests that bring an out-of-process iframe up to date, every
+    /// script is registered once
+    fn requests(&self) -> Vec<(MethodId, serde_json::Value)> {
+        let mut scripts: Vec<&serde_json::Value> = Vec::new();
+        let registered = self.init_scripts.iter().map(|script| &script.params);
+        let pending = self
+            .pending_init_scripts
+            .iter()
+            .map(|script| &script.params);
+        for params in registered.chain(pending) {
+            if !scripts.contains(&params) {
+                scripts.push(params);
+            }
+        }
+        scripts
+            .into_iter()
+            .map(|params| (AddScriptToEvaluateOnNewDocumentParams::IDENTIFIER, params))
+            .chain(
+                self.user_agent_override
+                    .iter()
+                    .map(|params| (SetUserAgentOverrideParams::IDENTIFIER, params)),
+            )
+            .map(|(method, params)| (method.into(), params.clone()))
+            .collect()
+    }
+}
+
+/// An init script registered on a target
+#[derive(Debug)]
+struct InitScript {
+    /// The identifiers of every registration of the script
+    identifiers: Vec<ScriptIdentifier>,
+    params: serde_json::Value,
+}
+
+/// An init script waiting for Chrome to return its identifier
+#[derive(Debug)]
+struct PendingInitScript {
+    params: serde_json::Value,
+    rx: oneshot::Receiver<Result<Response>>,
+    /// The sender of the command that registered the script
+    caller: Sender<Result<Response>>,
 }
 
 // TODO this can be moved into the classes?
@@ -781,4 +1054,28 @@ pub enum TargetMessage {
     /// Get the `ExecutionContext` if available
     GetExecutionContext(GetExecutionContext),
     Authenticate(Credentials),
+    /// Answer the server auth challenges of an origin with the credentials
+    SetHttpCredentials(String, Credentials),
+    /// Forget the credentials of all origins
+    ClearHttpCredentials,
+    /// Replace the rules deciding which requests are blocked
+    Intercept(InterceptRules),
+    /// Set the callback deciding what happens to every paused request
+    ModifyRequests(RequestModifier),
+    /// Remove the callback of `ModifyRequests`
+    ClearRequestModifier,
+    /// Leave the paused requests matching the pattern to the listeners of
+    /// `Fetch.requestPaused`, `None` stops
+    UserRequestInterception(Option<RequestPattern>),
+    /// Set the callback deciding what happens to every paused response
+    ModifyResponses(ResponseModifier),
+    /// Forward the paused responses matching the capture to its subscriber
+    CaptureResponses(ResponseCapture),
+    /// Set the headers of the next navigation of a frame, `None` forgets them
+    NavigationHeaders(Option<NavigationHeaders>),
+    /// Remove the storage restore script once the main frame navigated to
+    /// the origin
+    RestoreStorage(String, ScriptIdentifier),
+    /// Stop tracking the target once it was closed, answers once it's gone
+    Dispose(Sender<()>),
 }
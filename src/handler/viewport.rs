@@ -1,10 +1,18 @@
+use crate::profiles::{ChaserProfile, ChaserProfileBuilder, Gpu, Os};
+use crate::utils;
+
 #[derive(Debug, Clone)]
 pub struct Viewport {
     pub width: u32,
     pub height: u32,
+    /// The device pixel ratio, `None` keeps a ratio of 1
     pub device_scale_factor: Option<f64>,
+    /// Whether to emulate a mobile device: meta viewport tags are respected
+    /// and overlay scrollbars are used
     pub emulating_mobile: bool,
     pub is_landscape: bool,
+    /// Whether to emulate a touch screen, which exposes touch events and a
+    /// nonzero `navigator.maxTouchPoints`
     pub has_touch: bool,
 }
 
@@ -20,3 +28,232 @@ impl Default for Viewport {
         }
     }
 }
+
+/// Common phones and tablets, see [`crate::devices`] to look them up by name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DevicePreset {
+    IPhoneSE,
+    IPhone13,
+    IPhone13Pro,
+    IPhone14ProMax,
+    IPhone15,
+    Pixel7,
+    Pixel7Pro,
+    GalaxyS23,
+    GalaxyS23Ultra,
+    OnePlus11,
+    IPadAir,
+    GalaxyTabS9,
+    PixelTablet,
+}
+
+/// The metrics and identity of a [`DevicePreset`]
+struct DeviceSpec {
+    name: &'static str,
+    os: Os,
+    /// The model reported in the client hints, empty for Apple devices
+    model: &'static str,
+    /// Width and height of the screen in CSS pixels, in portrait orientation
+    screen: (u32, u32),
+    device_scale_factor: f64,
+    /// The value of `navigator.maxTouchPoints`
+    touch_points: u32,
+    /// Whether the device is a phone, tablets aren't reported as mobile
+    phone: bool,
+    gpu: Gpu,
+}
+
+impl DevicePreset {
+    /// Every preset, phones first
+    pub const ALL: &'static [DevicePreset] = &[
+        DevicePreset::IPhoneSE,
+        DevicePreset::IPhone13,
+        DevicePreset::IPhone13Pro,
+        DevicePreset::IPhone14ProMax,
+        DevicePreset::IPhone15,
+        DevicePreset::Pixel7,
+        DevicePreset::Pixel7Pro,
+        DevicePreset::GalaxyS23,
+        DevicePreset::GalaxyS23Ultra,
+        DevicePreset::OnePlus11,
+        DevicePreset::IPadAir,
+        DevicePreset::GalaxyTabS9,
+        DevicePreset::PixelTablet,
+    ];
+
+    fn spec(&self) -> DeviceSpec {
+        let iphone = |name, screen, device_scale_factor| DeviceSpec {
+            name,
+            os: Os::IOS,
+            model: "",
+            screen,
+            device_scale_factor,
+            touch_points: 5,
+            phone: true,
+            gpu: Gpu::AppleGpu,
+        };
+        let android = |name, model, screen, device_scale_factor, phone, gpu| DeviceSpec {
+            name,
+            os: Os::Android,
+            model,
+            screen,
+            device_scale_factor,
+            touch_points: if phone { 5 } else { 10 },
+            phone,
+            gpu,
+        };
+        match self {
+            DevicePreset::IPhoneSE => iphone("iPhone SE", (375, 667), 2.),
+            DevicePreset::IPhone13 => iphone("iPhone 13", (390, 844), 3.),
+            DevicePreset::IPhone13Pro => iphone("iPhone 13 Pro", (390, 844), 3.),
+            DevicePreset::IPhone14ProMax => iphone("iPhone 14 Pro Max", (430, 932), 3.),
+            DevicePreset::IPhone15 => iphone("iPhone 15", (393, 852), 3.),
+            DevicePreset::Pixel7 => {
+                android("Pixel 7", "Pixel 7", (412, 915), 2.625, true, Gpu::MaliG710)
+            }
+            DevicePreset::Pixel7Pro => android(
+                "Pixel 7 Pro",
+                "Pixel 7 Pro",
+                (412, 892),
+                3.5,
+                true,
+                Gpu::MaliG710,
+            ),
+            DevicePreset::GalaxyS23 => android(
+                "Galaxy S23",
+                "SM-S911B",
+                (360, 780),
+                3.,
+                true,
+                Gpu::Adreno740,
+            ),
+            DevicePreset::GalaxyS23Ultra => android(
+                "Galaxy S23 Ultra",
+                "SM-S918B",
+                (384, 824),
+                3.75,
+                true,
+                Gpu::Adreno740,
+            ),
+            DevicePreset::OnePlus11 => android(
+                "OnePlus 11",
+                "CPH2449",
+                (412, 919),
+                3.5,
+                true,
+                Gpu::Adreno740,
+            ),
+            DevicePreset::IPadAir => DeviceSpec {
+                phone: false,
+                ..iphone("iPad Air", (820, 1180), 2.)
+            },
+            DevicePreset::GalaxyTabS9 => android(
+                "Galaxy Tab S9",
+                "SM-X710",
+                (800, 1280),
+                2.,
+                false,
+                Gpu::Adreno740,
+            ),
+            DevicePreset::PixelTablet => android(
+                "Pixel Tablet",
+                "Pixel Tablet",
+                (800, 1280),
+                2.,
+                false,
+                Gpu::MaliG710,
+            ),
+        }
+    }
+
+    /// The name the device is looked up by, e.g. `"iPhone 13 Pro"`
+    pub fn name(&self) -> &'static str {
+        self.spec().name
+    }
+
+    /// The OS of the device
+    pub fn os(&self) -> Os {
+        self.spec().os
+    }
+
+    /// Whether the device is a phone rather than a tablet
+    pub fn is_phone(&self) -> bool {
+        self.spec().phone
+    }
+
+    /// The viewport of the device in portrait orientation, with mobile and
+    /// touch emulation
+    pub fn viewport(&self) -> Viewport {
+        let spec = self.spec();
+        Viewport {
+            width: spec.screen.0,
+            height: spec.screen.1,
+            device_scale_factor: Some(spec.device_scale_factor),
+            emulating_mobile: true,
+            is_landscape: false,
+            has_touch: true,
+        }
+    }
+
+    /// A profile of Chrome on this device, to customize further or
+    /// [`build`](ChaserProfileBuilder::build)
+    pub fn profile(&self) -> ChaserProfileBuilder {
+        let spec = self.spec();
+        ChaserProfile::new(spec.os)
+            .gpu(spec.gpu)
+            .screen(spec.screen.0, spec.screen.1)
+            .device_pixel_ratio(spec.device_scale_factor)
+            .touch_points(spec.touch_points)
+            .mobile(spec.phone)
+            .model(spec.model)
+    }
+
+    /// The user agent of Chrome on the device, the one of its
+    /// [profile](Self::profile).
+    ///
+    /// iOS devices report Chrome for iOS (`CriOS`) rather than Safari, which
+    /// matches the Chrome features of the emulating browser more closely.
+    /// Chrome for iOS still runs on WebKit though, so pages that probe the
+    /// engine can tell an emulated iPhone or iPad apart.
+    pub fn user_agent(&self) -> String {
+        self.profile().build().user_agent()
+    }
+}
+
+/// Common desktop screen resolutions, weighted by their approximate share of
+/// desktop users
+const DESKTOP_SCREENS: &[((u32, u32), u32)] = &[
+    ((1920, 1080), 24),
+    ((1366, 768), 12),
+    ((1536, 864), 10),
+    ((1440, 900), 6),
+    ((2560, 1440), 6),
+    ((1280, 720), 5),
+    ((1600, 900), 4),
+    ((1680, 1050), 3),
+    ((1280, 800), 3),
+    ((1280, 1024), 2),
+    ((1920, 1200), 2),
+    ((1360, 768), 2),
+];
+
+/// Height of the taskbar, which a maximized window doesn't cover
+pub(crate) const TASKBAR_HEIGHT: u32 = 40;
+
+/// Height of the tab strip and toolbar above the page
+pub(crate) const BROWSER_UI_HEIGHT: u32 = 85;
+
+/// A desktop screen resolution picked from [`DESKTOP_SCREENS`] by weight,
+/// always the same for a `seed`
+pub fn desktop_screen(seed: u64) -> (u32, u32) {
+    let total: u32 = DESKTOP_SCREENS.iter().map(|(_, weight)| weight).sum();
+    let mut pick = (utils::splitmix64(seed) % u64::from(total)) as u32;
+    for (screen, weight) in DESKTOP_SCREENS {
+        if pick < *weight {
+            return *screen;
+        }
+        pick -= weight;
+    }
+    unreachable!("the pick is below the total weight")
+}
@@ -0,0 +1,546 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use brotli_decompressor::Decompressor as BrotliDecoder;
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use futures::channel::mpsc::UnboundedSender;
+use futures::Stream;
+
+use chromiumoxide_cdp::cdp::browser_protocol::fetch::{EventRequestPaused, HeaderEntry};
+use chromiumoxide_cdp::cdp::browser_protocol::network::ResourceType;
+
+use crate::utils;
+
+/// Rules deciding which requests of a page are blocked.
+///
+/// The rules are evaluated by the handler on every `Fetch.requestPaused`
+/// event: matching requests fail with `BlockedByClient`, all others are
+/// continued untouched.
+///
+/// # Example
+///
+/// ```ignore
+/// let rules = InterceptRules::new()
+///     .block_resource_types([ResourceType::Image, ResourceType::Font])
+///     .block_url("*.doubleclick.net/*");
+/// page.intercept(rules).await?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InterceptRules {
+    blocked_resource_types: Vec<ResourceType>,
+    blocked_urls: Vec<String>,
+}
+
+impl InterceptRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Block all requests of this resource type
+    pub fn block_resource_type(mut self, resource_type: ResourceType) -> Self {
+        self.blocked_resource_types.push(resource_type);
+        self
+    }
+
+    /// Block all requests of these resource types
+    pub fn block_resource_types(
+        mut self,
+        resource_types: impl IntoIterator<Item = ResourceType>,
+    ) -> Self {
+        self.blocked_resource_types.extend(resource_types);
+        self
+    }
+
+    /// Block all requests whose url matches the glob. `*` matches any
+    /// sequence of characters, `?` a single one and `\` escapes either.
+    pub fn block_url(mut self, glob: impl Into<String>) -> Self {
+        self.blocked_urls.push(glob.into());
+        self
+    }
+
+    /// Whether these rules never block anything
+    pub fn is_empty(&self) -> bool {
+        self.blocked_resource_types.is_empty() && self.blocked_urls.is_empty()
+    }
+
+    /// Whether a request to `url` for a resource of type `resource_type` is
+    /// blocked by these rules
+    pub fn is_blocked(&self, url: &str, resource_type: &ResourceType) -> bool {
+        self.blocked_resource_types.contains(resource_type)
+            || self.blocked_urls.iter().any(|glob| glob_match(glob, url))
+    }
+}
+
+/// A request paused by `Fetch.requestPaused`, passed to the callback of
+/// [`Page::modify_requests`](crate::Page::modify_requests)
+#[derive(Debug, Clone)]
+pub struct InterceptedRequest {
+    /// The url of the request, including the fragment
+    pub url: String,
+    /// The HTTP method of the request
+    pub method: String,
+    /// The request headers
+    pub headers: HashMap<String, String>,
+    /// The decoded request body, if any
+    pub post_data: Option<Vec<u8>>,
+    /// How the requested resource will be used
+    pub resource_type: ResourceType,
+}
+
+impl From<&EventRequestPaused> for InterceptedRequest {
+    fn from(event: &EventRequestPaused) -> Self {
+        let request = &event.request;
+        let headers = request
+            .headers
+            .inner()
+            .as_object()
+            .map(|headers| {
+                headers
+                    .iter()
+                    .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let post_data = request.post_data_entries.as_ref().map(|entries| {
+            entries
+                .iter()
+                .filter_map(|entry| entry.bytes.as_ref())
+                .filter_map(|bytes| utils::base64::decode(AsRef::<str>::as_ref(bytes)).ok())
+                .flatten()
+                .collect()
+        });
+        Self {
+            url: format!(
+                "{}{}",
+                request.url,
+                request.url_fragment.as_deref().unwrap_or_default()
+            ),
+            method: request.method.clone(),
+            headers,
+            post_data,
+            resource_type: event.resource_type.clone(),
+        }
+    }
+}
+
+/// What to do with a request paused by `Fetch.requestPaused`
+#[derive(Debug, Clone)]
+pub enum RequestAction {
+    /// Continue the request untouched (`Fetch.continueRequest`)
+    Continue,
+    /// Continue the request with the given overrides, `None` keeps the
+    /// original value (`Fetch.continueRequest`).
+    ///
+    /// A modified url is not observable by the page.
+    ContinueWith {
+        url: Option<String>,
+        method: Option<String>,
+        /// Replaces all headers of the request
+        headers: Option<HashMap<String, String>>,
+        post_data: Option<Vec<u8>>,
+    },
+    /// Fail the request with `BlockedByClient` (`Fetch.failRequest`)
+    Block,
+    /// Answer the request without sending it (`Fetch.fulfillRequest`)
+    Fulfill {
+        status: i64,
+        headers: HashMap<String, String>,
+        body: Vec<u8>,
+    },
+}
+
+pub(crate) fn header_entries(headers: HashMap<String, String>) -> Vec<HeaderEntry> {
+    headers
+        .into_iter()
+        .map(|(name, value)| HeaderEntry { name, value })
+        .collect()
+}
+
+/// The callback deciding the [`RequestAction`] of every paused request
+pub struct RequestModifier(
+    pub(crate) Arc<dyn Fn(&InterceptedRequest) -> RequestAction + Send + Sync>,
+);
+
+impl fmt::Debug for RequestModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RequestModifier").finish()
+    }
+}
+
+/// The headers of the Reporting API and Network Error Logging, which make the
+/// browser send reports a detector can watch for, for
+/// [`ResponseAction::StripHeaders`]
+pub const REPORTING_HEADERS: &[&str] = &[
+    "Report-To",
+    "Reporting-Endpoints",
+    "NEL",
+    "Content-Security-Policy-Report-Only",
+];
+
+/// A response paused at the response stage of `Fetch.requestPaused`, before
+/// its body is read, passed to the callback of
+/// [`Page::modify_responses`](crate::Page::modify_responses)
+#[derive(Debug, Clone)]
+pub struct InterceptedResponseHead {
+    /// The url of the request
+    pub url: String,
+    /// The HTTP status code of the response
+    pub status: i64,
+    /// The response headers, in the order the server sent them
+    pub headers: Vec<HeaderEntry>,
+    /// How the requested resource will be used
+    pub resource_type: ResourceType,
+}
+
+impl InterceptedResponseHead {
+    /// The value of the first header named `name`, ignoring case
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|header| header.name.eq_ignore_ascii_case(name))
+            .map(|header| header.value.as_str())
+    }
+}
+
+impl From<&EventRequestPaused> for InterceptedResponseHead {
+    fn from(event: &EventRequestPaused) -> Self {
+        Self {
+            url: event.request.url.clone(),
+            status: event.response_status_code.unwrap_or_default(),
+            headers: event.response_headers.clone().unwrap_or_default(),
+            resource_type: event.resource_type.clone(),
+        }
+    }
+}
+
+/// What to do with a response paused at the response stage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResponseAction {
+    /// Continue the response untouched (`Fetch.continueRequest`)
+    Continue,
+    /// Continue the response without the headers of the given names, which
+    /// are compared ignoring case (`Fetch.continueResponse`), e.g.
+    /// [`REPORTING_HEADERS`]
+    StripHeaders(&'static [&'static str]),
+}
+
+/// The callback deciding the [`ResponseAction`] of every paused response
+pub struct ResponseModifier(
+    pub(crate) Arc<dyn Fn(&InterceptedResponseHead) -> ResponseAction + Send + Sync>,
+);
+
+impl fmt::Debug for ResponseModifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ResponseModifier").finish()
+    }
+}
+
+/// The headers without those named in `names`, `None` if there are none to
+/// strip
+pub(crate) fn strip_headers(headers: &[HeaderEntry], names: &[&str]) -> Option<Vec<HeaderEntry>> {
+    let stripped = |header: &HeaderEntry| {
+        names
+            .iter()
+            .any(|name| header.name.eq_ignore_ascii_case(name))
+    };
+    if !headers.iter().any(stripped) {
+        return None;
+    }
+    Some(
+        headers
+            .iter()
+            .filter(|header| !stripped(header))
+            .cloned()
+            .collect(),
+    )
+}
+
+/// A response read at the response stage of `Fetch.requestPaused`
+#[derive(Debug, Clone)]
+pub struct InterceptedResponse {
+    /// The url of the request
+    pub url: String,
+    /// The HTTP status code of the response
+    pub status: i64,
+    /// The value of the `Content-Type` response header, if any
+    pub content_type: Option<String>,
+    /// The value of the `Content-Encoding` response header, if any
+    pub content_encoding: Option<String>,
+    /// The response body, decoded unless [`ResponseBody::Raw`] was requested.
+    /// Empty for responses without a body such as redirects
+    pub body: Vec<u8>,
+}
+
+impl InterceptedResponse {
+    /// The body as utf-8 text
+    pub fn text(&self) -> Option<&str> {
+        std::str::from_utf8(&self.body).ok()
+    }
+
+    /// Whether the `Content-Type` of the response is JSON
+    pub fn is_json(&self) -> bool {
+        self.content_type
+            .as_deref()
+            .map(|ty| {
+                ty.split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .ends_with("json")
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Whether the response capture helpers undo the `Content-Encoding` of the
+/// bodies they return, see [`decode_body`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResponseBody {
+    /// Decode `gzip`, `deflate` and `br` bodies
+    #[default]
+    Decoded,
+    /// The bytes as reported by the browser
+    Raw,
+}
+
+/// Undo the `content_encoding` (a `Content-Encoding` header value) of `body`,
+/// the raw bytes of a response read with `Fetch.getResponseBody`.
+///
+/// Supports `gzip`, `deflate` and `br`. The body is returned unchanged if
+/// one of the encodings is unsupported, such as `zstd`, or the body isn't
+/// encoded as announced.
+pub fn decode_body(body: Vec<u8>, content_encoding: &str) -> Vec<u8> {
+    fn read(mut reader: impl Read) -> Option<Vec<u8>> {
+        let mut decoded = Vec::new();
+        reader.read_to_end(&mut decoded).ok()?;
+        Some(decoded)
+    }
+
+    /// Raw deflate data has no header, so it only counts as such if the
+    /// whole body is a single deflate stream
+    fn inflate_raw(data: &[u8]) -> Option<Vec<u8>> {
+        let mut decoder = DeflateDecoder::new(data);
+        let decoded = read(&mut decoder)?;
+        (decoder.total_in() == data.len() as u64).then_some(decoded)
+    }
+
+    let mut decoded: Option<Vec<u8>> = None;
+    // the encodings are listed in the order they were applied
+    for encoding in content_encoding.rsplit(',').map(str::trim) {
+        let data = decoded.as_deref().unwrap_or(&body);
+        let layer = match encoding.to_ascii_lowercase().as_str() {
+            "" | "identity" => continue,
+            "gzip" | "x-gzip" if data.starts_with(&[0x1f, 0x8b]) => read(GzDecoder::new(data)),
+            // the zlib format, though some servers send raw deflate data
+            "deflate" if is_zlib(data) => read(ZlibDecoder::new(data)),
+            "deflate" => inflate_raw(data),
+            "br" => read(BrotliDecoder::new(data, 4096)),
+            _ => None,
+        };
+        match layer {
+            Some(layer) => decoded = Some(layer),
+            None => return body,
+        }
+    }
+    decoded.unwrap_or(body)
+}
+
+/// Whether `data` starts with a zlib header (RFC 1950)
+fn is_zlib(data: &[u8]) -> bool {
+    match data {
+        [cmf, flg, ..] => cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0,
+        _ => false,
+    }
+}
+
+/// The predicate selecting the paused responses a [`ResponseStream`] reads
+pub(crate) type ResponsePredicate = Arc<dyn Fn(&EventRequestPaused) -> bool + Send + Sync>;
+
+/// A subscription for paused responses, held by the `NetworkManager`.
+///
+/// The `NetworkManager` forwards every matching response to the subscriber,
+/// who is then responsible for continuing it, and continues all other
+/// responses itself.
+pub struct ResponseCapture {
+    pub(crate) predicate: ResponsePredicate,
+    pub(crate) tx: UnboundedSender<EventRequestPaused>,
+}
+
+impl fmt::Debug for ResponseCapture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseCapture")
+            .field("tx", &self.tx)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A stream of the responses matching the predicate passed to
+/// [`Page::on_response`](crate::Page::on_response).
+///
+/// Matching responses stay paused until the stream reads their body, so the
+/// stream must be polled. Once it is dropped, the handler continues all later
+/// responses itself.
+#[must_use = "streams do nothing unless polled"]
+pub struct ResponseStream {
+    inner: Pin<Box<dyn Stream<Item = InterceptedResponse> + Send>>,
+}
+
+impl ResponseStream {
+    pub(crate) fn new(inner: impl Stream<Item = InterceptedResponse> + Send + 'static) -> Self {
+        Self {
+            inner: Box::pin(inner),
+        }
+    }
+}
+
+impl fmt::Debug for ResponseStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseStream").finish_non_exhaustive()
+    }
+}
+
+impl Stream for ResponseStream {
+    type Item = InterceptedResponse;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+/// Matches `input` against a url pattern in the syntax of the `Fetch` domain
+pub(crate) fn glob_match(pattern: &str, input: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let input: Vec<char> = input.chars().collect();
+    let (mut p, mut i) = (0, 0);
+    // position of the last `*` in the pattern and the input index it matched up to
+    let mut backtrack = None;
+
+    while i < input.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, i));
+                p += 1;
+                continue;
+            }
+            Some('?') => {
+                p += 1;
+                i += 1;
+                continue;
+            }
+            Some('\\') if p + 1 < pattern.len() && pattern[p + 1] == input[i] => {
+                p += 2;
+                i += 1;
+                continue;
+            }
+            Some(c) if *c != '\\' && *c == input[i] => {
+                p += 1;
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        match backtrack {
+            Some((star, matched)) => {
+                p = star + 1;
+                i = matched + 1;
+                backtrack = Some((star, matched + 1));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("*", "https://example.com/"));
+        assert!(glob_match("*.png", "https://example.com/logo.png"));
+        assert!(!glob_match("*.png", "https://example.com/logo.png?v=1"));
+        assert!(glob_match(
+            "https://*.example.com/*",
+            "https://cdn.example.com/a.js"
+        ));
+        assert!(!glob_match(
+            "https://*.example.com/*",
+            "https://example.org/a.js"
+        ));
+        assert!(glob_match("https://example.com/?", "https://example.com/a"));
+        assert!(!glob_match("https://example.com/?", "https://example.com/"));
+        assert!(glob_match(r"*\?v=1", "https://example.com/logo.png?v=1"));
+        assert!(!glob_match(r"*\?v=1", "https://example.com/logo.pngXv=1"));
+    }
+
+    #[test]
+    fn blocks_by_type_and_url() {
+        let rules = InterceptRules::new()
+            .block_resource_types([ResourceType::Image, ResourceType::Font])
+            .block_url("*/analytics.js");
+
+        assert!(rules.is_blocked("https://example.com/a.png", &ResourceType::Image));
+        assert!(rules.is_blocked("https://example.com/analytics.js", &ResourceType::Script));
+        assert!(!rules.is_blocked("https://example.com/", &ResourceType::Document));
+        assert!(InterceptRules::new().is_empty());
+    }
+
+    #[test]
+    fn decodes_content_encoding() {
+        use flate2::write::{GzEncoder, ZlibEncoder};
+        use flate2::Compression;
+        use std::io::Write;
+
+        let text = b"{\"gzipped\": true}".to_vec();
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(&text).unwrap();
+        let gzip = gzip.finish().unwrap();
+        assert_eq!(decode_body(gzip.clone(), "gzip"), text);
+        assert_eq!(decode_body(gzip.clone(), "zstd"), gzip);
+
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(&gzip).unwrap();
+        let zlib = zlib.finish().unwrap();
+        assert_eq!(decode_body(zlib, "gzip, Deflate"), text);
+
+        let mut br = Vec::new();
+        brotli::BrotliCompress(&mut text.as_slice(), &mut br, &Default::default()).unwrap();
+        assert_eq!(decode_body(br.clone(), "br"), text);
+        // a failing layer keeps the body as it was
+        assert_eq!(decode_body(br.clone(), "br, gzip"), br);
+
+        // not encoded as announced
+        assert_eq!(decode_body(text.clone(), "gzip"), text);
+        assert_eq!(decode_body(text.clone(), "deflate"), text);
+        assert_eq!(decode_body(text.clone(), "br"), text);
+    }
+
+    #[test]
+    fn strips_reporting_headers() {
+        let header = |name: &str, value: &str| HeaderEntry {
+            name: name.to_string(),
+            value: value.to_string(),
+        };
+        let headers = vec![
+            header("content-type", "text/html"),
+            header("report-to", r#"{"group":"default"}"#),
+            header("Nel", r#"{"report_to":"default"}"#),
+            header("set-cookie", "a=1"),
+            header("set-cookie", "b=2"),
+        ];
+        assert_eq!(
+            strip_headers(&headers, REPORTING_HEADERS),
+            Some(vec![
+                header("content-type", "text/html"),
+                header("set-cookie", "a=1"),
+                header("set-cookie", "b=2"),
+            ])
+        );
+        assert_eq!(strip_headers(&headers[..1], REPORTING_HEADERS), None);
+    }
+}
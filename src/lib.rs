@@ -67,7 +67,7 @@ use std::sync::Arc;
 pub use chromiumoxide_cdp::cdp;
 pub use chromiumoxide_types::{self as types, Binary, Command, Method, MethodType};
 
-pub use crate::browser::{Browser, BrowserConfig};
+pub use crate::browser::{Browser, BrowserConfig, BrowserHandle};
 pub use crate::conn::Connection;
 pub use crate::element::Element;
 pub use crate::error::Result;
@@ -80,7 +80,9 @@ pub mod auth;
 pub mod browser;
 pub mod cmd;
 pub mod conn;
+pub mod cookie;
 pub mod detection;
+pub mod devices;
 pub mod element;
 pub mod error;
 #[cfg(feature = "fetcher")]
@@ -89,6 +91,7 @@ pub mod fetcher {
 }
 pub mod async_process;
 pub mod handler;
+pub mod intercept;
 pub mod js;
 pub mod keys;
 pub mod layout;
@@ -107,5 +110,10 @@ pub use crate::stealth::*;
 pub mod profiles;
 pub use crate::profiles::*;
 
+pub mod pool;
+pub use crate::pool::{PagePool, PooledPage};
+
+mod slider;
+
 // Re-export useful CDP types for request interception
 pub use chromiumoxide_cdp::cdp::browser_protocol::network::ResourceType;
@@ -1,39 +1,54 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 use futures::channel::mpsc::unbounded;
 use futures::channel::oneshot::channel as oneshot_channel;
-use futures::{stream, SinkExt, StreamExt};
+use futures::task::Poll;
+use futures::{future, stream, StreamExt};
 
 use chromiumoxide_cdp::cdp::browser_protocol::dom::*;
 use chromiumoxide_cdp::cdp::browser_protocol::emulation::{
     MediaFeature, SetEmulatedMediaParams, SetGeolocationOverrideParams, SetLocaleOverrideParams,
     SetTimezoneOverrideParams,
 };
+use chromiumoxide_cdp::cdp::browser_protocol::fetch;
 use chromiumoxide_cdp::cdp::browser_protocol::network::{
-    Cookie, CookieParam, DeleteCookiesParams, GetCookiesParams, SetCookiesParams,
-    SetUserAgentOverrideParams,
+    Cookie, CookieParam, DeleteCookiesParams, GetCookiesParams, ResourceType, SetBlockedUrLsParams,
+    SetBlockedUrLsReturns, SetCookiesParams, SetUserAgentOverrideParams,
 };
 use chromiumoxide_cdp::cdp::browser_protocol::page::*;
 use chromiumoxide_cdp::cdp::browser_protocol::performance::{GetMetricsParams, Metric};
-use chromiumoxide_cdp::cdp::browser_protocol::target::{SessionId, TargetId};
+use chromiumoxide_cdp::cdp::browser_protocol::target::{
+    CloseTargetParams, GetTargetInfoParams, SessionId, TargetId, TargetInfo,
+};
 use chromiumoxide_cdp::cdp::js_protocol;
 use chromiumoxide_cdp::cdp::js_protocol::debugger::GetScriptSourceParams;
 use chromiumoxide_cdp::cdp::js_protocol::runtime::{
     AddBindingParams, CallArgument, CallFunctionOnParams, EvaluateParams, ExecutionContextId,
     RemoteObjectType, ScriptId,
 };
-use chromiumoxide_cdp::cdp::{browser_protocol, IntoEventKind};
+use chromiumoxide_cdp::cdp::{browser_protocol, CdpEventMessage, IntoEventKind};
 use chromiumoxide_types::*;
 
 use crate::auth::Credentials;
+use crate::browser::SessionSnapshot;
+use crate::cmd::{to_command_response, BatchCommand};
+use crate::conn::Connection;
+use crate::cookie::validate_cookie;
 use crate::element::Element;
 use crate::error::{CdpError, Result};
 use crate::handler::commandfuture::CommandFuture;
 use crate::handler::domworld::DOMWorldKind;
 use crate::handler::httpfuture::HttpFuture;
+use crate::handler::network::NavigationHeaders;
 use crate::handler::target::{GetName, GetParent, GetUrl, TargetMessage};
-use crate::handler::PageInner;
+use crate::handler::{Handler, HandlerConfig, PageInner};
+use crate::intercept::{
+    decode_body, InterceptRules, InterceptedRequest, InterceptedResponse, InterceptedResponseHead,
+    RequestAction, RequestModifier, ResponseAction, ResponseBody, ResponseCapture,
+    ResponseModifier, ResponseStream,
+};
 use crate::js::{Evaluation, EvaluationResult};
 use crate::layout::Point;
 use crate::listeners::{EventListenerRequest, EventStream};
@@ -45,6 +60,60 @@ pub struct Page {
 }
 
 impl Page {
+    /// Attach to a single page via its websocket URL, e.g. the
+    /// `webSocketDebuggerUrl` of an entry of the browser's `/json/list`.
+    ///
+    /// Like [`Browser::connect`](crate::Browser::connect), the returned
+    /// [`Handler`] must be polled for the page to make progress.
+    ///
+    /// The connection only reaches this page, so there are limitations:
+    /// - there is no [`Browser`](crate::Browser): browser wide commands such
+    ///   as creating pages or contexts, or `Browser.*` methods, are unavailable
+    /// - pages this page opens (popups) are not tracked
+    /// - the handler ends when the page is closed or its websocket drops, the
+    ///   browser itself keeps running
+    ///
+    /// # Example
+    /// ```ignore
+    /// let (page, mut handler) = Page::attach("ws://127.0.0.1:9222/devtools/page/4F2C...").await?;
+    /// tokio::spawn(async move { while handler.next().await.is_some() {} });
+    /// page.goto("https://example.com").await?;
+    /// ```
+    pub async fn attach(ws_url: &str) -> Result<(Self, Handler)> {
+        Self::attach_with_config(ws_url, HandlerConfig::default()).await
+    }
+
+    /// Attach to a single page via its websocket URL with a given
+    /// `HandlerConfig`, see [`Page::attach`].
+    pub async fn attach_with_config(
+        ws_url: &str,
+        config: HandlerConfig,
+    ) -> Result<(Self, Handler)> {
+        let mut conn =
+            Connection::<CdpEventMessage>::connect_with_limit(ws_url, config.max_message_size)
+                .await?;
+        let info = page_target_info(&mut conn, ws_url).await?;
+        let target_id = info.target_id.clone();
+        let mut handler = Handler::attach_page(conn, info, config);
+
+        // drive the handler until the page is initialized
+        let page = future::poll_fn(|cx| loop {
+            match handler.poll_next_unpin(cx) {
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Err(err)),
+                Poll::Ready(None) => return Poll::Ready(Err(CdpError::ConnectionClosed)),
+                Poll::Ready(Some(Ok(()))) => continue,
+                Poll::Pending => {
+                    return match handler.initialized_page(&target_id) {
+                        Some(page) => Poll::Ready(Ok(page)),
+                        None => Poll::Pending,
+                    }
+                }
+            }
+        })
+        .await?;
+        Ok((page, handler))
+    }
+
     /// Removes the `navigator.webdriver` property
     /// changes permissions, pluggins rendering contexts and the `window.chrome`
     /// property to make it harder to detect the scraper as a bot
@@ -79,8 +148,8 @@ impl Page {
                     get: () => ({
                         brands: [
                             { brand: "Google Chrome", version: "129" },
-                            { brand: "Chromium", version: "129" },
-                            { brand: "Not=A?Brand", version: "24" }
+                            { brand: "Not=A?Brand", version: "8" },
+                            { brand: "Chromium", version: "129" }
                         ],
                         mobile: false,
                         platform: "Windows"
@@ -330,6 +399,38 @@ impl Page {
         self.command_future(cmd)?.await
     }
 
+    /// Execute a command that may take longer than the configured request
+    /// timeout, e.g. `Page.printToPDF` of a huge page, and return the
+    /// `Command::Response`.
+    ///
+    /// The command fails with [`CdpError::Timeout`] after `timeout` instead.
+    pub async fn execute_timeout<T: Command>(
+        &self,
+        cmd: T,
+        timeout: Duration,
+    ) -> Result<CommandResponse<T::Response>> {
+        self.inner.command_future_with_timeout(cmd, timeout)?.await
+    }
+
+    /// Execute several commands at once and return their json results, in
+    /// the order of `cmds`.
+    ///
+    /// All commands are sent before waiting for the first response, instead
+    /// of one round trip per command. Fails with the first error.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let results = page
+    ///     .execute_batch(vec![
+    ///         BatchCommand::new(SetBypassCspParams::new(true))?,
+    ///         BatchCommand::new(GetLayoutMetricsParams::default())?,
+    ///     ])
+    ///     .await?;
+    /// ```
+    pub async fn execute_batch(&self, cmds: Vec<BatchCommand>) -> Result<Vec<serde_json::Value>> {
+        self.inner.execute_batch(cmds).await
+    }
+
     /// Execute a command and return the `Command::Response`
     pub fn command_future<T: Command>(&self, cmd: T) -> Result<CommandFuture<T>> {
         self.inner.command_future(cmd)
@@ -407,8 +508,6 @@ impl Page {
     pub async fn event_listener<T: IntoEventKind>(&self) -> Result<EventStream<T>> {
         let (tx, rx) = unbounded();
         self.inner
-            .sender()
-            .clone()
             .send(TargetMessage::AddEventListener(
                 EventListenerRequest::new::<T>(tx),
             ))
@@ -454,11 +553,17 @@ impl Page {
 
     /// Navigate directly to the given URL.
     ///
-    /// This resolves directly after the requested URL is fully loaded.
+    /// This resolves directly after the requested URL is fully loaded. The
+    /// URL isn't parsed or normalized before it's sent to the browser.
+    ///
+    /// Fails with [`CdpError::Navigation`] if the browser couldn't load the
+    /// URL, e.g. because its host doesn't resolve.
     pub async fn goto(&self, params: impl Into<NavigateParams>) -> Result<&Self> {
-        let res = self.execute(params.into()).await?;
-        if let Some(err) = res.result.error_text {
-            return Err(CdpError::ChromeMessage(err));
+        let params = params.into();
+        let url = params.url.clone();
+        let res = self.execute(params).await?;
+        if let Some(net_error) = res.result.error_text {
+            return Err(CdpError::Navigation { url, net_error });
         }
 
         Ok(self)
@@ -483,8 +588,6 @@ impl Page {
     pub async fn frame_name(&self, frame_id: FrameId) -> Result<Option<String>> {
         let (tx, rx) = oneshot_channel();
         self.inner
-            .sender()
-            .clone()
             .send(TargetMessage::Name(GetName {
                 frame_id: Some(frame_id),
                 tx,
@@ -495,22 +598,310 @@ impl Page {
 
     pub async fn authenticate(&self, credentials: Credentials) -> Result<()> {
         self.inner
-            .sender()
-            .clone()
             .send(TargetMessage::Authenticate(credentials))
             .await?;
 
         Ok(())
     }
 
+    /// Answer HTTP Basic, Digest and NTLM auth challenges of the server at
+    /// `origin` (e.g. `https://example.com:8443`) with the credentials,
+    /// instead of leaving the page waiting on the auth dialog.
+    ///
+    /// Credentials of several origins can be set to use multiple protected
+    /// hosts. The credentials of an origin are forgotten once the page
+    /// navigates away from it to another origin, the credentials of the other
+    /// origins are kept until [`Page::clear_http_credentials`]. Proxy
+    /// challenges are answered with the credentials of
+    /// [`Page::authenticate`].
+    pub async fn set_http_credentials(
+        &self,
+        origin: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<()> {
+        let parsed = url::Url::parse(origin)?.origin();
+        if !parsed.is_tuple() {
+            return Err(CdpError::msg(format!("`{origin}` is not an HTTP origin")));
+        }
+        let credentials = Credentials {
+            username: username.to_string(),
+            password: password.to_string(),
+        };
+        self.inner
+            .send(TargetMessage::SetHttpCredentials(
+                parsed.ascii_serialization(),
+                credentials,
+            ))
+            .await?;
+        Ok(())
+    }
+
+    /// Forget the credentials set with [`Page::set_http_credentials`]
+    pub async fn clear_http_credentials(&self) -> Result<()> {
+        self.inner.send(TargetMessage::ClearHttpCredentials).await?;
+        Ok(())
+    }
+
+    /// Block the requests of this page matching the `rules`, all other
+    /// requests continue untouched.
+    ///
+    /// This enables request interception via the `Fetch` domain. Calling it
+    /// again replaces the previous rules, passing empty rules disables it.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// page.intercept(
+    ///     InterceptRules::new()
+    ///         .block_resource_type(ResourceType::Media)
+    ///         .block_url("*://*.google-analytics.com/*"),
+    /// )
+    /// .await?;
+    /// ```
+    pub async fn intercept(&self, rules: InterceptRules) -> Result<&Self> {
+        self.inner.send(TargetMessage::Intercept(rules)).await?;
+        Ok(self)
+    }
+
+    /// Decide for every request of this page whether to continue, modify,
+    /// block or fulfill it.
+    ///
+    /// The callback runs in the handler task for each `Fetch.requestPaused`
+    /// event, after the rules set by [`Page::intercept`]. Calling it again
+    /// replaces the previous callback.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// page.modify_requests(|request| {
+    ///     if request.url.contains("/telemetry") {
+    ///         RequestAction::Block
+    ///     } else if request.url.ends_with("/config.json") {
+    ///         RequestAction::Fulfill {
+    ///             status: 200,
+    ///             headers: HashMap::from([("content-type".into(), "application/json".into())]),
+    ///             body: br#"{"tracking":false}"#.to_vec(),
+    ///         }
+    ///     } else {
+    ///         RequestAction::Continue
+    ///     }
+    /// })
+    /// .await?;
+    /// ```
+    pub async fn modify_requests(
+        &self,
+        f: impl Fn(&InterceptedRequest) -> RequestAction + Send + Sync + 'static,
+    ) -> Result<&Self> {
+        self.inner
+            .send(TargetMessage::ModifyRequests(RequestModifier(Arc::new(f))))
+            .await?;
+        Ok(self)
+    }
+
+    /// Remove the callback set by [`Page::modify_requests`], requests are
+    /// continued untouched again
+    pub async fn clear_request_modifier(&self) -> Result<&Self> {
+        self.inner.send(TargetMessage::ClearRequestModifier).await?;
+        Ok(self)
+    }
+
+    /// Leave the paused requests matching `pattern` to the listeners of
+    /// `Fetch.requestPaused`, which have to continue them. `None` stops.
+    ///
+    /// Requests the handler blocks, modifies or adds navigation headers to
+    /// are never passed to the listeners.
+    pub(crate) async fn set_user_request_interception(
+        &self,
+        pattern: Option<fetch::RequestPattern>,
+    ) -> Result<()> {
+        self.inner
+            .send(TargetMessage::UserRequestInterception(pattern))
+            .await?;
+        Ok(())
+    }
+
+    /// Replace the headers Chrome sends on the document request of a
+    /// navigation, see [`NavigationHeaders`]. `None` forgets the headers
+    pub(crate) async fn set_navigation_headers(
+        &self,
+        headers: Option<NavigationHeaders>,
+    ) -> Result<()> {
+        self.inner
+            .send(TargetMessage::NavigationHeaders(headers))
+            .await?;
+        Ok(())
+    }
+
+    /// Decide for every response of this page whether to continue it
+    /// untouched or strip some of its headers, e.g. the
+    /// [`REPORTING_HEADERS`](crate::intercept::REPORTING_HEADERS)
+    /// that make the browser send reports a detector watches for.
+    ///
+    /// The callback runs in the handler task for each response paused at the
+    /// response stage, except those read by [`Page::on_response`]. Calling it
+    /// again replaces the previous callback. Responses aren't paused unless a
+    /// callback is set.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// page.modify_responses(|_| ResponseAction::StripHeaders(REPORTING_HEADERS))
+    ///     .await?;
+    /// ```
+    pub async fn modify_responses(
+        &self,
+        f: impl Fn(&InterceptedResponseHead) -> ResponseAction + Send + Sync + 'static,
+    ) -> Result<&Self> {
+        self.inner
+            .send(TargetMessage::ModifyResponses(ResponseModifier(Arc::new(
+                f,
+            ))))
+            .await?;
+        Ok(self)
+    }
+
+    /// Returns a stream of the responses of this page matching the
+    /// `predicate`, read without a second network round trip.
+    ///
+    /// This pauses responses via the `Fetch` domain. Each matching response
+    /// stays paused until the stream has read its body, then it continues to
+    /// the page untouched. Responses not matching the predicate are continued
+    /// by the handler right away.
+    ///
+    /// Bodies are decoded according to their `Content-Encoding`, see
+    /// [`Page::on_response_with`] for the raw bytes. Responses whose body
+    /// can't be read, e.g. redirects, are continued but skipped by the
+    /// stream, so an empty body always means the response had none.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut responses = page
+    ///     .on_response(|event| event.request.url.contains("/api/"))
+    ///     .await?;
+    /// while let Some(response) = responses.next().await {
+    ///     if response.is_json() {
+    ///         println!("{}: {:?}", response.url, response.text());
+    ///     }
+    /// }
+    /// ```
+    pub async fn on_response(
+        &self,
+        predicate: impl Fn(&fetch::EventRequestPaused) -> bool + Send + Sync + 'static,
+    ) -> Result<ResponseStream> {
+        self.on_response_with(predicate, ResponseBody::Decoded)
+            .await
+    }
+
+    /// Like [`Page::on_response`], with control over the decoding of the
+    /// response bodies
+    pub async fn on_response_with(
+        &self,
+        predicate: impl Fn(&fetch::EventRequestPaused) -> bool + Send + Sync + 'static,
+        body: ResponseBody,
+    ) -> Result<ResponseStream> {
+        let (tx, rx) = unbounded();
+        self.inner
+            .send(TargetMessage::CaptureResponses(ResponseCapture {
+                predicate: Arc::new(predicate),
+                tx,
+            }))
+            .await?;
+
+        let page = self.clone();
+        Ok(ResponseStream::new(rx.filter_map(move |event| {
+            let page = page.clone();
+            async move { page.read_paused_response(event, body).await }
+        })))
+    }
+
+    /// Reads the body of a response paused at the response stage and
+    /// continues it.
+    ///
+    /// Returns `None` if the body can't be read, the response is continued
+    /// either way.
+    async fn read_paused_response(
+        &self,
+        event: fetch::EventRequestPaused,
+        mode: ResponseBody,
+    ) -> Option<InterceptedResponse> {
+        let body = self
+            .execute(fetch::GetResponseBodyParams::new(event.request_id.clone()))
+            .await
+            .and_then(|resp| {
+                if resp.result.base64_encoded {
+                    Ok(utils::base64::decode(&resp.result.body)?)
+                } else {
+                    Ok(resp.result.body.into_bytes())
+                }
+            });
+        let _ = self
+            .execute(fetch::ContinueRequestParams::new(event.request_id))
+            .await;
+        let body = match body {
+            Ok(body) => body,
+            Err(err) => {
+                tracing::warn!(
+                    "Skipping the response of {}, its body can't be read: {}",
+                    event.request.url,
+                    err
+                );
+                return None;
+            }
+        };
+
+        let header = |name: &str| {
+            event.response_headers.as_ref().and_then(|headers| {
+                headers
+                    .iter()
+                    .find(|header| header.name.eq_ignore_ascii_case(name))
+                    .map(|header| header.value.clone())
+            })
+        };
+        let content_type = header("content-type");
+        let content_encoding = header("content-encoding");
+        let body = match (&content_encoding, mode) {
+            (Some(encoding), ResponseBody::Decoded) => decode_body(body, encoding),
+            _ => body,
+        };
+        Some(InterceptedResponse {
+            url: event.request.url,
+            status: event.response_status_code.unwrap_or_default(),
+            content_type,
+            content_encoding,
+            body,
+        })
+    }
+
+    /// Block all requests of the given resource types, e.g. images and fonts
+    /// to speed up scraping.
+    ///
+    /// This is a shorthand for [`Page::intercept`].
+    pub async fn block_resource_types(&self, resource_types: &[ResourceType]) -> Result<&Self> {
+        self.intercept(InterceptRules::new().block_resource_types(resource_types.iter().cloned()))
+            .await
+    }
+
+    /// Block requests whose URL matches any of `patterns`, replacing the
+    /// previously blocked patterns. `*` matches any sequence of characters,
+    /// e.g. `*://*.doubleclick.net/*`. An empty slice unblocks everything.
+    ///
+    /// Blocked requests fail with `net::ERR_BLOCKED_BY_CLIENT` without being
+    /// intercepted, see [`BrowserConfigBuilder::block_domains`](crate::browser::BrowserConfigBuilder::block_domains)
+    /// to block domains in every page.
+    pub async fn set_blocked_urls(&self, patterns: &[String]) -> Result<&Self> {
+        self.execute(SetBlockedUrls {
+            urls: patterns.to_vec(),
+        })
+        .await?;
+        Ok(self)
+    }
+
     /// Returns the current url of the page
     pub async fn url(&self) -> Result<Option<String>> {
         let (tx, rx) = oneshot_channel();
-        self.inner
-            .sender()
-            .clone()
-            .send(TargetMessage::Url(GetUrl::new(tx)))
-            .await?;
+        self.inner.send(TargetMessage::Url(GetUrl::new(tx))).await?;
         Ok(rx.await?)
     }
 
@@ -518,8 +909,6 @@ impl Page {
     pub async fn frame_url(&self, frame_id: FrameId) -> Result<Option<String>> {
         let (tx, rx) = oneshot_channel();
         self.inner
-            .sender()
-            .clone()
             .send(TargetMessage::Url(GetUrl {
                 frame_id: Some(frame_id),
                 tx,
@@ -532,8 +921,6 @@ impl Page {
     pub async fn frame_parent(&self, frame_id: FrameId) -> Result<Option<FrameId>> {
         let (tx, rx) = oneshot_channel();
         self.inner
-            .sender()
-            .clone()
             .send(TargetMessage::Parent(GetParent { frame_id, tx }))
             .await?;
         Ok(rx.await?)
@@ -542,22 +929,14 @@ impl Page {
     /// Return the main frame of the page
     pub async fn mainframe(&self) -> Result<Option<FrameId>> {
         let (tx, rx) = oneshot_channel();
-        self.inner
-            .sender()
-            .clone()
-            .send(TargetMessage::MainFrame(tx))
-            .await?;
+        self.inner.send(TargetMessage::MainFrame(tx)).await?;
         Ok(rx.await?)
     }
 
     /// Return the frames of the page
     pub async fn frames(&self) -> Result<Vec<FrameId>> {
         let (tx, rx) = oneshot_channel();
-        self.inner
-            .sender()
-            .clone()
-            .send(TargetMessage::AllFrames(tx))
-            .await?;
+        self.inner.send(TargetMessage::AllFrames(tx)).await?;
         Ok(rx.await?)
     }
 
@@ -570,6 +949,94 @@ impl Page {
         Ok(self)
     }
 
+    /// Enable or disable bypassing the page's Content-Security-Policy, via
+    /// `Page.setBypassCSP`.
+    ///
+    /// With a strict CSP, e.g. one that disallows `blob:` workers, injected
+    /// scripts can fail. Note that a page can notice the bypass, as content
+    /// its CSP should have blocked still loads.
+    pub async fn bypass_csp(&self, enabled: bool) -> Result<&Self> {
+        self.execute(SetBypassCspParams::new(enabled)).await?;
+        Ok(self)
+    }
+
+    /// Allow downloads started by this page and save them to `dir` under
+    /// their suggested file name.
+    ///
+    /// This applies to the browser context of the page. Use
+    /// [`Page::wait_for_download`] to wait for a file to land.
+    pub async fn set_download_behavior(&self, dir: impl AsRef<Path>) -> Result<&Self> {
+        let dir = utils::absolute(dir.as_ref().to_path_buf())?;
+        let info = self.execute(GetTargetInfoParams::default()).await?;
+        let mut params = browser_protocol::browser::SetDownloadBehaviorParams::builder()
+            .behavior(browser_protocol::browser::SetDownloadBehaviorBehavior::Allow)
+            .download_path(dir.to_string_lossy())
+            .events_enabled(true);
+        if let Some(context_id) = info.result.target_info.browser_context_id.clone() {
+            params = params.browser_context_id(context_id);
+        }
+        self.execute(params.build().map_err(CdpError::msg)?).await?;
+        self.inner.set_download_dir(dir);
+        Ok(self)
+    }
+
+    /// Wait for the next download of this page to complete and return the
+    /// path of the file.
+    ///
+    /// Requires [`Page::set_download_behavior`]. Start waiting before
+    /// triggering the download, so its start isn't missed. Fails with
+    /// [`CdpError::DownloadCanceled`] if the download is canceled and with
+    /// [`CdpError::Timeout`] if it didn't complete within `timeout`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// page.set_download_behavior("downloads").await?;
+    /// let (path, _) = futures::join!(
+    ///     page.wait_for_download(Duration::from_secs(30)),
+    ///     link.click(),
+    /// );
+    /// let report = std::fs::read(path?)?;
+    /// ```
+    pub async fn wait_for_download(&self, timeout: Duration) -> Result<PathBuf> {
+        use browser_protocol::browser::{
+            DownloadProgressState, EventDownloadProgress, EventDownloadWillBegin,
+        };
+
+        let mut will_begin = self.event_listener::<EventDownloadWillBegin>().await?;
+        let mut progress = self.event_listener::<EventDownloadProgress>().await?;
+
+        let download = async {
+            let begin = will_begin.next().await.ok_or(CdpError::NoResponse)?;
+            while let Some(event) = progress.next().await {
+                if event.guid != begin.guid {
+                    continue;
+                }
+                match event.state {
+                    DownloadProgressState::InProgress => {}
+                    DownloadProgressState::Completed => {
+                        // older versions don't report the path
+                        return match (&event.file_path, self.inner.download_dir()) {
+                            (Some(path), _) => Ok(PathBuf::from(path)),
+                            (None, Some(dir)) => Ok(dir.join(&begin.suggested_filename)),
+                            (None, None) => Err(CdpError::msg(
+                                "No download directory, call `Page::set_download_behavior` first",
+                            )),
+                        };
+                    }
+                    DownloadProgressState::Canceled => {
+                        return Err(CdpError::DownloadCanceled(begin.url.clone()))
+                    }
+                }
+            }
+            Err(CdpError::NoResponse)
+        };
+
+        utils::timeout(timeout, download)
+            .await
+            .ok_or(CdpError::Timeout)?
+    }
+
     /// Returns the user agent of the browser
     pub async fn user_agent(&self) -> Result<String> {
         Ok(self.inner.version().await?.user_agent)
@@ -631,10 +1098,29 @@ impl Page {
         Ok(resp.result.node)
     }
 
-    /// Tries to close page, running its beforeunload hooks, if any.
-    /// Calls Page.close with [`CloseParams`]
-    pub async fn close(self) -> Result<()> {
-        self.execute(CloseParams::default()).await?;
+    /// Close the page and its target with `Target.closeTarget`, without
+    /// running its beforeunload hooks.
+    ///
+    /// Like [`Browser::close_page`](crate::Browser::close_page), the target
+    /// is no longer tracked once this returns: the page and its clones fail
+    /// with [`CdpError::PageClosed`] afterwards, closing it again does
+    /// nothing.
+    pub async fn close(&self) -> Result<()> {
+        // the session may go away before the browser answers
+        match self
+            .execute(CloseTargetParams::new(self.target_id().clone()))
+            .await
+        {
+            Ok(_) | Err(CdpError::PageClosed) => {}
+            Err(err) => return Err(err),
+        }
+        let (tx, rx) = oneshot_channel();
+        match self.inner.send(TargetMessage::Dispose(tx)).await {
+            // already disposed after `Target.targetDestroyed`
+            Ok(()) | Err(CdpError::PageClosed) => {}
+            Err(err) => return Err(err),
+        }
+        let _ = rx.await;
         Ok(())
     }
 
@@ -944,7 +1430,9 @@ impl Page {
     /// Set a single cookie
     ///
     /// This fails if the cookie's url or if not provided, the page's url is
-    /// `about:blank` or a `data:` url.
+    /// `about:blank` or a `data:` url, or if its attributes conflict, see
+    /// [`validate_cookie`]. [`CookieBuilder`](crate::cookie::CookieBuilder)
+    /// helps to set them.
     ///
     /// # Example
     /// ```no_run
@@ -958,9 +1446,8 @@ impl Page {
     /// ```
     pub async fn set_cookie(&self, cookie: impl Into<CookieParam>) -> Result<&Self> {
         let mut cookie = cookie.into();
-        if let Some(url) = cookie.url.as_ref() {
-            validate_cookie_url(url)?;
-        } else {
+        validate_cookie(&cookie)?;
+        if cookie.url.is_none() {
             let url = self
                 .url()
                 .await?
@@ -977,6 +1464,9 @@ impl Page {
     }
 
     /// Set all the cookies
+    ///
+    /// All fields of the cookies are passed on, like `set_cookie` this fails
+    /// for a cookie with conflicting attributes.
     pub async fn set_cookies(&self, mut cookies: Vec<CookieParam>) -> Result<&Self> {
         let url = self
             .url()
@@ -988,9 +1478,8 @@ impl Page {
         }
 
         for cookie in &mut cookies {
-            if let Some(url) = cookie.url.as_ref() {
-                validate_cookie_url(url)?;
-            } else if is_http {
+            validate_cookie(cookie)?;
+            if cookie.url.is_none() && is_http {
                 cookie.url = Some(url.clone());
             }
         }
@@ -1297,6 +1786,10 @@ impl Page {
     /// - Injecting polyfills
     /// - Setting up global variables
     ///
+    /// Returns the identifier of the script, which can be passed to
+    /// [`Page::remove_script_on_new_document`]. Callers that never remove the
+    /// script can simply drop it.
+    ///
     /// # Example
     /// ```
     /// # use chromiumoxide::page::Page;
@@ -1326,6 +1819,49 @@ impl Page {
         self.evaluate_on_new_document(script).await
     }
 
+    /// Removes a script registered with [`Page::evaluate_on_new_document`]
+    /// using the identifier it returned.
+    ///
+    /// Documents created afterwards no longer run the script, already loaded
+    /// documents are unaffected.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use chromiumoxide::page::Page;
+    /// # use chromiumoxide::error::Result;
+    /// # async fn demo(page: Page) -> Result<()> {
+    /// let id = page.evaluate_on_new_document("window.injected = true;").await?;
+    /// page.remove_script_on_new_document(id).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn remove_script_on_new_document(&self, id: ScriptIdentifier) -> Result<&Self> {
+        self.execute(RemoveScriptToEvaluateOnNewDocumentParams::new(id))
+            .await?;
+        Ok(self)
+    }
+
+    /// Restore the web storage of a [`SessionSnapshot`] into this page.
+    ///
+    /// Storage can only be written on the matching origin, so an init script
+    /// fills in the missing keys of an origin once this page navigates to it.
+    /// The script is removed after the first visit of each origin, keys the
+    /// site removes later stay removed.
+    pub async fn restore_storage(&self, snapshot: &SessionSnapshot) -> Result<()> {
+        for storage in &snapshot.origins {
+            let identifier = self
+                .evaluate_on_new_document(storage.restore_script())
+                .await?;
+            self.inner
+                .send(TargetMessage::RestoreStorage(
+                    storage.origin.clone(),
+                    identifier,
+                ))
+                .await?;
+        }
+        Ok(())
+    }
+
     /// Set the content of the frame.
     ///
     /// # Example
@@ -1436,6 +1972,45 @@ pub(crate) fn validate_cookie_url(url: &str) -> Result<()> {
     }
 }
 
+/// The target info of the page a page websocket is attached to
+async fn page_target_info(
+    conn: &mut Connection<CdpEventMessage>,
+    ws_url: &str,
+) -> Result<TargetInfo> {
+    let params = GetTargetInfoParams::default();
+    let method = params.identifier();
+    let call_id = conn.submit_command(method.clone(), None, serde_json::to_value(params)?)?;
+    loop {
+        match conn.next().await {
+            Some(Ok(Message::Response(resp))) if resp.id == call_id => {
+                if let Ok(resp) = to_command_response::<GetTargetInfoParams>(resp, method) {
+                    return Ok(resp.result.target_info);
+                }
+                break;
+            }
+            Some(Ok(_)) => {}
+            Some(Err(err)) => return Err(err),
+            None => return Err(CdpError::ConnectionClosed),
+        }
+    }
+    // the endpoint doesn't expose `Target.getTargetInfo`, the target id is
+    // the last segment of `ws://host/devtools/page/<id>`
+    let target_id = ws_url
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default();
+    TargetInfo::builder()
+        .target_id(target_id.to_string())
+        .r#type("page")
+        .title("")
+        .url("")
+        .attached(true)
+        .can_access_opener(false)
+        .build()
+        .map_err(CdpError::msg)
+}
+
 /// Page screenshot parameters with extra options.
 #[derive(Debug, Default)]
 pub struct ScreenshotParams {
@@ -1554,3 +2129,20 @@ impl From<MediaTypeParams> for String {
         }
     }
 }
+
+/// `Network.setBlockedURLs`, unlike [`SetBlockedUrLsParams`] this keeps an
+/// empty list of urls, which Chrome requires to unblock all urls
+#[derive(Debug, Clone, serde::Serialize)]
+struct SetBlockedUrls {
+    urls: Vec<String>,
+}
+
+impl Method for SetBlockedUrls {
+    fn identifier(&self) -> MethodId {
+        SetBlockedUrLsParams::IDENTIFIER.into()
+    }
+}
+
+impl Command for SetBlockedUrls {
+    type Response = SetBlockedUrLsReturns;
+}
@@ -0,0 +1,265 @@
+//! A pool of reusable pages for high-volume scraping.
+//!
+//! Creating a page per job is expensive, [`PagePool`] creates its pages up
+//! front and hands them out with [`PagePool::acquire`]. Every page lives in a
+//! browser context of its own, which is replaced by a fresh one between jobs.
+//!
+//! ```ignore
+//! let pool = PagePool::with_profile(browser, 4, ChaserProfile::windows().build()).await?;
+//! let page = pool.acquire().await?;
+//! page.goto("https://example.com").await?;
+//! let html = page.content().await?;
+//! page.release().await?;
+//! ```
+
+use std::ops::Deref;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use chromiumoxide_cdp::cdp::browser_protocol::browser::BrowserContextId;
+use chromiumoxide_cdp::cdp::browser_protocol::target::CreateBrowserContextParams;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::future::join_all;
+use futures::lock::Mutex;
+use futures::StreamExt;
+
+use crate::browser::Browser;
+use crate::chaser::ChaserPage;
+use crate::profiles::ChaserProfile;
+
+/// A fixed number of pages that are reused across jobs.
+///
+/// [`PagePool::acquire`] waits while all pages are in use, waiters are served
+/// in order.
+#[derive(Debug)]
+pub struct PagePool {
+    inner: Arc<PoolInner>,
+}
+
+#[derive(Debug)]
+struct PoolInner {
+    browser: Browser,
+    profile: Option<ChaserProfile>,
+    size: usize,
+    idle: Mutex<UnboundedReceiver<Slot>>,
+    returned: UnboundedSender<Slot>,
+}
+
+/// A pooled page and the browser context it lives in
+#[derive(Debug)]
+struct Slot {
+    page: ChaserPage,
+    context: BrowserContextId,
+    /// Whether the page was returned without a successful
+    /// [`PooledPage::release`] and must be reset before its next use
+    dirty: bool,
+}
+
+impl PagePool {
+    /// Create a pool of `size` pages
+    pub async fn new(browser: Browser, size: usize) -> Result<Self> {
+        Self::create(browser, size, None).await
+    }
+
+    /// Create a pool of `size` pages that have `profile` applied whenever
+    /// they are acquired
+    pub async fn with_profile(
+        browser: Browser,
+        size: usize,
+        profile: ChaserProfile,
+    ) -> Result<Self> {
+        Self::create(browser, size, Some(profile)).await
+    }
+
+    async fn create(browser: Browser, size: usize, profile: Option<ChaserProfile>) -> Result<Self> {
+        if size == 0 {
+            return Err(anyhow!("page pool needs at least one page"));
+        }
+        let results = join_all((0..size).map(|_| async {
+            let context = browser
+                .create_browser_context(CreateBrowserContextParams::default())
+                .await
+                .map_err(|e| anyhow!("{}", e))?;
+            match browser
+                .new_page_in_context(context.clone(), "about:blank")
+                .await
+            {
+                Ok(page) => Ok(Slot {
+                    page: ChaserPage::new(page),
+                    context,
+                    dirty: false,
+                }),
+                Err(err) => {
+                    let _ = browser.dispose_browser_context(context).await;
+                    Err(anyhow!("{}", err))
+                }
+            }
+        }))
+        .await;
+
+        let mut slots = Vec::with_capacity(size);
+        let mut error = None;
+        for result in results {
+            match result {
+                Ok(slot) => slots.push(slot),
+                Err(err) => error = error.or(Some(err)),
+            }
+        }
+        if let Some(err) = error {
+            // don't leak the contexts of the pages that were created
+            for slot in slots {
+                let _ = browser.dispose_browser_context(slot.context).await;
+            }
+            return Err(err);
+        }
+
+        let (returned, idle) = unbounded();
+        for slot in slots {
+            returned.unbounded_send(slot).expect("receiver is alive");
+        }
+        Ok(Self {
+            inner: Arc::new(PoolInner {
+                browser,
+                profile,
+                size,
+                idle: Mutex::new(idle),
+                returned,
+            }),
+        })
+    }
+
+    /// The browser the pages belong to
+    pub fn browser(&self) -> &Browser {
+        &self.inner.browser
+    }
+
+    /// The number of pages in the pool
+    pub fn size(&self) -> usize {
+        self.inner.size
+    }
+
+    /// Close the pages of the pool and return its browser, e.g. to
+    /// [close](Browser::close) it.
+    ///
+    /// Fails if pages are still acquired.
+    pub async fn into_browser(self) -> Result<Browser> {
+        let inner =
+            Arc::try_unwrap(self.inner).map_err(|_| anyhow!("pooled pages are still in use"))?;
+        let PoolInner {
+            browser,
+            idle,
+            returned,
+            ..
+        } = inner;
+        drop(returned);
+        let slots: Vec<Slot> = idle.into_inner().collect().await;
+        for slot in slots {
+            browser
+                .dispose_browser_context(slot.context)
+                .await
+                .map_err(|e| anyhow!("{}", e))?;
+        }
+        Ok(browser)
+    }
+
+    /// Take a page out of the pool, waiting until one is released if all
+    /// are in use.
+    ///
+    /// The page is on `about:blank` without cookies or storage of previous
+    /// jobs, with the profile of the pool applied.
+    pub async fn acquire(&self) -> Result<PooledPage> {
+        let slot = {
+            let mut idle = self.inner.idle.lock().await;
+            idle.next().await.expect("pool holds a sender")
+        };
+        let dirty = slot.dirty;
+        // returns the slot to the pool on errors
+        let mut page = PooledPage {
+            slot: Some(slot),
+            pool: Arc::clone(&self.inner),
+            released: false,
+        };
+        if dirty {
+            page.reset().await?;
+        }
+        if let Some(profile) = &self.inner.profile {
+            page.apply_profile(profile).await?;
+        }
+        Ok(page)
+    }
+}
+
+/// A page taken out of a [`PagePool`].
+///
+/// Dereferences to [`ChaserPage`]. Return it with [`PooledPage::release`],
+/// which resets the page right away. A page that is dropped instead is
+/// returned too, but only reset when it is acquired again.
+#[derive(Debug)]
+pub struct PooledPage {
+    slot: Option<Slot>,
+    pool: Arc<PoolInner>,
+    /// Whether the page was reset by [`PooledPage::release`]
+    released: bool,
+}
+
+impl PooledPage {
+    /// Reset the page and return it to the pool
+    pub async fn release(mut self) -> Result<()> {
+        self.reset().await?;
+        self.released = true;
+        Ok(())
+    }
+
+    /// Forget the page and its state: replaces the page and its browser
+    /// context with fresh ones, which drops the cookies, storage and caches
+    /// of every origin along with the scripts installed on the page
+    async fn reset(&mut self) -> Result<()> {
+        let browser = &self.pool.browser;
+        let context = browser
+            .create_browser_context(CreateBrowserContextParams::default())
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        let page = match browser
+            .new_page_in_context(context.clone(), "about:blank")
+            .await
+        {
+            Ok(page) => page,
+            Err(err) => {
+                let _ = browser.dispose_browser_context(context).await;
+                return Err(anyhow!("{}", err));
+            }
+        };
+        let slot = self.slot.as_mut().expect("slot is set");
+        let old = std::mem::replace(
+            slot,
+            Slot {
+                page: ChaserPage::new(page),
+                context,
+                dirty: false,
+            },
+        );
+        // closes the old page too
+        browser
+            .dispose_browser_context(old.context)
+            .await
+            .map_err(|e| anyhow!("{}", e))
+    }
+}
+
+impl Deref for PooledPage {
+    type Target = ChaserPage;
+
+    fn deref(&self) -> &Self::Target {
+        &self.slot.as_ref().expect("slot is set").page
+    }
+}
+
+impl Drop for PooledPage {
+    fn drop(&mut self) {
+        if let Some(mut slot) = self.slot.take() {
+            slot.dirty = !self.released;
+            // the pool may be gone already
+            let _ = self.pool.returned.unbounded_send(slot);
+        }
+    }
+}
@@ -18,8 +18,17 @@
 
 use std::fmt;
 
+use chromiumoxide_cdp::cdp::browser_protocol::emulation::{
+    MediaFeature, UserAgentBrandVersion, UserAgentMetadata,
+};
+use chromiumoxide_cdp::cdp::browser_protocol::network::SetUserAgentOverrideParams;
+use thiserror::Error;
+
+use crate::utils;
+
 /// GPU presets for WebGL spoofing
 #[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
 pub enum Gpu {
     /// NVIDIA GeForce RTX 3080 (high-trust gaming GPU)
     NvidiaRTX3080,
@@ -39,20 +48,43 @@ pub enum Gpu {
     AppleM4Max,
     /// AMD Radeon RX 6800
     AmdRadeonRX6800,
+    /// Qualcomm Adreno 740 (Snapdragon 8 Gen 2 phones)
+    Adreno740,
+    /// ARM Mali-G710 (Google Tensor G2 phones)
+    MaliG710,
+    /// The GPU of iPhones, which WebKit reports without a model
+    AppleGpu,
 }
 
 impl Gpu {
-    /// Returns the WebGL vendor string
+    /// Returns the WebGL vendor string, the one of Windows and macOS. See
+    /// [`Gpu::vendor_for`] for the one of a specific OS.
     pub fn vendor(&self) -> &'static str {
         match self {
             Gpu::NvidiaRTX3080 | Gpu::NvidiaRTX4080 | Gpu::NvidiaGTX1660 => "Google Inc. (NVIDIA)",
             Gpu::IntelUHD630 | Gpu::IntelIrisXe => "Google Inc. (Intel)",
             Gpu::AppleM1Pro | Gpu::AppleM2Max | Gpu::AppleM4Max => "Google Inc. (Apple)",
             Gpu::AmdRadeonRX6800 => "Google Inc. (AMD)",
+            Gpu::Adreno740 => "Google Inc. (Qualcomm)",
+            Gpu::MaliG710 => "Google Inc. (ARM)",
+            Gpu::AppleGpu => "Apple Inc.",
+        }
+    }
+
+    /// Returns the WebGL vendor string Chrome reports for this GPU on `os`,
+    /// the NVIDIA driver on Linux names the full company
+    pub fn vendor_for(&self, os: Os) -> &'static str {
+        match (os, self) {
+            (Os::Linux, Gpu::NvidiaRTX3080 | Gpu::NvidiaRTX4080 | Gpu::NvidiaGTX1660) => {
+                "Google Inc. (NVIDIA Corporation)"
+            }
+            _ => self.vendor(),
         }
     }
 
-    /// Returns the WebGL renderer string
+    /// Returns the WebGL renderer string regardless of the OS: the Direct3D
+    /// 11 one of Windows for desktop GPUs. Profiles use
+    /// [`Gpu::renderer_for`], which matches the backend to the OS.
     pub fn renderer(&self) -> &'static str {
         match self {
             Gpu::NvidiaRTX3080 => {
@@ -74,12 +106,383 @@ impl Gpu {
                 "ANGLE (Apple, ANGLE Metal Renderer: Apple M4 Max, Unspecified Version)"
             }
             Gpu::AmdRadeonRX6800 => "ANGLE (AMD, AMD Radeon RX 6800 XT Direct3D11 vs_5_0 ps_5_0)",
+            Gpu::Adreno740 => "ANGLE (Qualcomm, Adreno (TM) 740, OpenGL ES 3.2)",
+            Gpu::MaliG710 => "ANGLE (ARM, Mali-G710, OpenGL ES 3.2)",
+            Gpu::AppleGpu => "Apple GPU",
         }
     }
+
+    /// Returns the WebGL renderer string Chrome reports for this GPU on `os`.
+    ///
+    /// ANGLE renders through Direct3D 11 on Windows, OpenGL on Linux and
+    /// Metal on macOS, and the renderer string names the backend. Phone GPUs
+    /// and GPUs that never shipped with `os` report their [`Gpu::renderer`].
+    pub fn renderer_for(&self, os: Os) -> &'static str {
+        match os {
+            Os::Linux => match self {
+                Gpu::NvidiaRTX3080 => {
+                    "ANGLE (NVIDIA Corporation, NVIDIA GeForce RTX 3080/PCIe/SSE2, OpenGL 4.5.0)"
+                }
+                Gpu::NvidiaRTX4080 => {
+                    "ANGLE (NVIDIA Corporation, NVIDIA GeForce RTX 4080/PCIe/SSE2, OpenGL 4.5.0)"
+                }
+                Gpu::NvidiaGTX1660 => {
+                    "ANGLE (NVIDIA Corporation, NVIDIA GeForce GTX 1660 SUPER/PCIe/SSE2, OpenGL 4.5.0)"
+                }
+                Gpu::IntelUHD630 => {
+                    "ANGLE (Intel, Mesa Intel(R) UHD Graphics 630 (CFL GT2), OpenGL 4.6)"
+                }
+                Gpu::IntelIrisXe => "ANGLE (Intel, Mesa Intel(R) Xe Graphics (TGL GT2), OpenGL 4.6)",
+                Gpu::AmdRadeonRX6800 => {
+                    "ANGLE (AMD, AMD Radeon RX 6800 XT (radeonsi, navi21, LLVM 15.0.7, DRM 3.54, 6.5.0-14-generic), OpenGL 4.6)"
+                }
+                _ => self.renderer(),
+            },
+            Os::MacOSIntel | Os::MacOSArm => match self {
+                Gpu::IntelUHD630 => {
+                    "ANGLE (Intel, ANGLE Metal Renderer: Intel(R) UHD Graphics 630, Unspecified Version)"
+                }
+                Gpu::IntelIrisXe => {
+                    "ANGLE (Intel, ANGLE Metal Renderer: Intel(R) Iris(R) Xe Graphics, Unspecified Version)"
+                }
+                Gpu::AmdRadeonRX6800 => {
+                    "ANGLE (AMD, ANGLE Metal Renderer: AMD Radeon RX 6800 XT, Unspecified Version)"
+                }
+                Gpu::AppleM1Pro => {
+                    "ANGLE (Apple, ANGLE Metal Renderer: Apple M1 Pro, Unspecified Version)"
+                }
+                Gpu::AppleM2Max => {
+                    "ANGLE (Apple, ANGLE Metal Renderer: Apple M2 Max, Unspecified Version)"
+                }
+                _ => self.renderer(),
+            },
+            Os::Windows | Os::Android | Os::IOS => self.renderer(),
+        }
+    }
+
+    /// Returns the WebGL limits and extensions of this GPU
+    pub fn webgl_params(&self) -> WebGlParams {
+        match self {
+            Gpu::NvidiaRTX3080
+            | Gpu::NvidiaRTX4080
+            | Gpu::NvidiaGTX1660
+            | Gpu::IntelUHD630
+            | Gpu::IntelIrisXe
+            | Gpu::AmdRadeonRX6800 => WebGlParams {
+                max_texture_size: 16384,
+                max_cube_map_texture_size: 16384,
+                max_renderbuffer_size: 16384,
+                max_viewport_dims: [32767, 32767],
+                max_vertex_attribs: 16,
+                max_vertex_uniform_vectors: 4096,
+                max_fragment_uniform_vectors: 1024,
+                max_varying_vectors: 30,
+                max_texture_image_units: 16,
+                max_vertex_texture_image_units: 16,
+                max_combined_texture_image_units: 32,
+                max_anisotropy: 16,
+                aliased_line_width_range: [1.0, 1.0],
+                aliased_point_size_range: [1.0, 1024.0],
+                float_precision: [127, 127, 23],
+                int_precision: [31, 30, 0],
+                extensions: D3D11_EXTENSIONS,
+                webgl2_extensions: D3D11_WEBGL2_EXTENSIONS,
+            },
+            Gpu::AppleM1Pro | Gpu::AppleM2Max | Gpu::AppleM4Max | Gpu::AppleGpu => WebGlParams {
+                max_texture_size: 16384,
+                max_cube_map_texture_size: 16384,
+                max_renderbuffer_size: 16384,
+                max_viewport_dims: [16384, 16384],
+                max_vertex_attribs: 16,
+                max_vertex_uniform_vectors: 1024,
+                max_fragment_uniform_vectors: 1024,
+                max_varying_vectors: 31,
+                max_texture_image_units: 16,
+                max_vertex_texture_image_units: 16,
+                max_combined_texture_image_units: 32,
+                max_anisotropy: 16,
+                aliased_line_width_range: [1.0, 1.0],
+                aliased_point_size_range: [1.0, 511.0],
+                float_precision: [127, 127, 23],
+                int_precision: [31, 30, 0],
+                extensions: METAL_EXTENSIONS,
+                webgl2_extensions: METAL_WEBGL2_EXTENSIONS,
+            },
+            Gpu::Adreno740 | Gpu::MaliG710 => WebGlParams {
+                max_texture_size: 16384,
+                max_cube_map_texture_size: 16384,
+                max_renderbuffer_size: 16384,
+                max_viewport_dims: [16384, 16384],
+                max_vertex_attribs: 16,
+                max_vertex_uniform_vectors: 256,
+                max_fragment_uniform_vectors: 256,
+                max_varying_vectors: 31,
+                max_texture_image_units: 16,
+                max_vertex_texture_image_units: 16,
+                max_combined_texture_image_units: 32,
+                max_anisotropy: 16,
+                aliased_line_width_range: [1.0, 8.0],
+                aliased_point_size_range: [1.0, 1023.0],
+                float_precision: [127, 127, 23],
+                int_precision: [31, 30, 0],
+                extensions: GLES_EXTENSIONS,
+                webgl2_extensions: GLES_WEBGL2_EXTENSIONS,
+            },
+        }
+    }
+}
+
+/// WebGL limits and extensions reported for a [`Gpu`].
+///
+/// Software GL (SwiftShader) reports distinctively low limits and a short
+/// extension list, so these are spoofed alongside the vendor and renderer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebGlParams {
+    pub max_texture_size: u32,
+    pub max_cube_map_texture_size: u32,
+    pub max_renderbuffer_size: u32,
+    pub max_viewport_dims: [u32; 2],
+    pub max_vertex_attribs: u32,
+    pub max_vertex_uniform_vectors: u32,
+    pub max_fragment_uniform_vectors: u32,
+    pub max_varying_vectors: u32,
+    pub max_texture_image_units: u32,
+    pub max_vertex_texture_image_units: u32,
+    pub max_combined_texture_image_units: u32,
+    pub max_anisotropy: u32,
+    pub aliased_line_width_range: [f32; 2],
+    pub aliased_point_size_range: [f32; 2],
+    /// `[rangeMin, rangeMax, precision]` of `getShaderPrecisionFormat` for
+    /// float precisions
+    pub float_precision: [i32; 3],
+    /// `[rangeMin, rangeMax, precision]` of `getShaderPrecisionFormat` for
+    /// int precisions
+    pub int_precision: [i32; 3],
+    /// Extensions reported by a WebGL 1 context
+    pub extensions: &'static [&'static str],
+    /// Extensions reported by a WebGL 2 context
+    pub webgl2_extensions: &'static [&'static str],
+}
+
+impl WebGlParams {
+    /// The scalar parameters as a JS object literal keyed by GLenum
+    fn js_scalars(&self) -> String {
+        format!(
+            "{{ 3379: {}, 34076: {}, 34024: {}, 34921: {}, 36347: {}, 36349: {}, 36348: {}, \
+             34930: {}, 35660: {}, 35661: {}, 34047: {} }}",
+            self.max_texture_size,
+            self.max_cube_map_texture_size,
+            self.max_renderbuffer_size,
+            self.max_vertex_attribs,
+            self.max_vertex_uniform_vectors,
+            self.max_fragment_uniform_vectors,
+            self.max_varying_vectors,
+            self.max_texture_image_units,
+            self.max_vertex_texture_image_units,
+            self.max_combined_texture_image_units,
+            self.max_anisotropy,
+        )
+    }
+
+    /// The typed array parameters as a JS object literal keyed by GLenum
+    fn js_arrays(&self) -> String {
+        format!(
+            "{{ 3386: [Int32Array, [{}, {}]], 33902: [Float32Array, [{:?}, {:?}]], \
+             33901: [Float32Array, [{:?}, {:?}]] }}",
+            self.max_viewport_dims[0],
+            self.max_viewport_dims[1],
+            self.aliased_line_width_range[0],
+            self.aliased_line_width_range[1],
+            self.aliased_point_size_range[0],
+            self.aliased_point_size_range[1],
+        )
+    }
 }
 
+/// Extensions of WebGL 1 on ANGLE's Direct3D 11 backend
+const D3D11_EXTENSIONS: &[&str] = &[
+    "ANGLE_instanced_arrays",
+    "EXT_blend_minmax",
+    "EXT_clip_control",
+    "EXT_color_buffer_half_float",
+    "EXT_depth_clamp",
+    "EXT_disjoint_timer_query",
+    "EXT_float_blend",
+    "EXT_frag_depth",
+    "EXT_polygon_offset_clamp",
+    "EXT_shader_texture_lod",
+    "EXT_texture_compression_bptc",
+    "EXT_texture_compression_rgtc",
+    "EXT_texture_filter_anisotropic",
+    "EXT_texture_mirror_clamp_to_edge",
+    "EXT_sRGB",
+    "KHR_parallel_shader_compile",
+    "OES_element_index_uint",
+    "OES_fbo_render_mipmap",
+    "OES_standard_derivatives",
+    "OES_texture_float",
+    "OES_texture_float_linear",
+    "OES_texture_half_float",
+    "OES_texture_half_float_linear",
+    "OES_vertex_array_object",
+    "WEBGL_blend_func_extended",
+    "WEBGL_color_buffer_float",
+    "WEBGL_compressed_texture_s3tc",
+    "WEBGL_compressed_texture_s3tc_srgb",
+    "WEBGL_debug_renderer_info",
+    "WEBGL_debug_shaders",
+    "WEBGL_depth_texture",
+    "WEBGL_draw_buffers",
+    "WEBGL_lose_context",
+    "WEBGL_multi_draw",
+    "WEBGL_polygon_mode",
+];
+
+/// Extensions of WebGL 2 on ANGLE's Direct3D 11 backend
+const D3D11_WEBGL2_EXTENSIONS: &[&str] = &[
+    "EXT_clip_control",
+    "EXT_color_buffer_float",
+    "EXT_color_buffer_half_float",
+    "EXT_conservative_depth",
+    "EXT_depth_clamp",
+    "EXT_disjoint_timer_query_webgl2",
+    "EXT_float_blend",
+    "EXT_polygon_offset_clamp",
+    "EXT_render_snorm",
+    "EXT_texture_compression_bptc",
+    "EXT_texture_compression_rgtc",
+    "EXT_texture_filter_anisotropic",
+    "EXT_texture_mirror_clamp_to_edge",
+    "EXT_texture_norm16",
+    "KHR_parallel_shader_compile",
+    "NV_shader_noperspective_interpolation",
+    "OES_draw_buffers_indexed",
+    "OES_sample_variables",
+    "OES_shader_multisample_interpolation",
+    "OES_texture_float_linear",
+    "OVR_multiview2",
+    "WEBGL_blend_func_extended",
+    "WEBGL_clip_cull_distance",
+    "WEBGL_compressed_texture_s3tc",
+    "WEBGL_compressed_texture_s3tc_srgb",
+    "WEBGL_debug_renderer_info",
+    "WEBGL_debug_shaders",
+    "WEBGL_lose_context",
+    "WEBGL_multi_draw",
+    "WEBGL_polygon_mode",
+    "WEBGL_provoking_vertex",
+    "WEBGL_stencil_texturing",
+];
+
+/// Extensions of WebGL 1 on ANGLE's Metal backend (Apple Silicon)
+const METAL_EXTENSIONS: &[&str] = &[
+    "ANGLE_instanced_arrays",
+    "EXT_blend_minmax",
+    "EXT_color_buffer_half_float",
+    "EXT_float_blend",
+    "EXT_frag_depth",
+    "EXT_shader_texture_lod",
+    "EXT_texture_compression_bptc",
+    "EXT_texture_compression_rgtc",
+    "EXT_texture_filter_anisotropic",
+    "EXT_sRGB",
+    "KHR_parallel_shader_compile",
+    "OES_element_index_uint",
+    "OES_fbo_render_mipmap",
+    "OES_standard_derivatives",
+    "OES_texture_float",
+    "OES_texture_float_linear",
+    "OES_texture_half_float",
+    "OES_texture_half_float_linear",
+    "OES_vertex_array_object",
+    "WEBGL_color_buffer_float",
+    "WEBGL_compressed_texture_astc",
+    "WEBGL_compressed_texture_etc",
+    "WEBGL_compressed_texture_etc1",
+    "WEBGL_compressed_texture_pvrtc",
+    "WEBGL_compressed_texture_s3tc",
+    "WEBGL_compressed_texture_s3tc_srgb",
+    "WEBGL_debug_renderer_info",
+    "WEBGL_debug_shaders",
+    "WEBGL_depth_texture",
+    "WEBGL_draw_buffers",
+    "WEBGL_lose_context",
+    "WEBGL_multi_draw",
+];
+
+/// Extensions of WebGL 2 on ANGLE's Metal backend (Apple Silicon)
+const METAL_WEBGL2_EXTENSIONS: &[&str] = &[
+    "EXT_color_buffer_float",
+    "EXT_color_buffer_half_float",
+    "EXT_float_blend",
+    "EXT_texture_compression_bptc",
+    "EXT_texture_compression_rgtc",
+    "EXT_texture_filter_anisotropic",
+    "EXT_texture_norm16",
+    "KHR_parallel_shader_compile",
+    "OES_draw_buffers_indexed",
+    "OES_texture_float_linear",
+    "WEBGL_compressed_texture_astc",
+    "WEBGL_compressed_texture_etc",
+    "WEBGL_compressed_texture_etc1",
+    "WEBGL_compressed_texture_pvrtc",
+    "WEBGL_compressed_texture_s3tc",
+    "WEBGL_compressed_texture_s3tc_srgb",
+    "WEBGL_debug_renderer_info",
+    "WEBGL_debug_shaders",
+    "WEBGL_lose_context",
+    "WEBGL_multi_draw",
+    "WEBGL_provoking_vertex",
+];
+
+/// Extensions of WebGL 1 on ANGLE's OpenGL ES backend of Android phones
+const GLES_EXTENSIONS: &[&str] = &[
+    "ANGLE_instanced_arrays",
+    "EXT_blend_minmax",
+    "EXT_color_buffer_half_float",
+    "EXT_float_blend",
+    "EXT_sRGB",
+    "EXT_texture_filter_anisotropic",
+    "OES_element_index_uint",
+    "OES_fbo_render_mipmap",
+    "OES_standard_derivatives",
+    "OES_texture_float",
+    "OES_texture_float_linear",
+    "OES_texture_half_float",
+    "OES_texture_half_float_linear",
+    "OES_vertex_array_object",
+    "WEBGL_color_buffer_float",
+    "WEBGL_compressed_texture_astc",
+    "WEBGL_compressed_texture_etc",
+    "WEBGL_compressed_texture_etc1",
+    "WEBGL_debug_renderer_info",
+    "WEBGL_debug_shaders",
+    "WEBGL_depth_texture",
+    "WEBGL_draw_buffers",
+    "WEBGL_lose_context",
+    "WEBGL_multi_draw",
+];
+
+/// Extensions of WebGL 2 on ANGLE's OpenGL ES backend of Android phones
+const GLES_WEBGL2_EXTENSIONS: &[&str] = &[
+    "EXT_color_buffer_float",
+    "EXT_color_buffer_half_float",
+    "EXT_float_blend",
+    "EXT_texture_filter_anisotropic",
+    "OES_texture_float_linear",
+    "OVR_multiview2",
+    "WEBGL_compressed_texture_astc",
+    "WEBGL_compressed_texture_etc",
+    "WEBGL_compressed_texture_etc1",
+    "WEBGL_debug_renderer_info",
+    "WEBGL_debug_shaders",
+    "WEBGL_lose_context",
+    "WEBGL_multi_draw",
+];
+
 /// Operating system presets
 #[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
 pub enum Os {
     /// Windows 10/11 64-bit
     Windows,
@@ -89,6 +492,11 @@ pub enum Os {
     MacOSArm,
     /// Linux x86_64
     Linux,
+    /// Android phones and tablets (Chrome for Android)
+    Android,
+    /// iOS (Chrome for iOS). Like every iOS browser it runs on WebKit, so
+    /// there is no `navigator.userAgentData` and no client hints.
+    IOS,
 }
 
 impl Os {
@@ -98,6 +506,8 @@ impl Os {
             Os::Windows => "Win32",
             Os::MacOSIntel | Os::MacOSArm => "MacIntel",
             Os::Linux => "Linux x86_64",
+            Os::Android => "Linux armv81",
+            Os::IOS => "iPhone",
         }
     }
 
@@ -107,7 +517,458 @@ impl Os {
             Os::Windows => "Windows",
             Os::MacOSIntel | Os::MacOSArm => "macOS",
             Os::Linux => "Linux",
+            Os::Android => "Android",
+            Os::IOS => "iOS",
+        }
+    }
+
+    /// Returns the client hints platform version (`Sec-CH-UA-Platform-Version`)
+    pub fn platform_version(&self) -> &'static str {
+        match self {
+            Os::Windows => "19.0.0",
+            Os::MacOSIntel => "14.6.1",
+            Os::MacOSArm => "15.1.0",
+            Os::Linux => "6.8.0",
+            Os::Android => "14.0.0",
+            Os::IOS => "17.6.0",
+        }
+    }
+
+    /// Returns the client hints CPU architecture (`Sec-CH-UA-Arch`)
+    pub fn architecture(&self) -> &'static str {
+        match self {
+            Os::MacOSArm => "arm",
+            Os::Windows | Os::MacOSIntel | Os::Linux => "x86",
+            // Chrome for Android doesn't report one
+            Os::Android | Os::IOS => "",
+        }
+    }
+
+    /// Whether the OS runs on phones and tablets, whose profiles report a
+    /// touch screen by default
+    pub fn is_mobile(&self) -> bool {
+        matches!(self, Os::Android | Os::IOS)
+    }
+
+    /// Returns the default screen resolution in CSS pixels, e.g. the scaled
+    /// Retina resolution of a MacBook
+    pub fn screen(&self) -> (u32, u32) {
+        match self {
+            Os::Windows | Os::Linux => (1920, 1080),
+            // 13" MacBook Pro
+            Os::MacOSIntel => (1440, 900),
+            // 14" MacBook Pro
+            Os::MacOSArm => (1512, 982),
+            // Pixel 7
+            Os::Android => (412, 915),
+            // iPhone 13
+            Os::IOS => (390, 844),
+        }
+    }
+
+    /// Returns the default `window.devicePixelRatio`
+    pub fn device_pixel_ratio(&self) -> f64 {
+        match self {
+            Os::Windows | Os::Linux => 1.0,
+            Os::MacOSIntel | Os::MacOSArm => 2.0,
+            Os::Android => 2.625,
+            Os::IOS => 3.0,
+        }
+    }
+
+    /// Height of the screen area taken by the taskbar or menu bar, which is
+    /// missing from `screen.availHeight`
+    fn reserved_height(&self) -> u32 {
+        match self {
+            Os::Windows => 40,
+            Os::MacOSIntel | Os::MacOSArm => 25,
+            Os::Linux | Os::Android | Os::IOS => 0,
+        }
+    }
+}
+
+/// The `prefers-color-scheme` a profile reports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorScheme {
+    /// Light theme, the browser default
+    #[default]
+    Light,
+    /// Dark theme
+    Dark,
+}
+
+impl ColorScheme {
+    /// Returns the `prefers-color-scheme` media feature value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColorScheme::Light => "light",
+            ColorScheme::Dark => "dark",
+        }
+    }
+}
+
+/// The notification permission a profile reports, through both
+/// `Notification.permission` and `navigator.permissions.query`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NotificationPermission {
+    /// Not asked yet, the default of a fresh browser profile
+    #[default]
+    Default,
+    /// The user allowed notifications
+    Granted,
+    /// The user blocked notifications
+    Denied,
+}
+
+impl NotificationPermission {
+    /// Returns the value of `Notification.permission`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationPermission::Default => "default",
+            NotificationPermission::Granted => "granted",
+            NotificationPermission::Denied => "denied",
+        }
+    }
+
+    /// Returns the `state` `navigator.permissions.query` reports for
+    /// `notifications`, which calls the default `prompt`
+    pub fn permission_state(&self) -> &'static str {
+        match self {
+            NotificationPermission::Default => "prompt",
+            NotificationPermission::Granted => "granted",
+            NotificationPermission::Denied => "denied",
+        }
+    }
+}
+
+/// A shipped Chrome build, recorded exactly as it reports itself
+#[derive(Debug)]
+struct RealBuild {
+    /// The identifier accepted by [`ChaserProfile::from_real_build`]
+    id: &'static str,
+    os: Os,
+    /// The full version, e.g. `131.0.6778.86`
+    full_version: &'static str,
+    /// The (reduced) `User-Agent` header
+    user_agent: &'static str,
+    /// The brands with major versions, including the GREASE brand, in the
+    /// order this build sends them
+    brands: [(&'static str, &'static str); 3],
+    platform_version: &'static str,
+}
+
+/// Curated Chrome builds for [`ChaserProfile::from_real_build`]
+const REAL_BUILDS: &[RealBuild] = &[
+    RealBuild {
+        id: "131.0.6778.86-windows",
+        os: Os::Windows,
+        full_version: "131.0.6778.86",
+        user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36",
+        brands: [
+            ("Google Chrome", "131"),
+            ("Chromium", "131"),
+            ("Not_A Brand", "24"),
+        ],
+        platform_version: "15.0.0",
+    },
+    RealBuild {
+        id: "130.0.6723.117-macos-arm",
+        os: Os::MacOSArm,
+        full_version: "130.0.6723.117",
+        user_agent: "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36",
+        brands: [
+            ("Chromium", "130"),
+            ("Google Chrome", "130"),
+            ("Not?A_Brand", "99"),
+        ],
+        platform_version: "15.1.0",
+    },
+    RealBuild {
+        id: "129.0.6668.90-windows",
+        os: Os::Windows,
+        full_version: "129.0.6668.90",
+        user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/129.0.0.0 Safari/537.36",
+        brands: [
+            ("Google Chrome", "129"),
+            ("Not=A?Brand", "8"),
+            ("Chromium", "129"),
+        ],
+        platform_version: "10.0.0",
+    },
+    RealBuild {
+        id: "126.0.6478.126-linux",
+        os: Os::Linux,
+        full_version: "126.0.6478.126",
+        user_agent: "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Safari/537.36",
+        brands: [
+            ("Not/A)Brand", "8"),
+            ("Chromium", "126"),
+            ("Google Chrome", "126"),
+        ],
+        platform_version: "6.5.0",
+    },
+    RealBuild {
+        id: "124.0.6367.207-macos-intel",
+        os: Os::MacOSIntel,
+        full_version: "124.0.6367.207",
+        user_agent: "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
+        brands: [
+            ("Chromium", "124"),
+            ("Google Chrome", "124"),
+            ("Not-A.Brand", "99"),
+        ],
+        platform_version: "14.4.1",
+    },
+];
+
+/// The characters Chrome picks the GREASE brand separators from
+const GREASE_CHARS: [&str; 11] = [" ", "(", ":", "-", ".", "/", ")", ";", "=", "?", "_"];
+
+/// The versions Chrome picks the GREASE brand version from
+const GREASE_VERSIONS: [&str; 3] = ["8", "99", "24"];
+
+/// The positions of the GREASE, Chromium and Google Chrome brands
+const GREASE_ORDERS: [[usize; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+/// The GREASE brand and version Chrome reports for a major version, e.g.
+/// `("Not=A?Brand", "8")` for Chrome 129.
+///
+/// This follows Chromium's `GetGreasedUserAgentBrandVersion`, which seeds
+/// the choice with the major version.
+pub fn grease_brand(chrome_version: u32) -> (String, &'static str) {
+    let seed = chrome_version as usize;
+    let brand = format!(
+        "Not{}A{}Brand",
+        GREASE_CHARS[seed % GREASE_CHARS.len()],
+        GREASE_CHARS[(seed + 1) % GREASE_CHARS.len()]
+    );
+    (brand, GREASE_VERSIONS[seed % GREASE_VERSIONS.len()])
+}
+
+/// The CPU cores and the memory in GB of common desktops, each core count
+/// with the memory it usually comes with, for [`ChaserProfileBuilder::jitter`]
+const DESKTOP_HARDWARE: &[(u32, &[u32])] = &[
+    (4, &[4, 8]),
+    (6, &[8, 16]),
+    (8, &[8, 16]),
+    (12, &[16, 32]),
+    (16, &[16, 32]),
+];
+
+/// Like [`DESKTOP_HARDWARE`], for phones and tablets
+const MOBILE_HARDWARE: &[(u32, &[u32])] = &[(6, &[4, 8]), (8, &[4, 8])];
+
+/// The cores and the reported memory of a machine picked by `seed`.
+///
+/// The memory is capped at 8 GB, the most `navigator.deviceMemory` reports.
+fn jittered_hardware(seed: u64, mobile: bool) -> (u32, u32) {
+    let z = utils::splitmix64(seed);
+
+    let machines = if mobile {
+        MOBILE_HARDWARE
+    } else {
+        DESKTOP_HARDWARE
+    };
+    let (cores, memory) = machines[(z % machines.len() as u64) as usize];
+    let memory = memory[((z >> 32) % memory.len() as u64) as usize];
+    (cores, memory.min(8))
+}
+
+/// The main city of common IANA timezones, for [`timezone_to_coords`]
+const TIMEZONE_COORDS: &[(&str, f64, f64)] = &[
+    // Americas
+    ("America/New_York", 40.7128, -74.0060),
+    ("America/Chicago", 41.8781, -87.6298),
+    ("America/Denver", 39.7392, -104.9903),
+    ("America/Phoenix", 33.4484, -112.0740),
+    ("America/Los_Angeles", 34.0522, -118.2437),
+    ("America/Anchorage", 61.2181, -149.9003),
+    ("Pacific/Honolulu", 21.3069, -157.8583),
+    ("America/Toronto", 43.6532, -79.3832),
+    ("America/Vancouver", 49.2827, -123.1207),
+    ("America/Mexico_City", 19.4326, -99.1332),
+    ("America/Bogota", 4.7110, -74.0721),
+    ("America/Lima", -12.0464, -77.0428),
+    ("America/Santiago", -33.4489, -70.6693),
+    ("America/Sao_Paulo", -23.5505, -46.6333),
+    ("America/Argentina/Buenos_Aires", -34.6037, -58.3816),
+    // Europe
+    ("Europe/London", 51.5074, -0.1278),
+    ("Europe/Dublin", 53.3498, -6.2603),
+    ("Europe/Lisbon", 38.7223, -9.1393),
+    ("Europe/Madrid", 40.4168, -3.7038),
+    ("Europe/Paris", 48.8566, 2.3522),
+    ("Europe/Brussels", 50.8503, 4.3517),
+    ("Europe/Amsterdam", 52.3676, 4.9041),
+    ("Europe/Berlin", 52.5200, 13.4050),
+    ("Europe/Zurich", 47.3769, 8.5417),
+    ("Europe/Rome", 41.9028, 12.4964),
+    ("Europe/Vienna", 48.2082, 16.3738),
+    ("Europe/Prague", 50.0755, 14.4378),
+    ("Europe/Warsaw", 52.2297, 21.0122),
+    ("Europe/Stockholm", 59.3293, 18.0686),
+    ("Europe/Oslo", 59.9139, 10.7522),
+    ("Europe/Copenhagen", 55.6761, 12.5683),
+    ("Europe/Helsinki", 60.1699, 24.9384),
+    ("Europe/Athens", 37.9838, 23.7275),
+    ("Europe/Bucharest", 44.4268, 26.1025),
+    ("Europe/Kiev", 50.4501, 30.5234),
+    ("Europe/Kyiv", 50.4501, 30.5234),
+    ("Europe/Istanbul", 41.0082, 28.9784),
+    ("Europe/Moscow", 55.7558, 37.6173),
+    // Africa and the Middle East
+    ("Africa/Cairo", 30.0444, 31.2357),
+    ("Africa/Lagos", 6.5244, 3.3792),
+    ("Africa/Nairobi", -1.2921, 36.8219),
+    ("Africa/Johannesburg", -26.2041, 28.0473),
+    ("Asia/Dubai", 25.2048, 55.2708),
+    ("Asia/Riyadh", 24.7136, 46.6753),
+    ("Asia/Jerusalem", 31.7683, 35.2137),
+    // Asia and Oceania
+    ("Asia/Kolkata", 19.0760, 72.8777),
+    ("Asia/Calcutta", 19.0760, 72.8777),
+    ("Asia/Karachi", 24.8607, 67.0011),
+    ("Asia/Dhaka", 23.8103, 90.4125),
+    ("Asia/Bangkok", 13.7563, 100.5018),
+    ("Asia/Jakarta", -6.2088, 106.8456),
+    ("Asia/Ho_Chi_Minh", 10.8231, 106.6297),
+    ("Asia/Singapore", 1.3521, 103.8198),
+    ("Asia/Kuala_Lumpur", 3.1390, 101.6869),
+    ("Asia/Manila", 14.5995, 120.9842),
+    ("Asia/Hong_Kong", 22.3193, 114.1694),
+    ("Asia/Shanghai", 31.2304, 121.4737),
+    ("Asia/Taipei", 25.0330, 121.5654),
+    ("Asia/Seoul", 37.5665, 126.9780),
+    ("Asia/Tokyo", 35.6762, 139.6503),
+    ("Australia/Perth", -31.9505, 115.8605),
+    ("Australia/Brisbane", -27.4698, 153.0251),
+    ("Australia/Sydney", -33.8688, 151.2093),
+    ("Australia/Melbourne", -37.8136, 144.9631),
+    ("Pacific/Auckland", -36.8485, 174.7633),
+    ("UTC", 51.4779, -0.0015),
+];
+
+/// Plausible `(latitude, longitude)` for an IANA timezone such as
+/// `"Europe/Berlin"`: the largest city of the zone, so that a spoofed
+/// geolocation doesn't contradict the timezone.
+///
+/// Returns `None` for zones not in the built-in table.
+pub fn timezone_to_coords(tz: &str) -> Option<(f64, f64)> {
+    TIMEZONE_COORDS
+        .iter()
+        .find(|(zone, ..)| zone.eq_ignore_ascii_case(tz))
+        .map(|&(_, latitude, longitude)| (latitude, longitude))
+}
+
+/// Returned by [`ChaserProfile::from_real_build`] for a build that is not in
+/// the curated table
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("unknown Chrome build `{0}`, expected one of {known:?}", known = ChaserProfile::real_builds().collect::<Vec<_>>())]
+pub struct UnknownBuild(pub String);
+
+/// Returned by [`ChaserProfile::with_extra_script`] for a script without any
+/// code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("extra script is empty")]
+pub struct EmptyScript;
+
+/// Returned by [`ChaserProfile::try_bootstrap_script`] for an extra script
+/// that leaves a bracket, string or comment open or closes one it didn't
+/// open, which would break the code around it
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("extra script {index} is malformed: {reason}")]
+pub struct MalformedScript {
+    /// The position of the script in [`ChaserProfile::extra_scripts`]
+    pub index: usize,
+    pub reason: String,
+}
+
+/// How much a [`CoherenceIssue`] gives a profile away
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// A combination real devices report, but rarely enough to stand out
+    Warning,
+    /// A combination no real device reports
+    Error,
+}
+
+/// Fields of a [`ChaserProfile`] that contradict each other, see
+/// [`ChaserProfile::coherence`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoherenceIssue {
+    pub severity: Severity,
+    /// The conflicting fields, named like the builder methods
+    pub fields: &'static [&'static str],
+    pub message: String,
+}
+
+impl fmt::Display for CoherenceIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(
+            f,
+            "{severity} ({}): {}",
+            self.fields.join(", "),
+            self.message
+        )
+    }
+}
+
+/// The result of [`ChaserProfile::coherence`], empty for a profile whose
+/// fields describe one plausible device
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoherenceReport {
+    pub issues: Vec<CoherenceIssue>,
+}
+
+impl CoherenceReport {
+    /// Whether there are no [errors](Severity::Error), warnings are allowed
+    pub fn is_coherent(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &CoherenceIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &CoherenceIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+
+    fn push(
+        &mut self,
+        severity: Severity,
+        fields: &'static [&'static str],
+        message: impl Into<String>,
+    ) {
+        self.issues.push(CoherenceIssue {
+            severity,
+            fields,
+            message: message.into(),
+        });
+    }
+}
+
+impl fmt::Display for CoherenceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{issue}")?;
         }
+        Ok(())
     }
 }
 
@@ -140,8 +1001,25 @@ pub struct ChaserProfile {
     cpu_cores: u32,
     locale: String,
     timezone: String,
+    geolocation: Option<(f64, f64)>,
     screen_width: u32,
     screen_height: u32,
+    device_pixel_ratio: f64,
+    touch_points: u32,
+    grease: Option<(String, String)>,
+    build: Option<&'static RealBuild>,
+    bypass_csp: bool,
+    color_scheme: ColorScheme,
+    reduced_motion: bool,
+    extended_screen: bool,
+    notification_permission: NotificationPermission,
+    mobile: bool,
+    model: String,
+    vendor: Option<String>,
+    app_version: Option<String>,
+    webgl_vendor: Option<String>,
+    webgl_renderer: Option<String>,
+    extra_scripts: Vec<String>,
 }
 
 impl Default for ChaserProfile {
@@ -154,6 +1032,7 @@ impl ChaserProfile {
     /// Create a new profile builder with the specified OS
     #[allow(clippy::new_ret_no_self)]
     pub fn new(os: Os) -> ChaserProfileBuilder {
+        let (screen_width, screen_height) = os.screen();
         ChaserProfileBuilder {
             os,
             chrome_version: 129,
@@ -162,13 +1041,32 @@ impl ChaserProfile {
                 Os::MacOSIntel => Gpu::AppleM1Pro,
                 Os::MacOSArm => Gpu::AppleM4Max,
                 Os::Linux => Gpu::NvidiaGTX1660,
+                Os::Android => Gpu::Adreno740,
+                Os::IOS => Gpu::AppleGpu,
             },
             memory_gb: 8,
             cpu_cores: 8,
             locale: "en-US".to_string(),
             timezone: "America/New_York".to_string(),
-            screen_width: 1920,
-            screen_height: 1080,
+            geolocation: None,
+            screen_width,
+            screen_height,
+            device_pixel_ratio: os.device_pixel_ratio(),
+            touch_points: if os.is_mobile() { 5 } else { 0 },
+            grease: None,
+            build: None,
+            bypass_csp: false,
+            color_scheme: ColorScheme::Light,
+            reduced_motion: false,
+            extended_screen: false,
+            notification_permission: NotificationPermission::Default,
+            mobile: os.is_mobile(),
+            model: String::new(),
+            vendor: None,
+            app_version: None,
+            webgl_vendor: None,
+            webgl_renderer: None,
+            jitter: None,
         }
     }
 
@@ -192,6 +1090,51 @@ impl ChaserProfile {
         Self::new(Os::Linux)
     }
 
+    /// Create an Android phone profile, see [`crate::devices`] for the
+    /// metrics of specific devices
+    pub fn android() -> ChaserProfileBuilder {
+        Self::new(Os::Android)
+    }
+
+    /// Create an iPhone profile, see [`crate::devices`] for the metrics of
+    /// specific devices
+    pub fn ios() -> ChaserProfileBuilder {
+        Self::new(Os::IOS)
+    }
+
+    /// Create a profile that reports itself exactly like a shipped Chrome
+    /// build: full version, `User-Agent`, brand list (including the GREASE
+    /// brand of that version) and platform version.
+    ///
+    /// See [`ChaserProfile::real_builds`] for the known builds. Setting
+    /// [`ChaserProfileBuilder::chrome_version`] afterwards drops the build.
+    ///
+    /// ```ignore
+    /// let profile = ChaserProfile::from_real_build("131.0.6778.86-windows")?
+    ///     .gpu(Gpu::NvidiaRTX4080)
+    ///     .build();
+    /// ```
+    pub fn from_real_build(build: &str) -> Result<ChaserProfileBuilder, UnknownBuild> {
+        let real = REAL_BUILDS
+            .iter()
+            .find(|b| b.id == build)
+            .ok_or_else(|| UnknownBuild(build.to_string()))?;
+        let major = real
+            .full_version
+            .split('.')
+            .next()
+            .and_then(|major| major.parse().ok())
+            .unwrap_or_default();
+        let mut builder = Self::new(real.os).chrome_version(major);
+        builder.build = Some(real);
+        Ok(builder)
+    }
+
+    /// The identifiers accepted by [`ChaserProfile::from_real_build`]
+    pub fn real_builds() -> impl Iterator<Item = &'static str> {
+        REAL_BUILDS.iter().map(|b| b.id)
+    }
+
     // Getters
     pub fn os(&self) -> Os {
         self.os
@@ -214,29 +1157,506 @@ impl ChaserProfile {
     pub fn timezone(&self) -> &str {
         &self.timezone
     }
+    /// The explicit [geolocation](ChaserProfileBuilder::geolocation), or the
+    /// coordinates of the profile's timezone, see [`timezone_to_coords`]
+    pub fn geolocation(&self) -> Option<(f64, f64)> {
+        self.geolocation
+            .or_else(|| timezone_to_coords(&self.timezone))
+    }
     pub fn screen_width(&self) -> u32 {
         self.screen_width
     }
     pub fn screen_height(&self) -> u32 {
         self.screen_height
     }
+    pub fn device_pixel_ratio(&self) -> f64 {
+        self.device_pixel_ratio
+    }
+    pub fn touch_points(&self) -> u32 {
+        self.touch_points
+    }
+    pub fn bypass_csp(&self) -> bool {
+        self.bypass_csp
+    }
+    pub fn color_scheme(&self) -> ColorScheme {
+        self.color_scheme
+    }
+    pub fn reduced_motion(&self) -> bool {
+        self.reduced_motion
+    }
+    pub fn extended_screen(&self) -> bool {
+        self.extended_screen
+    }
+    pub fn notification_permission(&self) -> NotificationPermission {
+        self.notification_permission
+    }
+    pub fn mobile(&self) -> bool {
+        self.mobile
+    }
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// The `navigator.platform` value, the one of the [OS](Os::platform)
+    /// except for iPads
+    pub fn platform(&self) -> &'static str {
+        match self.os {
+            Os::IOS if !self.mobile => "iPad",
+            os => os.platform(),
+        }
+    }
+
+    /// `navigator.vendor`: `Google Inc.` for Chrome, `Apple Computer, Inc.`
+    /// for the WebKit based Chrome on [iOS](Os::IOS), unless overridden
+    pub fn vendor(&self) -> &str {
+        match (&self.vendor, self.os) {
+            (Some(vendor), _) => vendor,
+            (None, Os::IOS) => "Apple Computer, Inc.",
+            (None, _) => "Google Inc.",
+        }
+    }
+
+    /// The unmasked WebGL vendor, the [`Gpu`]'s on the OS unless overridden
+    pub fn webgl_vendor(&self) -> &str {
+        self.webgl_vendor
+            .as_deref()
+            .unwrap_or_else(|| self.gpu.vendor_for(self.os))
+    }
+
+    /// The unmasked WebGL renderer, the [`Gpu`]'s on the OS unless
+    /// overridden
+    pub fn webgl_renderer(&self) -> &str {
+        self.webgl_renderer
+            .as_deref()
+            .unwrap_or_else(|| self.gpu.renderer_for(self.os))
+    }
+
+    /// `navigator.appVersion`: the user agent without its `Mozilla/` prefix,
+    /// unless overridden
+    pub fn app_version(&self) -> String {
+        match &self.app_version {
+            Some(version) => version.clone(),
+            None => {
+                let user_agent = self.user_agent();
+                match user_agent.strip_prefix("Mozilla/") {
+                    Some(version) => version.to_string(),
+                    None => user_agent,
+                }
+            }
+        }
+    }
+
+    /// The full Chrome version, e.g. `131.0.6778.86` for a real build
+    pub fn full_version(&self) -> String {
+        match self.build {
+            Some(build) => build.full_version.to_string(),
+            None => format!("{}.0.0.0", self.chrome_version),
+        }
+    }
+
+    /// The client hints platform version of the OS, or of the real build
+    fn platform_version(&self) -> &'static str {
+        self.build
+            .map_or_else(|| self.os.platform_version(), |b| b.platform_version)
+    }
 
     /// Generate the User-Agent string for this profile
     pub fn user_agent(&self) -> String {
+        if let Some(build) = self.build {
+            return build.user_agent.to_string();
+        }
         let os_part = match self.os {
             Os::Windows => "Windows NT 10.0; Win64; x64",
             Os::MacOSIntel | Os::MacOSArm => "Macintosh; Intel Mac OS X 10_15_7",
             Os::Linux => "X11; Linux x86_64",
+            // the reduced user agent hides the Android version and model
+            Os::Android => "Linux; Android 10; K",
+            Os::IOS => {
+                let version = self.os.platform_version().trim_end_matches(".0");
+                let device = if self.mobile {
+                    "iPhone; CPU iPhone OS"
+                } else {
+                    "iPad; CPU OS"
+                };
+                return format!(
+                    "Mozilla/5.0 ({} {} like Mac OS X) AppleWebKit/605.1.15 \
+                     (KHTML, like Gecko) CriOS/{} Mobile/15E148 Safari/604.1",
+                    device,
+                    version.replace('.', "_"),
+                    self.full_version()
+                );
+            }
         };
+        // Android tablets report themselves without `Mobile`
+        let mobile = if self.mobile { "Mobile " } else { "" };
         format!(
-            "Mozilla/5.0 ({}) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{}.0.0.0 Safari/537.36",
-            os_part, self.chrome_version
+            "Mozilla/5.0 ({}) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{}.0.0.0 {}Safari/537.36",
+            os_part, self.chrome_version, mobile
         )
     }
 
-    /// Generate the complete JavaScript bootstrap script for this profile
+    /// The client hints brand list, shared by `navigator.userAgentData` and
+    /// the `Sec-CH-UA` header.
+    ///
+    /// The GREASE brand, its version and the brand order follow what Chrome
+    /// reports for the profile's version, see [`grease_brand`].
+    pub fn brands(&self) -> Vec<UserAgentBrandVersion> {
+        if let Some(build) = self.build {
+            return build
+                .brands
+                .iter()
+                .map(|(brand, version)| UserAgentBrandVersion::new(*brand, *version))
+                .collect();
+        }
+        let version = self.chrome_version.to_string();
+        let grease = match &self.grease {
+            Some((brand, version)) => UserAgentBrandVersion::new(brand, version),
+            None => {
+                let (brand, version) = grease_brand(self.chrome_version);
+                UserAgentBrandVersion::new(brand, version)
+            }
+        };
+        let order = GREASE_ORDERS[self.chrome_version as usize % GREASE_ORDERS.len()];
+        let mut brands = vec![grease.clone(); 3];
+        brands[order[0]] = grease;
+        brands[order[1]] = UserAgentBrandVersion::new("Chromium", version.clone());
+        brands[order[2]] = UserAgentBrandVersion::new("Google Chrome", version);
+        brands
+    }
+
+    /// The brand list with full versions (`Sec-CH-UA-Full-Version-List`)
+    pub fn full_version_list(&self) -> Vec<UserAgentBrandVersion> {
+        let full_version = self.full_version();
+        self.brands()
+            .into_iter()
+            .map(|b| {
+                let version = if b.brand == "Google Chrome" || b.brand == "Chromium" {
+                    full_version.clone()
+                } else {
+                    format!("{}.0.0.0", b.version)
+                };
+                UserAgentBrandVersion::new(b.brand, version)
+            })
+            .collect()
+    }
+
+    /// The value Chrome sends in the `Sec-CH-UA` header for this profile
+    pub fn sec_ch_ua(&self) -> String {
+        self.brands()
+            .iter()
+            .map(|b| format!(r#""{}";v="{}""#, b.brand, b.version))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Generate the client hints metadata (`Sec-CH-UA*` headers and
+    /// `navigator.userAgentData`) for this profile
+    pub fn user_agent_metadata(&self) -> UserAgentMetadata {
+        UserAgentMetadata {
+            brands: Some(self.brands()),
+            full_version_list: Some(self.full_version_list()),
+            platform: self.os.hints_platform().to_string(),
+            platform_version: self.platform_version().to_string(),
+            architecture: self.os.architecture().to_string(),
+            model: self.model.clone(),
+            mobile: self.mobile,
+            bitness: Some("64".to_string()),
+            wow64: Some(false),
+            form_factors: None,
+        }
+    }
+
+    /// Whether the profile's browser supports client hints, which WebKit
+    /// based [iOS](Os::IOS) browsers don't
+    pub(crate) fn has_client_hints(&self) -> bool {
+        !matches!(self.os, Os::IOS)
+    }
+
+    /// Generate the `Network.setUserAgentOverride` params for this profile.
+    ///
+    /// This overrides the HTTP `User-Agent` header, `Accept-Language` and the
+    /// client hints so that the network layer matches what the bootstrap
+    /// script reports to JavaScript.
+    pub fn user_agent_override(&self) -> SetUserAgentOverrideParams {
+        SetUserAgentOverrideParams {
+            user_agent: self.user_agent(),
+            accept_language: Some(self.locale.clone()),
+            platform: Some(self.platform().to_string()),
+            user_agent_metadata: self.has_client_hints().then(|| self.user_agent_metadata()),
+        }
+    }
+
+    /// The media features for `Emulation.setEmulatedMedia`, so `matchMedia`
+    /// and CSS media queries match the profile
+    pub fn media_features(&self) -> Vec<MediaFeature> {
+        let motion = if self.reduced_motion {
+            "reduce"
+        } else {
+            "no-preference"
+        };
+        vec![
+            MediaFeature::new("prefers-color-scheme", self.color_scheme.as_str()),
+            MediaFeature::new("prefers-reduced-motion", motion),
+        ]
+    }
+
+    /// Check the profile for fields that contradict each other, like a GPU
+    /// that never shipped with the OS, a memory size `navigator.deviceMemory`
+    /// can't report, or an `appVersion` that doesn't match the user agent.
+    ///
+    /// Fingerprinting scripts compare these values, so a profile with
+    /// [errors](Severity::Error) is easier to detect than a real browser.
+    ///
+    /// ```ignore
+    /// let report = ChaserProfile::windows().gpu(Gpu::AppleM4Max).build().coherence();
+    /// if !report.is_coherent() {
+    ///     eprint!("{report}");
+    /// }
+    /// ```
+    pub fn coherence(&self) -> CoherenceReport {
+        use Severity::{Error, Warning};
+
+        let mut report = CoherenceReport::default();
+        let (os, gpu) = (self.os, self.gpu);
+        let apple_silicon = matches!(gpu, Gpu::AppleM1Pro | Gpu::AppleM2Max | Gpu::AppleM4Max);
+        let phone_gpu = matches!(gpu, Gpu::Adreno740 | Gpu::MaliG710);
+
+        let gpu_fits = match os {
+            Os::IOS => matches!(gpu, Gpu::AppleGpu),
+            Os::Android => phone_gpu,
+            Os::MacOSArm => apple_silicon,
+            // Intel Macs shipped with Intel and AMD GPUs, an Apple GPU means
+            // x86 Chrome under Rosetta
+            Os::MacOSIntel => {
+                if apple_silicon {
+                    report.push(
+                        Warning,
+                        &["gpu", "os"],
+                        format!("{gpu:?} on {os:?} is only seen with Chrome running under Rosetta"),
+                    );
+                }
+                matches!(
+                    gpu,
+                    Gpu::IntelUHD630 | Gpu::IntelIrisXe | Gpu::AmdRadeonRX6800
+                ) || apple_silicon
+            }
+            Os::Windows | Os::Linux => {
+                !apple_silicon && !phone_gpu && !matches!(gpu, Gpu::AppleGpu)
+            }
+        };
+        if !gpu_fits {
+            report.push(Error, &["gpu", "os"], format!("{gpu:?} doesn't run {os:?}"));
+        } else {
+            // ANGLE only uses Direct3D on Windows, and always uses it there
+            let direct3d = self.webgl_renderer().contains("Direct3D");
+            if direct3d != matches!(os, Os::Windows) {
+                report.push(
+                    Error,
+                    &["webgl_renderer", "os"],
+                    format!(
+                        "the WebGL renderer `{}` doesn't match {os:?}",
+                        self.webgl_renderer()
+                    ),
+                );
+            }
+        }
+
+        let renderer_vendor = self
+            .webgl_renderer()
+            .strip_prefix("ANGLE (")
+            .and_then(|rest| rest.split(',').next());
+        if let Some(vendor) = renderer_vendor {
+            let expected = format!("Google Inc. ({vendor})");
+            if self.webgl_vendor() != expected {
+                report.push(
+                    Error,
+                    &["webgl_vendor", "webgl_renderer"],
+                    format!(
+                        "the WebGL vendor `{}` doesn't match the renderer, expected `{expected}`",
+                        self.webgl_vendor()
+                    ),
+                );
+            }
+        }
+
+        // navigator.deviceMemory rounds down to a power of two and caps at 8
+        if ![1, 2, 4, 8].contains(&self.memory_gb) {
+            report.push(
+                Error,
+                &["memory_gb"],
+                format!(
+                    "navigator.deviceMemory is 1, 2, 4 or 8, not {}",
+                    self.memory_gb
+                ),
+            );
+        }
+        let cores = self.cpu_cores;
+        if cores == 0 {
+            report.push(
+                Error,
+                &["cpu_cores"],
+                "navigator.hardwareConcurrency is at least 1",
+            );
+        } else if os.is_mobile() && cores > 8 {
+            report.push(
+                Warning,
+                &["cpu_cores", "os"],
+                format!("{os:?} devices have up to 8 cores, not {cores}"),
+            );
+        }
+        if cores >= 16 && self.memory_gb <= 2 {
+            report.push(
+                Warning,
+                &["cpu_cores", "memory_gb"],
+                format!("{cores} cores with {} GB of memory", self.memory_gb),
+            );
+        }
+        let high_end = apple_silicon
+            || matches!(
+                gpu,
+                Gpu::NvidiaRTX3080 | Gpu::NvidiaRTX4080 | Gpu::AmdRadeonRX6800
+            );
+        if high_end && (1..4).contains(&cores) {
+            report.push(
+                Warning,
+                &["cpu_cores", "gpu"],
+                format!("{gpu:?} with {cores} cores"),
+            );
+        }
+
+        if os.is_mobile() {
+            if self.touch_points == 0 {
+                report.push(
+                    Error,
+                    &["touch_points", "os"],
+                    format!("{os:?} devices have a touch screen"),
+                );
+            }
+        } else {
+            if self.mobile {
+                report.push(
+                    Error,
+                    &["mobile", "os"],
+                    format!("{os:?} isn't a mobile OS"),
+                );
+            }
+            if !self.model.is_empty() {
+                report.push(
+                    Error,
+                    &["model", "os"],
+                    format!("Chrome on {os:?} reports an empty model"),
+                );
+            }
+            if matches!(os, Os::MacOSIntel | Os::MacOSArm) && self.touch_points > 0 {
+                report.push(
+                    Warning,
+                    &["touch_points", "os"],
+                    "Macs have no touch screen",
+                );
+            }
+        }
+
+        if self.app_version.is_some() {
+            let user_agent = self.user_agent();
+            if !user_agent.ends_with(&self.app_version()) {
+                report.push(
+                    Error,
+                    &["app_version"],
+                    "navigator.appVersion doesn't match the user agent",
+                );
+            }
+        }
+        if self.vendor.is_some() {
+            let expected = if matches!(os, Os::IOS) {
+                "Apple Computer, Inc."
+            } else {
+                "Google Inc."
+            };
+            if self.vendor() != expected {
+                report.push(
+                    Warning,
+                    &["vendor"],
+                    format!(
+                        "navigator.vendor is `{}`, Chrome reports `{expected}`",
+                        self.vendor()
+                    ),
+                );
+            }
+        }
+        report
+    }
+
+    /// Append `js` to the bootstrap script of this profile, so it is
+    /// registered together with the stealth patches instead of through a
+    /// separate [`Page::evaluate_on_new_document`](crate::Page::evaluate_on_new_document).
+    ///
+    /// Ordering guarantees:
+    /// - the core patches always run first, so `js` observes (and may
+    ///   override) the spoofed environment
+    /// - extra scripts run in the order they were added
+    /// - a runtime exception in an extra script doesn't affect the core
+    ///   patches, but skips the extra scripts added after it
+    ///
+    /// Everything is a single script, so a syntax error in `js` prevents the
+    /// core patches from running as well. Like the core patches, extra
+    /// scripts are also injected into dedicated workers unless worker
+    /// spoofing is disabled.
+    pub fn with_extra_script(&mut self, js: &str) -> Result<&mut Self, EmptyScript> {
+        if js.trim().is_empty() {
+            return Err(EmptyScript);
+        }
+        self.extra_scripts.push(js.to_string());
+        Ok(self)
+    }
+
+    /// The scripts added with [`ChaserProfile::with_extra_script`]
+    pub fn extra_scripts(&self) -> &[String] {
+        &self.extra_scripts
+    }
+
+    /// Generate the complete JavaScript bootstrap script for this profile,
+    /// including the `Worker` shim that injects it into dedicated workers
     pub fn bootstrap_script(&self) -> String {
-        let mut script = format!(
+        let mut script = self.page_script();
+        script.push_str(&Self::worker_script(&script));
+        script
+    }
+
+    /// Like [`ChaserProfile::bootstrap_script`], but fails if an extra script
+    /// would break out of its place in the bootstrap script.
+    ///
+    /// A script like `}); (function() {` runs fine on its own, but in the
+    /// bootstrap script it swallows the code following it, and the stealth
+    /// patches stop working without any error.
+    pub fn try_bootstrap_script(&self) -> Result<String, MalformedScript> {
+        self.check_extra_scripts()?;
+        Ok(self.bootstrap_script())
+    }
+
+    /// Fail for the first extra script that would break the bootstrap script
+    pub(crate) fn check_extra_scripts(&self) -> Result<(), MalformedScript> {
+        for (index, script) in self.extra_scripts.iter().enumerate() {
+            check_script(script).map_err(|reason| MalformedScript { index, reason })?;
+        }
+        Ok(())
+    }
+
+    /// The bootstrap script without the `Worker` shim, for sites whose
+    /// workers break when wrapped
+    pub fn page_script(&self) -> String {
+        let mut script = self.core_script();
+        for extra in &self.extra_scripts {
+            // the newline ends a trailing line comment of the previous script
+            script.push_str(extra);
+            script.push_str("\n;");
+        }
+        script
+    }
+
+    /// The stealth patches of the profile
+    fn core_script(&self) -> String {
+        let webgl = self.gpu.webgl_params();
+        format!(
             r#"
             (function() {{
                 // === chaser-oxide HARDWARE HARMONY ===
@@ -269,6 +1689,17 @@ impl ChaserProfile {
                     get: () => '{platform}',
                     configurable: true
                 }});
+                for (const [name, value] of Object.entries({{
+                    vendor: {vendor},
+                    appVersion: {app_version},
+                    appName: 'Netscape',
+                    product: 'Gecko'
+                }})) {{
+                    Object.defineProperty(Navigator.prototype, name, {{
+                        get: () => value,
+                        configurable: true
+                    }});
+                }}
 
                 // 2. Hardware (on prototype)
                 Object.defineProperty(Navigator.prototype, 'hardwareConcurrency', {{
@@ -279,34 +1710,144 @@ impl ChaserProfile {
                     get: () => {memory},
                     configurable: true
                 }});
+                // report touch points only where touch events are exposed,
+                // e.g. under touch emulation, so that both stay consistent
+                const maxTouchPoints = Object.getOwnPropertyDescriptor(Navigator.prototype, 'maxTouchPoints').get;
                 Object.defineProperty(Navigator.prototype, 'maxTouchPoints', {{
-                    get: () => 0,
+                    get: function() {{
+                        if (!('ontouchstart' in self)) return 0;
+                        return {touch_points} || maxTouchPoints.call(this);
+                    }},
                     configurable: true
                 }});
+                // device sensors are only exposed for mobile personas
+                if ({mobile}) {{
+                    for (const name of ['DeviceOrientationEvent', 'DeviceMotionEvent']) {{
+                        if (name in self) continue;
+                        const SensorEvent = class extends Event {{
+                            constructor(type, init = {{}}) {{
+                                super(type, init);
+                                for (const [key, value] of Object.entries(init)) {{
+                                    if (!(key in this)) Object.defineProperty(this, key, {{ value }});
+                                }}
+                            }}
+                        }};
+                        Object.defineProperty(SensorEvent, 'name', {{ value: name }});
+                        Object.defineProperty(self, name, {{
+                            value: SensorEvent,
+                            configurable: true,
+                            writable: true
+                        }});
+                    }}
+                }}
 
                 // 3. WebGL
-                const spoofWebGL = (proto) => {{
+                const webglScalars = {webgl_scalars};
+                const webglArrays = {webgl_arrays};
+                // contexts that enabled EXT_texture_filter_anisotropic
+                const anisotropic = new WeakSet();
+                const spoofWebGL = (proto, extensions) => {{
                     const getParameter = proto.getParameter;
                     proto.getParameter = function(parameter) {{
-                        if (parameter === 37445) return '{webgl_vendor}';
-                        if (parameter === 37446) return '{webgl_renderer}';
+                        if (parameter === 37445) return {webgl_vendor};
+                        if (parameter === 37446) return {webgl_renderer};
+                        // MAX_TEXTURE_MAX_ANISOTROPY_EXT is an INVALID_ENUM until the
+                        // extension is enabled, which the native call reports
+                        if (parameter === 34047 && !anisotropic.has(this)) {{
+                            return getParameter.apply(this, arguments);
+                        }}
+                        if (parameter in webglScalars) return webglScalars[parameter];
+                        if (parameter in webglArrays) {{
+                            const [ArrayType, values] = webglArrays[parameter];
+                            return new ArrayType(values);
+                        }}
                         return getParameter.apply(this, arguments);
                     }};
+
+                    const getShaderPrecisionFormat = proto.getShaderPrecisionFormat;
+                    proto.getShaderPrecisionFormat = function(shaderType, precisionType) {{
+                        const format = getShaderPrecisionFormat.apply(this, arguments);
+                        if (!format) return format;
+                        // LOW/MEDIUM/HIGH_FLOAT are 0x8DF0-0x8DF2, the ints follow
+                        const [rangeMin, rangeMax, precision] =
+                            precisionType <= 0x8DF2 ? {float_precision:?} : {int_precision:?};
+                        const values = {{ rangeMin, rangeMax, precision }};
+                        return new Proxy(format, {{
+                            get: (target, prop) => prop in values ? values[prop] : Reflect.get(target, prop)
+                        }});
+                    }};
+
+                    proto.getSupportedExtensions = function() {{
+                        return this.isContextLost() ? null : extensions.slice();
+                    }};
+                    const getExtension = proto.getExtension;
+                    proto.getExtension = function(name) {{
+                        if (!extensions.includes(name)) return null;
+                        const extension = getExtension.apply(this, arguments);
+                        if (extension && name === 'EXT_texture_filter_anisotropic') {{
+                            anisotropic.add(this);
+                        }}
+                        return extension;
+                    }};
                 }};
-                spoofWebGL(WebGLRenderingContext.prototype);
+                spoofWebGL(WebGLRenderingContext.prototype, {webgl_extensions:?});
                 if (typeof WebGL2RenderingContext !== 'undefined') {{
-                    spoofWebGL(WebGL2RenderingContext.prototype);
+                    spoofWebGL(WebGL2RenderingContext.prototype, {webgl2_extensions:?});
+                }}
+
+                // 4. Screen layout, consistent with the profile's resolution
+                if (typeof Screen !== 'undefined') {{
+                    const screenValues = {{
+                        width: {screen_width}, height: {screen_height},
+                        availWidth: {screen_width}, availHeight: {avail_height},
+                        isExtended: {extended_screen}
+                    }};
+                    for (const [prop, value] of Object.entries(screenValues)) {{
+                        Object.defineProperty(Screen.prototype, prop, {{
+                            get: () => value,
+                            configurable: true
+                        }});
+                    }}
+                    Object.defineProperty(window, 'devicePixelRatio', {{
+                        get: () => {device_pixel_ratio},
+                        configurable: true
+                    }});
+                    // only where the Window Management API exists (secure contexts)
+                    if ('getScreenDetails' in window) {{
+                        const detailed = (left, isPrimary, label) => ({{
+                            left, top: 0, width: {screen_width}, height: {screen_height},
+                            availLeft: left, availTop: 0, availWidth: {screen_width},
+                            availHeight: {avail_height},
+                            colorDepth: screen.colorDepth, pixelDepth: screen.pixelDepth,
+                            orientation: screen.orientation, isExtended: {extended_screen},
+                            isPrimary, isInternal: false, devicePixelRatio: {device_pixel_ratio},
+                            label, onchange: null,
+                            addEventListener() {{}}, removeEventListener() {{}}
+                        }});
+                        const screens = [detailed(0, true, 'Generic PnP Monitor')];
+                        if ({extended_screen}) screens.push(detailed({screen_width}, false, 'Generic PnP Monitor'));
+                        const details = {{
+                            screens, currentScreen: screens[0],
+                            onscreenschange: null, oncurrentscreenchange: null,
+                            addEventListener() {{}}, removeEventListener() {{}}
+                        }};
+                        Object.defineProperty(window, 'getScreenDetails', {{
+                            value: async function getScreenDetails() {{ return details; }},
+                            configurable: true,
+                            writable: true
+                        }});
+                    }}
                 }}
 
-                // 4. Client Hints (on prototype)
+                // 5. Client Hints (on prototype), which WebKit doesn't have
+                if (!{client_hints}) {{
+                    delete Navigator.prototype.userAgentData;
+                    delete self.NavigatorUAData;
+                }} else {{
                 Object.defineProperty(Navigator.prototype, 'userAgentData', {{
                     get: () => ({{
-                        brands: [
-                            {{ brand: "Google Chrome", version: "{chrome_ver}" }},
-                            {{ brand: "Chromium", version: "{chrome_ver}" }},
-                            {{ brand: "Not=A?Brand", version: "24" }}
-                        ],
-                        mobile: false,
+                        brands: [{brands}],
+                        mobile: {mobile},
                         platform: "{hints_platform}"
                     }}),
                     configurable: true
@@ -317,18 +1858,21 @@ impl ChaserProfile {
                         const values = {{}};
                         for (const hint of hints) {{
                             if (hint === 'platform') values.platform = "{platform}";
-                            else if (hint === 'platformVersion') values.platformVersion = "19.0.0";
-                            else if (hint === 'architecture') values.architecture = "x86";
-                            else if (hint === 'model') values.model = "";
+                            else if (hint === 'platformVersion') values.platformVersion = "{platform_version}";
+                            else if (hint === 'architecture') values.architecture = "{architecture}";
+                            else if (hint === 'model') values.model = {model};
                             else if (hint === 'bitness') values.bitness = "64";
+                            else if (hint === 'uaFullVersion') values.uaFullVersion = "{full_version}";
+                            else if (hint === 'fullVersionList') values.fullVersionList = [{full_version_list}];
                         }}
                         return values;
 
                     }},
                     configurable: true
                 }});
+                }}
 
-                // 5. Video Codecs
+                // 6. Video Codecs
                 const canPlayType = HTMLMediaElement.prototype.canPlayType;
                 HTMLMediaElement.prototype.canPlayType = function(type) {{
                     if (type.includes('avc1')) return 'probably';
@@ -337,14 +1881,14 @@ impl ChaserProfile {
                     return canPlayType.apply(this, arguments);
                 }};
 
-                // 6. WebDriver (set to false instead of delete - more realistic)
+                // 7. WebDriver (set to false instead of delete - more realistic)
                 Object.defineProperty(Object.getPrototypeOf(navigator), 'webdriver', {{
                     get: () => false,
                     configurable: true,
                     enumerable: true
                 }});
 
-                // 7. Chrome Object (enhanced with runtime APIs)
+                // 8. Chrome Object (enhanced with runtime APIs)
                 if (!window.chrome) {{
                     window.chrome = {{}};
                 }}
@@ -379,37 +1923,71 @@ impl ChaserProfile {
                     window.chrome.runtime.sendMessage = function() {{ return; }};
                 }}
 
+                // The navigation timings in ms relative to `performance.timeOrigin`,
+                // strictly increasing; unreached ones are 0. Staggered synthetic
+                // timings stand in where the Navigation Timing API is missing.
+                const navTimings = () => {{
+                    const nav = performance.getEntriesByType('navigation')[0];
+                    const paint = performance.getEntriesByName('first-paint')[0];
+                    const t = nav ? {{
+                        fetch: nav.fetchStart,
+                        response: nav.responseStart,
+                        domContentLoaded: nav.domContentLoadedEventEnd,
+                        load: nav.loadEventEnd,
+                        type: nav.type,
+                        protocol: nav.nextHopProtocol
+                    }} : {{
+                        fetch: 1.3,
+                        response: 38.6,
+                        domContentLoaded: 96.2,
+                        load: 142.7,
+                        type: 'navigate',
+                        protocol: 'http/1.1'
+                    }};
+                    t.fetch = Math.max(t.fetch, 0.1);
+                    let last = 0;
+                    for (const key of ['fetch', 'response', 'domContentLoaded', 'load']) {{
+                        if (t[key] > 0) last = t[key] = Math.max(t[key], last + 0.1);
+                    }}
+                    t.paint = paint ? Math.max(paint.startTime, t.response) : 0;
+                    t.at = (offset) => offset > 0 ? performance.timeOrigin + offset : 0;
+                    return t;
+                }};
+
                 // Chrome CSI (Chrome Speed Index) - some sites check this
                 if (!window.chrome.csi) {{
                     window.chrome.csi = function() {{
-                        const now = Date.now();
-                        return {{ 
-                            startE: now, 
-                            onloadT: now, 
-                            pageT: now, 
-                            tran: 15 
+                        const t = navTimings();
+                        const tran = {{ navigate: 15, reload: 16, back_forward: 6 }};
+                        return {{
+                            startE: Math.round(performance.timeOrigin),
+                            onloadT: Math.round(t.at(t.domContentLoaded)),
+                            pageT: performance.now(),
+                            tran: tran[t.type] || 15
                         }};
                     }};
                 }}
 
-                // Chrome loadTimes (deprecated but still checked)
+                // Chrome loadTimes (deprecated but still checked), in seconds
                 if (!window.chrome.loadTimes) {{
                     window.chrome.loadTimes = function() {{
-                        const now = Date.now() / 1000;
+                        const t = navTimings();
+                        const types = {{ reload: 'Reload', back_forward: 'BackForward' }};
+                        const spdy = t.protocol === 'h2' || t.protocol === 'h3';
                         return {{
-                            requestTime: now,
-                            startLoadTime: now,
-                            commitLoadTime: now,
-                            finishDocumentLoadTime: now,
-                            finishLoadTime: now,
-                            firstPaintTime: now,
+                            requestTime: performance.timeOrigin / 1000,
+                            startLoadTime: t.at(t.fetch) / 1000,
+                            commitLoadTime: t.at(t.response) / 1000,
+                            finishDocumentLoadTime: t.at(t.domContentLoaded) / 1000,
+                            finishLoadTime: t.at(t.load) / 1000,
+                            firstPaintTime: t.at(t.paint) / 1000,
                             firstPaintAfterLoadTime: 0,
-                            navigationType: "Other",
-                            wasFetchedViaSpdy: false,
-                            wasNpnNegotiated: false,
-                            npnNegotiatedProtocol: "",
+                            navigationType: types[t.type] || "Other",
+                            wasFetchedViaSpdy: spdy,
+                            wasNpnNegotiated: spdy,
+                            npnNegotiatedProtocol: spdy ? t.protocol : "unknown",
                             wasAlternateProtocolAvailable: false,
-                            connectionInfo: "http/1.1"
+                            connectionInfo: t.protocol || "http/1.1"
                         }};
                     }};
                 }}
@@ -432,63 +2010,324 @@ impl ChaserProfile {
                         getIsInstalled: function() {{ return false; }}
                     }};
                 }}
+
+                // 9. Notifications, consistent with the Permissions API.
+                // Headless Chrome may lack `Notification` or deny it while
+                // the Permissions API reports `prompt`.
+                const notificationPermission = {notification_permission};
+                if (typeof Notification === 'undefined' && typeof window !== 'undefined') {{
+                    const Notification = class Notification extends EventTarget {{
+                        constructor(title, options = {{}}) {{
+                            if (arguments.length === 0) {{
+                                throw new TypeError("Failed to construct 'Notification': 1 argument required, but only 0 present.");
+                            }}
+                            super();
+                            Object.assign(this, {{
+                                title: String(title), body: '', tag: '', icon: '', badge: '',
+                                image: '', lang: '', dir: 'auto', data: null, silent: null,
+                                renotify: false, requireInteraction: false, actions: [],
+                                timestamp: Date.now(),
+                                onclick: null, onshow: null, onerror: null, onclose: null
+                            }}, options);
+                        }}
+                        close() {{}}
+                    }};
+                    Object.defineProperty(Notification, 'maxActions', {{ get: () => 2, configurable: true }});
+                    Object.defineProperty(window, 'Notification', {{
+                        value: Notification,
+                        configurable: true,
+                        writable: true
+                    }});
+                }}
+                if (typeof Notification !== 'undefined') {{
+                    Object.defineProperty(Notification, 'permission', {{
+                        get: () => notificationPermission,
+                        configurable: true,
+                        enumerable: true
+                    }});
+                    // only windows may ask for the permission
+                    if (typeof window !== 'undefined') {{
+                        Object.defineProperty(Notification, 'requestPermission', {{
+                            value: function requestPermission(callback) {{
+                                if (typeof callback === 'function') {{
+                                    Promise.resolve().then(() => callback(notificationPermission));
+                                }}
+                                return Promise.resolve(notificationPermission);
+                            }},
+                            configurable: true,
+                            writable: true,
+                            enumerable: true
+                        }});
+                    }}
+                }}
+                if (typeof Permissions !== 'undefined') {{
+                    const query = Permissions.prototype.query;
+                    Permissions.prototype.query = function(descriptor) {{
+                        const status = query.apply(this, arguments);
+                        if (!descriptor || descriptor.name !== 'notifications') return status;
+                        return status.then((status) => {{
+                            Object.defineProperty(status, 'state', {{
+                                get: () => {permission_state},
+                                configurable: true
+                            }});
+                            return status;
+                        }});
+                    }};
+                }}
             }})();
         "#,
             ua = self.user_agent(),
-            platform = self.os.platform(),
+            platform = self.platform(),
+            vendor = serde_json::to_string(self.vendor()).unwrap(),
+            app_version = serde_json::to_string(&self.app_version()).unwrap(),
             cores = self.cpu_cores,
             memory = self.memory_gb,
-            webgl_vendor = self.gpu.vendor(),
-            webgl_renderer = self.gpu.renderer(),
-            chrome_ver = self.chrome_version,
+            touch_points = self.touch_points,
+            extended_screen = self.extended_screen,
+            notification_permission =
+                serde_json::to_string(self.notification_permission.as_str()).unwrap(),
+            permission_state =
+                serde_json::to_string(self.notification_permission.permission_state()).unwrap(),
+            mobile = self.mobile,
+            client_hints = self.has_client_hints(),
+            model = serde_json::to_string(&self.model).unwrap(),
+            screen_width = self.screen_width,
+            screen_height = self.screen_height,
+            avail_height = self.screen_height.saturating_sub(self.os.reserved_height()),
+            device_pixel_ratio = self.device_pixel_ratio,
+            webgl_vendor = serde_json::to_string(self.webgl_vendor()).unwrap(),
+            webgl_renderer = serde_json::to_string(self.webgl_renderer()).unwrap(),
+            webgl_scalars = webgl.js_scalars(),
+            webgl_arrays = webgl.js_arrays(),
+            float_precision = webgl.float_precision,
+            int_precision = webgl.int_precision,
+            webgl_extensions = webgl.extensions,
+            webgl2_extensions = webgl.webgl2_extensions,
+            brands = self
+                .brands()
+                .iter()
+                .map(|b| format!(
+                    "{{ brand: {}, version: {} }}",
+                    serde_json::to_string(&b.brand).unwrap(),
+                    serde_json::to_string(&b.version).unwrap()
+                ))
+                .collect::<Vec<_>>()
+                .join(", "),
+            full_version = self.full_version(),
+            full_version_list = self
+                .full_version_list()
+                .iter()
+                .map(|b| format!(
+                    "{{ brand: {}, version: {} }}",
+                    serde_json::to_string(&b.brand).unwrap(),
+                    serde_json::to_string(&b.version).unwrap()
+                ))
+                .collect::<Vec<_>>()
+                .join(", "),
             hints_platform = self.os.hints_platform(),
-        );
+            platform_version = self.platform_version(),
+            architecture = self.os.architecture(),
+        )
+    }
 
-        // Prevent CDP detection via worker threads
-        let worker_script = format!(
+    /// Prevent CDP detection via worker threads by prepending `script` to
+    /// the source of dedicated workers.
+    ///
+    /// Module workers resolve their `import`s against their own URL, which a
+    /// blob URL breaks, so they are not wrapped. Neither are workers whose
+    /// source can't be fetched, e.g. cross-origin ones.
+    fn worker_script(script: &str) -> String {
+        format!(
             r#"
                 const OriginalWorker = Worker;
                 window.Worker = function (url, options) {{
-                
-                    const injectedCode = `{script}`
+                    if (options && options.type === 'module') {{
+                        return new OriginalWorker(url, options);
+                    }}
+
+                    // the page patches expect a window, don't let them abort the worker
+                    const injectedCode = `try {{ {script} }} catch (e) {{}}
+`;
                     const workerPromise = fetch(url)
-                        .then((res) => res.text())
+                        .then((res) => {{
+                            if (!res.ok) throw new Error(res.statusText);
+                            return res.text();
+                        }})
                         .then((code) => {{
                             const blob = new Blob([injectedCode + code], {{
                                 type: "application/javascript",
                             }});
                             return new OriginalWorker(URL.createObjectURL(blob), options);
-                        }});
+                        }})
+                        .catch(() => new OriginalWorker(url, options));
 
-                    
-                        let realWorker = null;
-                        const pendingMessages = [];
-                        workerPromise.then((w) => {{
-                            realWorker = w;
-                            pendingMessages.forEach((msg) => w.postMessage(msg));
-                        }});
-                        return {{
-                            postMessage(msg) {{
+                    let realWorker = null;
+                    const pendingMessages = [];
+                    workerPromise.then((w) => {{
+                        realWorker = w;
+                        pendingMessages.forEach((msg) => w.postMessage(...msg));
+                    }});
+                    return {{
+                        postMessage(...msg) {{
                             if (realWorker) {{
-                                realWorker.postMessage(msg);
+                                realWorker.postMessage(...msg);
                             }} else {{
                                 pendingMessages.push(msg);
                             }}
                         }},
-                            set onmessage(fn) {{
-                                workerPromise.then((w) => (w.onmessage = fn));
-                            }},
-                            terminate() {{
-                                workerPromise.then((w) => w.terminate());
-                            }},
-                        }};
+                        set onmessage(fn) {{
+                            workerPromise.then((w) => (w.onmessage = fn));
+                        }},
+                        set onerror(fn) {{
+                            workerPromise.then((w) => (w.onerror = fn));
+                        }},
+                        addEventListener(...args) {{
+                            workerPromise.then((w) => w.addEventListener(...args));
+                        }},
+                        removeEventListener(...args) {{
+                            workerPromise.then((w) => w.removeEventListener(...args));
+                        }},
+                        terminate() {{
+                            workerPromise.then((w) => w.terminate());
+                        }},
+                    }};
                 }};
             "#,
+            // embedded in a template literal
             script = script
-        );
+                .replace('\\', "\\\\")
+                .replace('`', "\\`")
+                .replace("${", "\\${")
+        )
+    }
+}
 
-        script.push_str(&worker_script);
-        script
+/// Keywords after which a `/` starts a regular expression rather than a
+/// division
+const REGEX_KEYWORDS: &[&str] = &[
+    "await",
+    "case",
+    "delete",
+    "do",
+    "else",
+    "in",
+    "instanceof",
+    "new",
+    "of",
+    "return",
+    "throw",
+    "typeof",
+    "void",
+    "yield",
+];
+
+/// Check that `js` closes every bracket, string, template literal, comment
+/// and regular expression it opens, and nothing else.
+///
+/// This is a tokenizer, not a parser: it catches scripts that would break
+/// out of the bootstrap script, not every syntax error.
+fn check_script(js: &str) -> Result<(), String> {
+    let chars: Vec<char> = js.chars().collect();
+    // the expected closing brackets, a backtick while inside a template
+    let mut open: Vec<char> = Vec::new();
+    let mut regex_allowed = true;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if open.last() == Some(&'`') {
+            match c {
+                '\\' => i += 1,
+                '`' => {
+                    open.pop();
+                    regex_allowed = false;
+                }
+                '$' if chars.get(i + 1) == Some(&'{') => {
+                    open.push('}');
+                    regex_allowed = true;
+                    i += 1;
+                }
+                _ => {}
+            }
+            i += 1;
+            continue;
+        }
+        match c {
+            c if c.is_whitespace() => {}
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                let end = (i + 2..chars.len().saturating_sub(1))
+                    .find(|&j| chars[j] == '*' && chars[j + 1] == '/')
+                    .ok_or("unterminated comment")?;
+                i = end + 1;
+            }
+            '/' if regex_allowed => {
+                let mut class = false;
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None | Some('\n') => return Err("unterminated regular expression".into()),
+                        Some('\\') => i += 1,
+                        Some('[') => class = true,
+                        Some(']') => class = false,
+                        Some('/') if !class => break,
+                        _ => {}
+                    }
+                    i += 1;
+                }
+                regex_allowed = false;
+            }
+            '\'' | '"' => {
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None | Some('\n') => return Err("unterminated string".into()),
+                        Some('\\') => i += 1,
+                        Some(&q) if q == c => break,
+                        _ => {}
+                    }
+                    i += 1;
+                }
+                regex_allowed = false;
+            }
+            '`' => open.push('`'),
+            '(' | '[' | '{' => {
+                open.push(match c {
+                    '(' => ')',
+                    '[' => ']',
+                    _ => '}',
+                });
+                regex_allowed = true;
+            }
+            ')' | ']' | '}' => {
+                if open.pop() != Some(c) {
+                    return Err(format!("unexpected `{c}`"));
+                }
+                // `}` also ends a block, after which a regex may follow
+                regex_allowed = c == '}';
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '$' => {
+                let start = i;
+                while chars
+                    .get(i + 1)
+                    .is_some_and(|&c| c.is_alphanumeric() || c == '_' || c == '$')
+                {
+                    i += 1;
+                }
+                let word: String = chars[start..=i].iter().collect();
+                regex_allowed = REGEX_KEYWORDS.contains(&word.as_str());
+            }
+            _ => regex_allowed = true,
+        }
+        i += 1;
+    }
+    match open.last() {
+        None => Ok(()),
+        Some('`') => Err("unterminated template literal".into()),
+        Some(close) => Err(format!("missing `{close}`")),
     }
 }
 
@@ -512,14 +2351,34 @@ pub struct ChaserProfileBuilder {
     cpu_cores: u32,
     locale: String,
     timezone: String,
+    geolocation: Option<(f64, f64)>,
     screen_width: u32,
     screen_height: u32,
+    device_pixel_ratio: f64,
+    touch_points: u32,
+    grease: Option<(String, String)>,
+    build: Option<&'static RealBuild>,
+    bypass_csp: bool,
+    color_scheme: ColorScheme,
+    reduced_motion: bool,
+    extended_screen: bool,
+    notification_permission: NotificationPermission,
+    mobile: bool,
+    model: String,
+    vendor: Option<String>,
+    app_version: Option<String>,
+    webgl_vendor: Option<String>,
+    webgl_renderer: Option<String>,
+    jitter: Option<u64>,
 }
 
 impl ChaserProfileBuilder {
-    /// Set the Chrome version (default: 129)
+    /// Set the Chrome version (default: 129).
+    ///
+    /// This drops a build selected with [`ChaserProfile::from_real_build`].
     pub fn chrome_version(mut self, version: u32) -> Self {
         self.chrome_version = version;
+        self.build = None;
         self
     }
 
@@ -541,6 +2400,20 @@ impl ChaserProfileBuilder {
         self
     }
 
+    /// Pick the CPU cores and memory from common machines by `seed`, instead
+    /// of the same 8 cores and 8 GB for every profile of a fleet.
+    ///
+    /// Cores and memory are picked together, e.g. 4 cores with 4 or 8 GB
+    /// but 16 cores with 16 or 32 GB, of which `navigator.deviceMemory`
+    /// reports at most 8. Phones and tablets ([`ChaserProfileBuilder::mobile`])
+    /// get 6 or 8 cores. The same seed always picks the same machine. This
+    /// replaces [`ChaserProfileBuilder::cpu_cores`] and
+    /// [`ChaserProfileBuilder::memory_gb`].
+    pub fn jitter(mut self, seed: u64) -> Self {
+        self.jitter = Some(seed);
+        self
+    }
+
     /// Set the locale (e.g., "en-US", "de-DE")
     pub fn locale(mut self, locale: impl Into<String>) -> Self {
         self.locale = locale.into();
@@ -553,28 +2426,695 @@ impl ChaserProfileBuilder {
         self
     }
 
-    /// Set screen resolution
+    /// Set the `latitude` and `longitude` reported by the Geolocation API
+    /// (default: the main city of the [timezone](Self::timezone))
+    pub fn geolocation(mut self, latitude: f64, longitude: f64) -> Self {
+        self.geolocation = Some((latitude, longitude));
+        self
+    }
+
+    /// Set screen resolution in CSS pixels (default: [`Os::screen`])
     pub fn screen(mut self, width: u32, height: u32) -> Self {
         self.screen_width = width;
         self.screen_height = height;
         self
     }
 
+    /// Set `window.devicePixelRatio` (default: [`Os::device_pixel_ratio`]),
+    /// e.g. 1 for a Mac driving an external non-Retina display
+    pub fn device_pixel_ratio(mut self, ratio: f64) -> Self {
+        self.device_pixel_ratio = ratio;
+        self
+    }
+
+    /// Set `navigator.maxTouchPoints` (default: 5 for [mobile](Os::is_mobile)
+    /// OSes, else 0). Use 5 or 10 for mobile devices and touchscreen laptops.
+    ///
+    /// [`ChaserPage::apply_profile`](crate::ChaserPage::apply_profile) enables
+    /// touch emulation for a nonzero value, so touch events are exposed too.
+    pub fn touch_points(mut self, points: u32) -> Self {
+        self.touch_points = points;
+        self
+    }
+
+    /// Override the GREASE brand and version (e.g. `"Not=A?Brand"`, `"8"`)
+    /// instead of deriving them from the Chrome version, for versions that
+    /// changed the scheme.
+    pub fn grease_brand(mut self, brand: impl Into<String>, version: impl Into<String>) -> Self {
+        self.grease = Some((brand.into(), version.into()));
+        self
+    }
+
+    /// Bypass the Content-Security-Policy of pages when the profile is
+    /// applied (default: false), so the bootstrap script and its `blob:`
+    /// workers run under a strict CSP.
+    ///
+    /// Opt-in, as a page can detect that its CSP isn't enforced.
+    pub fn bypass_csp(mut self, enabled: bool) -> Self {
+        self.bypass_csp = enabled;
+        self
+    }
+
+    /// Set `prefers-color-scheme` (default: [`ColorScheme::Light`])
+    pub fn color_scheme(mut self, scheme: ColorScheme) -> Self {
+        self.color_scheme = scheme;
+        self
+    }
+
+    /// Report `prefers-reduced-motion: reduce` (default: false), as
+    /// accessibility-minded users often do
+    pub fn reduced_motion(mut self, reduced: bool) -> Self {
+        self.reduced_motion = reduced;
+        self
+    }
+
+    /// Report a second monitor next to the main one (default: false), via
+    /// `screen.isExtended` and `window.getScreenDetails()`. Both screens
+    /// have the profile's resolution.
+    pub fn extended_screen(mut self, extended: bool) -> Self {
+        self.extended_screen = extended;
+        self
+    }
+
+    /// Set the notification permission (default:
+    /// [`NotificationPermission::Default`]), e.g. to pose as a user who
+    /// already allowed or blocked them. `Notification.permission`,
+    /// `Notification.requestPermission()` and
+    /// `navigator.permissions.query({ name: 'notifications' })` all agree on it.
+    pub fn notification_permission(mut self, permission: NotificationPermission) -> Self {
+        self.notification_permission = permission;
+        self
+    }
+
+    /// Report a mobile device (default: [`Os::is_mobile`]) in the client hints and
+    /// expose the device orientation and motion events, see
+    /// [`ChaserPage::set_device_orientation`](crate::ChaserPage::set_device_orientation).
+    /// Combine with [`ChaserProfileBuilder::touch_points`].
+    pub fn mobile(mut self, mobile: bool) -> Self {
+        self.mobile = mobile;
+        self
+    }
+
+    /// Set the device model of the `model` client hint (default: empty, as
+    /// desktops report), e.g. `"Pixel 7"` for an [Android](Os::Android)
+    /// profile
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Override `navigator.vendor` (default: [`ChaserProfile::vendor`]), e.g.
+    /// `""` to imitate Firefox
+    pub fn vendor(mut self, vendor: impl Into<String>) -> Self {
+        self.vendor = Some(vendor.into());
+        self
+    }
+
+    /// Override `navigator.appVersion` (default: derived from the user agent,
+    /// see [`ChaserProfile::app_version`])
+    pub fn app_version(mut self, version: impl Into<String>) -> Self {
+        self.app_version = Some(version.into());
+        self
+    }
+
+    /// Override the unmasked WebGL vendor (default: the vendor of the
+    /// [`Gpu`] on the OS, see [`Gpu::vendor_for`]), e.g. to copy a string
+    /// seen on a real device
+    pub fn webgl_vendor(mut self, vendor: impl Into<String>) -> Self {
+        self.webgl_vendor = Some(vendor.into());
+        self
+    }
+
+    /// Override the unmasked WebGL renderer (default: the renderer of the
+    /// [`Gpu`] on the OS, see [`Gpu::renderer_for`]). The WebGL limits and
+    /// extensions still follow the `Gpu`.
+    pub fn webgl_renderer(mut self, renderer: impl Into<String>) -> Self {
+        self.webgl_renderer = Some(renderer.into());
+        self
+    }
+
     /// Build the final profile
     pub fn build(self) -> ChaserProfile {
+        let (cpu_cores, memory_gb) = match self.jitter {
+            Some(seed) => jittered_hardware(seed, self.mobile),
+            None => (self.cpu_cores, self.memory_gb),
+        };
         ChaserProfile {
             os: self.os,
             chrome_version: self.chrome_version,
             gpu: self.gpu,
-            memory_gb: self.memory_gb,
-            cpu_cores: self.cpu_cores,
+            memory_gb,
+            cpu_cores,
             locale: self.locale,
             timezone: self.timezone,
+            geolocation: self.geolocation,
             screen_width: self.screen_width,
             screen_height: self.screen_height,
+            device_pixel_ratio: self.device_pixel_ratio,
+            touch_points: self.touch_points,
+            grease: self.grease,
+            build: self.build,
+            bypass_csp: self.bypass_csp,
+            color_scheme: self.color_scheme,
+            reduced_motion: self.reduced_motion,
+            extended_screen: self.extended_screen,
+            notification_permission: self.notification_permission,
+            mobile: self.mobile,
+            model: self.model,
+            vendor: self.vendor,
+            app_version: self.app_version,
+            webgl_vendor: self.webgl_vendor,
+            webgl_renderer: self.webgl_renderer,
+            extra_scripts: Vec::new(),
         }
     }
 }
 
 // Re-export the old trait-based system for backwards compatibility
 pub use crate::stealth::{LinuxProfile, MacOSProfile, StealthProfile, WindowsNvidiaProfile};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn macos_arm_reports_arm_architecture() {
+        let profile = ChaserProfile::macos_arm().build();
+        let metadata = profile.user_agent_metadata();
+        assert_eq!(metadata.architecture, "arm");
+        assert_eq!(metadata.platform, "macOS");
+
+        let script = profile.bootstrap_script();
+        assert!(script.contains(r#"values.architecture = "arm""#));
+    }
+
+    #[test]
+    fn macos_arm_reports_macos_platform_version() {
+        let profile = ChaserProfile::macos_arm().build();
+        let version = profile.user_agent_metadata().platform_version;
+        let major: u32 = version
+            .split('.')
+            .next()
+            .and_then(|major| major.parse().ok())
+            .expect("platform version should start with a major version");
+        // macOS 11 (Big Sur) is the first release on Apple Silicon
+        assert!(
+            (11..=26).contains(&major),
+            "unexpected macOS version {version}"
+        );
+        assert_eq!(version.split('.').count(), 3);
+
+        let script = profile.bootstrap_script();
+        assert!(script.contains(&format!(r#"values.platformVersion = "{version}""#)));
+    }
+
+    #[test]
+    fn media_features_follow_profile() {
+        let features = |profile: ChaserProfile| {
+            profile
+                .media_features()
+                .into_iter()
+                .map(|f| (f.name, f.value))
+                .collect::<Vec<_>>()
+        };
+        let pair = |name: &str, value: &str| (name.to_string(), value.to_string());
+        assert_eq!(
+            features(ChaserProfile::windows().build()),
+            [
+                pair("prefers-color-scheme", "light"),
+                pair("prefers-reduced-motion", "no-preference")
+            ]
+        );
+        let profile = ChaserProfile::macos_arm()
+            .color_scheme(ColorScheme::Dark)
+            .reduced_motion(true)
+            .build();
+        assert_eq!(
+            features(profile),
+            [
+                pair("prefers-color-scheme", "dark"),
+                pair("prefers-reduced-motion", "reduce")
+            ]
+        );
+    }
+
+    #[test]
+    fn extended_screen_is_injected() {
+        let script = ChaserProfile::windows().build().page_script();
+        assert!(script.contains("isExtended: false"));
+        assert!(script.contains("if (false) screens.push(detailed(1920"));
+
+        let script = ChaserProfile::windows()
+            .screen(2560, 1440)
+            .extended_screen(true)
+            .build()
+            .page_script();
+        assert!(script.contains("isExtended: true"));
+        assert!(script.contains("if (true) screens.push(detailed(2560"));
+    }
+
+    #[test]
+    fn notification_permission_is_injected() {
+        let script = ChaserProfile::windows().build().page_script();
+        assert!(script.contains(r#"const notificationPermission = "default";"#));
+        assert!(script.contains(r#"get: () => "prompt","#));
+
+        let script = ChaserProfile::windows()
+            .notification_permission(NotificationPermission::Denied)
+            .build()
+            .page_script();
+        assert!(script.contains(r#"const notificationPermission = "denied";"#));
+        assert!(script.contains(r#"get: () => "denied","#));
+    }
+
+    #[test]
+    fn jitter_picks_hardware_by_seed() {
+        let hardware = |profile: ChaserProfile| (profile.cpu_cores(), profile.memory_gb());
+        let windows = |seed| hardware(ChaserProfile::windows().jitter(seed).build());
+        assert_eq!(hardware(ChaserProfile::windows().build()), (8, 8));
+        assert_eq!(windows(42), windows(42));
+        assert_ne!(windows(1), windows(3));
+
+        let mut picked = Vec::new();
+        for seed in 0..64 {
+            let profile = ChaserProfile::windows().jitter(seed).build();
+            assert!(
+                profile.coherence().issues.is_empty(),
+                "{}",
+                profile.coherence()
+            );
+            let (cores, memory) = hardware(profile);
+            assert!([4, 6, 8, 12, 16].contains(&cores));
+            // only the 4 core machines may have less than 8 GB
+            assert!(memory == 8 || (cores == 4 && memory == 4));
+            if !picked.contains(&(cores, memory)) {
+                picked.push((cores, memory));
+            }
+        }
+        assert_eq!(picked.len(), 6);
+
+        for seed in 0..16 {
+            let profile = ChaserProfile::android().jitter(seed).build();
+            assert!(
+                profile.coherence().issues.is_empty(),
+                "{}",
+                profile.coherence()
+            );
+            assert!([6, 8].contains(&profile.cpu_cores()));
+        }
+    }
+
+    #[test]
+    fn windows_reports_x86_architecture() {
+        let profile = ChaserProfile::windows().build();
+        assert_eq!(profile.user_agent_metadata().architecture, "x86");
+        assert_eq!(profile.user_agent_metadata().platform_version, "19.0.0");
+    }
+
+    #[test]
+    fn webgl_params_match_gpu_backend() {
+        let nvidia = Gpu::NvidiaRTX3080.webgl_params();
+        assert_eq!(nvidia.max_viewport_dims, [32767, 32767]);
+        assert!(nvidia.extensions.contains(&"WEBGL_debug_renderer_info"));
+        assert!(!nvidia.extensions.contains(&"WEBGL_compressed_texture_astc"));
+
+        let apple = Gpu::AppleM1Pro.webgl_params();
+        assert_eq!(apple.max_viewport_dims, [16384, 16384]);
+        assert!(apple.extensions.contains(&"WEBGL_compressed_texture_astc"));
+        assert!(apple
+            .webgl2_extensions
+            .contains(&"WEBGL_compressed_texture_astc"));
+    }
+
+    #[test]
+    fn bootstrap_script_spoofs_webgl_params() {
+        let profile = ChaserProfile::windows().gpu(Gpu::NvidiaRTX4080).build();
+        let script = profile.bootstrap_script();
+        assert!(script.contains(Gpu::NvidiaRTX4080.renderer()));
+        assert!(script.contains("3379: 16384"));
+        assert!(script.contains("3386: [Int32Array, [32767, 32767]]"));
+        assert!(script.contains(r#""EXT_texture_filter_anisotropic""#));
+        assert!(script.contains("getShaderPrecisionFormat"));
+    }
+
+    #[test]
+    fn webgl_strings_dont_break_the_script() {
+        let renderer = "ANGLE (`Evil`, ${x} 'GPU' \"v2\" \\ Direct3D11)";
+        let profile = ChaserProfile::windows()
+            .webgl_vendor("Google Inc. (O'Brien)")
+            .webgl_renderer(renderer)
+            .build();
+        assert_eq!(profile.webgl_renderer(), renderer);
+        let script = profile.try_bootstrap_script().unwrap();
+        assert!(script.contains(&format!(
+            "return {};",
+            serde_json::to_string(renderer).unwrap()
+        )));
+        assert!(script.contains(r#"return "Google Inc. (O'Brien)";"#));
+        // balanced, including the copy in the worker shim's template literal
+        check_script(&script).unwrap();
+        check_script(&ChaserProfile::android().build().bootstrap_script()).unwrap();
+    }
+
+    #[test]
+    fn try_bootstrap_script_rejects_malformed_extra_scripts() {
+        let malformed = |js: &str| {
+            let mut profile = ChaserProfile::windows().build();
+            profile.with_extra_script("window.ok = 1;").unwrap();
+            profile.with_extra_script(js).unwrap();
+            profile.try_bootstrap_script().unwrap_err()
+        };
+        assert_eq!(
+            malformed("}); (function() {"),
+            MalformedScript {
+                index: 1,
+                reason: "unexpected `}`".into()
+            }
+        );
+        assert_eq!(malformed("if (a) {").reason, "missing `}`");
+        assert_eq!(malformed("const s = 'open;").reason, "unterminated string");
+        assert_eq!(
+            malformed("const t = `${a}").reason,
+            "unterminated template literal"
+        );
+        assert_eq!(malformed("/* never closed").reason, "unterminated comment");
+
+        for js in [
+            "const re = /[}{(]/g; const half = a / 2 / b;",
+            "const t = `a ${ {b: `c${d}`}.b } e`; // } ) ]",
+            "if (x) { return /\\//.test(y); }",
+            "const s = \"it's\" + '\\'' + \"}\";",
+        ] {
+            let mut profile = ChaserProfile::windows().build();
+            profile.with_extra_script(js).unwrap();
+            assert!(profile.try_bootstrap_script().is_ok(), "{js}");
+        }
+    }
+
+    #[test]
+    fn bootstrap_script_reports_profile_touch_points() {
+        let desktop = ChaserProfile::windows().build();
+        assert_eq!(desktop.touch_points(), 0);
+        assert!(desktop
+            .bootstrap_script()
+            .contains("return 0 || maxTouchPoints"));
+
+        let touch = ChaserProfile::windows().touch_points(10).build();
+        assert!(touch
+            .bootstrap_script()
+            .contains("return 10 || maxTouchPoints"));
+    }
+
+    #[test]
+    fn navigator_identity_matches_chrome() {
+        let windows = ChaserProfile::windows().build();
+        assert_eq!(windows.vendor(), "Google Inc.");
+        assert_eq!(
+            windows.app_version(),
+            "5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) \
+             Chrome/129.0.0.0 Safari/537.36"
+        );
+        let script = windows.bootstrap_script();
+        assert!(script.contains(r#"vendor: "Google Inc.""#));
+        assert!(script.contains(&format!("appVersion: \"{}\"", windows.app_version())));
+        assert!(script.contains("appName: 'Netscape'"));
+        assert!(script.contains("product: 'Gecko'"));
+
+        let mac = ChaserProfile::macos_arm().build();
+        assert!(mac
+            .app_version()
+            .starts_with("5.0 (Macintosh; Intel Mac OS X 10_15_7)"));
+        assert_eq!(
+            ChaserProfile::ios().build().vendor(),
+            "Apple Computer, Inc."
+        );
+
+        let custom = ChaserProfile::linux()
+            .vendor("")
+            .app_version("5.0 (X11)")
+            .build();
+        assert_eq!(custom.vendor(), "");
+        assert!(custom
+            .bootstrap_script()
+            .contains(r#"appVersion: "5.0 (X11)""#));
+    }
+
+    #[test]
+    fn renderer_matches_the_backend_of_the_os() {
+        let profile = ChaserProfile::linux().gpu(Gpu::NvidiaRTX3080).build();
+        let renderer = profile.webgl_renderer();
+        assert!(renderer.contains("OpenGL"), "{renderer}");
+        assert!(!renderer.contains("Direct3D"), "{renderer}");
+        assert_eq!(profile.webgl_vendor(), "Google Inc. (NVIDIA Corporation)");
+        assert!(profile
+            .bootstrap_script()
+            .contains(&serde_json::to_string(renderer).unwrap()));
+
+        let backend = |os, gpu: Gpu| gpu.renderer_for(os);
+        assert!(backend(Os::Windows, Gpu::NvidiaRTX3080).contains("Direct3D11"));
+        assert!(backend(Os::MacOSIntel, Gpu::IntelUHD630).contains("Metal"));
+        assert!(backend(Os::MacOSArm, Gpu::AppleM1Pro).contains("Metal"));
+        assert_eq!(
+            backend(Os::Android, Gpu::Adreno740),
+            Gpu::Adreno740.renderer()
+        );
+        // the OS-agnostic strings are unchanged
+        assert!(Gpu::NvidiaRTX3080.renderer().contains("Direct3D11"));
+        assert_eq!(Gpu::NvidiaRTX3080.vendor(), "Google Inc. (NVIDIA)");
+    }
+
+    #[test]
+    fn presets_are_coherent() {
+        let presets = [
+            ChaserProfile::windows(),
+            ChaserProfile::macos_arm(),
+            ChaserProfile::linux(),
+            ChaserProfile::android(),
+            ChaserProfile::ios(),
+        ];
+        for profile in presets.map(|b| b.build()) {
+            let report = profile.coherence();
+            assert!(report.issues.is_empty(), "{profile}: {report}");
+        }
+
+        // the Apple GPU of the Intel Mac preset is only a warning
+        let report = ChaserProfile::macos_intel().build().coherence();
+        assert!(report.is_coherent(), "{report}");
+        assert_eq!(report.warnings().count(), 1);
+        for id in ChaserProfile::real_builds() {
+            let report = ChaserProfile::from_real_build(id)
+                .unwrap()
+                .build()
+                .coherence();
+            assert!(report.is_coherent(), "{id}: {report}");
+        }
+    }
+
+    #[test]
+    fn incoherent_profile_reports_each_issue() {
+        let profile = ChaserProfile::windows()
+            .gpu(Gpu::AppleM4Max)
+            .cpu_cores(2)
+            .memory_gb(3)
+            .mobile(true)
+            .model("Pixel 7")
+            .app_version("5.0 (X11)")
+            .build();
+        let report = profile.coherence();
+        assert!(!report.is_coherent());
+        let issues = report
+            .issues
+            .iter()
+            .map(|issue| (issue.severity, issue.fields))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            issues,
+            [
+                (Severity::Error, &["gpu", "os"][..]),
+                (Severity::Error, &["memory_gb"]),
+                (Severity::Warning, &["cpu_cores", "gpu"]),
+                (Severity::Error, &["mobile", "os"]),
+                (Severity::Error, &["model", "os"]),
+                (Severity::Error, &["app_version"]),
+            ]
+        );
+        assert_eq!(report.issues[0].message, "AppleM4Max doesn't run Windows");
+        assert_eq!(
+            report.to_string().lines().nth(1),
+            Some("error (memory_gb): navigator.deviceMemory is 1, 2, 4 or 8, not 3")
+        );
+
+        // the Windows strings on Linux
+        let report = ChaserProfile::linux()
+            .webgl_vendor(Gpu::NvidiaGTX1660.vendor())
+            .webgl_renderer(Gpu::NvidiaGTX1660.renderer())
+            .build()
+            .coherence();
+        let fields = report
+            .errors()
+            .map(|issue| issue.fields)
+            .collect::<Vec<_>>();
+        assert_eq!(fields, [&["webgl_renderer", "os"]]);
+    }
+
+    #[test]
+    fn real_build_reports_its_grease_brand() {
+        let profile = ChaserProfile::from_real_build("131.0.6778.86-windows")
+            .unwrap()
+            .build();
+        assert_eq!(profile.chrome_version(), 131);
+        assert_eq!(
+            profile.sec_ch_ua(),
+            r#""Google Chrome";v="131", "Chromium";v="131", "Not_A Brand";v="24""#
+        );
+        let full = profile.full_version_list();
+        assert_eq!(full[0].version, "131.0.6778.86");
+        assert_eq!(full[2].version, "24.0.0.0");
+
+        let profile = ChaserProfile::from_real_build("130.0.6723.117-macos-arm")
+            .unwrap()
+            .build();
+        assert!(matches!(profile.os(), Os::MacOSArm));
+        assert_eq!(
+            profile.sec_ch_ua(),
+            r#""Chromium";v="130", "Google Chrome";v="130", "Not?A_Brand";v="99""#
+        );
+        assert!(profile.user_agent().contains("Chrome/130.0.0.0"));
+        assert_eq!(profile.user_agent_metadata().platform_version, "15.1.0");
+        assert!(profile
+            .bootstrap_script()
+            .contains(r#"values.uaFullVersion = "130.0.6723.117""#));
+    }
+
+    #[test]
+    fn real_build_rejects_unknown_builds() {
+        let err = ChaserProfile::from_real_build("999.0.0.0-windows").unwrap_err();
+        assert_eq!(err, UnknownBuild("999.0.0.0-windows".to_string()));
+        assert!(err.to_string().contains("131.0.6778.86-windows"));
+
+        let profile = ChaserProfile::from_real_build("129.0.6668.90-windows")
+            .unwrap()
+            .chrome_version(128)
+            .build();
+        assert_eq!(profile.full_version(), "128.0.0.0");
+        assert_eq!(profile.user_agent_metadata().platform_version, "19.0.0");
+    }
+
+    #[test]
+    fn grease_brand_follows_chrome_version() {
+        assert_eq!(grease_brand(120), ("Not_A Brand".to_string(), "8"));
+        assert_eq!(grease_brand(124), ("Not-A.Brand".to_string(), "99"));
+        assert_eq!(grease_brand(125), ("Not.A/Brand".to_string(), "24"));
+        assert_eq!(grease_brand(129), ("Not=A?Brand".to_string(), "8"));
+        assert_eq!(grease_brand(132), ("Not A(Brand".to_string(), "8"));
+    }
+
+    #[test]
+    fn brands_match_real_builds() {
+        for id in ChaserProfile::real_builds() {
+            let real = ChaserProfile::from_real_build(id).unwrap().build();
+            let derived = ChaserProfile::new(real.os())
+                .chrome_version(real.chrome_version())
+                .build();
+            assert_eq!(real.sec_ch_ua(), derived.sec_ch_ua(), "{id}");
+        }
+    }
+
+    #[test]
+    fn grease_brand_can_be_overridden() {
+        let profile = ChaserProfile::windows()
+            .chrome_version(129)
+            .grease_brand("Not;A Brand", "42")
+            .build();
+        assert_eq!(
+            profile.sec_ch_ua(),
+            r#""Google Chrome";v="129", "Not;A Brand";v="42", "Chromium";v="129""#
+        );
+        assert!(profile
+            .bootstrap_script()
+            .contains(r#"{ brand: "Not;A Brand", version: "42" }"#));
+        let metadata = profile.user_agent_metadata();
+        assert_eq!(metadata.full_version_list.unwrap()[1].version, "42.0.0.0");
+    }
+
+    #[test]
+    fn page_script_skips_worker_shim() {
+        let profile = ChaserProfile::windows().build();
+        assert!(!profile.page_script().contains("OriginalWorker"));
+
+        let script = profile.bootstrap_script();
+        assert!(script.starts_with(&profile.page_script()));
+        assert!(script.contains("options.type === 'module'"));
+    }
+
+    #[test]
+    fn extra_scripts_run_after_core_patches() {
+        let mut profile = ChaserProfile::windows().build();
+        assert_eq!(profile.with_extra_script(" \n").unwrap_err(), EmptyScript);
+
+        profile
+            .with_extra_script("window.first = `${1}`; // trailing")
+            .unwrap()
+            .with_extra_script("window.second = 2;")
+            .unwrap();
+        let script = profile.page_script();
+        let core = script.find("HARDWARE HARMONY").unwrap();
+        let first = script.find("window.first").unwrap();
+        let second = script.find("window.second").unwrap();
+        assert!(core < first && first < second);
+        assert!(script.contains("// trailing\n;window.second"));
+
+        // escaped for the template literal of the worker shim
+        let shim = &profile.bootstrap_script()[script.len()..];
+        assert!(shim.contains(r"window.first = \`\${1}\`;"));
+    }
+
+    #[test]
+    fn macos_arm_defaults_to_retina_screen() {
+        let profile = ChaserProfile::macos_arm().build();
+        assert_eq!(profile.device_pixel_ratio(), 2.0);
+        assert_eq!(
+            (profile.screen_width(), profile.screen_height()),
+            (1512, 982)
+        );
+        let script = profile.page_script();
+        assert!(script.contains("get: () => 2,"));
+        assert!(script.contains("width: 1512, height: 982"));
+        assert!(script.contains("availHeight: 957"));
+
+        let windows = ChaserProfile::windows().build();
+        assert_eq!(windows.device_pixel_ratio(), 1.0);
+        assert_eq!(
+            (windows.screen_width(), windows.screen_height()),
+            (1920, 1080)
+        );
+
+        let external = ChaserProfile::macos_arm()
+            .screen(2560, 1440)
+            .device_pixel_ratio(1.0)
+            .build();
+        assert!(external.page_script().contains("width: 2560, height: 1440"));
+        assert_eq!(external.device_pixel_ratio(), 1.0);
+    }
+
+    #[test]
+    fn geolocation_follows_timezone() {
+        let (lat, lon) = timezone_to_coords("Europe/Berlin").unwrap();
+        assert!((52.0..53.0).contains(&lat) && (13.0..14.0).contains(&lon));
+        let (lat, lon) = timezone_to_coords("america/los_angeles").unwrap();
+        assert!((33.0..35.0).contains(&lat) && (-119.0..-118.0).contains(&lon));
+        let (lat, lon) = timezone_to_coords("Australia/Sydney").unwrap();
+        assert!(lat < -33.0 && lon > 151.0);
+        assert_eq!(timezone_to_coords("Mars/Olympus_Mons"), None);
+
+        let profile = ChaserProfile::windows().timezone("Asia/Tokyo").build();
+        assert_eq!(profile.geolocation(), timezone_to_coords("Asia/Tokyo"));
+        let profile = ChaserProfile::windows()
+            .timezone("Asia/Tokyo")
+            .geolocation(34.6937, 135.5023)
+            .build();
+        assert_eq!(profile.geolocation(), Some((34.6937, 135.5023)));
+        let profile = ChaserProfile::windows().timezone("Etc/GMT+5").build();
+        assert_eq!(profile.geolocation(), None);
+    }
+}
@@ -0,0 +1,272 @@
+//! Gap detection for [`ChaserPage::solve_slider`](crate::ChaserPage::solve_slider).
+//!
+//! Works on the PNG screenshots chromium takes, with a minimal decoder for
+//! 8-bit non-interlaced images instead of an image processing dependency.
+
+use std::io::Read;
+
+use anyhow::{anyhow, Result};
+use flate2::read::ZlibDecoder;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// How much stronger than the average candidate the gap's edges have to be
+const MIN_CONTRAST: f64 = 2.5;
+
+/// The average luminance difference per row the gap's edges need at least
+const MIN_EDGE: f64 = 8.0;
+
+/// A grayscale image, one luminance value (0 to 255) per pixel
+#[derive(Debug)]
+pub(crate) struct Luma {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<f64>,
+}
+
+/// A box of pixels, `x1` and `y1` are exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PixelBox {
+    pub x0: usize,
+    pub y0: usize,
+    pub x1: usize,
+    pub y1: usize,
+}
+
+impl Luma {
+    /// Decode an 8-bit, non-interlaced grayscale or truecolor PNG
+    pub fn from_png(png: &[u8]) -> Result<Self> {
+        let mut data = png
+            .strip_prefix(PNG_SIGNATURE)
+            .ok_or_else(|| anyhow!("not a PNG image"))?;
+        let mut header = None;
+        let mut compressed = Vec::new();
+        while data.len() >= 12 {
+            let len = u32::from_be_bytes(data[..4].try_into().unwrap()) as usize;
+            let kind = &data[4..8];
+            let chunk = data
+                .get(8..8 + len)
+                .ok_or_else(|| anyhow!("truncated PNG chunk"))?;
+            match kind {
+                b"IHDR" if len >= 13 => header = Some(chunk),
+                b"IDAT" => compressed.extend_from_slice(chunk),
+                b"IEND" => break,
+                _ => {}
+            }
+            // skip the chunk and its CRC
+            data = &data[(12 + len).min(data.len())..];
+        }
+        let header = header.ok_or_else(|| anyhow!("PNG without header"))?;
+        let width = u32::from_be_bytes(header[..4].try_into().unwrap()) as usize;
+        let height = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+        let (bit_depth, color_type, interlace) = (header[8], header[9], header[12]);
+        let channels = match color_type {
+            0 => 1,
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => return Err(anyhow!("unsupported PNG color type {color_type}")),
+        };
+        if bit_depth != 8 || interlace != 0 {
+            return Err(anyhow!("only 8-bit non-interlaced PNGs are supported"));
+        }
+
+        let stride = width * channels;
+        let mut raw = Vec::with_capacity((stride + 1) * height);
+        ZlibDecoder::new(compressed.as_slice()).read_to_end(&mut raw)?;
+        if raw.len() < (stride + 1) * height {
+            return Err(anyhow!("truncated PNG image data"));
+        }
+
+        let mut samples = vec![0u8; stride * height];
+        for y in 0..height {
+            let filter = raw[y * (stride + 1)];
+            let line = &raw[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+            let (done, rest) = samples.split_at_mut(y * stride);
+            let previous = (y > 0).then(|| &done[(y - 1) * stride..]);
+            let current = &mut rest[..stride];
+            for i in 0..stride {
+                let a = if i >= channels {
+                    current[i - channels]
+                } else {
+                    0
+                };
+                let b = previous.map_or(0, |row| row[i]);
+                let c = match previous {
+                    Some(row) if i >= channels => row[i - channels],
+                    _ => 0,
+                };
+                let predicted = match filter {
+                    0 => 0,
+                    1 => a,
+                    2 => b,
+                    3 => ((a as u16 + b as u16) / 2) as u8,
+                    4 => paeth(a, b, c),
+                    _ => return Err(anyhow!("invalid PNG filter {filter}")),
+                };
+                current[i] = line[i].wrapping_add(predicted);
+            }
+        }
+
+        let pixels = samples
+            .chunks_exact(channels)
+            .map(|px| match channels {
+                1 | 2 => px[0] as f64,
+                _ => 0.299 * px[0] as f64 + 0.587 * px[1] as f64 + 0.114 * px[2] as f64,
+            })
+            .collect();
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    fn at(&self, x: usize, y: usize) -> f64 {
+        self.pixels[y * self.width + x]
+    }
+
+    /// How strongly column `x` differs from column `x - 1` in rows
+    /// `y0..y1`
+    fn vertical_edge(&self, x: usize, y0: usize, y1: usize) -> f64 {
+        (y0..y1)
+            .map(|y| (self.at(x, y) - self.at(x - 1, y)).abs())
+            .sum()
+    }
+
+    /// Find the left edge of the gap the `piece` fits into, searching the
+    /// columns right of the piece up to `search_end`.
+    ///
+    /// The gap is where two strong vertical edges a piece width apart line
+    /// up with the rows of the piece. Returns `None` if no column stands out.
+    pub fn find_gap(&self, piece: PixelBox, search_end: usize) -> Option<usize> {
+        let y0 = piece.y0.min(self.height);
+        let y1 = piece.y1.min(self.height);
+        let width = piece.x1.saturating_sub(piece.x0);
+        let search_end = search_end.min(self.width);
+        // skip the piece's own right edge
+        let start = piece.x1 + 2;
+        if y0 >= y1 || width == 0 || start >= search_end {
+            return None;
+        }
+
+        let edges: Vec<f64> = (0..self.width)
+            .map(|x| match x {
+                0 => 0.0,
+                x => self.vertical_edge(x, y0, y1),
+            })
+            .collect();
+        let scores: Vec<(usize, f64)> = (start..search_end)
+            .map(|x| (x, edges[x] + edges.get(x + width).copied().unwrap_or(0.0)))
+            .collect();
+        let mean = scores.iter().map(|(_, score)| score).sum::<f64>() / scores.len() as f64;
+        let (gap, best) = scores.into_iter().max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+
+        (best >= MIN_EDGE * (y1 - y0) as f64 && best >= MIN_CONTRAST * mean).then_some(gap)
+    }
+}
+
+/// The Paeth predictor of the PNG filter type 4
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    /// Encode RGB pixels as a PNG, alternating the row filters
+    fn encode_png(width: usize, height: usize, rgb: &[u8]) -> Vec<u8> {
+        let stride = width * 3;
+        let mut raw = Vec::new();
+        for y in 0..height {
+            let row = &rgb[y * stride..(y + 1) * stride];
+            let filter = (y % 3) as u8;
+            raw.push(filter);
+            for i in 0..stride {
+                let predicted = match filter {
+                    1 if i >= 3 => row[i - 3],
+                    2 if y > 0 => rgb[(y - 1) * stride + i],
+                    _ => 0,
+                };
+                raw.push(row[i].wrapping_sub(predicted));
+            }
+        }
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&raw).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&(width as u32).to_be_bytes());
+        header.extend_from_slice(&(height as u32).to_be_bytes());
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        let mut png = PNG_SIGNATURE.to_vec();
+        for (kind, data) in [
+            (b"IHDR", header.as_slice()),
+            (b"IDAT", compressed.as_slice()),
+            (b"IEND", &[][..]),
+        ] {
+            png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            png.extend_from_slice(kind);
+            png.extend_from_slice(data);
+            // the decoder doesn't check the CRC
+            png.extend_from_slice(&[0; 4]);
+        }
+        png
+    }
+
+    #[test]
+    fn finds_the_gap_of_a_synthetic_puzzle() {
+        let (width, height) = (320, 120);
+        let piece = PixelBox {
+            x0: 10,
+            y0: 40,
+            x1: 60,
+            y1: 90,
+        };
+        let gap_x = 200;
+        let mut rgb = Vec::with_capacity(width * height * 3);
+        for y in 0..height {
+            for x in 0..width {
+                let in_rows = (piece.y0..piece.y1).contains(&y);
+                // a soft gradient background with a light piece and a dark gap
+                let value = if in_rows && (piece.x0..piece.x1).contains(&x) {
+                    230
+                } else if in_rows && (gap_x..gap_x + 50).contains(&x) {
+                    60
+                } else {
+                    100 + (x * 60 / width) as u8
+                };
+                rgb.extend_from_slice(&[value, value, value / 2]);
+            }
+        }
+
+        let image = Luma::from_png(&encode_png(width, height, &rgb)).unwrap();
+        assert_eq!((image.width, image.height), (width, height));
+        assert_eq!(image.find_gap(piece, width), Some(gap_x));
+        // the piece has to move by the distance of the left edges
+        assert_eq!(gap_x - piece.x0, 190);
+
+        let flat = vec![128; width * height * 3];
+        let image = Luma::from_png(&encode_png(width, height, &flat)).unwrap();
+        assert_eq!(image.find_gap(piece, width), None);
+    }
+}
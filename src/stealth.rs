@@ -49,12 +49,12 @@ pub trait StealthProfile: Send + Sync {
     /// The `navigator.deviceMemory` value (RAM in GB)
     fn device_memory(&self) -> u32;
 
-    /// Client hints brands array
+    /// Client hints brands array, as Chrome 129 reports it
     fn client_hints_brands(&self) -> Vec<(&str, &str)> {
         vec![
             ("Google Chrome", "129"),
+            ("Not=A?Brand", "8"),
             ("Chromium", "129"),
-            ("Not=A?Brand", "24"),
         ]
     }
 
@@ -1,4 +1,8 @@
+use std::future::Future;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use futures::future::{self, Either};
 
 /// Write to file with configured runtime
 pub(crate) async fn write<P: AsRef<Path> + Unpin, C: AsRef<[u8]>>(
@@ -14,6 +18,38 @@ pub(crate) async fn write<P: AsRef<Path> + Unpin, C: AsRef<[u8]>>(
     }
 }
 
+/// Read a file with configured runtime
+pub(crate) async fn read<P: AsRef<Path> + Unpin>(path: P) -> std::io::Result<Vec<u8>> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "async-std-runtime")] {
+            async_std::fs::read(path.as_ref()).await
+        } else if #[cfg(feature = "tokio-runtime")] {
+            tokio::fs::read(path.as_ref()).await
+        }
+    }
+}
+
+/// Sleep with configured runtime
+pub(crate) async fn sleep(duration: Duration) {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "async-std-runtime")] {
+            async_std::task::sleep(duration).await
+        } else if #[cfg(feature = "tokio-runtime")] {
+            tokio::time::sleep(duration).await
+        }
+    }
+}
+
+/// Await `fut` for at most `duration`, `None` if it didn't complete in time
+pub(crate) async fn timeout<F: Future>(duration: Duration, fut: F) -> Option<F::Output> {
+    let fut = std::pin::pin!(fut);
+    let sleep = std::pin::pin!(sleep(duration));
+    match future::select(fut, sleep).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
+
 /// Canonicalize path
 ///
 /// Chromium sandboxing does not support Window UNC paths which are used by Rust
@@ -54,6 +90,16 @@ pub(crate) async fn canonicalize_except_snap(path: PathBuf) -> std::io::Result<P
     })
 }
 
+/// Mixes `seed` with SplitMix64, so that nearby seeds give unrelated values
+/// and a seed gives the same value in every release, unlike the generators
+/// of `rand`
+pub(crate) fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
 pub(crate) mod base64 {
     use base64::engine::general_purpose::STANDARD;
     use base64::{DecodeError, Engine};
@@ -62,6 +108,11 @@ pub(crate) mod base64 {
     pub fn decode<T: AsRef<[u8]>>(input: T) -> Result<Vec<u8>, DecodeError> {
         STANDARD.decode(input)
     }
+
+    /// Encode base64 using the standard alphabet and padding
+    pub fn encode<T: AsRef<[u8]>>(input: T) -> String {
+        STANDARD.encode(input)
+    }
 }
 
 /// Creates a javascript function string as `(<function>)("<param 1>", "<param
@@ -139,4 +190,29 @@ mod tests {
         assert!(is_likely_js_function("((abc), (def)) => {}"));
         assert!(is_likely_js_function("() => Promise.resolve(100 / 25)"));
     }
+
+    async fn assert_sleeps_and_times_out() {
+        let start = std::time::Instant::now();
+        sleep(Duration::from_millis(50)).await;
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        assert_eq!(
+            timeout(Duration::from_secs(5), async { 42 }).await,
+            Some(42)
+        );
+        let never = future::pending::<()>();
+        assert_eq!(timeout(Duration::from_millis(10), never).await, None);
+    }
+
+    #[cfg(feature = "tokio-runtime")]
+    #[tokio::test]
+    async fn sleep_on_tokio() {
+        assert_sleeps_and_times_out().await;
+    }
+
+    #[cfg(feature = "async-std-runtime")]
+    #[async_std::test]
+    async fn sleep_on_async_std() {
+        assert_sleeps_and_times_out().await;
+    }
 }
@@ -0,0 +1,606 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chaser_oxide::cdp::browser_protocol::browser::GetVersionParams;
+use chaser_oxide::cdp::browser_protocol::network::CookieParam;
+use chaser_oxide::cdp::browser_protocol::target::{
+    CreateBrowserContextParams, GetBrowserContextsParams, GetTargetsParams,
+};
+use chaser_oxide::error::CdpError;
+use chaser_oxide::{Browser, BrowserConfig, ChaserPage};
+use futures::future::join_all;
+use futures::StreamExt;
+
+use crate::test;
+
+#[tokio::test]
+async fn test_session_round_trip() {
+    test(async |browser| {
+        let path = std::env::temp_dir().join("chaser-oxide-session-round-trip.json");
+        let page = browser
+            .new_page("https://www.google.com")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page.clone());
+
+        page.set_cookie(CookieParam::new("chaser-session", "cookie-value"))
+            .await
+            .expect("should set cookie");
+        let kv = HashMap::from([("chaser-session".to_string(), "storage-value".to_string())]);
+        chaser
+            .set_local_storage(&kv)
+            .await
+            .expect("should write local storage");
+
+        let exported = browser
+            .export_session(&path)
+            .await
+            .expect("should export session");
+        assert_eq!(
+            exported.version,
+            chaser_oxide::browser::SESSION_FORMAT_VERSION
+        );
+
+        browser.clear_cookies().await.expect("should clear cookies");
+        chaser
+            .evaluate("localStorage.clear()")
+            .await
+            .expect("should clear local storage");
+
+        let imported = browser
+            .import_session(&path)
+            .await
+            .expect("should import session");
+        assert_eq!(imported, exported);
+
+        page.reload().await.expect("should reload the page");
+
+        let cookies = browser.get_cookies().await.expect("should get cookies");
+        assert!(cookies
+            .iter()
+            .any(|c| c.name == "chaser-session" && c.value == "cookie-value"));
+        let local = chaser
+            .local_storage()
+            .await
+            .expect("should read local storage");
+        assert_eq!(local.get("chaser-session"), kv.get("chaser-session"));
+
+        let _ = std::fs::remove_file(path);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_storage_is_restored_once() {
+    test(async |browser| {
+        let path = std::env::temp_dir().join("chaser-oxide-storage-restored-once.json");
+        let url = html_server("<p>session</p>");
+        let page = browser
+            .new_page(url.as_str())
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page.clone());
+
+        let kv = HashMap::from([("chaser-session".to_string(), "storage-value".to_string())]);
+        chaser
+            .set_local_storage(&kv)
+            .await
+            .expect("should write local storage");
+        chaser
+            .set_session_storage(&kv)
+            .await
+            .expect("should write session storage");
+        let exported = browser
+            .export_session(&path)
+            .await
+            .expect("should export session");
+        chaser
+            .evaluate("localStorage.clear(); sessionStorage.clear()")
+            .await
+            .expect("should clear storage");
+
+        browser
+            .import_session(&path)
+            .await
+            .expect("should import session");
+        page.reload().await.expect("should reload the page");
+        let local = chaser
+            .local_storage()
+            .await
+            .expect("should read local storage");
+        let session = chaser
+            .session_storage()
+            .await
+            .expect("should read session storage");
+        assert_eq!(local, kv);
+        assert_eq!(session, kv);
+
+        // nothing but the restored keys is written into the page's storage
+        let snapshot = browser
+            .session_snapshot()
+            .await
+            .expect("should snapshot session");
+        assert_eq!(snapshot.origins, exported.origins);
+
+        // keys the site removes stay removed on later navigations
+        chaser
+            .evaluate("localStorage.clear(); sessionStorage.clear()")
+            .await
+            .expect("should remove the keys");
+        page.reload().await.expect("should reload the page");
+        let local = chaser
+            .local_storage()
+            .await
+            .expect("should read local storage");
+        let session = chaser
+            .session_storage()
+            .await
+            .expect("should read session storage");
+        assert!(local.is_empty());
+        assert!(session.is_empty());
+
+        let _ = std::fs::remove_file(path);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_new_page_concurrently() {
+    test(async |browser| {
+        let pages = join_all((0..16).map(|_| browser.new_page("about:blank"))).await;
+
+        for page in pages {
+            let page = page.expect("should create new page");
+            let sum: u32 = page
+                .evaluate("1 + 1")
+                .await
+                .expect("should evaluate on new page")
+                .into_value()
+                .expect("should be a number");
+            assert_eq!(sum, 2);
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_handles_drive_commands_concurrently() {
+    test(async |browser| {
+        let tasks = (0..2).map(|i| {
+            let handle = browser.handle();
+            tokio::spawn(async move {
+                let mut pages = Vec::new();
+                for _ in 0..4 {
+                    let (page, version) = futures::join!(
+                        handle.new_page("about:blank"),
+                        handle.execute(GetVersionParams::default())
+                    );
+                    let page = page.expect("should create new page");
+                    assert!(!version.unwrap().result.product.is_empty());
+                    page.evaluate(format!("window.task = {i}")).await.unwrap();
+                    pages.push(page.target_id().clone());
+                }
+                (handle.pages().await.unwrap(), pages)
+            })
+        });
+
+        let mut created = Vec::new();
+        for task in join_all(tasks).await {
+            let (pages, ids) = task.expect("handle task should finish");
+            // the other task may still be creating pages
+            assert!(pages.len() >= ids.len());
+            created.extend(ids);
+        }
+        let pages = browser.pages().await.unwrap();
+        for id in &created {
+            assert!(pages.iter().any(|page| page.target_id() == id));
+        }
+        assert_eq!(created.len(), 8);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_first_page_adopts_initial_tab() {
+    test(async |browser| {
+        let page = browser
+            .first_page()
+            .await
+            .expect("should adopt the initial tab");
+        page.goto("data:text/html,<title>first</title>")
+            .await
+            .expect("should navigate the initial tab");
+
+        let targets = browser
+            .execute(GetTargetsParams::default())
+            .await
+            .expect("should list targets")
+            .result
+            .target_infos;
+        let pages: Vec<_> = targets
+            .iter()
+            .filter(|target| target.r#type == "page")
+            .collect();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(&pages[0].target_id, page.target_id());
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_new_pages() {
+    test(async |browser| {
+        let pages = browser
+            .new_pages(16, "about:blank")
+            .await
+            .expect("should create new pages");
+        assert_eq!(pages.len(), 16);
+
+        for page in pages {
+            let url = page.url().await.expect("should read url");
+            assert_eq!(url.as_deref(), Some("about:blank"));
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_close_page_disposes_only_its_target() {
+    test(async |browser| {
+        let open = browser.pages().await.expect("should list pages").len();
+        let pages = browser
+            .new_pages(3, "about:blank")
+            .await
+            .expect("should create new pages");
+
+        browser
+            .close_page(pages[0].target_id().clone())
+            .await
+            .expect("should close the page");
+        let remaining = browser.pages().await.expect("should list pages");
+        assert_eq!(remaining.len(), open + 2);
+        assert!(remaining
+            .iter()
+            .all(|page| page.target_id() != pages[0].target_id()));
+
+        let err = pages[0].url().await.expect_err("closed page should fail");
+        assert!(matches!(err, CdpError::PageClosed), "{err:?}");
+
+        pages[1].close().await.expect("should close the page");
+        let err = pages[1]
+            .clone()
+            .url()
+            .await
+            .expect_err("clone of a closed page should fail");
+        assert!(matches!(err, CdpError::PageClosed), "{err:?}");
+        assert_eq!(
+            browser.pages().await.expect("should list pages").len(),
+            open + 1
+        );
+        pages[1].close().await.expect("should close a closed page");
+        let url = pages[2].url().await.expect("should read url");
+        assert_eq!(url.as_deref(), Some("about:blank"));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_drop_disposes_incognito_context_of_connected_browser() {
+    test(async |browser| {
+        let context_count = async || {
+            browser
+                .execute(GetBrowserContextsParams::default())
+                .await
+                .expect("should list browser contexts")
+                .result
+                .browser_context_ids
+                .len()
+        };
+        let before = context_count().await;
+
+        let (mut connected, mut handler) = Browser::connect(browser.websocket_address())
+            .await
+            .expect("should connect to browser");
+        let handle = tokio::spawn(async move { while handler.next().await.is_some() {} });
+
+        connected
+            .start_incognito_context()
+            .await
+            .expect("should start incognito context");
+        assert_eq!(context_count().await, before + 1);
+
+        // the disposal is queued on drop and sent by the still running handler
+        drop(connected);
+        let disposed = tokio::time::timeout(Duration::from_secs(5), async {
+            while context_count().await != before {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await;
+        handle.abort();
+
+        assert!(disposed.is_ok(), "should dispose the incognito context");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_disposed_incognito_context_is_not_tracked() {
+    test(async |browser| {
+        let (mut connected, mut handler) = Browser::connect(browser.websocket_address())
+            .await
+            .expect("should connect to browser");
+        let handle = tokio::spawn(async move { while handler.next().await.is_some() {} });
+
+        connected
+            .start_incognito_context()
+            .await
+            .expect("should start incognito context");
+        let contexts = connected.contexts();
+        assert_eq!(contexts.len(), 1);
+
+        // the drop must not dispose it a second time
+        connected
+            .dispose_browser_context(contexts[0].clone())
+            .await
+            .expect("should dispose the context");
+        assert!(connected.contexts().is_empty());
+        drop(connected);
+        handle.abort();
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_new_page_in_context_isolates_cookies() {
+    test(async |browser| {
+        let first = browser
+            .create_browser_context(CreateBrowserContextParams::default())
+            .await
+            .expect("should create first context");
+        let second = browser
+            .create_browser_context(CreateBrowserContextParams::default())
+            .await
+            .expect("should create second context");
+        assert_eq!(browser.contexts(), vec![first.clone(), second.clone()]);
+
+        let first_page = browser
+            .new_page_in_context(first.clone(), "https://www.google.com")
+            .await
+            .expect("should create page in first context");
+        let second_page = browser
+            .new_page_in_context(second.clone(), "https://www.google.com")
+            .await
+            .expect("should create page in second context");
+
+        first_page
+            .set_cookie(CookieParam::new("chaser-account", "first"))
+            .await
+            .expect("should set cookie");
+
+        let has_cookie = async |page: &chaser_oxide::Page| {
+            page.get_cookies()
+                .await
+                .expect("should read cookies")
+                .iter()
+                .any(|cookie| cookie.name == "chaser-account")
+        };
+        assert!(has_cookie(&first_page).await);
+        assert!(!has_cookie(&second_page).await);
+
+        // the default context is untouched
+        let default_page = browser
+            .new_page("https://www.google.com")
+            .await
+            .expect("should create page in default context");
+        assert!(!has_cookie(&default_page).await);
+
+        browser
+            .dispose_browser_context(first)
+            .await
+            .expect("should dispose first context");
+        assert_eq!(browser.contexts(), vec![second]);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_idle_timeout_closes_browser() {
+    let config = BrowserConfig::builder()
+        .idle_timeout(Duration::from_secs(1))
+        .build()
+        .unwrap();
+    let (browser, mut handler) = Browser::launch(config)
+        .await
+        .expect("should launch browser");
+    let handle = tokio::spawn(async move { while handler.next().await.is_some() {} });
+
+    // commands keep the browser open
+    for _ in 0..3 {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        browser.version().await.expect("should still be open");
+    }
+
+    tokio::time::timeout(Duration::from_secs(5), handle)
+        .await
+        .expect("handler should end once idle")
+        .unwrap();
+    assert!(browser.version().await.is_err());
+}
+
+/// An HTTP server answering every request with `html`
+fn html_server(html: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("should bind listener");
+    let addr = listener.local_addr().unwrap();
+
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: text/html\r\ncontent-length: {}\r\n\
+                 connection: close\r\n\r\n{html}",
+                html.len()
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn test_wait_for_cookie() {
+    test(async |browser| {
+        let url = html_server(
+            "<script>setTimeout(() => document.cookie = 'session=logged-in; path=/', 500)\
+             </script>",
+        );
+        browser.new_page(url).await.expect("should create new page");
+
+        let cookie = browser
+            .wait_for_cookie("session", Duration::from_secs(10))
+            .await
+            .expect("should wait for the cookie");
+        assert_eq!(cookie.value, "logged-in");
+        assert_eq!(cookie.domain, "127.0.0.1");
+
+        let cookie = browser
+            .wait_for_cookie_on("session", Some("127.0.0.1"), Duration::from_secs(1))
+            .await
+            .expect("should find the cookie of the domain");
+        assert_eq!(cookie.value, "logged-in");
+
+        let err = browser
+            .wait_for_cookie_on("session", Some("example.com"), Duration::from_millis(300))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CdpError::Timeout));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_list_targets_http() {
+    test(async |browser| {
+        let page = browser
+            .new_page("data:text/html,<title>listed</title>")
+            .await
+            .expect("should create new page");
+
+        // ws://127.0.0.1:<port>/devtools/browser/<id>
+        let host = browser
+            .websocket_address()
+            .trim_start_matches("ws://")
+            .split('/')
+            .next()
+            .unwrap()
+            .to_string();
+        let targets = Browser::list_targets_http(&format!("http://{host}"))
+            .await
+            .expect("should list the targets");
+        let target = targets
+            .iter()
+            .find(|target| target.id == page.target_id().as_ref())
+            .expect("should list the page");
+        assert_eq!(target.target_type, "page");
+        assert_eq!(target.title, "listed");
+        assert!(target
+            .web_socket_debugger_url
+            .as_deref()
+            .is_some_and(|url| url.starts_with(&format!("ws://{host}/devtools/page/"))));
+    })
+    .await;
+}
+
+/// A writer for `tracing_subscriber` that collects the logs into a buffer
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_detach_keeps_the_process_running() {
+    let (mut browser, mut handler) = Browser::launch(BrowserConfig::builder().build().unwrap())
+        .await
+        .unwrap();
+    let handle = tokio::spawn(async move { while let Some(Ok(_)) = handler.next().await {} });
+
+    let mut child = browser
+        .detach()
+        .expect("should hand over the launched process");
+    assert!(browser.detach().is_none());
+
+    let logs = Logs::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer({
+            let logs = logs.clone();
+            move || logs.clone()
+        })
+        .finish();
+    {
+        let _guard = tracing::subscriber::set_default(subscriber);
+        drop(browser);
+    }
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    assert!(!logs.contains("not closed"), "{logs}");
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(
+        child.try_wait().unwrap().is_none(),
+        "the browser should still run"
+    );
+    child.kill().await.unwrap();
+    handle.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_wait_for_new_page() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+
+        let (popup, _) = futures::try_join!(
+            browser.wait_for_new_page(Duration::from_secs(10)),
+            page.evaluate("window.open('about:blank'), true"),
+        )
+        .expect("should capture the popup");
+        assert_ne!(popup.target_id(), page.target_id());
+        assert_eq!(popup.opener_id().as_ref(), Some(page.target_id()));
+
+        let title: String = popup
+            .evaluate("document.title = 'popup'")
+            .await
+            .expect("should drive the popup")
+            .into_value()
+            .unwrap();
+        assert_eq!(title, "popup");
+
+        let err = browser
+            .wait_for_new_page(Duration::from_millis(300))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CdpError::Timeout));
+    })
+    .await;
+}
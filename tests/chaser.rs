@@ -0,0 +1,2320 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chaser_oxide::cdp::browser_protocol::browser::{GrantPermissionsParams, PermissionType};
+use chaser_oxide::cdp::browser_protocol::fetch::EventRequestPaused;
+use chaser_oxide::cdp::browser_protocol::page::{CaptureScreenshotFormat, ReferrerPolicy};
+use chaser_oxide::cdp::js_protocol::runtime::EvaluateParams;
+use chaser_oxide::error::CdpError;
+use chaser_oxide::intercept::{RequestAction, ResponseBody};
+use chaser_oxide::page::ScreenshotParams;
+use chaser_oxide::{
+    timezone_to_coords, BrowserConfig, CallFunctionOptions, ChallengeCheck, ChallengeKind,
+    ChallengeMarker, ChaserPage, ChaserProfile, ColorScheme, ConsoleLevel, ConsoleSource,
+    EvaluateOptions, ExecutionWorld, Modifier, NetworkConditions, NetworkEvent,
+    NotificationPermission, ResourceType, WaitUntil,
+};
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{test, test_config};
+
+/// Navigate to `url` and return the headers of the intercepted document request
+async fn document_request_headers(chaser: &ChaserPage, url: &'static str) -> serde_json::Value {
+    chaser
+        .enable_request_interception("*", Some(ResourceType::Document))
+        .await
+        .expect("should enable request interception");
+
+    let mut paused = chaser
+        .raw_page()
+        .event_listener::<EventRequestPaused>()
+        .await
+        .expect("should listen for paused requests");
+
+    let navigation = {
+        let chaser = chaser.clone();
+        tokio::spawn(async move { chaser.goto(url).await })
+    };
+
+    let event = paused.next().await.expect("should pause the navigation");
+    let headers = event.request.headers.inner().clone();
+
+    chaser
+        .continue_request(event.request_id.inner().clone())
+        .await
+        .expect("should continue request");
+    navigation.await.unwrap().expect("should navigate to url");
+
+    headers
+}
+
+#[tokio::test]
+async fn test_goto_keeps_query_order_and_fragment() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        chaser
+            .enable_request_interception("*", Some(ResourceType::Document))
+            .await
+            .expect("should enable request interception");
+        let mut paused = chaser
+            .raw_page()
+            .event_listener::<EventRequestPaused>()
+            .await
+            .expect("should listen for paused requests");
+
+        let url = "https://example.com/api?z=1&a=2&sig=a%2Fb%3D&a=0#frag?x=1";
+        let navigation = {
+            let chaser = chaser.clone();
+            tokio::spawn(async move { chaser.goto(url).await })
+        };
+        let event = paused.next().await.expect("should pause the navigation");
+        assert_eq!(
+            event.request.url,
+            "https://example.com/api?z=1&a=2&sig=a%2Fb%3D&a=0"
+        );
+        assert_eq!(event.request.url_fragment.as_deref(), Some("#frag?x=1"));
+
+        chaser
+            .fulfill_request_html(event.request_id.inner().clone(), "<p>signed</p>", 200)
+            .await
+            .expect("should fulfill request");
+        navigation.await.unwrap().expect("should navigate to url");
+        let href: String = chaser.evaluate_as("location.href").await.unwrap();
+        assert_eq!(href, url);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_paused_requests_are_handled_once() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        page.modify_requests(|request| {
+            if request.url.ends_with("/modified") {
+                RequestAction::Fulfill {
+                    status: 200,
+                    headers: HashMap::from([("content-type".into(), "text/html".into())]),
+                    body: b"<p>modified</p>".to_vec(),
+                }
+            } else {
+                RequestAction::Continue
+            }
+        })
+        .await
+        .expect("should modify requests");
+        let chaser = ChaserPage::new(page);
+        chaser
+            .enable_request_interception("*", Some(ResourceType::Document))
+            .await
+            .expect("should enable request interception");
+        let mut paused = chaser
+            .raw_page()
+            .event_listener::<EventRequestPaused>()
+            .await
+            .expect("should listen for paused requests");
+
+        // the modifier handles the request, it's not passed on
+        chaser
+            .goto("https://example.com/modified")
+            .await
+            .expect("should navigate to the modified page");
+        let text: String = chaser
+            .evaluate_as("document.body.textContent")
+            .await
+            .unwrap();
+        assert_eq!(text, "modified");
+
+        chaser
+            .raw_page()
+            .clear_request_modifier()
+            .await
+            .expect("should clear the request modifier");
+        let navigation = {
+            let chaser = chaser.clone();
+            tokio::spawn(async move { chaser.goto("https://example.com/user").await })
+        };
+        let event = paused.next().await.expect("should pause the navigation");
+        assert_eq!(event.request.url, "https://example.com/user");
+        chaser
+            .fulfill_request_html(event.request_id.inner().clone(), "<p>user</p>", 200)
+            .await
+            .expect("should fulfill request");
+        navigation.await.unwrap().expect("should navigate to url");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_goto_with_referer_sends_referer() {
+    test(async |browser| {
+        let chaser = ChaserPage::new(browser.new_page("about:blank").await.unwrap());
+        chaser
+            .enable_request_interception("*", Some(ResourceType::Document))
+            .await
+            .expect("should enable request interception");
+        let mut paused = chaser
+            .raw_page()
+            .event_listener::<EventRequestPaused>()
+            .await
+            .expect("should listen for paused requests");
+
+        let cases = [
+            (
+                "https://example.com/search?q=shoes",
+                None,
+                "https://example.com/search?q=shoes",
+            ),
+            // cross-origin referers are cut to their origin by default
+            (
+                "https://www.google.com/search?q=shoes",
+                None,
+                "https://www.google.com/",
+            ),
+            (
+                "https://www.google.com/search?q=shoes",
+                Some(ReferrerPolicy::UnsafeUrl),
+                "https://www.google.com/search?q=shoes",
+            ),
+        ];
+        for (referer, policy, expected) in cases {
+            let navigation = {
+                let (chaser, policy) = (chaser.clone(), policy.clone());
+                tokio::spawn(async move {
+                    let url = "https://example.com/item/42";
+                    match policy {
+                        Some(policy) => chaser.goto_with_referer_policy(url, referer, policy).await,
+                        None => chaser.goto_with_referer(url, referer).await,
+                    }
+                })
+            };
+            let event = paused.next().await.expect("should pause the navigation");
+            assert_eq!(
+                event.request.headers.inner()["Referer"],
+                json!(expected),
+                "{referer} with {policy:?}"
+            );
+            chaser
+                .fulfill_request_html(event.request_id.inner().clone(), "<p>item</p>", 200)
+                .await
+                .expect("should fulfill request");
+            navigation.await.unwrap().expect("should navigate to url");
+        }
+
+        for referer in ["google.com", "file:///etc/passwd"] {
+            let err = chaser
+                .goto_with_referer("https://example.com/", referer)
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains(referer), "{err}");
+        }
+    })
+    .await;
+}
+
+/// An HTTP server recording the head of every request, its pages poll
+/// `/poll` and load `/pixel.png`
+fn recording_server() -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("should bind listener");
+    let addr = listener.local_addr().unwrap();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let recorded = requests.clone();
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let request = String::from_utf8_lossy(&request).to_string();
+            let html = if request.starts_with("GET /poll ") || request.starts_with("GET /pixel") {
+                ""
+            } else {
+                "<img src='/pixel.png'><script>setInterval(() => fetch('/poll'), 5)</script>"
+            };
+            recorded.lock().unwrap().push(request);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: text/html\r\ncontent-length: {}\r\n\
+                 connection: close\r\n\r\n{html}",
+                html.len()
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    (format!("http://{addr}"), requests)
+}
+
+#[tokio::test]
+async fn test_navigate_like_user_sends_fetch_metadata() {
+    test(async |browser| {
+        let (origin, requests) = recording_server();
+        let chaser = ChaserPage::new(browser.new_page("about:blank").await.unwrap());
+        // the page keeps fetching while the navigation starts
+        chaser.goto(&origin).await.expect("should navigate");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        chaser
+            .navigate_like_user(&format!("{origin}/typed"))
+            .await
+            .expect("should navigate to url");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let requests = requests.lock().unwrap().clone();
+        let header = |request: &str, name: &str| {
+            request.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                key.eq_ignore_ascii_case(name)
+                    .then(|| value.trim().to_string())
+            })
+        };
+        let document = requests
+            .iter()
+            .find(|request| request.starts_with("GET /typed "))
+            .expect("should request the document");
+        assert_eq!(header(document, "Sec-Fetch-Site").as_deref(), Some("none"));
+        assert_eq!(
+            header(document, "Sec-Fetch-Mode").as_deref(),
+            Some("navigate")
+        );
+        assert_eq!(header(document, "Sec-Fetch-User").as_deref(), Some("?1"));
+        assert_eq!(
+            header(document, "Sec-Fetch-Dest").as_deref(),
+            Some("document")
+        );
+        assert!(header(document, "Accept")
+            .unwrap()
+            .starts_with("text/html,"));
+
+        // the requests of both pages keep their own headers
+        let others: Vec<_> = requests
+            .iter()
+            .filter(|request| {
+                request.starts_with("GET /poll ") || request.starts_with("GET /pixel")
+            })
+            .collect();
+        assert!(others.len() > 2);
+        for request in others {
+            assert_eq!(header(request, "Sec-Fetch-User"), None, "{request}");
+            assert_ne!(
+                header(request, "Sec-Fetch-Dest").as_deref(),
+                Some("document")
+            );
+            assert_ne!(header(request, "Sec-Fetch-Site").as_deref(), Some("none"));
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_apply_profile_user_agent_header() {
+    test(async |browser| {
+        let profile = ChaserProfile::windows().chrome_version(130).build();
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        chaser
+            .apply_profile(&profile)
+            .await
+            .expect("should apply profile");
+
+        let headers = document_request_headers(&chaser, "https://www.google.com").await;
+
+        assert_eq!(
+            headers.get("User-Agent").and_then(|v| v.as_str()),
+            Some(profile.user_agent().as_str())
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_apply_profile_client_hint_headers() {
+    test(async |browser| {
+        let profile = ChaserProfile::macos_arm().chrome_version(130).build();
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        chaser
+            .apply_profile(&profile)
+            .await
+            .expect("should apply profile");
+
+        let headers = document_request_headers(&chaser, "https://www.google.com").await;
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        };
+
+        assert_eq!(header("sec-ch-ua"), Some(profile.sec_ch_ua()));
+        assert_eq!(header("sec-ch-ua-mobile").as_deref(), Some("?0"));
+        assert_eq!(
+            header("sec-ch-ua-platform"),
+            Some(format!("\"{}\"", profile.os().hints_platform()))
+        );
+    })
+    .await;
+}
+
+/// An HTTP server whose root page embeds `/frame` from another site, the
+/// frame posts its `navigator.platform` and `navigator.webdriver` to the page
+fn cross_site_frame_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("should bind listener");
+    let port = listener.local_addr().unwrap().port();
+
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            // `localhost` and `127.0.0.1` are different sites
+            let html = if request.starts_with(b"GET /frame ") {
+                "<script>parent.postMessage([navigator.platform, navigator.webdriver], '*')\
+                 </script>"
+                    .to_string()
+            } else {
+                format!(
+                    "<script>window.frameReport = new Promise(r => \
+                     addEventListener('message', e => r(e.data)))</script>\
+                     <iframe src='http://localhost:{port}/frame'></iframe>"
+                )
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: text/html\r\ncontent-length: {}\r\n\
+                 connection: close\r\n\r\n{html}",
+                html.len()
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    format!("http://127.0.0.1:{port}")
+}
+
+#[tokio::test]
+async fn test_apply_profile_reaches_iframes() {
+    let config = BrowserConfig::builder()
+        .arg("--site-per-process")
+        .build()
+        .unwrap();
+    test_config(config, async |browser| {
+        let url = cross_site_frame_server();
+        let profile = ChaserProfile::windows().build();
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        let frame_report = async || -> (String, bool) {
+            chaser.goto(&url).await.expect("should navigate");
+            chaser
+                .raw_page()
+                .evaluate("window.frameReport")
+                .await
+                .expect("should receive the frame report")
+                .into_value()
+                .expect("should be a platform and webdriver flag")
+        };
+
+        // re-applying replaces the scripts instead of stacking them
+        chaser
+            .apply_profile(&profile)
+            .await
+            .expect("should apply profile");
+        chaser
+            .apply_profile(&ChaserProfile::macos_arm().build())
+            .await
+            .expect("should apply profile");
+        chaser
+            .apply_profile(&profile)
+            .await
+            .expect("should apply profile");
+        assert_eq!(
+            frame_report().await,
+            (profile.os().platform().to_string(), false)
+        );
+
+        // removed scripts no longer reach new iframes
+        chaser
+            .reset_identity()
+            .await
+            .expect("should reset identity");
+        let (platform, _) = frame_report().await;
+        assert_ne!(platform, profile.os().platform());
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_evaluate_as() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Screen {
+        width: u32,
+        height: u32,
+    }
+
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        let number: u32 = chaser
+            .evaluate_as("6 * 7")
+            .await
+            .expect("should deserialize a number");
+        assert_eq!(number, 42);
+
+        let list: Vec<String> = chaser
+            .evaluate_as("['a', 'b', 'c']")
+            .await
+            .expect("should deserialize a vec");
+        assert_eq!(list, vec!["a", "b", "c"]);
+
+        let screen: Screen = chaser
+            .evaluate_as("({ width: 1920, height: 1080, extra: true })")
+            .await
+            .expect("should deserialize a struct");
+        assert_eq!(
+            screen,
+            Screen {
+                width: 1920,
+                height: 1080
+            }
+        );
+
+        let missing: Option<u32> = chaser
+            .evaluate_as("undefined")
+            .await
+            .expect("should deserialize undefined into an option");
+        assert_eq!(missing, None);
+
+        let err = chaser
+            .evaluate_as::<u32>("undefined")
+            .await
+            .expect_err("should not deserialize undefined into a number");
+        assert!(err.to_string().contains("undefined"));
+
+        chaser
+            .evaluate_as::<Vec<u32>>("'not a list'")
+            .await
+            .expect_err("should not deserialize a string into a vec");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_evaluate_with_awaits_promises_and_times_out() {
+    test(async |browser| {
+        let chaser = ChaserPage::new(browser.new_page("about:blank").await.unwrap());
+        let timeout = EvaluateOptions {
+            timeout: Some(Duration::from_millis(500)),
+            ..Default::default()
+        };
+
+        let value = chaser
+            .evaluate_with("Promise.resolve(42)", timeout)
+            .await
+            .expect("should await the promise");
+        assert_eq!(value, Some(json!(42)));
+
+        let err = chaser
+            .evaluate_with("new Promise(() => {})", timeout)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err.downcast_ref(), Some(CdpError::Timeout)),
+            "{err}"
+        );
+
+        // the promise itself, which has no JSON value
+        let value = chaser
+            .evaluate_with(
+                "Promise.resolve(42)",
+                EvaluateOptions {
+                    await_promise: false,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(value, Some(json!({})));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_call_function_round_trips_arguments() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        let tricky = r#"it's "quoted" \ and \n escaped `${x}` </script>"#;
+
+        let isolated = chaser
+            .call_function("(s) => s", &[json!(tricky)])
+            .await
+            .expect("should call function in isolated world");
+        assert_eq!(isolated, json!(tricky));
+
+        let main = chaser
+            .call_function_with(
+                "(s, n) => [s, n + 1]",
+                &[json!(tricky), json!(41)],
+                CallFunctionOptions {
+                    world: ExecutionWorld::Main,
+                    ..Default::default()
+                },
+            )
+            .await
+            .expect("should call function in main world");
+        assert_eq!(main, json!([tricky, 42]));
+
+        let undefined = chaser
+            .call_function("() => undefined", &[])
+            .await
+            .expect("should call function returning undefined");
+        assert_eq!(undefined, serde_json::Value::Null);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_local_storage_survives_reload() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        chaser
+            .local_storage()
+            .await
+            .expect_err("should not read storage on about:blank");
+
+        chaser
+            .goto("https://www.google.com")
+            .await
+            .expect("should navigate to www.google.com");
+
+        let kv = HashMap::from([("chaser-token".to_string(), r#"{"a":"b"}"#.to_string())]);
+        chaser
+            .set_local_storage(&kv)
+            .await
+            .expect("should write local storage");
+        chaser
+            .set_session_storage(&kv)
+            .await
+            .expect("should write session storage");
+
+        chaser
+            .raw_page()
+            .reload()
+            .await
+            .expect("should reload the page");
+
+        let local = chaser
+            .local_storage()
+            .await
+            .expect("should read local storage");
+        assert_eq!(local.get("chaser-token"), kv.get("chaser-token"));
+
+        let session = chaser
+            .session_storage()
+            .await
+            .expect("should read session storage");
+        assert_eq!(session.get("chaser-token"), kv.get("chaser-token"));
+    })
+    .await;
+}
+
+/// A page that sets its title once a delayed XHR finished
+const DELAYED_XHR_PAGE: &str = "data:text/html,<title>loading</title><script>\
+    setTimeout(() => fetch('https://httpbin.org/delay/1')\
+        .then(() => { document.title = 'done'; }), 100);\
+    </script>";
+
+#[tokio::test]
+async fn test_goto_with_wait_until() {
+    test(async |browser| {
+        for (wait, expect_done) in [
+            (WaitUntil::None, false),
+            (WaitUntil::DomContentLoaded, false),
+            (WaitUntil::Load, false),
+            (WaitUntil::NetworkIdle, true),
+            (WaitUntil::NetworkAlmostIdle, false),
+        ] {
+            let page = browser
+                .new_page("about:blank")
+                .await
+                .expect("should create new page");
+            let chaser = ChaserPage::new(page);
+
+            chaser
+                .goto_with(DELAYED_XHR_PAGE, wait)
+                .await
+                .unwrap_or_else(|e| panic!("should navigate with {wait:?}: {e}"));
+
+            let title: String = chaser
+                .evaluate_as("document.title")
+                .await
+                .expect("should read the title");
+            assert_eq!(title == "done", expect_done, "{wait:?}");
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_wait_for_challenge_clear() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        chaser
+            .goto("data:text/html,<title>Welcome</title>")
+            .await
+            .expect("should navigate to plain page");
+        let detected = chaser
+            .wait_for_challenge_clear(Duration::from_secs(5))
+            .await
+            .expect("should not detect a challenge");
+        assert_eq!(detected, None);
+
+        chaser
+            .goto(
+                "data:text/html,<title>Just a moment...</title>\
+                 <script>setTimeout(() => { document.title = 'Welcome'; }, 1000);</script>",
+            )
+            .await
+            .expect("should navigate to interstitial page");
+        let detected = chaser
+            .wait_for_challenge_clear(Duration::from_secs(10))
+            .await
+            .expect("should clear the interstitial");
+        assert_eq!(detected, Some(ChallengeKind::CloudflareInterstitial));
+
+        chaser
+            .goto("data:text/html,<div id='blocker'></div>")
+            .await
+            .expect("should navigate to blocked page");
+        let mut markers = ChallengeMarker::defaults();
+        markers.push(ChallengeMarker::new(
+            ChallengeKind::Custom("blocker".into()),
+            ChallengeCheck::Selector("#blocker".into()),
+        ));
+        chaser
+            .wait_for_challenge_clear_with(Duration::from_secs(2), &markers)
+            .await
+            .expect_err("should time out on a custom challenge");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_goto_reports_net_error() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        let url = "https://chaser-oxide.invalid/";
+
+        for wait in [WaitUntil::Load, WaitUntil::None] {
+            let err = chaser
+                .goto_with(url, wait)
+                .await
+                .expect_err("should not resolve an .invalid host");
+            match err.downcast_ref::<CdpError>() {
+                Some(CdpError::Navigation {
+                    url: failed,
+                    net_error,
+                }) => {
+                    assert_eq!(failed, url);
+                    assert_eq!(net_error, "net::ERR_NAME_NOT_RESOLVED");
+                }
+                other => panic!("should be a navigation error, got {other:?}"),
+            }
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_offline_navigation_fails() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        chaser
+            .emulate_network(NetworkConditions::Fast3G)
+            .await
+            .expect("should throttle the network");
+        chaser.set_offline(true).await.expect("should go offline");
+
+        let err = chaser
+            .goto("https://example.com/")
+            .await
+            .expect_err("should not load while offline");
+        match err.downcast_ref::<CdpError>() {
+            Some(CdpError::Navigation { net_error, .. }) => {
+                assert_eq!(net_error, "net::ERR_INTERNET_DISCONNECTED");
+            }
+            other => panic!("should be a navigation error, got {other:?}"),
+        }
+
+        chaser.set_offline(false).await.expect("should go online");
+        chaser
+            .goto("https://example.com/")
+            .await
+            .expect("should load once online again");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_drag_element_to() {
+    test(async |browser| {
+        let page = browser
+            .new_page(
+                "data:text/html,<div class=handle style='position:absolute;left:0;top:50px;\
+                 width:40px;height:40px;background:red'></div>\
+                 <div class=target style='position:absolute;left:300px;top:50px;\
+                 width:40px;height:40px'></div>\
+                 <script>let dragging = false; const handle = document.querySelector('.handle');\
+                 handle.onmousedown = () => dragging = true;\
+                 onmousemove = (e) => { if (dragging) handle.style.left = (e.clientX - 20) + 'px' };\
+                 onmouseup = () => dragging = false;</script>",
+            )
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        chaser
+            .drag_element_to(".handle", ".target")
+            .await
+            .expect("should drag the handle");
+        let left: f64 = chaser
+            .evaluate_as("document.querySelector('.handle').getBoundingClientRect().x")
+            .await
+            .expect("should read the handle position");
+        assert!((270.0..=310.0).contains(&left), "handle at {left}");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_element_center_skips_hidden_and_occluded_elements() {
+    test(async |browser| {
+        let page = browser
+            .new_page(
+                "data:text/html,<button id=visible style='position:absolute;left:10px;top:10px;\
+                 width:100px;height:40px'><span>label</span></button>\
+                 <button id=hidden style='display:none'>hidden</button>\
+                 <div style='opacity:0'><button id=transparent>transparent</button></div>\
+                 <button id=offscreen style='position:absolute;left:10px;top:5000px'>far</button>\
+                 <button id=occluded style='position:absolute;left:200px;top:10px;\
+                 width:100px;height:40px'>occluded</button>\
+                 <div style='position:absolute;left:180px;top:0;width:200px;height:100px;\
+                 background:white'></div>",
+            )
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        let center = chaser
+            .element_center("#visible")
+            .await
+            .expect("should locate the button");
+        assert_eq!(center, Some((60.0, 30.0)));
+        assert!(chaser.is_visible("#visible").await.unwrap());
+
+        for selector in [
+            "#hidden",
+            "#transparent",
+            "#offscreen",
+            "#occluded",
+            "#missing",
+        ] {
+            assert_eq!(
+                chaser.element_center(selector).await.unwrap(),
+                None,
+                "{selector}"
+            );
+            assert!(!chaser.is_visible(selector).await.unwrap(), "{selector}");
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_mouse_position_tracks_moves() {
+    test(async |browser| {
+        let page = browser
+            .new_page(
+                "data:text/html,<body style='height:100vh'></body>\
+                 <script>onmousemove = (e) => window.last = [e.clientX, e.clientY];</script>",
+            )
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        assert_eq!(chaser.mouse_position(), (0.0, 0.0));
+
+        chaser.move_mouse_human(200.0, 150.0).await.unwrap();
+        let (x, y) = chaser.mouse_position();
+        // the target is jittered by up to 2 pixels
+        assert!(
+            (x - 200.0).abs() <= 2.0 && (y - 150.0).abs() <= 2.0,
+            "({x}, {y})"
+        );
+        assert_eq!(chaser.clone().mouse_position(), (x, y));
+
+        chaser.set_mouse_position(10.0, 20.0).await.unwrap();
+        assert_eq!(chaser.mouse_position(), (10.0, 20.0));
+        // the page listens in the main world
+        let last = chaser
+            .call_function_with(
+                "() => window.last",
+                &[],
+                CallFunctionOptions {
+                    world: ExecutionWorld::Main,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(last, json!([10, 20]));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_accessibility_tree() {
+    test(async |browser| {
+        let page = browser
+            .new_page(
+                "data:text/html,<title>Form</title><main><h1>Sign up</h1>\
+                 <button id=submit>Submit</button></main>",
+            )
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page.clone());
+
+        let tree = chaser
+            .accessibility_tree()
+            .await
+            .expect("should read the accessibility tree");
+        assert_eq!(tree.role, "RootWebArea");
+        assert_eq!(tree.name, "Form");
+        assert!(tree.parent_id.is_none());
+        let button = tree
+            .find_by_role("button", "Submit")
+            .expect("should contain the button");
+        assert!(button.parent_id.is_some());
+
+        let element = page.find_element("#submit").await.unwrap();
+        let subtree = chaser
+            .accessibility_subtree(element.backend_node_id)
+            .await
+            .expect("should read the subtree of the button");
+        assert_eq!(subtree.id, button.id);
+        assert_eq!(subtree.role, "button");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_performance_metrics() {
+    test(async |browser| {
+        let chaser = ChaserPage::new(browser.new_page("about:blank").await.unwrap());
+        chaser.goto("https://example.com/").await.unwrap();
+
+        let perf = chaser
+            .performance_metrics()
+            .await
+            .expect("should read the performance metrics");
+        let ttfb = perf.ttfb.expect("should report the TTFB");
+        let dom_content_loaded = perf.dom_content_loaded.expect("should report DCL");
+        let load = perf.load.expect("should report the load time");
+        assert!(ttfb > Duration::ZERO);
+        assert!(ttfb <= dom_content_loaded && dom_content_loaded <= load);
+        assert!(perf.metrics.contains_key("Nodes"));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_load_times_are_ordered() {
+    test(async |browser| {
+        let page = browser.new_page("about:blank").await.unwrap();
+        // headful chrome has the native functions, drop them to test the stubs
+        page.evaluate_on_new_document(
+            "if (window.chrome) { delete window.chrome.loadTimes; delete window.chrome.csi; }",
+        )
+        .await
+        .unwrap();
+        let chaser = ChaserPage::new(page);
+        chaser
+            .apply_profile(&ChaserProfile::windows().build())
+            .await
+            .unwrap();
+        chaser.goto("https://example.com/").await.unwrap();
+
+        let times: Vec<f64> = chaser
+            .raw_page()
+            .evaluate(
+                "(() => { const t = chrome.loadTimes(); return [t.requestTime, t.startLoadTime, \
+                 t.commitLoadTime, t.finishDocumentLoadTime, t.finishLoadTime]; })()",
+            )
+            .await
+            .unwrap()
+            .into_value()
+            .unwrap();
+        assert!(times.windows(2).all(|w| w[0] < w[1]), "{times:?}");
+
+        let (start, onload): (f64, f64) = chaser
+            .raw_page()
+            .evaluate("(() => { const c = chrome.csi(); return [c.startE, c.onloadT]; })()")
+            .await
+            .unwrap()
+            .into_value()
+            .unwrap();
+        assert!(start < onload);
+        assert_eq!(start.round(), (times[0] * 1000.0).round());
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_record_property_access() {
+    test(async |browser| {
+        let chaser = ChaserPage::new(browser.new_page("about:blank").await.unwrap());
+        let log = chaser
+            .record_property_access(&["navigator", "WebGLRenderingContext"])
+            .await
+            .expect("should instrument the page");
+        chaser
+            .goto("data:text/html,<script>window.probed = navigator.webdriver;</script>")
+            .await
+            .unwrap();
+
+        let entries = log.entries().await.expect("should read the log");
+        let access = entries
+            .iter()
+            .find(|access| access.object == "navigator" && access.property == "webdriver")
+            .expect("should log the webdriver access");
+        assert!(access.timestamp >= 0.0);
+
+        // the instrumentation looks native and adds no globals
+        let native: bool = chaser
+            .raw_page()
+            .evaluate(
+                "Object.getOwnPropertyDescriptor(Navigator.prototype, 'webdriver').get.toString()\
+                 .includes('[native code]') && !Object.keys(window).some((k) => k.length === 16) \
+                 && Object.getOwnPropertySymbols(window).length === 0 \
+                 && Object.getOwnPropertySymbols(Object.getOwnPropertyDescriptor(\
+                 Navigator.prototype, 'webdriver').get).length === 0",
+            )
+            .await
+            .unwrap()
+            .into_value()
+            .unwrap();
+        assert!(native);
+
+        log.clear().await.unwrap();
+        assert!(log.entries().await.unwrap().is_empty());
+        log.stop().await.unwrap();
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_simulate_browsing() {
+    test(async |browser| {
+        let page = browser
+            .new_page(
+                "data:text/html,<body style='height:5000px'><script>let events = 0;\
+                 for (const type of ['wheel', 'mousemove']) addEventListener(type, () => \
+                 document.body.dataset.events = ++events);</script></body>",
+            )
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        chaser
+            .simulate_browsing(Duration::from_secs(4))
+            .await
+            .expect("should browse the page");
+        let events: String = chaser
+            .evaluate_as("document.body.dataset.events || '0'")
+            .await
+            .unwrap();
+        assert!(events.parse::<u32>().unwrap() > 1, "{events} input events");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_press_key_and_combo() {
+    test(async |browser| {
+        let page = browser
+            .new_page(
+                "data:text/html,<form onsubmit=\"document.title='submitted';return false\">\
+                 <input id=q value='hello world'></form>",
+            )
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        chaser
+            .evaluate("document.getElementById('q').focus()")
+            .await
+            .expect("should focus the input");
+
+        chaser
+            .press_combo(&[Modifier::Control], "a")
+            .await
+            .expect("should press Ctrl+A");
+        let selection: (u32, u32, String) = chaser
+            .evaluate_as(
+                "(() => { const q = document.getElementById('q'); \
+                 return [q.selectionStart, q.selectionEnd, q.value]; })()",
+            )
+            .await
+            .expect("should read the selection");
+        assert_eq!(selection, (0, 11, "hello world".to_string()));
+
+        // replaces the selection
+        chaser
+            .press_combo(&[Modifier::Shift], "a")
+            .await
+            .expect("should press Shift+A");
+        chaser
+            .press_combo(&[Modifier::Shift], "1")
+            .await
+            .expect("should press Shift+1");
+        let value: String = chaser
+            .evaluate_as("document.getElementById('q').value")
+            .await
+            .expect("should read the value");
+        assert_eq!(value, "A!");
+
+        chaser.press_key("Enter").await.expect("should press Enter");
+        let title: String = chaser
+            .evaluate_as("document.title")
+            .await
+            .expect("should read the title");
+        assert_eq!(title, "submitted");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_touch_points_match_touch_events() {
+    test(async |browser| {
+        for touch_points in [0, 5] {
+            let profile = ChaserProfile::windows().touch_points(touch_points).build();
+            let page = browser
+                .new_page("about:blank")
+                .await
+                .expect("should create new page");
+            let chaser = ChaserPage::new(page);
+
+            chaser
+                .apply_profile(&profile)
+                .await
+                .expect("should apply profile");
+            chaser
+                .goto("data:text/html,<p>touch</p>")
+                .await
+                .expect("should navigate to page");
+
+            let (max_touch_points, has_touch_events): (u32, bool) = chaser
+                .evaluate_as("[navigator.maxTouchPoints, 'ontouchstart' in window]")
+                .await
+                .expect("should read touch support");
+            assert_eq!(max_touch_points, touch_points);
+            assert_eq!(has_touch_events, touch_points > 0);
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_anisotropy_needs_the_extension() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        chaser
+            .apply_profile(&ChaserProfile::windows().build())
+            .await
+            .expect("should apply profile");
+        chaser
+            .goto("data:text/html,<canvas></canvas>")
+            .await
+            .expect("should navigate to page");
+
+        let (before, error, after): (Option<u32>, u32, Option<u32>) = chaser
+            .evaluate_as(
+                "(() => {
+                    const gl = document.querySelector('canvas').getContext('webgl');
+                    const before = gl.getParameter(0x84FF);
+                    const error = gl.getError();
+                    gl.getExtension('EXT_texture_filter_anisotropic');
+                    return [before, error, gl.getParameter(0x84FF)];
+                })()",
+            )
+            .await
+            .expect("should read the anisotropy");
+        assert_eq!(before, None);
+        // INVALID_ENUM
+        assert_eq!(error, 0x0500);
+        assert_eq!(after, Some(16));
+    })
+    .await;
+}
+
+/// Spawns a worker from a blob URL and resolves with its first message
+const SPAWN_WORKER: &str = "(type) => new Promise((resolve, reject) => {
+    const source = type === 'module'
+        ? 'postMessage(typeof import.meta)'
+        : 'postMessage(typeof importScripts)';
+    const url = URL.createObjectURL(new Blob([source], { type: 'application/javascript' }));
+    const worker = new Worker(url, { type });
+    worker.addEventListener('message', (e) => resolve(e.data));
+    setTimeout(() => reject(new Error(type + ' worker hung')), 5000);
+})";
+
+#[tokio::test]
+async fn test_worker_spoofing_keeps_workers_running() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        chaser
+            .apply_profile(&ChaserProfile::windows().build())
+            .await
+            .expect("should apply profile");
+
+        for enabled in [true, false] {
+            chaser
+                .set_worker_spoofing(enabled)
+                .await
+                .expect("should toggle worker spoofing");
+            chaser
+                .goto("data:text/html,<p>workers</p>")
+                .await
+                .expect("should navigate to page");
+
+            for (ty, expected) in [("classic", "function"), ("module", "object")] {
+                let message = chaser
+                    .call_function_with(
+                        SPAWN_WORKER,
+                        &[json!(ty)],
+                        CallFunctionOptions {
+                            world: ExecutionWorld::Main,
+                            ..Default::default()
+                        },
+                    )
+                    .await
+                    .unwrap_or_else(|e| panic!("{ty} worker should run: {e}"));
+                assert_eq!(message, json!(expected), "{ty} worker, spoofing {enabled}");
+            }
+
+            let shimmed = chaser
+                .call_function_with(
+                    "() => Worker.toString().includes('OriginalWorker')",
+                    &[],
+                    CallFunctionOptions {
+                        world: ExecutionWorld::Main,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .expect("should inspect the worker constructor");
+            assert_eq!(shimmed, json!(enabled));
+        }
+    })
+    .await;
+}
+
+/// Resolves with the ICE candidates a data channel offer of the
+/// `RTCPeerConnection` stored in the global `name` gathers
+const GATHER_CANDIDATES: &str = "(name) => new Promise((resolve, reject) => {
+    const pc = new window[name]();
+    const candidates = [];
+    pc.addEventListener('icecandidate', (e) => {
+        if (e.candidate) candidates.push(e.candidate.candidate);
+        else resolve(candidates);
+    });
+    pc.createDataChannel('probe');
+    pc.createOffer().then((offer) => pc.setLocalDescription(offer)).catch(reject);
+    setTimeout(() => resolve(candidates), 5000);
+})";
+
+#[tokio::test]
+async fn test_disable_webrtc_gathers_no_host_candidates() {
+    test(async |browser| {
+        let chaser = ChaserPage::new(browser.new_page("about:blank").await.unwrap());
+        let gather = async |name: &str| -> Vec<String> {
+            let candidates = chaser
+                .call_function_with(
+                    GATHER_CANDIDATES,
+                    &[json!(name)],
+                    CallFunctionOptions {
+                        world: ExecutionWorld::Main,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap_or_else(|e| panic!("{name} should gather candidates: {e}"));
+            serde_json::from_value(candidates).unwrap()
+        };
+
+        chaser
+            .evaluate("window.SavedPeerConnection = RTCPeerConnection; true")
+            .await
+            .unwrap();
+        chaser
+            .disable_webrtc()
+            .await
+            .expect("should disable WebRTC");
+        for name in ["RTCPeerConnection", "SavedPeerConnection"] {
+            let candidates = gather(name).await;
+            assert!(
+                candidates.iter().all(|c| c.contains("typ relay")),
+                "{name}: {candidates:?}"
+            );
+        }
+
+        chaser.goto("data:text/html,<p>webrtc</p>").await.unwrap();
+        let candidates = gather("RTCPeerConnection").await;
+        assert!(
+            !candidates.iter().any(|c| c.contains("typ host")),
+            "{candidates:?}"
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_rendered_html_includes_script_nodes() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        chaser
+            .goto(
+                "data:text/html,<div id='app'></div><script>\
+                 document.getElementById('app').innerHTML = '<p class=\"late\">rendered</p>';\
+                 </script>",
+            )
+            .await
+            .expect("should navigate to page");
+
+        let html = chaser
+            .rendered_html()
+            .await
+            .expect("should read the rendered html");
+        assert!(html.starts_with("<html>"), "{html}");
+        assert!(html.contains(r#"<p class="late">rendered</p>"#), "{html}");
+
+        let app = chaser
+            .inner_html("#app")
+            .await
+            .expect("should read the subtree");
+        assert_eq!(app.as_deref(), Some(r#"<p class="late">rendered</p>"#));
+
+        let missing = chaser
+            .inner_html("#missing")
+            .await
+            .expect("should read a missing subtree");
+        assert_eq!(missing, None);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_query_all_text_and_attr() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        chaser
+            .goto(
+                "data:text/html,<ul>\
+                 <li><a href='/one'>One</a></li>\
+                 <li><a href='/two'>Two</a></li>\
+                 <li><a>Three</a></li>\
+                 </ul>",
+            )
+            .await
+            .expect("should navigate to page");
+
+        let text = chaser
+            .query_all_text("li")
+            .await
+            .expect("should read the texts");
+        assert_eq!(text, vec!["One", "Two", "Three"]);
+
+        let hrefs = chaser
+            .query_all_attr("li a", "href")
+            .await
+            .expect("should read the attributes");
+        assert_eq!(
+            hrefs,
+            vec![Some("/one".to_string()), Some("/two".to_string()), None]
+        );
+
+        let none = chaser
+            .query_all_text("table td")
+            .await
+            .expect("should not fail without matches");
+        assert!(none.is_empty());
+        let none = chaser
+            .query_all_attr("table td", "class")
+            .await
+            .expect("should not fail without matches");
+        assert!(none.is_empty());
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_apply_profile_bypasses_strict_csp() {
+    test(async |browser| {
+        let profile = ChaserProfile::windows().bypass_csp(true).build();
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        chaser
+            .apply_profile(&profile)
+            .await
+            .expect("should apply profile");
+        chaser
+            .goto(
+                "data:text/html,<meta http-equiv='Content-Security-Policy' \
+                 content=\"default-src 'none'; worker-src 'none'\"><p>strict</p>",
+            )
+            .await
+            .expect("should navigate to strict page");
+
+        let main_world = || CallFunctionOptions {
+            world: ExecutionWorld::Main,
+            ..Default::default()
+        };
+        let platform = chaser
+            .call_function_with("() => navigator.platform", &[], main_world())
+            .await
+            .expect("should read the platform");
+        assert_eq!(platform, json!(profile.os().platform()));
+
+        let message = chaser
+            .call_function_with(SPAWN_WORKER, &[json!("classic")], main_world())
+            .await
+            .expect("should spawn a blob worker despite the CSP");
+        assert_eq!(message, json!("function"));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_network_events_include_document_request() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        let url = "https://www.google.com/";
+
+        let mut events = chaser
+            .network_events()
+            .await
+            .expect("should listen for network events");
+        chaser.goto(url).await.expect("should navigate to url");
+
+        let mut document = None;
+        while let Some(event) = events.next().await {
+            match event {
+                NetworkEvent::RequestWillBeSent(ev) if ev.request.url == url => {
+                    document = Some(ev.request_id.clone());
+                }
+                NetworkEvent::ResponseReceived(ev) if Some(&ev.request_id) == document.as_ref() => {
+                    assert_eq!(ev.response.status, 200);
+                }
+                NetworkEvent::LoadingFinished(ev) if Some(&ev.request_id) == document.as_ref() => {
+                    break;
+                }
+                _ => {}
+            }
+        }
+        assert!(document.is_some(), "should see the document request");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_console_messages_and_page_errors() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        let mut messages = chaser
+            .console_messages()
+            .await
+            .expect("should listen for console messages");
+        let mut errors = chaser
+            .page_errors()
+            .await
+            .expect("should listen for page errors");
+
+        chaser
+            .evaluate(r#"console.log("hi", 42); setTimeout(() => { throw new Error("x"); })"#)
+            .await
+            .expect("should evaluate");
+
+        let message = tokio::time::timeout(Duration::from_secs(5), messages.next())
+            .await
+            .expect("should log in time")
+            .expect("should receive the message");
+        assert_eq!(message.level, ConsoleLevel::Info);
+        assert_eq!(message.source, ConsoleSource::ConsoleApi);
+        assert_eq!(message.text, "hi 42");
+
+        let error = tokio::time::timeout(Duration::from_secs(5), errors.next())
+            .await
+            .expect("should throw in time")
+            .expect("should receive the error");
+        assert_eq!(error.level, ConsoleLevel::Error);
+        assert_eq!(error.source, ConsoleSource::Exception);
+        assert!(error.text.starts_with("Error: x"), "{}", error.text);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_screenshot_data_url() {
+    test(async |browser| {
+        let page = browser
+            .new_page("data:text/html,<h1 style='color:red'>captcha</h1>")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        let png = chaser
+            .screenshot_data_url(ScreenshotParams::default())
+            .await
+            .expect("should take png screenshot");
+        let data = png
+            .strip_prefix("data:image/png;base64,")
+            .expect("should be a png data url");
+        assert!(STANDARD.decode(data).unwrap().starts_with(b"\x89PNG"));
+
+        let jpeg = chaser
+            .screenshot_data_url(
+                ScreenshotParams::builder()
+                    .format(CaptureScreenshotFormat::Jpeg)
+                    .quality(80)
+                    .build(),
+            )
+            .await
+            .expect("should take jpeg screenshot");
+        let data = jpeg
+            .strip_prefix("data:image/jpeg;base64,")
+            .expect("should be a jpeg data url");
+        assert!(STANDARD.decode(data).unwrap().starts_with(b"\xff\xd8\xff"));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_screenshot_when_stable_waits_for_animation() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        chaser
+            .goto(
+                "data:text/html,<div id=box style='width:10px;height:50px;background:red'></div>\
+                 <script>let n = 0; const id = setInterval(() => { \
+                 box.style.width = (n += 20) + 'px'; \
+                 if (n >= 400) { clearInterval(id); window.settled = true; } }, 50);</script>",
+            )
+            .await
+            .expect("should navigate to animated page");
+
+        let png = chaser
+            .screenshot_when_stable(ScreenshotParams::default(), Duration::from_secs(10))
+            .await
+            .expect("should capture once the animation settled");
+        assert!(png.starts_with(b"\x89PNG"));
+
+        let settled = chaser
+            .call_function_with(
+                "() => window.settled === true",
+                &[],
+                CallFunctionOptions {
+                    world: ExecutionWorld::Main,
+                    ..Default::default()
+                },
+            )
+            .await
+            .expect("should read the animation state");
+        assert_eq!(settled, json!(true));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_profile_color_scheme_matches_media_queries() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        let profile = ChaserProfile::windows()
+            .color_scheme(ColorScheme::Dark)
+            .reduced_motion(true)
+            .build();
+        chaser
+            .apply_profile(&profile)
+            .await
+            .expect("should apply profile");
+        chaser
+            .goto("data:text/html,<p>media</p>")
+            .await
+            .expect("should navigate");
+
+        let media = chaser
+            .evaluate(
+                "[matchMedia('(prefers-color-scheme: dark)').matches, \
+                 matchMedia('(prefers-reduced-motion: reduce)').matches]",
+            )
+            .await
+            .expect("should query media features");
+        assert_eq!(media, Some(json!([true, true])));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_set_visible_reports_visible_and_focused() {
+    test(async |browser| {
+        let page = browser
+            .new_page("data:text/html,<p>first</p>")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        let state = "[document.visibilityState, document.hasFocus()]";
+
+        // another tab in front puts the first one in the background
+        let _other = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create second page");
+        let before = chaser.evaluate(state).await.expect("should read state");
+        assert_ne!(before, Some(json!(["visible", true])));
+
+        chaser
+            .set_visible(true)
+            .await
+            .expect("should make page visible");
+        let after = chaser.evaluate(state).await.expect("should read state");
+        assert_eq!(after, Some(json!(["visible", true])));
+
+        chaser
+            .simulate_attention()
+            .await
+            .expect("should look away and back");
+        let after = chaser.evaluate(state).await.expect("should read state");
+        assert_eq!(after, Some(json!(["visible", true])));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_wait_for_response_returns_xhr_body() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        let (response, navigated) = futures::join!(
+            chaser.wait_for_response("httpbin.org/delay", Duration::from_secs(15)),
+            chaser.goto(DELAYED_XHR_PAGE),
+        );
+        navigated.expect("should navigate to the XHR page");
+        let (status, body) = response.expect("should capture the XHR response");
+        assert_eq!(status, 200);
+        let body: serde_json::Value =
+            serde_json::from_slice(&body).expect("httpbin should return json");
+        assert_eq!(body["url"], json!("https://httpbin.org/delay/1"));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_profile_extended_screen() {
+    test(async |browser| {
+        for extended in [false, true] {
+            let page = browser
+                .new_page("about:blank")
+                .await
+                .expect("should create new page");
+            let chaser = ChaserPage::new(page);
+            let profile = ChaserProfile::windows()
+                .screen(2560, 1440)
+                .extended_screen(extended)
+                .build();
+            chaser
+                .apply_profile(&profile)
+                .await
+                .expect("should apply profile");
+            chaser
+                .goto("https://www.google.com")
+                .await
+                .expect("should navigate");
+
+            // the profile patches the main world
+            let layout = chaser
+                .call_function_with(
+                    "async () => {
+                        const details = await window.getScreenDetails();
+                        return [screen.isExtended, details.screens.length, details.screens[0].width];
+                    }",
+                    &[],
+                    CallFunctionOptions {
+                        world: ExecutionWorld::Main,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .expect("should read the screen layout");
+            let screens = if extended { 2 } else { 1 };
+            assert_eq!(layout, json!([extended, screens, 2560]));
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_profile_notification_permission_matches_permissions_api() {
+    test(async |browser| {
+        for (permission, expected) in [
+            (
+                NotificationPermission::Default,
+                json!(["default", "prompt", "default"]),
+            ),
+            (
+                NotificationPermission::Granted,
+                json!(["granted", "granted", "granted"]),
+            ),
+            (
+                NotificationPermission::Denied,
+                json!(["denied", "denied", "denied"]),
+            ),
+        ] {
+            let page = browser
+                .new_page("about:blank")
+                .await
+                .expect("should create new page");
+            let chaser = ChaserPage::new(page);
+            let profile = ChaserProfile::windows()
+                .notification_permission(permission)
+                .build();
+            chaser
+                .apply_profile(&profile)
+                .await
+                .expect("should apply profile");
+            chaser
+                .goto("https://www.google.com")
+                .await
+                .expect("should navigate");
+
+            let states = chaser
+                .call_function_with(
+                    "async () => {
+                        const status = await navigator.permissions.query({ name: 'notifications' });
+                        const requested = await Notification.requestPermission();
+                        return [Notification.permission, status.state, requested];
+                    }",
+                    &[],
+                    CallFunctionOptions {
+                        world: ExecutionWorld::Main,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .expect("should read the notification permission");
+            assert_eq!(states, expected);
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_set_extra_headers() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+
+        let headers = HashMap::from([("X-Api-Key".to_string(), "chaser-secret".to_string())]);
+        chaser
+            .set_extra_headers(headers)
+            .await
+            .expect("should set extra headers");
+
+        let headers = document_request_headers(&chaser, "https://www.google.com").await;
+        assert_eq!(
+            headers.get("X-Api-Key").and_then(|v| v.as_str()),
+            Some("chaser-secret")
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_reset_identity_restores_platform() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        // the profile patches the main world
+        let platform = async || {
+            chaser
+                .call_function_with(
+                    "() => navigator.platform",
+                    &[],
+                    CallFunctionOptions {
+                        world: ExecutionWorld::Main,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .expect("should read the platform")
+        };
+
+        chaser
+            .goto("data:text/html,<p>real</p>")
+            .await
+            .expect("should navigate to page");
+        let real = platform().await;
+        assert_ne!(real, json!("Win32"), "test requires a non-Windows host");
+
+        chaser
+            .apply_profile(&ChaserProfile::windows().build())
+            .await
+            .expect("should apply profile");
+        chaser
+            .goto("data:text/html,<p>spoofed</p>")
+            .await
+            .expect("should navigate to page");
+        assert_eq!(platform().await, json!("Win32"));
+
+        chaser
+            .reset_identity()
+            .await
+            .expect("should reset identity");
+        chaser
+            .goto("data:text/html,<p>reset</p>")
+            .await
+            .expect("should navigate to page");
+        assert_eq!(platform().await, real);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_profile_extra_script_runs_after_core_patches() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        let mut profile = ChaserProfile::windows().build();
+        profile
+            .with_extra_script("window.seenPlatform = navigator.platform;")
+            .expect("should accept extra script");
+        chaser
+            .apply_profile(&profile)
+            .await
+            .expect("should apply profile");
+        chaser
+            .goto("data:text/html,<p>extra</p>")
+            .await
+            .expect("should navigate to page");
+
+        // the profile patches the main world
+        let seen = chaser
+            .call_function_with(
+                "() => [navigator.platform, window.seenPlatform]",
+                &[],
+                CallFunctionOptions {
+                    world: ExecutionWorld::Main,
+                    ..Default::default()
+                },
+            )
+            .await
+            .expect("should read the platforms");
+        assert_eq!(seen, json!(["Win32", "Win32"]));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_set_device_orientation_fires_event() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        chaser
+            .apply_profile(&ChaserProfile::windows().build())
+            .await
+            .expect("should apply profile");
+        assert!(
+            chaser
+                .set_device_orientation(10.0, 20.0, 30.0)
+                .await
+                .is_err(),
+            "desktop profiles have no orientation sensor"
+        );
+
+        let profile = ChaserProfile::linux().touch_points(5).mobile(true).build();
+        chaser
+            .apply_profile(&profile)
+            .await
+            .expect("should apply profile");
+        chaser
+            .goto("data:text/html,<p>sensors</p>")
+            .await
+            .expect("should navigate to page");
+        chaser
+            .set_device_orientation(10.0, 20.0, 30.0)
+            .await
+            .expect("should override device orientation");
+
+        let orientation = chaser
+            .call_function_with(
+                "() => new Promise((resolve) => addEventListener('deviceorientation',
+                    (e) => resolve([e instanceof DeviceOrientationEvent, e.alpha, e.beta, e.gamma]),
+                    { once: true }))",
+                &[],
+                CallFunctionOptions {
+                    world: ExecutionWorld::Main,
+                    ..Default::default()
+                },
+            )
+            .await
+            .expect("should receive a deviceorientation event");
+        assert_eq!(orientation, json!([true, 10.0, 20.0, 30.0]));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_final_url_follows_script_redirect() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        chaser
+            .goto(
+                "data:text/html,<script>addEventListener('load', () => \
+                 setTimeout(() => location.href = 'https://www.google.com/', 300))</script>",
+            )
+            .await
+            .expect("should navigate to page");
+
+        let url = chaser
+            .final_url(Duration::from_secs(2))
+            .await
+            .expect("should settle on the redirect target");
+        assert!(url.starts_with("https://www.google.com/"), "{url}");
+    })
+    .await;
+}
+
+/// An HTTP server whose page redirects by script to `/slow`, which answers
+/// after a second
+fn slow_redirect_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("should bind listener");
+    let addr = listener.local_addr().unwrap();
+
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            std::thread::spawn(move || {
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let html = if request.starts_with(b"GET /slow ") {
+                    std::thread::sleep(Duration::from_secs(1));
+                    "<title>final</title>"
+                } else {
+                    "<script>addEventListener('load', () => location.href = '/slow')</script>"
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: text/html\r\ncontent-length: {}\r\n\
+                     connection: close\r\n\r\n{html}",
+                    html.len()
+                );
+                let _ = stream.write_all(response.as_bytes());
+            });
+        }
+    });
+
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn test_final_url_waits_for_slow_redirect() {
+    test(async |browser| {
+        let origin = slow_redirect_server();
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        chaser.goto(&origin).await.expect("should navigate to page");
+
+        // the redirect target answers later than the settle window
+        let url = chaser
+            .final_url(Duration::from_millis(200))
+            .await
+            .expect("should settle on the redirect target");
+        assert_eq!(url, format!("{origin}/slow"));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_execute_matches_evaluate() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        chaser
+            .goto("data:text/html,<title>raw cdp</title>")
+            .await
+            .expect("should navigate to page");
+
+        let raw = chaser
+            .execute(
+                EvaluateParams::builder()
+                    .expression("document.title")
+                    .return_by_value(true)
+                    .build()
+                    .unwrap(),
+            )
+            .await
+            .expect("should evaluate through raw CDP");
+        let evaluated = chaser
+            .evaluate("document.title")
+            .await
+            .expect("should evaluate");
+        assert_eq!(raw.result.result.value, evaluated);
+        assert_eq!(evaluated, Some(json!("raw cdp")));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_script_bundle_reload_does_not_duplicate() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let chaser = ChaserPage::new(page);
+        let mut bundle = chaser.script_bundle("counters");
+        bundle
+            .add(
+                "count",
+                "window.runs = (window.runs || []).concat('count');",
+            )
+            .await
+            .expect("should add script");
+        bundle
+            .add("mark", "window.runs = (window.runs || []).concat('mark');")
+            .await
+            .expect("should add script");
+        bundle.reload().await.expect("should reload bundle");
+
+        chaser
+            .goto("data:text/html,<p>bundle</p>")
+            .await
+            .expect("should navigate to page");
+        chaser
+            .raw_page()
+            .reload()
+            .await
+            .expect("should reload page");
+        // init scripts run in the main world
+        let runs = async || {
+            chaser
+                .call_function_with(
+                    "() => window.runs",
+                    &[],
+                    CallFunctionOptions {
+                        world: ExecutionWorld::Main,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .expect("should read the runs")
+        };
+        assert_eq!(runs().await, json!(["count", "mark"]));
+
+        bundle.remove_all().await.expect("should remove bundle");
+        chaser
+            .raw_page()
+            .reload()
+            .await
+            .expect("should reload page");
+        assert_eq!(runs().await, json!(null));
+    })
+    .await;
+}
+
+/// An HTTP server answering `/gzip` and `/br` with a text body compressed
+/// with that encoding
+fn encoded_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("should bind listener");
+    let addr = listener.local_addr().unwrap();
+
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let (encoding, body) = if request.starts_with(b"GET /br ") {
+                ("br", encode_body("br"))
+            } else {
+                ("gzip", encode_body("gzip"))
+            };
+            let head = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: text/plain\r\ncontent-encoding: {encoding}\r\n\
+                 content-length: {}\r\nconnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(head.as_bytes());
+            let _ = stream.write_all(&body);
+        }
+    });
+
+    format!("http://{addr}")
+}
+
+/// The body [`encoded_server`] sends for `encoding`
+fn encode_body(encoding: &str) -> Vec<u8> {
+    let text = format!("hello {encoding}");
+    match encoding {
+        "br" => {
+            let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+            encoder.write_all(text.as_bytes()).unwrap();
+            encoder.into_inner()
+        }
+        _ => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(text.as_bytes()).unwrap();
+            encoder.finish().unwrap()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_response_bodies_are_decoded() {
+    test(async |browser| {
+        let origin = encoded_server();
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+
+        for encoding in ["gzip", "br"] {
+            let url = format!("{origin}/{encoding}");
+            for mode in [ResponseBody::Decoded, ResponseBody::Raw] {
+                let mut responses = page
+                    .on_response_with(|event| event.resource_type == ResourceType::Document, mode)
+                    .await
+                    .expect("should capture responses");
+                page.goto(&url).await.expect("should navigate");
+                let response = responses.next().await.expect("should capture the response");
+                assert_eq!(response.content_encoding.as_deref(), Some(encoding));
+                let expected = match mode {
+                    ResponseBody::Decoded => format!("hello {encoding}").into_bytes(),
+                    ResponseBody::Raw => encode_body(encoding),
+                };
+                assert_eq!(response.body, expected, "{encoding} {mode:?}");
+            }
+        }
+
+        // `Network.getResponseBody` reports the body decoded already
+        let chaser = ChaserPage::new(page);
+        let url = format!("{origin}/br");
+        let (response, navigated) = futures::join!(
+            chaser.wait_for_response("/br", Duration::from_secs(5)),
+            chaser.goto(&url),
+        );
+        navigated.expect("should navigate");
+        let (status, body) = response.expect("should capture the response");
+        assert_eq!(status, 200);
+        assert_eq!(body, b"hello br");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_profile_geolocation_follows_timezone() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        browser
+            .execute(GrantPermissionsParams::new(vec![
+                PermissionType::Geolocation,
+            ]))
+            .await
+            .expect("should grant geolocation");
+        let chaser = ChaserPage::new(page);
+        let profile = ChaserProfile::windows().timezone("Europe/Berlin").build();
+        chaser
+            .apply_profile(&profile)
+            .await
+            .expect("should apply profile");
+        chaser
+            .goto("https://www.google.com")
+            .await
+            .expect("should navigate to www.google.com");
+
+        let coords = chaser
+            .evaluate(
+                "new Promise((resolve, reject) => navigator.geolocation.getCurrentPosition(
+                    (p) => resolve([p.coords.latitude, p.coords.longitude]), reject))",
+            )
+            .await
+            .expect("should read the position");
+        let (latitude, longitude) = timezone_to_coords("Europe/Berlin").unwrap();
+        assert_eq!(coords, Some(json!([latitude, longitude])));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_verify_stealth() {
+    test(async |browser| {
+        let url = "data:text/html,<p>stealth</p>";
+        for profile in [
+            ChaserProfile::windows().cpu_cores(12).build(),
+            ChaserProfile::ios().build(),
+        ] {
+            let page = browser
+                .new_page("about:blank")
+                .await
+                .expect("should create new page");
+            let chaser = ChaserPage::new(page);
+            chaser.goto(url).await.expect("should navigate to page");
+            assert!(!chaser.verify_stealth().await.expect("should verify"));
+
+            // registered after the navigation, so the document isn't patched
+            chaser
+                .apply_profile(&profile)
+                .await
+                .expect("should apply profile");
+            assert!(!chaser.verify_stealth().await.expect("should verify"));
+
+            chaser.goto(url).await.expect("should navigate to page");
+            assert!(
+                chaser.verify_stealth().await.expect("should verify"),
+                "{:?}",
+                profile.os()
+            );
+        }
+    })
+    .await;
+}
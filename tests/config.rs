@@ -1,3 +1,9 @@
+use chaser_oxide::cdp::browser_protocol::input::{
+    DispatchTouchEventParams, DispatchTouchEventType, TouchPoint,
+};
+use chaser_oxide::handler::viewport::DevicePreset;
+use chaser_oxide::ChaserPage;
+
 use crate::{test_config, BrowserConfig};
 
 #[tokio::test]
@@ -17,3 +23,134 @@ async fn test_config_disable_https_first() {
     )
     .await;
 }
+
+#[tokio::test]
+#[ignore] // Requires a GPU
+async fn test_config_enable_gpu() {
+    test_config(
+        BrowserConfig::builder()
+            .new_headless_mode()
+            .enable_gpu()
+            .build()
+            .unwrap(),
+        async |browser| {
+            let page = browser.new_page("about:blank").await.unwrap();
+            let renderer: String = page
+                .evaluate(
+                    "(() => {
+                        const gl = document.createElement('canvas').getContext('webgl');
+                        const info = gl.getExtension('WEBGL_debug_renderer_info');
+                        return gl.getParameter(info.UNMASKED_RENDERER_WEBGL);
+                    })()",
+                )
+                .await
+                .unwrap()
+                .into_value()
+                .unwrap();
+            assert!(!renderer.contains("SwiftShader"), "{renderer}");
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_config_device_preset_dispatches_touch_events() {
+    test_config(
+        BrowserConfig::builder()
+            .device_preset(DevicePreset::IPhone13)
+            .build()
+            .unwrap(),
+        async |browser| {
+            let page = browser.new_page("about:blank").await.unwrap();
+            page.goto("data:text/html,<body style='height:100vh'></body>")
+                .await
+                .unwrap();
+            page.evaluate(
+                "document.body.addEventListener('touchstart', () => { window.touched = true; })",
+            )
+            .await
+            .unwrap();
+
+            let point = TouchPoint::builder().x(100.).y(100.).build().unwrap();
+            page.execute(DispatchTouchEventParams::new(
+                DispatchTouchEventType::TouchStart,
+                vec![point],
+            ))
+            .await
+            .unwrap();
+            page.execute(DispatchTouchEventParams::new(
+                DispatchTouchEventType::TouchEnd,
+                vec![],
+            ))
+            .await
+            .unwrap();
+
+            let touched: bool = page
+                .evaluate("window.touched === true")
+                .await
+                .unwrap()
+                .into_value()
+                .unwrap();
+            assert!(touched);
+
+            let (dpr, touch_points): (f64, u32) = page
+                .evaluate("[window.devicePixelRatio, navigator.maxTouchPoints]")
+                .await
+                .unwrap()
+                .into_value()
+                .unwrap();
+            assert_eq!(dpr, 3.);
+            assert!(touch_points > 0);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_config_device_reports_mobile_navigator() {
+    test_config(
+        BrowserConfig::builder().device("Pixel 7").build().unwrap(),
+        async |browser| {
+            let device = browser.config().unwrap().device().unwrap();
+            let page = ChaserPage::new(browser.new_page("about:blank").await.unwrap());
+            page.apply_profile(&device.profile().build()).await.unwrap();
+            page.goto("about:blank").await.unwrap();
+
+            let values = page
+                .evaluate(
+                    "[innerWidth, devicePixelRatio, navigator.platform, navigator.maxTouchPoints, \
+                     navigator.userAgent, navigator.userAgentData.mobile]",
+                )
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(values[0], 412);
+            assert_eq!(values[1], 2.625);
+            assert_eq!(values[2], "Linux armv81");
+            assert_eq!(values[3], 5);
+            assert_eq!(values[4], device.user_agent().as_str());
+            assert_eq!(values[5], true);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_config_persistent_profile_is_reused() {
+    let mut dirs = Vec::new();
+    for _ in 0..2 {
+        test_config(
+            BrowserConfig::builder()
+                .persistent_profile("chaser-oxide-test")
+                .build()
+                .unwrap(),
+            async |browser| {
+                let dir = browser.config().unwrap().user_data_dir.clone().unwrap();
+                assert!(dir.is_dir());
+                dirs.push(dir);
+            },
+        )
+        .await;
+    }
+    assert_eq!(dirs[0], dirs[1]);
+}
@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_tungstenite::tungstenite::Message;
+use chaser_oxide::cdp::browser_protocol::network::GetCookiesParams;
+use chaser_oxide::cdp::js_protocol::runtime::EvaluateParams;
+use chaser_oxide::conn::PROTOCOL_TRACE_TARGET;
+use chaser_oxide::error::CdpError;
+use chaser_oxide::handler::HandlerConfig;
+use chaser_oxide::Browser;
+use futures::StreamExt;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+/// A websocket server that completes the handshake but never answers
+async fn stalled_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("should bind listener");
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.expect("should accept connection");
+        let mut ws = async_tungstenite::tokio::accept_async(stream)
+            .await
+            .expect("should accept websocket");
+        // read, but never respond
+        while ws.next().await.is_some() {}
+    });
+
+    format!("ws://{addr}")
+}
+
+/// A websocket server that answers every command, `Runtime.evaluate` with
+/// the number 2 and `Target.createTarget` with a target it never announces,
+/// and records the calls it received
+async fn answering_server() -> (String, Arc<Mutex<Vec<Value>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("should bind listener");
+    let addr = listener.local_addr().unwrap();
+    let calls = Arc::new(Mutex::new(Vec::new()));
+
+    let received = calls.clone();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.expect("should accept connection");
+        let mut ws = async_tungstenite::tokio::accept_async(stream)
+            .await
+            .expect("should accept websocket");
+        while let Some(Ok(Message::Text(text))) = ws.next().await {
+            let call: Value = serde_json::from_str(text.as_str()).unwrap();
+            received.lock().unwrap().push(call.clone());
+            let result = match call["method"].as_str() {
+                Some("Runtime.evaluate") => json!({ "result": { "type": "number", "value": 2 } }),
+                Some("Network.getCookies") => json!({ "cookies": [] }),
+                Some("Target.createTarget") => json!({ "targetId": "unannounced" }),
+                _ => json!({}),
+            };
+            let response = json!({ "id": call["id"], "result": result });
+            if ws.send(Message::text(response.to_string())).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    (format!("ws://{addr}"), calls)
+}
+
+/// A websocket server that answers every command with an empty result and
+/// records the methods it received
+async fn recording_server() -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("should bind listener");
+    let addr = listener.local_addr().unwrap();
+    let methods = Arc::new(Mutex::new(Vec::new()));
+
+    let received = methods.clone();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.expect("should accept connection");
+        let mut ws = async_tungstenite::tokio::accept_async(stream)
+            .await
+            .expect("should accept websocket");
+        while let Some(Ok(Message::Text(text))) = ws.next().await {
+            let call: Value = serde_json::from_str(text.as_str()).unwrap();
+            received
+                .lock()
+                .unwrap()
+                .push(call["method"].as_str().unwrap_or_default().to_string());
+            let response = json!({ "id": call["id"], "result": {} });
+            if ws.send(Message::text(response.to_string())).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    (format!("ws://{addr}"), methods)
+}
+
+/// Records the fields of the events logged with a `tracing` target
+#[derive(Clone)]
+struct TraceCapture {
+    target: &'static str,
+    events: Arc<Mutex<Vec<HashMap<String, String>>>>,
+}
+
+impl TraceCapture {
+    fn new(target: &'static str) -> Self {
+        Self {
+            target,
+            events: Default::default(),
+        }
+    }
+}
+
+struct Fields<'a>(&'a mut HashMap<String, String>);
+
+impl Visit for Fields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+impl<S: Subscriber> Layer<S> for TraceCapture {
+    fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+        if event.metadata().target() == self.target {
+            let mut fields = HashMap::new();
+            event.record(&mut Fields(&mut fields));
+            self.events.lock().unwrap().push(fields);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_trace_protocol_logs_commands() {
+    let capture = TraceCapture::new(PROTOCOL_TRACE_TARGET);
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+    let (url, _) = answering_server().await;
+    let config = HandlerConfig {
+        trace_protocol: true,
+        ..Default::default()
+    };
+    let (browser, mut handler) = Browser::connect_with_config(url, config)
+        .await
+        .expect("should connect to server");
+    let handler = tokio::spawn(async move { while handler.next().await.is_some() {} });
+
+    let evaluated = browser
+        .execute(EvaluateParams::new("1 + 1"))
+        .await
+        .expect("should evaluate");
+    assert_eq!(evaluated.result.result.value, Some(json!(2)));
+    browser
+        .execute(GetCookiesParams::default())
+        .await
+        .expect("should get cookies");
+    handler.abort();
+
+    let events = capture.events.lock().unwrap();
+    let find = |message: &str, method: &str| {
+        events
+            .iter()
+            .find(|fields| fields["message"] == message && fields["method"] == method)
+            .unwrap_or_else(|| panic!("should trace the {message} of {method}"))
+    };
+    assert!(find("command", "Runtime.evaluate")["params"].contains("1 + 1"));
+    assert!(find("response", "Runtime.evaluate")["payload"].contains("\"value\":2"));
+    assert_eq!(
+        find("command", "Network.getCookies")["params"],
+        "<redacted>"
+    );
+    assert_eq!(
+        find("response", "Network.getCookies")["payload"],
+        "<redacted>"
+    );
+}
+
+#[tokio::test]
+async fn test_connection_log_redacts_sensitive_methods() {
+    let capture = TraceCapture::new("chaser_oxide::conn");
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+    let (url, _) = answering_server().await;
+    let (browser, mut handler) = Browser::connect(url)
+        .await
+        .expect("should connect to server");
+    let handler = tokio::spawn(async move { while handler.next().await.is_some() {} });
+
+    browser
+        .execute(EvaluateParams::new("1 + 1"))
+        .await
+        .expect("should evaluate");
+    browser
+        .execute(
+            GetCookiesParams::builder()
+                .url("https://secret.example")
+                .build(),
+        )
+        .await
+        .expect("should get cookies");
+    handler.abort();
+
+    let messages: Vec<_> = capture
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|fields| fields["message"].clone())
+        .collect();
+    assert!(messages.iter().any(|msg| msg.contains("1 + 1")));
+    assert!(messages
+        .iter()
+        .any(|msg| msg.contains("Network.getCookies <redacted>")));
+    assert!(messages.iter().all(|msg| !msg.contains("secret.example")));
+    assert!(messages.iter().all(|msg| !msg.contains("cookies")));
+}
+
+#[tokio::test]
+async fn test_new_page_times_out_without_target() {
+    let (url, calls) = answering_server().await;
+    let config = HandlerConfig {
+        request_timeout: Duration::from_millis(200),
+        ..Default::default()
+    };
+    let (browser, mut handler) = Browser::connect_with_config(url, config)
+        .await
+        .expect("should connect to server");
+    let handler = tokio::spawn(async move { while handler.next().await.is_some() {} });
+
+    let created = tokio::time::timeout(Duration::from_secs(10), browser.new_page("about:blank"))
+        .await
+        .expect("should give up on the target");
+    assert!(
+        matches!(created, Err(CdpError::Timeout)),
+        "{:?}",
+        created.map(|_| ())
+    );
+
+    // every target chrome created is closed instead of left behind
+    let count = |method: &str| {
+        calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|call| call["method"] == method)
+            .count()
+    };
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while count("Target.closeTarget") < count("Target.createTarget") {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("should close the created targets");
+    handler.abort();
+    assert!(calls
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|call| call["method"] == "Target.closeTarget")
+        .all(|call| call["params"]["targetId"] == "unannounced"));
+}
+
+#[tokio::test]
+async fn test_heartbeat_detects_stalled_connection() {
+    let url = stalled_server().await;
+    let config = HandlerConfig {
+        heartbeat_interval: Some(Duration::from_millis(200)),
+        ..Default::default()
+    };
+    let (browser, mut handler) = Browser::connect_with_config(url, config)
+        .await
+        .expect("should connect to server");
+
+    let handler = tokio::spawn(async move {
+        let mut errors = Vec::new();
+        while let Some(event) = handler.next().await {
+            if let Err(err) = event {
+                errors.push(err);
+            }
+        }
+        errors
+    });
+
+    let version = tokio::time::timeout(Duration::from_secs(5), browser.version())
+        .await
+        .expect("should fail before the timeout");
+    assert!(matches!(version, Err(CdpError::ConnectionClosed)));
+
+    let errors = tokio::time::timeout(Duration::from_secs(5), handler)
+        .await
+        .expect("handler should end")
+        .unwrap();
+    assert!(matches!(errors.as_slice(), [CdpError::ConnectionClosed]));
+}
+
+#[tokio::test]
+async fn test_heartbeat_keeps_answering_connection_open() {
+    let (url, methods) = recording_server().await;
+    let config = HandlerConfig {
+        heartbeat_interval: Some(Duration::from_millis(100)),
+        ..Default::default()
+    };
+    let (_browser, mut handler) = Browser::connect_with_config(url, config)
+        .await
+        .expect("should connect to server");
+
+    // the handler is idle in between, only the heartbeat wakes it
+    let idle = tokio::time::timeout(Duration::from_secs(1), async {
+        while let Some(event) = handler.next().await {
+            event.expect("the connection should stay open");
+        }
+    })
+    .await;
+    assert!(idle.is_err(), "the handler should still be running");
+
+    let probes = methods
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|method| *method == "Browser.getVersion")
+        .count();
+    assert!(probes >= 5, "sent {probes} probes");
+}
+
+#[tokio::test]
+async fn test_idle_timeout_sends_browser_close() {
+    let (url, methods) = recording_server().await;
+    let config = HandlerConfig {
+        idle_timeout: Some(Duration::from_millis(200)),
+        ..Default::default()
+    };
+    let (_browser, mut handler) = Browser::connect_with_config(url, config)
+        .await
+        .expect("should connect to server");
+
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while let Some(event) = handler.next().await {
+            event.expect("the connection should stay open");
+        }
+    })
+    .await
+    .expect("handler should end once idle");
+    assert!(methods
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|method| method == "Browser.close"));
+}
@@ -1,11 +1,17 @@
 use std::panic;
 
-use chromiumoxide::{Browser, BrowserConfig};
+use chaser_oxide::{Browser, BrowserConfig};
 use futures::{FutureExt, StreamExt};
 
 mod basic;
+mod browser;
+mod chaser;
 mod config;
+// the mock websocket server relies on tokio
+#[cfg(feature = "tokio-runtime")]
+mod handler;
 mod page;
+mod pool;
 
 pub async fn test<T>(test: T)
 where
@@ -1,4 +1,23 @@
-use crate::test;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::time::Duration;
+
+use chaser_oxide::cdp::browser_protocol::emulation::{
+    SetEmulatedMediaParams, SetGeolocationOverrideParams, SetTouchEmulationEnabledParams,
+};
+use chaser_oxide::cdp::browser_protocol::network::{
+    BlockedReason, CookieParam, CookiePriority, CookieSameSite, EventLoadingFailed,
+};
+use chaser_oxide::cdp::js_protocol::runtime::EvaluateParams;
+use chaser_oxide::cmd::BatchCommand;
+use chaser_oxide::cookie::CookieBuilder;
+use chaser_oxide::error::CdpError;
+use chaser_oxide::intercept::{InterceptRules, RequestAction, ResponseAction, REPORTING_HEADERS};
+use chaser_oxide::{BrowserConfig, ChaserPage, ChaserProfile, ResourceType};
+use futures::future::join_all;
+use futures::StreamExt;
+
+use crate::{test, test_config};
 
 #[tokio::test]
 async fn test_evaluate_on_new_document() {
@@ -28,6 +47,41 @@ async fn test_evaluate_on_new_document() {
     .await;
 }
 
+#[tokio::test]
+async fn test_remove_script_on_new_document() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+
+        let first = page
+            .evaluate_on_new_document("window.first = true;")
+            .await
+            .expect("should add first script");
+        page.evaluate_on_new_document("window.second = true;")
+            .await
+            .expect("should add second script");
+        page.remove_script_on_new_document(first)
+            .await
+            .expect("should remove first script");
+
+        page.goto("https://www.google.com")
+            .await
+            .expect("should navigate to www.google.com");
+
+        let injected: Vec<bool> = page
+            .evaluate("[window.first === true, window.second === true]")
+            .await
+            .expect("should evaluate injected globals")
+            .into_value()
+            .expect("should convert to bools");
+
+        assert_eq!(injected, [false, true]);
+    })
+    .await;
+}
+
 #[tokio::test]
 async fn test_add_init_script() {
     test(async |browser| {
@@ -55,3 +109,676 @@ async fn test_add_init_script() {
     })
     .await;
 }
+
+#[tokio::test]
+async fn test_block_resource_types() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+
+        page.block_resource_types(&[ResourceType::Image])
+            .await
+            .expect("should block images");
+
+        let mut failed = page
+            .event_listener::<EventLoadingFailed>()
+            .await
+            .expect("should listen for failed requests");
+
+        page.goto(
+            "data:text/html,<title>blocked</title>\
+             <img src='https://www.google.com/images/branding/googlelogo/1x/googlelogo_color_272x92dp.png'>",
+        )
+        .await
+        .expect("should load the document");
+
+        let event = failed.next().await.expect("should fail the image request");
+        assert_eq!(event.r#type, ResourceType::Image);
+        assert_eq!(event.error_text, "net::ERR_BLOCKED_BY_CLIENT");
+
+        let title = page.get_title().await.expect("should get title");
+        assert_eq!(title.as_deref(), Some("blocked"));
+
+        let width: i64 = page
+            .evaluate("document.images[0].naturalWidth")
+            .await
+            .expect("should evaluate image width")
+            .into_value()
+            .expect("should convert to i64");
+        assert_eq!(width, 0);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_intercept_after_on_response() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+
+        // enables the response stage first
+        let _responses = page
+            .on_response(|_| false)
+            .await
+            .expect("should capture responses");
+        page.intercept(InterceptRules::new().block_resource_type(ResourceType::Image))
+            .await
+            .expect("should block images");
+
+        let mut failed = page
+            .event_listener::<EventLoadingFailed>()
+            .await
+            .expect("should listen for failed requests");
+
+        page.goto(
+            "data:text/html,<title>blocked</title>\
+             <img src='https://www.google.com/images/branding/googlelogo/1x/googlelogo_color_272x92dp.png'>",
+        )
+        .await
+        .expect("should load the document");
+
+        let event = failed.next().await.expect("should fail the image request");
+        assert_eq!(event.r#type, ResourceType::Image);
+        assert_eq!(event.error_text, "net::ERR_BLOCKED_BY_CLIENT");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_on_response() {
+    test(async |browser| {
+        let page = browser
+            .new_page("https://httpbin.org/")
+            .await
+            .expect("should create new page");
+
+        let mut responses = page
+            .on_response(|event| event.request.url.ends_with("/json"))
+            .await
+            .expect("should capture responses");
+
+        let xhr = {
+            let page = page.clone();
+            tokio::spawn(async move {
+                page.evaluate("fetch('/json').then(r => r.json()).then(j => j.slideshow.title)")
+                    .await
+                    .expect("should fetch json")
+                    .into_value::<String>()
+                    .expect("should convert to string")
+            })
+        };
+
+        let response = responses.next().await.expect("should capture the response");
+        let title = xhr.await.unwrap();
+
+        assert_eq!(response.url, "https://httpbin.org/json");
+        assert_eq!(response.status, 200);
+        assert!(response.is_json());
+        let json: serde_json::Value =
+            serde_json::from_slice(&response.body).expect("should be valid json");
+        assert_eq!(json["slideshow"]["title"], title.as_str());
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_modify_requests_rewrites_url() {
+    test(async |browser| {
+        let page = browser
+            .new_page("https://httpbin.org/")
+            .await
+            .expect("should create new page");
+
+        page.modify_requests(|request| {
+            if request.url.ends_with("/json") {
+                RequestAction::ContinueWith {
+                    url: Some("https://httpbin.org/uuid".to_string()),
+                    method: None,
+                    headers: None,
+                    post_data: None,
+                }
+            } else {
+                RequestAction::Continue
+            }
+        })
+        .await
+        .expect("should modify requests");
+
+        let body: serde_json::Value = page
+            .evaluate("fetch('/json').then(r => r.json())")
+            .await
+            .expect("should fetch json")
+            .into_value()
+            .expect("should convert to json");
+
+        assert!(body.get("uuid").is_some());
+        assert!(body.get("slideshow").is_none());
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_modify_responses_strips_reporting_headers() {
+    test(async |browser| {
+        let page = browser
+            .new_page("https://httpbin.org/")
+            .await
+            .expect("should create new page");
+
+        page.modify_responses(|response| {
+            if response.header("report-to").is_some() {
+                ResponseAction::StripHeaders(REPORTING_HEADERS)
+            } else {
+                ResponseAction::Continue
+            }
+        })
+        .await
+        .expect("should modify responses");
+
+        // httpbin answers with the query parameters as headers
+        let headers: Vec<Option<String>> = page
+            .evaluate(
+                "fetch('/response-headers?Report-To=%7B%7D&NEL=%7B%7D&X-Kept=yes')
+                    .then(r => ['report-to', 'nel', 'x-kept'].map(name => r.headers.get(name)))",
+            )
+            .await
+            .expect("should fetch the headers")
+            .into_value()
+            .expect("should convert to json");
+        assert_eq!(headers, [None, None, Some("yes".to_string())]);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_concurrent_commands_are_not_serialized() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+
+        // each evaluation takes 200ms, so 64 of them run one after another
+        // would take over 12s
+        let started = std::time::Instant::now();
+        let results = join_all((0..64).map(|i| {
+            page.evaluate(format!(
+                "new Promise(resolve => setTimeout(() => resolve({i}), 200))"
+            ))
+        }))
+        .await;
+
+        for (i, result) in results.into_iter().enumerate() {
+            let value: usize = result
+                .expect("should evaluate")
+                .into_value()
+                .expect("should be a number");
+            assert_eq!(value, i);
+        }
+        assert!(
+            started.elapsed() < std::time::Duration::from_secs(5),
+            "took {:?}",
+            started.elapsed()
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_execute_timeout_overrides_request_timeout() {
+    test_config(
+        BrowserConfig::builder()
+            .request_timeout(Duration::from_secs(1))
+            .build()
+            .unwrap(),
+        async |browser| {
+            let page = browser
+                .new_page("about:blank")
+                .await
+                .expect("should create new page");
+            let slow = || {
+                EvaluateParams::builder()
+                    .expression("new Promise(resolve => setTimeout(() => resolve(42), 2000))")
+                    .await_promise(true)
+                    .build()
+                    .unwrap()
+            };
+
+            let result = page.execute(slow()).await;
+            assert!(
+                matches!(result, Err(CdpError::Timeout)),
+                "should time out at the default"
+            );
+
+            let value = page
+                .execute_timeout(slow(), Duration::from_secs(5))
+                .await
+                .expect("should finish with a raised timeout")
+                .result
+                .result
+                .value;
+            assert_eq!(value, Some(serde_json::json!(42)));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_wait_for_download() {
+    test(async |browser| {
+        let dir = std::env::temp_dir().join("chaser-oxide-downloads");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("should create download dir");
+
+        let page = browser
+            .new_page("data:text/html,<a download='report.csv' href='data:text/csv,a;b'>csv</a>")
+            .await
+            .expect("should create new page");
+        page.set_download_behavior(&dir)
+            .await
+            .expect("should allow downloads");
+
+        let (path, clicked) = futures::join!(
+            page.wait_for_download(Duration::from_secs(10)),
+            page.evaluate("document.querySelector('a').click()"),
+        );
+        clicked.expect("should click the link");
+        let path = path.expect("should complete the download");
+
+        assert_eq!(path, dir.join("report.csv"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a;b");
+        let _ = std::fs::remove_dir_all(&dir);
+    })
+    .await;
+}
+
+const TRACKER_PAGE: &str = "data:text/html,<title>tracked</title>\
+    <img src='https://www.google-analytics.com/collect.gif'>";
+
+#[tokio::test]
+async fn test_block_domains() {
+    let config = BrowserConfig::builder()
+        .block_domains(&["google-analytics.com"])
+        .build()
+        .unwrap();
+    test_config(config, async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let mut failed = page
+            .event_listener::<EventLoadingFailed>()
+            .await
+            .expect("should listen for failed requests");
+
+        page.goto(TRACKER_PAGE)
+            .await
+            .expect("should load the document");
+
+        let event = failed
+            .next()
+            .await
+            .expect("should fail the tracker request");
+        assert_eq!(event.blocked_reason, Some(BlockedReason::Inspector));
+        let title = page.get_title().await.expect("should get title");
+        assert_eq!(title.as_deref(), Some("tracked"));
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_set_blocked_urls() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let mut failed = page
+            .event_listener::<EventLoadingFailed>()
+            .await
+            .expect("should listen for failed requests");
+
+        page.set_blocked_urls(&["*google-analytics.com/*.gif".to_string()])
+            .await
+            .expect("should block urls");
+        page.goto(TRACKER_PAGE)
+            .await
+            .expect("should load the document");
+        let event = failed
+            .next()
+            .await
+            .expect("should fail the tracker request");
+        assert_eq!(event.error_text, "net::ERR_BLOCKED_BY_CLIENT");
+
+        page.set_blocked_urls(&[])
+            .await
+            .expect("should unblock all urls");
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_execute_batch() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        // later commands finish first
+        let delayed = |i: usize| {
+            EvaluateParams::builder()
+                .expression(format!(
+                    "new Promise(resolve => setTimeout(() => resolve({i}), {}))",
+                    (16 - i) * 10
+                ))
+                .await_promise(true)
+                .return_by_value(true)
+                .build()
+                .unwrap()
+        };
+
+        let results = page
+            .execute_batch(
+                (0..16)
+                    .map(|i| BatchCommand::new(delayed(i)).unwrap())
+                    .collect(),
+            )
+            .await
+            .expect("should execute batch");
+
+        let values: Vec<_> = results
+            .iter()
+            .map(|res| res["result"]["value"].as_u64().unwrap())
+            .collect();
+        assert_eq!(values, (0..16).collect::<Vec<_>>());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[ignore] // Benchmark, the timings depend on the machine
+async fn bench_apply_profile() {
+    test(async |browser| {
+        let profile = ChaserProfile::windows().touch_points(5).build();
+
+        // one round trip per override, as before batching
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let started = std::time::Instant::now();
+        page.execute(profile.user_agent_override())
+            .await
+            .expect("should override user agent");
+        page.execute(
+            SetTouchEmulationEnabledParams::builder()
+                .enabled(true)
+                .max_touch_points(5)
+                .build()
+                .unwrap(),
+        )
+        .await
+        .expect("should enable touch");
+        page.execute(
+            SetEmulatedMediaParams::builder()
+                .features(profile.media_features())
+                .build(),
+        )
+        .await
+        .expect("should emulate media");
+        if let Some((latitude, longitude)) = profile.geolocation() {
+            page.execute(
+                SetGeolocationOverrideParams::builder()
+                    .latitude(latitude)
+                    .longitude(longitude)
+                    .build(),
+            )
+            .await
+            .expect("should override geolocation");
+        }
+        page.evaluate_on_new_document(profile.bootstrap_script())
+            .await
+            .expect("should add bootstrap script");
+        let sequential = started.elapsed();
+
+        let chaser = ChaserPage::new(
+            browser
+                .new_page("about:blank")
+                .await
+                .expect("should create new page"),
+        );
+        let started = std::time::Instant::now();
+        chaser
+            .apply_profile(&profile)
+            .await
+            .expect("should apply profile");
+        let batched = started.elapsed();
+
+        assert!(
+            batched < sequential,
+            "apply_profile took {batched:?}, sequential overrides {sequential:?}"
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_attach_to_page_endpoint() {
+    test(async |browser| {
+        let page = browser
+            .new_page("data:text/html,<title>shared tab</title>")
+            .await
+            .expect("should create new page");
+
+        // ws://127.0.0.1:<port>/devtools/browser/<id>
+        let host = browser
+            .websocket_address()
+            .trim_start_matches("ws://")
+            .split('/')
+            .next()
+            .unwrap()
+            .to_string();
+        let list = reqwest::get(format!("http://{host}/json/list"))
+            .await
+            .expect("should fetch /json/list")
+            .bytes()
+            .await
+            .expect("should read /json/list");
+        let list: Vec<serde_json::Value> =
+            serde_json::from_slice(&list).expect("should parse /json/list");
+        let ws_url = list
+            .iter()
+            .find(|entry| entry["id"] == page.target_id().as_ref())
+            .and_then(|entry| entry["webSocketDebuggerUrl"].as_str())
+            .expect("should list the page");
+
+        let (attached, mut handler) = chaser_oxide::Page::attach(ws_url)
+            .await
+            .expect("should attach to the page");
+        let handle = tokio::spawn(async move { while handler.next().await.is_some() {} });
+
+        assert_eq!(attached.target_id(), page.target_id());
+        let title: String = attached
+            .evaluate("document.title")
+            .await
+            .expect("should evaluate on the attached page")
+            .into_value()
+            .unwrap();
+        assert_eq!(title, "shared tab");
+
+        attached
+            .goto("data:text/html,<title>navigated</title>")
+            .await
+            .expect("should navigate the attached page");
+        assert_eq!(
+            page.get_title().await.unwrap().as_deref(),
+            Some("navigated")
+        );
+        handle.abort();
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_large_evaluate_result() {
+    const LEN: usize = 8 << 20;
+    test_config(
+        BrowserConfig::builder()
+            .max_message_size(16 << 20)
+            .build()
+            .unwrap(),
+        async |browser| {
+            let page = browser
+                .new_page("about:blank")
+                .await
+                .expect("should create new page");
+            let value: String = page
+                .evaluate(format!("'x'.repeat({LEN})"))
+                .await
+                .expect("should receive the whole result")
+                .into_value()
+                .unwrap();
+            assert_eq!(value.len(), LEN);
+        },
+    )
+    .await;
+}
+
+/// An HTTP server whose pages require Basic auth as `user:secret`
+fn basic_auth_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("should bind listener");
+    let addr = listener.local_addr().unwrap();
+
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let request = String::from_utf8_lossy(&request).to_lowercase();
+            // base64 of `user:secret`
+            let response = if request.contains("authorization: basic dxnlcjpzzwnyzxq=") {
+                "HTTP/1.1 200 OK\r\ncontent-type: text/html\r\ncontent-length: 26\r\n\
+                 connection: close\r\n\r\n<title>protected</title>ok"
+            } else {
+                "HTTP/1.1 401 Unauthorized\r\nwww-authenticate: Basic realm=\"test\"\r\n\
+                 content-length: 0\r\nconnection: close\r\n\r\n"
+            };
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn test_set_http_credentials() {
+    test(async |browser| {
+        let origin = basic_auth_server();
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        page.set_http_credentials(&origin, "user", "secret")
+            .await
+            .unwrap();
+
+        page.goto(format!("{origin}/private")).await.unwrap();
+        assert_eq!(
+            page.get_title().await.unwrap().as_deref(),
+            Some("protected")
+        );
+
+        page.clear_http_credentials().await.unwrap();
+        page.goto(format!("{origin}/other")).await.unwrap();
+        assert_ne!(
+            page.get_title().await.unwrap().as_deref(),
+            Some("protected")
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_http_credentials_survive_navigation_to_other_origins() {
+    test(async |browser| {
+        let (first, second) = (basic_auth_server(), basic_auth_server());
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        page.set_http_credentials(&first, "user", "secret")
+            .await
+            .unwrap();
+        page.set_http_credentials(&second, "user", "secret")
+            .await
+            .unwrap();
+
+        for origin in [&first, &second] {
+            page.goto(format!("{origin}/private")).await.unwrap();
+            assert_eq!(
+                page.get_title().await.unwrap().as_deref(),
+                Some("protected"),
+                "{origin}"
+            );
+        }
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_set_partitioned_cookie() {
+    test(async |browser| {
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .expect("should create new page");
+        let cookie = CookieBuilder::new("__Host-session", "abc")
+            .url("https://shop.example.com")
+            .path("/")
+            .secure(true)
+            .http_only(true)
+            .same_site(CookieSameSite::None)
+            .priority(CookiePriority::High)
+            .partitioned("https://example.com")
+            .build()
+            .expect("should build cookie");
+        page.set_cookie(cookie).await.expect("should set cookie");
+
+        let cookies = browser.get_cookies().await.unwrap();
+        let cookie = cookies
+            .iter()
+            .find(|cookie| cookie.name == "__Host-session")
+            .expect("should read the cookie back");
+        assert_eq!(cookie.value, "abc");
+        assert!(cookie.secure && cookie.http_only);
+        assert_eq!(cookie.same_site, Some(CookieSameSite::None));
+        assert_eq!(cookie.priority, CookiePriority::High);
+        assert_eq!(
+            cookie
+                .partition_key
+                .as_ref()
+                .map(|key| key.top_level_site.as_str()),
+            Some("https://example.com")
+        );
+
+        let insecure = CookieParam::builder()
+            .name("tracking")
+            .value("1")
+            .url("https://shop.example.com")
+            .same_site(CookieSameSite::None)
+            .build()
+            .unwrap();
+        let err = page.set_cookie(insecure).await.unwrap_err();
+        assert!(err.to_string().contains("SameSite=None must be Secure"));
+    })
+    .await;
+}
@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use chaser_oxide::{Browser, BrowserConfig, ChaserProfile, PagePool};
+use futures::future::join_all;
+use futures::StreamExt;
+use serde_json::json;
+
+async fn pool_test<T>(size: usize, test: T)
+where
+    T: AsyncFnOnce(&PagePool),
+{
+    let (browser, mut handler) = Browser::launch(BrowserConfig::builder().build().unwrap())
+        .await
+        .unwrap();
+    let handle = tokio::spawn(async move { while let Some(Ok(_)) = handler.next().await {} });
+
+    let pool = PagePool::with_profile(browser, size, ChaserProfile::windows().build())
+        .await
+        .expect("should create pool");
+    test(&pool).await;
+
+    let mut browser = pool.into_browser().await.expect("should return browser");
+    browser.close().await.unwrap();
+    handle.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_pool_caps_concurrent_pages() {
+    pool_test(2, async |pool| {
+        let active = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let jobs = (0..6).map(|i| {
+            let (active, peak) = (&active, &peak);
+            async move {
+                let page = pool.acquire().await.expect("should acquire page");
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                page.goto(&format!("data:text/html,<p>job {i}</p>"))
+                    .await
+                    .expect("should navigate to page");
+                active.fetch_sub(1, Ordering::SeqCst);
+                page.release().await.expect("should release page");
+            }
+        });
+        join_all(jobs).await;
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_pool_resets_page_state() {
+    pool_test(1, async |pool| {
+        let page = pool.acquire().await.expect("should acquire page");
+        page.goto("https://www.google.com")
+            .await
+            .expect("should navigate to www.google.com");
+        page.evaluate(
+            "localStorage.setItem('job', '1'); sessionStorage.setItem('job', '1');
+             document.cookie = 'job=1; max-age=3600'",
+        )
+        .await
+        .expect("should store state");
+        // state of origins the page navigated away from is cleared too
+        page.goto("https://example.com")
+            .await
+            .expect("should navigate to example.com");
+        page.release().await.expect("should release page");
+
+        let page = pool.acquire().await.expect("should acquire page");
+        let url = page.raw_page().url().await.expect("should read url");
+        assert_eq!(url.as_deref(), Some("about:blank"));
+        page.goto("https://www.google.com")
+            .await
+            .expect("should navigate to www.google.com");
+        let state = page
+            .evaluate(
+                "[localStorage.getItem('job'), sessionStorage.getItem('job'),
+                  document.cookie.includes('job=1')]",
+            )
+            .await
+            .expect("should read state");
+        assert_eq!(state, Some(json!([null, null, false])));
+    })
+    .await;
+}